@@ -0,0 +1,560 @@
+//! ANSI Art Loader
+//!
+//! Parses `.ans` files as drawn for DOS BBSes: CP437 bytes interleaved with
+//! ANSI escape sequences, usually 80 columns wide, optionally ending with a
+//! SAUCE metadata record.
+//!
+//! Supported:
+//! - SGR colours: the 16-colour palette (bold brightens the foreground),
+//!   `38;5;N` / `48;5;N` 256-colour and `38;2;R;G;B` / `48;2;R;G;B` truecolour
+//! - Blink, or bright backgrounds when iCE colours are on (SAUCE flag or `ESC[?33h`)
+//! - Cursor movement (`A` `B` `C` `D` `H` `f`), save/restore (`s` `u`),
+//!   clears (`J` `K`), tabs, CR/LF and the 0x1A end-of-file marker
+//! - SAUCE title, author, group, date, width and comment lines
+//!
+//! Every cell records the byte offset it was drawn at, so the art can be
+//! revealed at modem speed in the order it was drawn.
+
+use std::fs;
+use std::path::Path;
+
+/// Columns when the file doesn't say
+pub const DEFAULT_COLUMNS: usize = 80;
+/// Rows past this are drawn on the last one, so a stray cursor move can't
+/// allocate without limit
+const MAX_ROWS: usize = 10_000;
+
+const SAUCE_SIZE: usize = 128;
+const SAUCE_ID: &[u8] = b"SAUCE00";
+const COMMENT_ID: &[u8] = b"COMNT";
+const COMMENT_LINE: usize = 64;
+const SUB: u8 = 0x1A;
+const ESC: u8 = 0x1B;
+
+/// SAUCE data type for character-based files (the only type whose width and flags we read)
+const SAUCE_CHARACTER: u8 = 1;
+/// SAUCE flag: iCE colours (bright backgrounds instead of blink)
+const SAUCE_TFLAG_ICE: u8 = 0x01;
+
+/// The 16 colours in SGR order: black, red, green, brown, blue, magenta, cyan, grey, then bright
+pub const PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (170, 0, 0),
+    (0, 170, 0),
+    (170, 85, 0),
+    (0, 0, 170),
+    (170, 0, 170),
+    (0, 170, 170),
+    (170, 170, 170),
+    (85, 85, 85),
+    (255, 85, 85),
+    (85, 255, 85),
+    (255, 255, 85),
+    (85, 85, 255),
+    (255, 85, 255),
+    (85, 255, 255),
+    (255, 255, 255),
+];
+
+/// SAUCE metadata record
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sauce {
+    pub title: String,
+    pub author: String,
+    pub group: String,
+    /// CCYYMMDD
+    pub date: String,
+    /// Columns, if the file sets them
+    pub columns: Option<usize>,
+    /// Bright backgrounds instead of blinking
+    pub ice_colors: bool,
+    pub comments: Vec<String>,
+}
+
+/// One character cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    /// CP437 byte
+    pub ch: u8,
+    pub fg: (u8, u8, u8),
+    pub bg: (u8, u8, u8),
+    /// Foreground blinks (only without iCE colours)
+    pub blink: bool,
+    /// Offset into the file of the byte that drew this cell
+    pub offset: usize,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            ch: b' ',
+            fg: PALETTE[7],
+            bg: PALETTE[0],
+            blink: false,
+            offset: 0,
+        }
+    }
+}
+
+/// A parsed ANSI art file
+#[derive(Debug, Clone, Default)]
+pub struct AnsiArt {
+    columns: usize,
+    rows: Vec<Vec<Cell>>,
+    /// Art bytes, excluding SAUCE and comments
+    length: usize,
+    /// (byte offset, cursor row) each time the cursor changes rows
+    cursor_trail: Vec<(usize, usize)>,
+    pub sauce: Option<Sauce>,
+}
+
+impl AnsiArt {
+    /// Load and parse a file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let data = fs::read(path).map_err(|e| e.to_string())?;
+        Self::parse(&data)
+    }
+
+    /// Parse a file's bytes
+    pub fn parse(data: &[u8]) -> Result<Self, String> {
+        let (body, sauce) = split_sauce(data)?;
+        let columns = sauce
+            .as_ref()
+            .and_then(|s| s.columns)
+            .unwrap_or(DEFAULT_COLUMNS);
+        let ice = sauce.as_ref().is_some_and(|s| s.ice_colors);
+        let mut parser = Parser::new(columns, ice);
+        let length = parser.run(body);
+        Ok(Self {
+            columns,
+            rows: parser.rows,
+            length,
+            cursor_trail: parser.cursor_trail,
+            sauce,
+        })
+    }
+
+    /// Width in character cells
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Height in character cells
+    pub fn row_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Cell at a position (blank past the end of a row)
+    pub fn cell(&self, column: usize, row: usize) -> Cell {
+        self.rows
+            .get(row)
+            .and_then(|r| r.get(column))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Number of art bytes, for timing a reveal
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Row the cursor was on after `bytes` bytes had been drawn
+    pub fn cursor_row_at(&self, bytes: usize) -> usize {
+        let index = self
+            .cursor_trail
+            .partition_point(|&(offset, _)| offset <= bytes);
+        index.checked_sub(1).map_or(0, |i| self.cursor_trail[i].1)
+    }
+}
+
+/// Split off the SAUCE record and comment block, and cut at the EOF marker
+fn split_sauce(data: &[u8]) -> Result<(&[u8], Option<Sauce>), String> {
+    let Some(record_start) = data.len().checked_sub(SAUCE_SIZE) else {
+        return Ok((cut_at_eof(data), None));
+    };
+    let record = &data[record_start..];
+    if !record.starts_with(SAUCE_ID) {
+        return Ok((cut_at_eof(data), None));
+    }
+
+    let text = |range: std::ops::Range<usize>| {
+        let bytes = &record[range];
+        let end = bytes
+            .iter()
+            .rposition(|&b| b != b' ' && b != 0)
+            .map_or(0, |i| i + 1);
+        bytes[..end]
+            .iter()
+            .map(|&b| cp437_char(b))
+            .collect::<String>()
+    };
+    let word = |at: usize| u16::from_le_bytes([record[at], record[at + 1]]);
+    let data_type = record[94];
+    let columns = usize::from(word(96));
+    let comment_lines = usize::from(record[104]);
+    let flags = record[105];
+
+    let mut body_end = record_start;
+    let mut comments = Vec::new();
+    if comment_lines > 0 {
+        let block = COMMENT_ID.len() + comment_lines * COMMENT_LINE;
+        let start = record_start
+            .checked_sub(block)
+            .filter(|&start| data[start..].starts_with(COMMENT_ID))
+            .ok_or("SAUCE comment block missing")?;
+        comments = data[start + COMMENT_ID.len()..record_start]
+            .chunks(COMMENT_LINE)
+            .map(|line| {
+                let line: String = line.iter().map(|&b| cp437_char(b)).collect();
+                line.trim_end_matches([' ', '\0']).to_string()
+            })
+            .collect();
+        body_end = start;
+    }
+
+    let is_character = data_type == SAUCE_CHARACTER;
+    let sauce = Sauce {
+        title: text(7..42),
+        author: text(42..62),
+        group: text(62..82),
+        date: text(82..90),
+        columns: (is_character && columns > 0).then_some(columns),
+        ice_colors: is_character && flags & SAUCE_TFLAG_ICE != 0,
+        comments,
+    };
+    Ok((cut_at_eof(&data[..body_end]), Some(sauce)))
+}
+
+fn cut_at_eof(data: &[u8]) -> &[u8] {
+    data.iter()
+        .position(|&b| b == SUB)
+        .map_or(data, |end| &data[..end])
+}
+
+/// Unicode for a CP437 byte, for SAUCE text
+fn cp437_char(byte: u8) -> char {
+    match byte {
+        0 => ' ',
+        _ => crate::display::fonts::cp437::TO_UNICODE[usize::from(byte)],
+    }
+}
+
+/// A colour as set by SGR; palette colours stay indexed so bold can brighten them
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Color {
+    Palette(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    fn rgb(self, bright: bool) -> (u8, u8, u8) {
+        match self {
+            Color::Palette(i) if bright && i < 8 => PALETTE[usize::from(i) + 8],
+            Color::Palette(i) => PALETTE[usize::from(i)],
+            Color::Rgb(r, g, b) => (r, g, b),
+        }
+    }
+}
+
+/// Current colours and attributes, as set by SGR
+#[derive(Debug, Clone, Copy)]
+struct Pen {
+    fg: Color,
+    bg: Color,
+    bold: bool,
+    blink: bool,
+    inverse: bool,
+}
+
+impl Default for Pen {
+    fn default() -> Self {
+        Self {
+            fg: Color::Palette(7),
+            bg: Color::Palette(0),
+            bold: false,
+            blink: false,
+            inverse: false,
+        }
+    }
+}
+
+/// Terminal state while drawing
+struct Parser {
+    columns: usize,
+    rows: Vec<Vec<Cell>>,
+    cursor_trail: Vec<(usize, usize)>,
+    x: usize,
+    y: usize,
+    saved: (usize, usize),
+    pen: Pen,
+    ice: bool,
+}
+
+impl Parser {
+    fn new(columns: usize, ice: bool) -> Self {
+        Self {
+            columns: columns.max(1),
+            rows: Vec::new(),
+            cursor_trail: vec![(0, 0)],
+            x: 0,
+            y: 0,
+            saved: (0, 0),
+            pen: Pen::default(),
+            ice,
+        }
+    }
+
+    /// Draw all of `data`, returning the number of bytes consumed
+    fn run(&mut self, data: &[u8]) -> usize {
+        let mut i = 0;
+        while i < data.len() {
+            let byte = data[i];
+            match byte {
+                b'\r' => self.x = 0,
+                b'\n' => self.move_to(0, self.y + 1, i),
+                b'\t' => self.x = ((self.x / 8 + 1) * 8).min(self.columns - 1),
+                ESC if data.get(i + 1) == Some(&b'[') => {
+                    i = self.escape(data, i + 2);
+                    continue;
+                },
+                _ => self.put(byte, i),
+            }
+            i += 1;
+        }
+        data.len()
+    }
+
+    fn move_to(&mut self, x: usize, y: usize, offset: usize) {
+        self.x = x.min(self.columns - 1);
+        let y = y.min(MAX_ROWS - 1);
+        if y != self.y {
+            self.y = y;
+            self.cursor_trail.push((offset, y));
+        }
+    }
+
+    fn put(&mut self, ch: u8, offset: usize) {
+        if self.x >= self.columns {
+            self.move_to(0, self.y + 1, offset);
+        }
+        let pen = self.pen;
+        let (mut fg, mut bg) = (pen.fg.rgb(pen.bold), pen.bg.rgb(self.ice && pen.blink));
+        if pen.inverse {
+            std::mem::swap(&mut fg, &mut bg);
+        }
+        let cell = Cell {
+            ch,
+            fg,
+            bg,
+            blink: pen.blink && !self.ice,
+            offset,
+        };
+        *self.cell_mut(self.x, self.y) = cell;
+        // Past the last column the cursor waits; the next character wraps
+        self.x += 1;
+    }
+
+    fn cell_mut(&mut self, x: usize, y: usize) -> &mut Cell {
+        if self.rows.len() <= y {
+            self.rows.resize_with(y + 1, Vec::new);
+        }
+        let row = &mut self.rows[y];
+        if row.len() <= x {
+            row.resize(x + 1, Cell::default());
+        }
+        &mut row[x]
+    }
+
+    /// Handle a CSI sequence whose parameters start at `start`; returns the index after it
+    fn escape(&mut self, data: &[u8], start: usize) -> usize {
+        let Some(len) = data[start..].iter().position(|b| (0x40..=0x7E).contains(b)) else {
+            return data.len();
+        };
+        let end = start + len;
+        let raw = &data[start..end];
+        let private = raw.first() == Some(&b'?');
+        let params: Vec<usize> = String::from_utf8_lossy(raw.strip_prefix(b"?").unwrap_or(raw))
+            .split(';')
+            .map(|p| p.parse().unwrap_or(0))
+            .collect();
+        let n = params.first().copied().unwrap_or(0).max(1);
+
+        match data[end] {
+            b'h' | b'l' if private && params == [33] => self.ice = data[end] == b'h',
+            b'm' => self.sgr(&params),
+            b'A' => self.move_to(self.x, self.y.saturating_sub(n), start),
+            b'B' => self.move_to(self.x, self.y.saturating_add(n), start),
+            b'C' => self.x = self.x.saturating_add(n).min(self.columns - 1),
+            b'D' => self.x = self.x.min(self.columns - 1).saturating_sub(n),
+            b'H' | b'f' => {
+                let row = params.first().copied().unwrap_or(1).max(1);
+                let column = params.get(1).copied().unwrap_or(1).max(1);
+                self.move_to(column - 1, row - 1, start);
+            },
+            b'J' if params[0] == 2 => {
+                self.rows.clear();
+                self.move_to(0, 0, start);
+            },
+            b'K' => {
+                let x = self.x;
+                if let Some(row) = self.rows.get_mut(self.y) {
+                    row.truncate(x);
+                }
+            },
+            b's' => self.saved = (self.x, self.y),
+            b'u' => self.move_to(self.saved.0, self.saved.1, start),
+            _ => {},
+        }
+        end + 1
+    }
+
+    /// Select Graphic Rendition
+    fn sgr(&mut self, params: &[usize]) {
+        let mut codes = params.iter().copied();
+        while let Some(code) = codes.next() {
+            match code {
+                0 => self.pen = Pen::default(),
+                1 => self.pen.bold = true,
+                5 | 6 => self.pen.blink = true,
+                7 => self.pen.inverse = true,
+                22 => self.pen.bold = false,
+                25 => self.pen.blink = false,
+                27 => self.pen.inverse = false,
+                30..=37 => self.pen.fg = Color::Palette((code - 30) as u8),
+                39 => self.pen.fg = Pen::default().fg,
+                40..=47 => self.pen.bg = Color::Palette((code - 40) as u8),
+                49 => self.pen.bg = Pen::default().bg,
+                90..=97 => self.pen.fg = Color::Palette((code - 90 + 8) as u8),
+                100..=107 => self.pen.bg = Color::Palette((code - 100 + 8) as u8),
+                38 | 48 => {
+                    let Some(color) = extended_color(&mut codes) else {
+                        return;
+                    };
+                    if code == 38 {
+                        self.pen.fg = color;
+                    } else {
+                        self.pen.bg = color;
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+}
+
+/// The colour after a 38/48: `5;N` or `2;R;G;B`
+fn extended_color(codes: &mut impl Iterator<Item = usize>) -> Option<Color> {
+    let mut next = || codes.next().map(|c| c.min(255) as u8);
+    match next()? {
+        5 => Some(color_256(next()?)),
+        2 => Some(Color::Rgb(next()?, next()?, next()?)),
+        _ => None,
+    }
+}
+
+/// xterm 256-colour palette: 16 system colours, a 6x6x6 cube, then 24 greys
+fn color_256(index: u8) -> Color {
+    match index {
+        0..=15 => Color::Palette(index),
+        16..=231 => {
+            let i = index - 16;
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            Color::Rgb(level(i / 36), level(i / 6 % 6), level(i % 6))
+        },
+        _ => {
+            let grey = 8 + (index - 232) * 10;
+            Color::Rgb(grey, grey, grey)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sauce(columns: u16, flags: u8, comments: &[&str]) -> Vec<u8> {
+        let mut out = Vec::new();
+        if !comments.is_empty() {
+            out.extend_from_slice(COMMENT_ID);
+            for line in comments {
+                let mut line = line.as_bytes().to_vec();
+                line.resize(COMMENT_LINE, b' ');
+                out.extend(line);
+            }
+        }
+        let mut record = vec![b' '; SAUCE_SIZE];
+        record[..7].copy_from_slice(SAUCE_ID);
+        record[7..12].copy_from_slice(b"Title");
+        record[42..47].copy_from_slice(b"artst");
+        record[94] = SAUCE_CHARACTER;
+        record[95] = 1;
+        record[96..98].copy_from_slice(&columns.to_le_bytes());
+        record[104] = comments.len() as u8;
+        record[105] = flags;
+        out.extend(record);
+        out
+    }
+
+    #[test]
+    fn test_colors_and_cursor() {
+        let art = AnsiArt::parse(b"\x1b[1;31mA\x1b[0;44mB\r\n\x1b[3CC\x1b[38;2;1;2;3mD").unwrap();
+        assert_eq!(art.columns(), DEFAULT_COLUMNS);
+        assert_eq!(art.row_count(), 2);
+        let a = art.cell(0, 0);
+        assert_eq!((a.ch, a.fg, a.bg), (b'A', PALETTE[9], PALETTE[0]));
+        let b = art.cell(1, 0);
+        assert_eq!((b.fg, b.bg), (PALETTE[7], PALETTE[4]));
+        assert_eq!(art.cell(3, 1).ch, b'C');
+        assert_eq!(art.cell(4, 1).fg, (1, 2, 3));
+        // Reveal order follows the bytes
+        assert!(art.cell(3, 1).offset > b.offset);
+        assert_eq!(art.cursor_row_at(0), 0);
+        assert_eq!(art.cursor_row_at(art.len()), 1);
+    }
+
+    #[test]
+    fn test_sauce_width_and_ice() {
+        let mut data = b"\x1b[5;41mXYZ\x1a".to_vec();
+        data.extend(sauce(2, SAUCE_TFLAG_ICE, &["first line", "second"]));
+        let art = AnsiArt::parse(&data).unwrap();
+        let info = art.sauce.as_ref().unwrap();
+        assert_eq!(info.title, "Title");
+        assert_eq!(info.author, "artst");
+        assert_eq!(info.columns, Some(2));
+        assert_eq!(info.comments, ["first line", "second"]);
+        // Two columns: the third character wraps
+        assert_eq!(art.cell(0, 1).ch, b'Z');
+        // iCE: blink becomes a bright background
+        let x = art.cell(0, 0);
+        assert_eq!((x.bg, x.blink), (PALETTE[9], false));
+        assert_eq!(art.len(), 10);
+    }
+
+    #[test]
+    fn test_huge_cursor_moves() {
+        let art = AnsiArt::parse(
+            b"\x1b[999999999BX\x1b[18446744073709551615B\
+              \x1b[18446744073709551615CY\x1b[999999999;1HZ",
+        )
+        .unwrap();
+        // Clamped to the last row instead of allocating for every row on the way
+        assert_eq!(art.row_count(), MAX_ROWS);
+        let last = MAX_ROWS - 1;
+        assert_eq!(
+            (art.cell(0, last).ch, art.cell(DEFAULT_COLUMNS - 1, last).ch),
+            (b'Z', b'Y')
+        );
+    }
+
+    #[test]
+    fn test_256_colors() {
+        assert_eq!(color_256(3), Color::Palette(3));
+        assert_eq!(color_256(16), Color::Rgb(0, 0, 0));
+        assert_eq!(color_256(231), Color::Rgb(255, 255, 255));
+        assert_eq!(color_256(232), Color::Rgb(8, 8, 8));
+    }
+}
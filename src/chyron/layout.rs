@@ -0,0 +1,404 @@
+//! Chyron strip layout
+//!
+//! Where the chyron strips go, how thick they are and what is drawn behind
+//! them. Strips can hug any screen edge, cover a rectangle or follow a
+//! region's bounding box; tall strips turn their text on its side. Each
+//! strip can also push effects away by adding a virtual collision region.
+//!
+//! ```json
+//! "chyron_strips": [
+//!   { "name": "top", "edge": { "type": "Top" } },
+//!   { "name": "side", "edge": { "type": "Right" }, "height": 0.1,
+//!     "background": { "color": [0, 0, 64], "alpha": 160 } },
+//!   { "name": "bar", "edge": { "type": "Region", "name": "bar_sign" }, "avoid": false },
+//!   { "name": "bottom", "edge": { "type": "Bottom" }, "enabled": false }
+//! ]
+//! ```
+
+use super::ChyronStyle;
+use crate::display::{PixelBuffer, ScrollDirection, StyledScroller};
+use crate::regions::{Point, Polygon, Scene};
+use serde::{Deserialize, Serialize};
+
+/// Default strip thickness as a fraction of the screen height
+const DEFAULT_HEIGHT: f32 = 0.126;
+/// Text baseline offset within the strip, leaving headroom for orbital motion
+const TEXT_OFFSET: f32 = 0.2;
+
+/// Where a strip sits on the screen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum StripEdge {
+    Top,
+    Bottom,
+    /// Text reads bottom to top
+    Left,
+    /// Text reads top to bottom
+    Right,
+    /// Rectangle in fractions of the screen size; vertical if taller than wide
+    Rect {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    },
+    /// Bounding box of a scene region; vertical if taller than wide
+    Region {
+        name: String,
+    },
+}
+
+/// Translucent fill behind a strip's text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StripBackground {
+    #[serde(default)]
+    pub color: (u8, u8, u8),
+    /// 0 = no background, 255 = opaque
+    #[serde(default = "default_alpha")]
+    pub alpha: u8,
+}
+
+fn default_alpha() -> u8 {
+    200
+}
+
+impl Default for StripBackground {
+    fn default() -> Self {
+        Self {
+            color: (0, 0, 0),
+            alpha: default_alpha(),
+        }
+    }
+}
+
+/// One chyron strip, as configured
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StripLayout {
+    /// Messages target strips by name ("both" means every strip)
+    pub name: String,
+    pub edge: StripEdge,
+    /// Thickness as a fraction of the screen height, for every edge so text
+    /// is the same size on all strips (ignored for rects and regions)
+    #[serde(default = "default_height")]
+    pub height: f32,
+    #[serde(default)]
+    pub background: StripBackground,
+    /// Scroll direction unless the style sets one (default: bottom strips
+    /// rightward, everything else leftward)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direction: Option<ScrollDirection>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Add a collision region so effects bounce off the strip
+    #[serde(default = "default_true")]
+    pub avoid: bool,
+}
+
+fn default_height() -> f32 {
+    DEFAULT_HEIGHT
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl StripLayout {
+    pub fn new(name: impl Into<String>, edge: StripEdge) -> Self {
+        Self {
+            name: name.into(),
+            edge,
+            height: DEFAULT_HEIGHT,
+            background: StripBackground::default(),
+            direction: None,
+            enabled: true,
+            avoid: true,
+        }
+    }
+
+    /// The house layout: full-width strips along the top and bottom
+    pub fn defaults() -> Vec<StripLayout> {
+        vec![
+            StripLayout::new("top", StripEdge::Top),
+            StripLayout::new("bottom", StripEdge::Bottom),
+        ]
+    }
+
+    /// Scroll direction when the style doesn't set one
+    pub fn default_direction(&self) -> ScrollDirection {
+        self.direction.unwrap_or(match self.edge {
+            StripEdge::Bottom => ScrollDirection::Rightward,
+            _ => ScrollDirection::Leftward,
+        })
+    }
+
+    /// Place the strip on a `width` x `height` screen. None if it's disabled,
+    /// its region is missing or it ends up empty.
+    pub fn place(&self, width: u32, height: u32, scene: &Scene) -> Option<StripRect> {
+        if !self.enabled {
+            return None;
+        }
+        let (w, h) = (width as f32, height as f32);
+        let thickness = h * self.height;
+        let (x0, y0, x1, y1, orientation) = match self.edge {
+            StripEdge::Top => (0.0, 0.0, w, thickness, Orientation::Horizontal),
+            StripEdge::Bottom => (0.0, h - thickness, w, h, Orientation::Horizontal),
+            StripEdge::Left => (0.0, 0.0, thickness, h, Orientation::Up),
+            StripEdge::Right => (w - thickness, 0.0, w, h, Orientation::Down),
+            StripEdge::Rect {
+                x,
+                y,
+                width: rw,
+                height: rh,
+            } => {
+                let (x0, y0) = (x * w, y * h);
+                let (x1, y1) = (x0 + rw * w, y0 + rh * h);
+                (x0, y0, x1, y1, Orientation::fit(x1 - x0, y1 - y0))
+            },
+            StripEdge::Region { ref name } => {
+                let region = scene.regions.iter().find(|r| &r.name == name)?;
+                let (x0, y0, x1, y1) = region.get_shape().bounds()?;
+                (x0, y0, x1, y1, Orientation::fit(x1 - x0, y1 - y0))
+            },
+        };
+
+        // Whole pixels, clipped to the screen
+        let x0 = x0.round().clamp(0.0, w) as i32;
+        let y0 = y0.round().clamp(0.0, h) as i32;
+        let x1 = x1.round().clamp(0.0, w) as i32;
+        let y1 = y1.round().clamp(0.0, h) as i32;
+        (x1 > x0 && y1 > y0).then(|| StripRect {
+            x: x0,
+            y: y0,
+            width: (x1 - x0) as u32,
+            height: (y1 - y0) as u32,
+            orientation,
+        })
+    }
+}
+
+/// Which way a strip's text runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Horizontal,
+    /// Turned a quarter counter-clockwise, reading bottom to top
+    Up,
+    /// Turned a quarter clockwise, reading top to bottom
+    Down,
+}
+
+impl Orientation {
+    /// Horizontal unless the area is taller than it is wide
+    fn fit(width: f32, height: f32) -> Self {
+        if height > width {
+            Orientation::Up
+        } else {
+            Orientation::Horizontal
+        }
+    }
+}
+
+/// A strip placed on the screen, in pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StripRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub orientation: Orientation,
+}
+
+impl StripRect {
+    /// Size along the text
+    pub fn length(&self) -> u32 {
+        match self.orientation {
+            Orientation::Horizontal => self.width,
+            Orientation::Up | Orientation::Down => self.height,
+        }
+    }
+
+    /// Size across the text
+    pub fn thickness(&self) -> u32 {
+        match self.orientation {
+            Orientation::Horizontal => self.height,
+            Orientation::Up | Orientation::Down => self.width,
+        }
+    }
+
+    /// Outline for the strip's collision region
+    pub fn polygon(&self) -> Polygon {
+        let (x0, y0) = (self.x as f32, self.y as f32);
+        let (x1, y1) = (x0 + self.width as f32, y0 + self.height as f32);
+        Polygon::from_vertices(vec![
+            Point::new(x0, y0),
+            Point::new(x1, y0),
+            Point::new(x1, y1),
+            Point::new(x0, y1),
+        ])
+    }
+
+    /// Draw the background, then the scroller's text turned to fit the strip.
+    /// Horizontal text goes straight onto the buffer, clipped to the strip;
+    /// side strips draw it into an upright copy of the strip and turn it back.
+    pub fn render(
+        &self,
+        buffer: &mut PixelBuffer,
+        scroller: &StyledScroller,
+        background: StripBackground,
+    ) {
+        if background.alpha > 0 {
+            let (r, g, b) = background.color;
+            let x1 = self.x + self.width as i32 - 1;
+            for y in self.y..self.y + self.height as i32 {
+                buffer.hline_blend(self.x, x1, y, r, g, b, background.alpha);
+            }
+        }
+
+        let text_offset = (self.thickness() as f32 * TEXT_OFFSET) as i32;
+        if self.orientation == Orientation::Horizontal {
+            buffer.set_clip(Some((self.x, self.y, self.width, self.height)));
+            scroller.render_at(buffer, self.x, self.y + text_offset);
+            buffer.set_clip(None);
+            return;
+        }
+
+        let area = buffer.crop(self.x, self.y, self.width, self.height);
+        let up = self.orientation == Orientation::Up;
+        let mut upright = if up {
+            area.rotated_90()
+        } else {
+            area.rotated_270()
+        };
+        scroller.render(&mut upright, text_offset);
+        let area = if up {
+            upright.rotated_270()
+        } else {
+            upright.rotated_90()
+        };
+        buffer.blit(&area, self.x, self.y);
+    }
+}
+
+/// A strip placed on screen and the scroller showing on it
+pub struct ChyronStrip {
+    pub layout: StripLayout,
+    pub rect: StripRect,
+    pub scroller: StyledScroller,
+    /// Queue ID of the message being shown (None = base text)
+    pub shown: Option<u64>,
+}
+
+impl ChyronStrip {
+    /// A strip showing `text` in `style`
+    pub fn new(layout: StripLayout, rect: StripRect, style: &ChyronStyle, text: &str) -> Self {
+        let scroller = style.build(
+            text,
+            layout.default_direction(),
+            rect.length(),
+            rect.thickness(),
+        );
+        Self {
+            layout,
+            rect,
+            scroller,
+            shown: None,
+        }
+    }
+
+    /// Start showing `text` in `style`
+    pub fn show(&mut self, style: &ChyronStyle, text: &str) {
+        self.scroller = style.build(
+            text,
+            self.layout.default_direction(),
+            self.rect.length(),
+            self.rect.thickness(),
+        );
+    }
+
+    pub fn render(&self, buffer: &mut PixelBuffer) {
+        self.rect
+            .render(buffer, &self.scroller, self.layout.background);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_place_edges() {
+        let scene = Scene::default();
+        let [top, bottom] = [StripEdge::Top, StripEdge::Bottom].map(|edge| {
+            StripLayout::new("strip", edge)
+                .place(640, 480, &scene)
+                .unwrap()
+        });
+        assert_eq!((top.x, top.y, top.width, top.height), (0, 0, 640, 60));
+        assert_eq!((bottom.y, bottom.height), (420, 60));
+
+        let right = StripLayout::new("side", StripEdge::Right)
+            .place(640, 480, &scene)
+            .unwrap();
+        assert_eq!((right.x, right.width, right.height), (580, 60, 480));
+        assert_eq!(right.orientation, Orientation::Down);
+        assert_eq!((right.length(), right.thickness()), (480, 60));
+
+        let disabled = StripLayout {
+            enabled: false,
+            ..StripLayout::new("off", StripEdge::Top)
+        };
+        assert!(disabled.place(640, 480, &scene).is_none());
+        let missing = StripLayout::new(
+            "r",
+            StripEdge::Region {
+                name: "nope".into(),
+            },
+        );
+        assert!(missing.place(640, 480, &scene).is_none());
+    }
+
+    #[test]
+    fn test_layout_from_json() {
+        let strips: Vec<StripLayout> = serde_json::from_str(
+            r#"[{"name": "tall", "edge": {"type": "Rect", "x": 0.5, "y": 0.0, "width": 0.1, "height": 1.0},
+                 "background": {"alpha": 0}}]"#,
+        )
+        .unwrap();
+        let strip = &strips[0];
+        assert!(strip.enabled && strip.avoid);
+        assert_eq!(strip.background.alpha, 0);
+        let rect = strip.place(640, 480, &Scene::default()).unwrap();
+        assert_eq!((rect.x, rect.width, rect.height), (320, 64, 480));
+        assert_eq!(rect.orientation, Orientation::Up);
+    }
+
+    #[test]
+    fn test_horizontal_text_stays_in_strip() {
+        let rect = StripRect {
+            x: 20,
+            y: 10,
+            width: 60,
+            height: 12,
+            orientation: Orientation::Horizontal,
+        };
+        // Long enough to run past the strip's right end
+        let mut scroller = StyledScroller::new("W".repeat(40)).speed(100.0);
+        scroller.update(6.0);
+        let mut buffer = PixelBuffer::with_size(100, 40);
+        let background = StripBackground {
+            color: (0, 0, 0),
+            alpha: 0,
+        };
+        rect.render(&mut buffer, &scroller, background);
+
+        let mut lit = 0;
+        for y in 0..40 {
+            for x in 0..100 {
+                if buffer.get_pixel(x, y) != Some((0, 0, 0)) {
+                    assert!((20..80).contains(&x) && (10..22).contains(&y), "({x}, {y})");
+                    lit += 1;
+                }
+            }
+        }
+        assert!(lit > 0);
+    }
+}
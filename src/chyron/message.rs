@@ -0,0 +1,130 @@
+//! Chyron message format
+//!
+//! Only `text` is required; everything else has a default:
+//!
+//! ```json
+//! {
+//!   "text": "Doors close in 5 minutes",
+//!   "ttl": 300,
+//!   "priority": 10,
+//!   "delay": 0,
+//!   "repeat": 3,
+//!   "urgent": false,
+//!   "target": "both",
+//!   "id": "doors",
+//!   "style": "info"
+//! }
+//! ```
+//!
+//! `target` is "top", "bottom", "both" (every strip) or the name of any
+//! strip in the layout. Style fields (see `style`) can also be given inline
+//! to override the preset.
+
+use super::ChyronStyle;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DEFAULT_TTL: f32 = 60.0;
+
+/// Which chyron strip(s) a message appears on
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChyronTarget {
+    Top,
+    Bottom,
+    /// Every strip
+    #[default]
+    Both,
+    /// A strip by name (see `layout`)
+    #[serde(untagged)]
+    Strip(String),
+}
+
+impl ChyronTarget {
+    /// Whether the strip called `name` shows messages with this target
+    pub fn accepts(&self, name: &str) -> bool {
+        match self {
+            ChyronTarget::Both => true,
+            ChyronTarget::Top => name == "top",
+            ChyronTarget::Bottom => name == "bottom",
+            ChyronTarget::Strip(strip) => strip == name,
+        }
+    }
+}
+
+/// A chyron message with text, lifetime and scheduling options
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChyronMessage {
+    /// Text to show; may use inline markup like `{red}BREAKING{/}` (see `display::markup`)
+    pub text: String,
+    /// Seconds the message stays active, counted from its start time
+    #[serde(default = "default_ttl")]
+    pub ttl: f32,
+    /// Higher priorities come first in the rotation
+    #[serde(default)]
+    pub priority: i32,
+    /// Seconds to wait before the message becomes active
+    #[serde(default)]
+    pub delay: f32,
+    /// Absolute start time as a Unix timestamp (overrides `delay`)
+    #[serde(default)]
+    pub at: Option<f64>,
+    /// Number of full passes to show before retiring (unlimited if unset)
+    #[serde(default)]
+    pub repeat: Option<u32>,
+    /// Interrupt the rotation: only urgent messages show while one is active
+    #[serde(default)]
+    pub urgent: bool,
+    #[serde(default)]
+    pub target: ChyronTarget,
+    /// Messages with the same ID replace each other
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Named style preset
+    #[serde(default)]
+    pub style: Option<String>,
+    /// Inline style fields, applied over the preset
+    #[serde(flatten)]
+    pub overrides: ChyronStyle,
+}
+
+fn default_ttl() -> f32 {
+    DEFAULT_TTL
+}
+
+impl ChyronMessage {
+    /// Message with the default TTL, shown on both strips
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ttl: DEFAULT_TTL,
+            priority: 0,
+            delay: 0.0,
+            at: None,
+            repeat: None,
+            urgent: false,
+            target: ChyronTarget::Both,
+            id: None,
+            style: None,
+            overrides: ChyronStyle::default(),
+        }
+    }
+
+    /// Parse the JSON payload format: {"text": "message", "ttl": 30, ...}
+    pub fn from_json(raw: &str) -> Result<Self, String> {
+        serde_json::from_str(raw).map_err(|e| e.to_string())
+    }
+
+    /// Seconds from now until the message should start (never negative)
+    pub fn start_delay(&self) -> f32 {
+        self.at.map_or_else(
+            || self.delay.max(0.0),
+            |at| {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0.0, |d| d.as_secs_f64());
+                (at - now).max(0.0) as f32
+            },
+        )
+    }
+}
@@ -0,0 +1,414 @@
+//! Chyron message queue
+//!
+//! Holds every active chyron message and decides what each strip shows.
+//! Messages rotate one full scroll pass at a time, highest priority first.
+//! Urgent messages interrupt the rotation on their strips until they expire
+//! or use up their repeats, then the rotation resumes after the message
+//! they interrupted.
+
+mod layout;
+mod message;
+mod style;
+
+#[allow(unused_imports)]
+pub use layout::{ChyronStrip, Orientation, StripBackground, StripEdge, StripLayout, StripRect};
+#[allow(unused_imports)]
+pub use message::{ChyronMessage, ChyronTarget};
+pub use style::{ChyronStyle, StyleSheet};
+
+/// Oldest, lowest-priority messages are dropped beyond this
+const MAX_MESSAGES: usize = 64;
+
+/// Rotation order: higher priority first, then arrival order
+type SortKey = (i32, u64);
+
+struct Entry {
+    id: u64,
+    message: ChyronMessage,
+    start: f32,
+    expires: f32,
+    /// Completed passes per strip
+    shows: Vec<u32>,
+}
+
+impl Entry {
+    fn key(&self) -> SortKey {
+        (-self.message.priority, self.id)
+    }
+
+    fn retired_on(&self, strip: usize) -> bool {
+        self.message
+            .repeat
+            .is_some_and(|repeat| self.shows[strip] >= repeat)
+    }
+
+    fn is_live(&self, strip: usize, name: &str, now: f32) -> bool {
+        now >= self.start
+            && now < self.expires
+            && self.message.target.accepts(name)
+            && !self.retired_on(strip)
+    }
+
+    /// Nothing left to show anywhere. Messages for strips that aren't in the
+    /// layout wait until they expire, in case the layout changes.
+    fn is_finished(&self, strips: &[String], now: f32) -> bool {
+        let targets: Vec<usize> = (0..strips.len())
+            .filter(|&i| self.message.target.accepts(&strips[i]))
+            .collect();
+        now >= self.expires || (!targets.is_empty() && targets.iter().all(|&i| self.retired_on(i)))
+    }
+}
+
+/// Where a strip is in the rotation
+#[derive(Debug, Clone, Copy, Default)]
+struct Showing {
+    /// Key of the message shown
+    key: Option<SortKey>,
+    /// Whether it was picked from urgent messages
+    urgent: bool,
+    /// Key shown when urgent messages took over, to resume after
+    interrupted: Option<SortKey>,
+}
+
+/// Queue of scheduled chyron messages with per-strip rotation.
+/// Strips are identified by their index in the layout.
+pub struct ChyronManager {
+    entries: Vec<Entry>,
+    next_id: u64,
+    /// Strip names, in layout order
+    strips: Vec<String>,
+    /// What each strip is showing
+    current: Vec<Showing>,
+}
+
+impl Default for ChyronManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChyronManager {
+    /// Queue for the default top and bottom strips
+    pub fn new() -> Self {
+        let mut manager = Self {
+            entries: Vec::new(),
+            next_id: 0,
+            strips: Vec::new(),
+            current: Vec::new(),
+        };
+        let names: Vec<String> = StripLayout::defaults()
+            .into_iter()
+            .map(|strip| strip.name)
+            .collect();
+        manager.set_strips(&names);
+        manager
+    }
+
+    /// Change the strip layout. Strips keep their place in the rotation and
+    /// their pass counts by name; new strips start from the beginning.
+    pub fn set_strips(&mut self, names: &[String]) {
+        if self.strips == names {
+            return;
+        }
+        let old: Vec<Option<usize>> = names
+            .iter()
+            .map(|name| self.strips.iter().position(|s| s == name))
+            .collect();
+        self.current = old
+            .iter()
+            .map(|i| i.map_or_else(Showing::default, |i| self.current[i]))
+            .collect();
+        for entry in &mut self.entries {
+            entry.shows = old
+                .iter()
+                .map(|i| i.map_or(0, |i| entry.shows[i]))
+                .collect();
+        }
+        self.strips = names.to_vec();
+    }
+
+    /// Add a message at time `now` (seconds, same clock as `update`).
+    /// A message with an `id` replaces any queued message with the same ID.
+    pub fn push(&mut self, message: ChyronMessage, now: f32) {
+        if let Some(ref id) = message.id {
+            self.entries.retain(|e| e.message.id.as_ref() != Some(id));
+        }
+
+        let start = now + message.start_delay();
+        self.entries.push(Entry {
+            id: self.next_id,
+            start,
+            expires: start + message.ttl.max(0.0),
+            message,
+            shows: vec![0; self.strips.len()],
+        });
+        self.next_id += 1;
+
+        if self.entries.len() > MAX_MESSAGES {
+            // Drop the lowest priority, oldest message
+            if let Some(pos) = self
+                .entries
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, e)| (e.message.priority, e.id))
+                .map(|(i, _)| i)
+            {
+                self.entries.remove(pos);
+            }
+        }
+
+        self.update(now);
+    }
+
+    /// Remove every message
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.current.fill(Showing::default());
+    }
+
+    /// Number of queued messages (including ones not started yet)
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop finished messages and make sure each strip shows a live one.
+    /// A strip keeps its message until it completes a pass unless the message
+    /// ends or an urgent message interrupts.
+    pub fn update(&mut self, now: f32) {
+        let strips = &self.strips;
+        self.entries.retain(|e| !e.is_finished(strips, now));
+
+        for strip in 0..self.strips.len() {
+            let (candidates, urgent) = self.candidates(strip, now);
+            let current = self.current[strip];
+            if current.key.is_some_and(|key| candidates.contains(&key)) {
+                continue;
+            }
+            self.advance(strip, &candidates, urgent);
+        }
+    }
+
+    /// Call when a strip finishes a full scroll pass: counts the pass and
+    /// moves that strip on to the next message in the rotation
+    pub fn pass_complete(&mut self, strip: usize, now: f32) {
+        let Some(&current) = self.current.get(strip) else {
+            return;
+        };
+        if let Some((_, id)) = current.key {
+            if let Some(entry) = self.entries.iter_mut().find(|e| e.id == id) {
+                entry.shows[strip] += 1;
+            }
+        }
+        let strips = &self.strips;
+        self.entries.retain(|e| !e.is_finished(strips, now));
+
+        let (candidates, urgent) = self.candidates(strip, now);
+        self.advance(strip, &candidates, urgent);
+    }
+
+    /// Move a strip on to the next of `candidates`. Urgent messages start from
+    /// the first; when they end, the rotation picks up after the message
+    /// they interrupted.
+    fn advance(&mut self, strip: usize, candidates: &[SortKey], urgent: bool) {
+        let showing = &mut self.current[strip];
+        let after = match (showing.urgent, urgent) {
+            (false, true) => {
+                showing.interrupted = showing.key;
+                None
+            },
+            (true, false) => showing.interrupted.take(),
+            _ => showing.key,
+        };
+        showing.key = next_after(candidates, after);
+        showing.urgent = urgent;
+    }
+
+    /// Message currently shown on a strip, with an ID that changes whenever
+    /// the message does (None means show the default text)
+    pub fn current(&self, strip: usize) -> Option<(u64, &ChyronMessage)> {
+        let (_, id) = self.current.get(strip)?.key?;
+        self.entries
+            .iter()
+            .find(|e| e.id == id)
+            .map(|e| (e.id, &e.message))
+    }
+
+    /// Live messages for a strip in rotation order (only urgent ones if any
+    /// are live), and whether they're urgent
+    fn candidates(&self, strip: usize, now: f32) -> (Vec<SortKey>, bool) {
+        let name = &self.strips[strip];
+        let live: Vec<&Entry> = self
+            .entries
+            .iter()
+            .filter(|e| e.is_live(strip, name, now))
+            .collect();
+        let urgent = live.iter().any(|e| e.message.urgent);
+        let mut keys: Vec<SortKey> = live
+            .iter()
+            .filter(|e| e.message.urgent || !urgent)
+            .map(|e| e.key())
+            .collect();
+        keys.sort_unstable();
+        (keys, urgent)
+    }
+}
+
+/// Next key in rotation order after `after`, wrapping to the start
+fn next_after(candidates: &[SortKey], after: Option<SortKey>) -> Option<SortKey> {
+    after
+        .and_then(|after| candidates.iter().find(|&&key| key > after))
+        .or_else(|| candidates.first())
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOP: usize = 0;
+    const BOTTOM: usize = 1;
+
+    fn message(text: &str, priority: i32) -> ChyronMessage {
+        ChyronMessage {
+            priority,
+            ..ChyronMessage::new(text)
+        }
+    }
+
+    fn text(manager: &ChyronManager, strip: usize) -> Option<&str> {
+        manager.current(strip).map(|(_, m)| m.text.as_str())
+    }
+
+    #[test]
+    fn test_rotation_by_priority() {
+        let mut manager = ChyronManager::new();
+        manager.push(message("low", 0), 0.0);
+        manager.push(message("high", 5), 0.0);
+        // The first message keeps the strip until its pass completes
+        assert_eq!(text(&manager, TOP), Some("low"));
+        manager.pass_complete(TOP, 1.0);
+        assert_eq!(text(&manager, TOP), Some("high"));
+        manager.pass_complete(TOP, 2.0);
+        assert_eq!(text(&manager, TOP), Some("low"));
+        // Strips rotate independently
+        assert_eq!(text(&manager, BOTTOM), Some("low"));
+    }
+
+    #[test]
+    fn test_urgent_interrupts_and_resumes() {
+        let mut manager = ChyronManager::new();
+        manager.push(message("a", 0), 0.0);
+        manager.push(message("b", 0), 0.0);
+        manager.pass_complete(TOP, 1.0);
+        assert_eq!(text(&manager, TOP), Some("b"));
+
+        manager.push(
+            ChyronMessage {
+                urgent: true,
+                repeat: Some(1),
+                target: ChyronTarget::Top,
+                ..ChyronMessage::new("alert")
+            },
+            2.0,
+        );
+        assert_eq!(text(&manager, TOP), Some("alert"));
+        assert_eq!(text(&manager, BOTTOM), Some("a"));
+
+        // One pass uses up the urgent message's repeats
+        manager.pass_complete(TOP, 3.0);
+        assert_eq!(text(&manager, TOP), Some("a"));
+    }
+
+    #[test]
+    fn test_rotation_resumes_after_the_interrupted_message() {
+        let mut manager = ChyronManager::new();
+        manager.push(message("a", 5), 0.0);
+        manager.push(message("b", 0), 0.0);
+        manager.push(message("c", -5), 0.0);
+        assert_eq!(text(&manager, TOP), Some("a"));
+
+        // The urgent message sorts between b and c...
+        manager.push(
+            ChyronMessage {
+                urgent: true,
+                ttl: 5.0,
+                target: ChyronTarget::Top,
+                ..message("alert", -1)
+            },
+            1.0,
+        );
+        assert_eq!(text(&manager, TOP), Some("alert"));
+
+        // ...but once it expires the strip carries on from a
+        manager.update(6.0);
+        assert_eq!(text(&manager, TOP), Some("b"));
+        manager.pass_complete(TOP, 7.0);
+        assert_eq!(text(&manager, TOP), Some("c"));
+    }
+
+    #[test]
+    fn test_schedule_and_expiry() {
+        let mut manager = ChyronManager::new();
+        manager.push(
+            ChyronMessage {
+                delay: 10.0,
+                ttl: 5.0,
+                ..ChyronMessage::new("later")
+            },
+            0.0,
+        );
+        assert_eq!(text(&manager, TOP), None);
+        manager.update(10.0);
+        assert_eq!(text(&manager, TOP), Some("later"));
+        manager.update(15.0);
+        assert_eq!(text(&manager, TOP), None);
+        assert!(manager.is_empty());
+    }
+
+    #[test]
+    fn test_id_replaces_message() {
+        let mut manager = ChyronManager::new();
+        let doors = |text: &str| ChyronMessage {
+            id: Some("doors".to_string()),
+            ..ChyronMessage::new(text)
+        };
+        manager.push(doors("Doors in 10"), 0.0);
+        manager.push(doors("Doors in 5"), 1.0);
+        assert_eq!(manager.len(), 1);
+        assert_eq!(text(&manager, TOP), Some("Doors in 5"));
+    }
+
+    #[test]
+    fn test_named_strips() {
+        let mut manager = ChyronManager::new();
+        manager.push(
+            ChyronMessage {
+                target: ChyronTarget::Strip("side".to_string()),
+                ..ChyronMessage::new("sideways")
+            },
+            0.0,
+        );
+        // Waits for its strip instead of being dropped
+        assert_eq!(text(&manager, TOP), None);
+        assert_eq!(manager.len(), 1);
+
+        manager.push(message("everywhere", 0), 0.0);
+        manager.pass_complete(BOTTOM, 1.0);
+        let names = ["side", "bottom"].map(String::from);
+        manager.set_strips(&names);
+        manager.update(1.0);
+        assert_eq!(text(&manager, 0), Some("sideways"));
+        // The bottom strip keeps its place in the rotation
+        assert_eq!(text(&manager, 1), Some("everywhere"));
+        assert_eq!(text(&manager, 2), None);
+
+        let target: ChyronTarget = serde_json::from_str(r#""side""#).unwrap();
+        assert_eq!(target, ChyronTarget::Strip("side".to_string()));
+        let target: ChyronTarget = serde_json::from_str(r#""top""#).unwrap();
+        assert_eq!(target, ChyronTarget::Top);
+    }
+}
@@ -0,0 +1,269 @@
+//! Chyron styles and named presets
+//!
+//! A style maps onto `StyledScroller`'s builders. Every field is optional:
+//! message fields win over the named preset, which wins over the "default"
+//! preset, which wins over the built-in house style.
+//!
+//! ```json
+//! {
+//!   "text": "Bar closes in 10 minutes",
+//!   "style": "alert",
+//!   "speed": 1.5,
+//!   "color_fx": { "type": "Rainbow", "speed": 2.0 },
+//!   "layer": { "type": "Shadow", "offset_x": 2, "offset_y": 2, "color": [0, 0, 0] }
+//! }
+//! ```
+
+use crate::display::{
+    ColorEffect, LayerEffect, OffsetEffect, ScrollDirection, StyledScroller, VisibilityEffect,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Style overrides for a chyron
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChyronStyle {
+    /// Scroll direction for every strip (default: set by the strip layout)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direction: Option<ScrollDirection>,
+    /// Speed as a multiple of the default (10% of the strip length per second)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f32>,
+    /// Text size as a multiple of the default (1/12.6 of the strip thickness per pixel)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f32>,
+    /// Base text color, used when `color_fx` is None. Setting a color
+    /// without a `color_fx` turns off the house gradient.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<(u8, u8, u8)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<OffsetEffect>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_fx: Option<ColorEffect>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<VisibilityEffect>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer: Option<LayerEffect>,
+}
+
+impl ChyronStyle {
+    /// Combine with a fallback: fields set on `self` win. `color` and
+    /// `color_fx` go together, so a plain color on `self` isn't painted over
+    /// by the fallback's effect.
+    pub fn or(&self, fallback: &ChyronStyle) -> ChyronStyle {
+        ChyronStyle {
+            direction: self.direction.or(fallback.direction),
+            speed: self.speed.or(fallback.speed),
+            scale: self.scale.or(fallback.scale),
+            color: self.color.or(fallback.color),
+            offset: self.offset.or(fallback.offset),
+            color_fx: if self.color.is_some() {
+                self.color_fx
+            } else {
+                self.color_fx.or(fallback.color_fx)
+            },
+            visibility: self.visibility.or(fallback.visibility),
+            layer: self.layer.or(fallback.layer),
+        }
+    }
+
+    /// The color effect to use: the house gradient unless the style picks
+    /// an effect, or a plain color
+    pub fn color_effect(&self) -> ColorEffect {
+        match (self.color_fx, self.color) {
+            (Some(effect), _) => effect,
+            (None, Some(_)) => ColorEffect::None,
+            (None, None) => ColorEffect::Gradient {
+                start: (255, 100, 255),
+                end: (100, 255, 255),
+            },
+        }
+    }
+
+    /// Build a scroller for a strip `length` pixels long and `thickness` thick,
+    /// scrolling `direction` unless the style says otherwise.
+    /// Unset fields use the house style, scaled to the strip size.
+    pub fn build(
+        &self,
+        text: &str,
+        direction: ScrollDirection,
+        length: u32,
+        thickness: u32,
+    ) -> StyledScroller {
+        // 1/12.6 of the strip thickness (the house strip is 12.6% of the
+        // screen height, giving 1% of the height per pixel)
+        let base_scale = (thickness as f32 / 12.6).max(1.0);
+        // Fractional: bitmap fonts round to whole steps, outline fonts render exactly
+        let scale = (base_scale * self.scale.unwrap_or(1.0)).max(1.0);
+        let speed = length as f32 * 0.1 * self.speed.unwrap_or(1.0);
+        let orbital_radius = thickness as f32 * 0.16;
+        let direction = self.direction.unwrap_or(direction);
+        let (r, g, b) = self.color.unwrap_or((255, 255, 255));
+
+        let mut scroller = StyledScroller::new(text)
+            .direction(direction)
+            .speed(speed)
+            .fractional_scale(scale)
+            .color(r, g, b)
+            .offset(self.offset.unwrap_or(OffsetEffect::Circle {
+                radius: orbital_radius,
+                speed: 3.0,
+            }))
+            .color_fx(self.color_effect())
+            .visibility(self.visibility.unwrap_or(VisibilityEffect::None))
+            .layer(self.layer.unwrap_or(LayerEffect::None));
+        scroller.set_screen_width(length);
+        scroller
+    }
+}
+
+/// Named style presets: built-ins plus any from the config file
+#[derive(Debug, Clone)]
+pub struct StyleSheet {
+    presets: BTreeMap<String, ChyronStyle>,
+}
+
+impl Default for StyleSheet {
+    fn default() -> Self {
+        Self::new(&BTreeMap::new())
+    }
+}
+
+impl StyleSheet {
+    /// Built-in presets, replaced or extended by `presets`
+    pub fn new(presets: &BTreeMap<String, ChyronStyle>) -> Self {
+        let mut all = BTreeMap::new();
+        all.insert(
+            "alert".to_string(),
+            ChyronStyle {
+                speed: Some(1.3),
+                color: Some((255, 40, 40)),
+                offset: Some(OffsetEffect::None),
+                color_fx: Some(ColorEffect::None),
+                visibility: Some(VisibilityEffect::Strobe { rate: 2.0 }),
+                layer: Some(LayerEffect::Outline { color: (0, 0, 0) }),
+                ..ChyronStyle::default()
+            },
+        );
+        all.insert(
+            "info".to_string(),
+            ChyronStyle {
+                speed: Some(0.7),
+                offset: Some(OffsetEffect::Wave {
+                    amplitude: 2.0,
+                    frequency: 1.0,
+                }),
+                color_fx: Some(ColorEffect::Gradient {
+                    start: (120, 200, 255),
+                    end: (220, 240, 255),
+                }),
+                ..ChyronStyle::default()
+            },
+        );
+        all.extend(
+            presets
+                .iter()
+                .map(|(name, style)| (name.clone(), style.clone())),
+        );
+        Self { presets: all }
+    }
+
+    /// Names of all presets
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.presets.keys().map(String::as_str)
+    }
+
+    /// Resolve a message's style: overrides, then the named preset, then "default".
+    /// Unknown preset names are reported and ignored.
+    pub fn resolve(&self, preset: Option<&str>, overrides: &ChyronStyle) -> ChyronStyle {
+        let named = preset.and_then(|name| {
+            let style = self.presets.get(name);
+            if style.is_none() {
+                eprintln!("Chyron: Unknown style '{}'", name);
+            }
+            style
+        });
+        let default = self.presets.get("default").cloned().unwrap_or_default();
+        let base = named.map_or_else(|| default.clone(), |style| style.or(&default));
+        overrides.or(&base)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chyron::ChyronMessage;
+
+    #[test]
+    fn test_message_overrides_preset() {
+        let msg = ChyronMessage::from_json(
+            r#"{"text": "hi", "style": "alert", "speed": 2.0,
+                "color_fx": {"type": "Rainbow", "speed": 1.0}}"#,
+        )
+        .unwrap();
+        let style = StyleSheet::default().resolve(msg.style.as_deref(), &msg.overrides);
+        assert_eq!(style.speed, Some(2.0));
+        assert_eq!(style.color_fx, Some(ColorEffect::Rainbow { speed: 1.0 }));
+        // Untouched fields come from the preset
+        assert_eq!(style.color, Some((255, 40, 40)));
+    }
+
+    #[test]
+    fn test_message_color_over_preset_effect() {
+        let msg =
+            ChyronMessage::from_json(r#"{"text": "hi", "style": "info", "color": [255, 0, 0]}"#)
+                .unwrap();
+        let style = StyleSheet::default().resolve(msg.style.as_deref(), &msg.overrides);
+        assert_eq!(style.color, Some((255, 0, 0)));
+        assert_eq!(style.color_effect(), ColorEffect::None);
+        // The rest of the preset still applies
+        assert_eq!(style.speed, Some(0.7));
+    }
+
+    #[test]
+    fn test_color_only_style_is_plain() {
+        // A config preset replaces the built-in one wholesale
+        let mut presets = BTreeMap::new();
+        let red = ChyronStyle {
+            color: Some((255, 0, 0)),
+            ..ChyronStyle::default()
+        };
+        presets.insert("alert".to_string(), red.clone());
+        let style = StyleSheet::new(&presets).resolve(Some("alert"), &ChyronStyle::default());
+        assert_eq!(style.color_effect(), ColorEffect::None);
+
+        // An explicit effect still wins, and no color keeps the house gradient
+        let rainbow = ChyronStyle {
+            color_fx: Some(ColorEffect::Rainbow { speed: 1.0 }),
+            ..red
+        };
+        assert_eq!(rainbow.color_effect(), ColorEffect::Rainbow { speed: 1.0 });
+        assert!(matches!(
+            ChyronStyle::default().color_effect(),
+            ColorEffect::Gradient { .. }
+        ));
+    }
+
+    #[test]
+    fn test_config_default_applies_under_presets() {
+        let mut presets = BTreeMap::new();
+        presets.insert(
+            "default".to_string(),
+            ChyronStyle {
+                scale: Some(2.0),
+                direction: Some(ScrollDirection::Rightward),
+                ..ChyronStyle::default()
+            },
+        );
+        let sheet = StyleSheet::new(&presets);
+        let style = sheet.resolve(Some("info"), &ChyronStyle::default());
+        assert_eq!(style.scale, Some(2.0));
+        assert_eq!(style.speed, Some(0.7));
+        assert_eq!(
+            sheet
+                .resolve(Some("missing"), &ChyronStyle::default())
+                .direction,
+            Some(ScrollDirection::Rightward)
+        );
+    }
+}
@@ -0,0 +1,81 @@
+//! Application config file
+//!
+//! Optional settings loaded from `wallfacer.json` (or `--config FILE`).
+//! Every section can be omitted.
+//!
+//! ```json
+//! {
+//!   "fonts": ["fonts/unifont.bdf", "fonts/ter-u16n.psf"],
+//!   "ansi_dir": "art/ansi",
+//!   "chyron_strips": [
+//!     { "name": "top", "edge": { "type": "Top" } },
+//!     { "name": "side", "edge": { "type": "Left" }, "height": 0.08 }
+//!   ],
+//!   "overlays": [
+//!     { "id": "clock", "type": "Clock", "x": 0.98, "y": 0.16, "anchor": "top_right" },
+//!     { "id": "tickets", "type": "Qr", "data": "https://example.com", "x": 0.02, "y": 0.84,
+//!       "anchor": "bottom_left", "size": 0.25 }
+//!   ],
+//!   "outputs": [
+//!     { "name": "left", "source": [0, 0, 1920, 1080], "position": [0, 0],
+//!       "blend": { "right": 320 } },
+//!     { "name": "right", "source": [1600, 0, 1920, 1080], "position": [1920, 0],
+//!       "blend": { "left": 320 } }
+//!   ],
+//!   "feeds": [
+//!     { "name": "news", "type": "Rss", "path": "/var/cache/news.xml", "interval": 600 }
+//!   ],
+//!   "chyron_styles": {
+//!     "default": { "scale": 1.5 },
+//!     "alert": { "color": [255, 0, 0], "visibility": { "type": "Strobe", "rate": 4.0 } },
+//!     "sponsor": { "color_fx": { "type": "Rainbow", "speed": 1.0 } }
+//!   }
+//! }
+//! ```
+
+use crate::chyron::{ChyronStyle, StripLayout};
+use crate::feeds::FeedConfig;
+use crate::output::OutputConfig;
+use crate::overlay::Widget;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Config file loaded when `--config` isn't given (ignored if missing)
+pub const DEFAULT_CONFIG: &str = "wallfacer.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Config {
+    /// Font files (BDF, PSF, TTF, OTF) tried in order before the built-in 8x8 font
+    #[serde(default)]
+    pub fonts: Vec<PathBuf>,
+    /// Directory of `.ans` files for the ANSI art slideshow
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ansi_dir: Option<PathBuf>,
+    /// Chyron strip layout (default: full-width strips at the top and bottom).
+    /// A scene's own `chyron_strips` take precedence.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chyron_strips: Option<Vec<StripLayout>>,
+    /// Overlay widgets (clock, countdown, QR code...) drawn over the effect
+    #[serde(default)]
+    pub overlays: Vec<Widget>,
+    /// Output windows, each showing part of the canvas (default: one window
+    /// showing all of it)
+    #[serde(default)]
+    pub outputs: Vec<OutputConfig>,
+    /// RSS/Atom, JSON and command feeds for the chyron
+    #[serde(default)]
+    pub feeds: Vec<FeedConfig>,
+    /// Named chyron style presets; "default" applies to every chyron
+    #[serde(default)]
+    pub chyron_styles: BTreeMap<String, ChyronStyle>,
+}
+
+impl Config {
+    /// Load config from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&json).map_err(|e| e.to_string())
+    }
+}
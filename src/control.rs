@@ -1,8 +1,13 @@
 //! Remote control via Unix socket
 //!
 //! Accepts commands over a Unix socket to control the application
-//! as if keyboard keys were pressed.
+//! as if keyboard keys were pressed. The same command language is used by
+//! the MQTT command topics (see `mqtt`).
 
+use crate::chyron::ChyronMessage;
+use crate::output::{ColorAdjust, ColorCorrection};
+use crate::overlay::Widget;
+use crate::regions::RegionContent;
 use std::io::{BufRead, BufReader};
 use std::os::unix::net::{UnixListener, UnixStream};
 use std::sync::mpsc::{self, Receiver, Sender};
@@ -21,6 +26,195 @@ pub enum Command {
     Load,
     Quit,
     Effect(usize),
+    /// Select an effect by name (case, spaces and underscores ignored)
+    EffectName(String),
+    /// Set a master parameter (speed, dimmer, hue, strobe, glow)
+    Param { name: String, value: f32 },
+    /// Load a scene from a specific file
+    LoadScene(String),
+    /// Show a chyron message
+    Chyron(ChyronMessage),
+    /// Set or clear (None) the content drawn for a named region
+    RegionContent {
+        region: String,
+        content: Option<RegionContent>,
+    },
+    /// Add or replace an overlay widget, or remove it (None)
+    Overlay {
+        id: String,
+        widget: Option<Widget>,
+    },
+    /// Show or hide an overlay widget
+    OverlayEnabled { id: String, enabled: bool },
+    /// Change an output's colour correction ("" is the single/unnamed output)
+    Color { output: String, adjust: ColorAdjust },
+}
+
+/// Settings a `color` command can change
+const COLOR_KEYS: [&str; 7] = [
+    "brightness",
+    "contrast",
+    "gain",
+    "temperature",
+    "gamma",
+    "lut",
+    "reset",
+];
+
+impl Command {
+    /// Parse a single command line.
+    ///
+    /// Accepted forms: `next`, `prev`, `calibrate`, `fps`, `save`, `load`,
+    /// `quit`, `N`, `effect N`, `effect NAME`, `param NAME VALUE`,
+    /// `scene load [PATH]`, `scene save`, `chyron TEXT` or `chyron {"text": ...}`,
+    /// `region NAME text TEXT`, `region NAME marquee TEXT`, `region NAME clear`
+    /// or `region NAME {"type": ...}`. In region text, `\n` starts a new paragraph.
+    /// `overlay {"id": ..., "type": ...}` adds or replaces an overlay widget;
+    /// `overlay ID show`, `overlay ID hide` and `overlay ID remove` act on one.
+    /// `color [OUTPUT] KEY VALUE` adjusts an output's colour, where KEY is
+    /// `brightness`, `contrast`, `temperature`, `gain` or `gamma` (one value,
+    /// or red green blue), `lut PATH` or `lut off`; `color [OUTPUT] reset` and
+    /// `color [OUTPUT] {"gain": ...}` replace it all.
+    pub fn parse(line: &str) -> Option<Command> {
+        let line = line.trim();
+        let (word, rest) = match line.split_once(char::is_whitespace) {
+            Some((word, rest)) => (word.to_lowercase(), rest.trim()),
+            None => (line.to_lowercase(), ""),
+        };
+
+        match (word.as_str(), rest) {
+            ("left" | "prev", "") => Some(Command::Left),
+            ("right" | "next", "") => Some(Command::Right),
+            ("tab" | "calibrate", "") => Some(Command::Tab),
+            ("f" | "fps", "") => Some(Command::ToggleFps),
+            ("s" | "save", "") => Some(Command::Save),
+            ("l" | "load", "") => Some(Command::Load),
+            ("q" | "quit" | "exit", "") => Some(Command::Quit),
+            ("effect", rest) if !rest.is_empty() => Some(
+                rest.parse()
+                    .map_or_else(|_| Command::EffectName(rest.to_string()), Command::Effect),
+            ),
+            ("param", rest) => {
+                let (name, value) = rest.split_once(char::is_whitespace)?;
+                Some(Command::Param {
+                    name: name.to_lowercase(),
+                    value: Self::parse_value(value.trim())?,
+                })
+            },
+            ("scene", rest) => {
+                let (action, path) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                match (action.to_lowercase().as_str(), path.trim()) {
+                    ("load", "") => Some(Command::Load),
+                    ("load", path) => Some(Command::LoadScene(path.to_string())),
+                    ("save", "") => Some(Command::Save),
+                    _ => None,
+                }
+            },
+            // A JSON payload, unless it's plain text that starts with a markup tag
+            ("chyron", rest)
+                if rest.starts_with('{')
+                    && serde_json::from_str::<serde_json::Value>(rest).is_ok() =>
+            {
+                ChyronMessage::from_json(rest).ok().map(Command::Chyron)
+            },
+            ("chyron", rest) if !rest.is_empty() => Some(Command::Chyron(ChyronMessage::new(rest))),
+            ("region", rest) => {
+                let (region, rest) = rest.split_once(char::is_whitespace)?;
+                let rest = rest.trim();
+                let (action, text) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                let text = text.trim().replace("\\n", "\n");
+                let content = match (action.to_lowercase().as_str(), text.as_str()) {
+                    ("clear", "") => None,
+                    ("text", text) if !text.is_empty() => Some(RegionContent::text(text)),
+                    ("marquee", text) if !text.is_empty() => Some(RegionContent::marquee(text)),
+                    _ if rest.starts_with('{') => Some(serde_json::from_str(rest).ok()?),
+                    _ => return None,
+                };
+                Some(Command::RegionContent {
+                    region: region.to_string(),
+                    content,
+                })
+            },
+            ("overlay", rest) if rest.starts_with('{') => {
+                let widget = Widget::from_json(rest).ok()?;
+                Some(Command::Overlay {
+                    id: widget.id.clone(),
+                    widget: Some(widget),
+                })
+            },
+            ("overlay", rest) => {
+                let (id, action) = rest.split_once(char::is_whitespace)?;
+                let id = id.to_string();
+                match action.trim().to_lowercase().as_str() {
+                    "show" | "on" => Some(Command::OverlayEnabled { id, enabled: true }),
+                    "hide" | "off" => Some(Command::OverlayEnabled { id, enabled: false }),
+                    "remove" | "clear" => Some(Command::Overlay { id, widget: None }),
+                    _ => None,
+                }
+            },
+            ("color" | "colour", rest) => {
+                let (first, after) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                let is_key = |word: &str| {
+                    word.starts_with('{') || COLOR_KEYS.contains(&word.to_lowercase().as_str())
+                };
+                let (output, rest) = if is_key(first) {
+                    ("", rest)
+                } else {
+                    (first, after.trim())
+                };
+                Some(Command::Color {
+                    output: output.to_string(),
+                    adjust: Self::parse_color_adjust(rest)?,
+                })
+            },
+            (word, "") => word.parse().ok().map(Command::Effect),
+            _ => None,
+        }
+    }
+
+    /// Parse the `KEY VALUE` part of a `color` command
+    fn parse_color_adjust(rest: &str) -> Option<ColorAdjust> {
+        if rest.starts_with('{') {
+            return serde_json::from_str::<ColorCorrection>(rest)
+                .ok()
+                .map(ColorAdjust::Set);
+        }
+        let (key, value) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let value = value.trim();
+        let number = || value.parse::<f32>().ok();
+        // One value for all three channels, or red green blue
+        let channels = || -> Option<[f32; 3]> {
+            let values: Vec<f32> = value
+                .split_whitespace()
+                .map(|v| v.parse().ok())
+                .collect::<Option<_>>()?;
+            match *values.as_slice() {
+                [v] => Some([v; 3]),
+                [r, g, b] => Some([r, g, b]),
+                _ => None,
+            }
+        };
+        match (key.to_lowercase().as_str(), value) {
+            ("brightness", _) => number().map(ColorAdjust::Brightness),
+            ("contrast", _) => number().map(ColorAdjust::Contrast),
+            ("temperature", _) => number().map(ColorAdjust::Temperature),
+            ("gain", _) => channels().map(ColorAdjust::Gain),
+            ("gamma", _) => channels().map(ColorAdjust::Gamma),
+            ("lut", "off" | "none") => Some(ColorAdjust::Lut(None)),
+            ("lut", path) if !path.is_empty() => Some(ColorAdjust::Lut(Some(path.into()))),
+            ("reset", "") => Some(ColorAdjust::Reset),
+            _ => None,
+        }
+    }
+
+    /// Parse a parameter value: a number, or on/off style booleans
+    fn parse_value(value: &str) -> Option<f32> {
+        match value.to_lowercase().as_str() {
+            "on" | "true" | "yes" => Some(1.0),
+            "off" | "false" | "no" => Some(0.0),
+            v => v.parse().ok(),
+        }
+    }
 }
 
 /// Controller that listens for commands on a Unix socket
@@ -78,8 +272,8 @@ impl Controller {
 
     fn handle_client(stream: UnixStream, sender: Sender<Command>) {
         let reader = BufReader::new(stream);
-        for line in reader.lines().flatten() {
-            if let Some(cmd) = Self::parse_command(&line) {
+        for line in reader.lines().map_while(Result::ok) {
+            if let Some(cmd) = Command::parse(&line) {
                 if sender.send(cmd).is_err() {
                     break;
                 }
@@ -87,27 +281,6 @@ impl Controller {
         }
     }
 
-    fn parse_command(line: &str) -> Option<Command> {
-        let line = line.trim().to_lowercase();
-        match line.as_str() {
-            "left" | "prev" => Some(Command::Left),
-            "right" | "next" => Some(Command::Right),
-            "tab" | "calibrate" => Some(Command::Tab),
-            "f" | "fps" => Some(Command::ToggleFps),
-            "s" | "save" => Some(Command::Save),
-            "l" | "load" => Some(Command::Load),
-            "q" | "quit" | "exit" => Some(Command::Quit),
-            _ => {
-                // Try to parse "effect N" or just a number
-                if let Some(rest) = line.strip_prefix("effect ") {
-                    rest.trim().parse().ok().map(Command::Effect)
-                } else {
-                    line.parse().ok().map(Command::Effect)
-                }
-            }
-        }
-    }
-
     /// Get any pending commands (non-blocking)
     pub fn poll(&self) -> Vec<Command> {
         let mut commands = Vec::new();
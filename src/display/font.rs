@@ -1,10 +1,14 @@
 //! 8x8 Bitmap Font System
 //!
 //! Public domain font data from: https://github.com/dhepper/font8x8
+//!
+//! The text functions draw through the default font (see `display::fonts`),
+//! which is this 8x8 font unless another one has been loaded.
 
+use super::fonts::with_default_font;
 use super::PixelBuffer;
 
-/// Font glyph dimensions
+/// Built-in font glyph dimensions
 pub const GLYPH_WIDTH: u32 = 8;
 pub const GLYPH_HEIGHT: u32 = 8;
 
@@ -62,9 +66,7 @@ pub fn draw_char_scaled(
     b: u8,
     scale: u32,
 ) {
-    if let Some(glyph) = get_glyph(ch) {
-        draw_glyph_scaled(buffer, x, y, glyph, r, g, b, scale);
-    }
+    with_default_font(|font| font.draw_char(buffer, x, y, ch, r, g, b, scale as f32));
 }
 
 /// Draw a text string at the given position
@@ -83,12 +85,7 @@ pub fn draw_text_scaled(
     b: u8,
     scale: u32,
 ) {
-    let char_width = GLYPH_WIDTH * scale;
-    let mut cursor_x = x;
-    for ch in text.chars() {
-        draw_char_scaled(buffer, cursor_x, y, ch, r, g, b, scale);
-        cursor_x += char_width as i32;
-    }
+    with_default_font(|font| font.draw_text(buffer, x, y, text, r, g, b, scale as f32));
 }
 
 /// Calculate the pixel width of a text string
@@ -100,7 +97,13 @@ pub fn text_width(text: &str) -> u32 {
 /// Calculate the pixel width of a scaled text string
 #[inline]
 pub fn text_width_scaled(text: &str, scale: u32) -> u32 {
-    text.chars().count() as u32 * GLYPH_WIDTH * scale
+    with_default_font(|font| font.text_width(text, scale as f32))
+}
+
+/// Calculate the pixel height of a line of scaled text
+#[inline]
+pub fn text_height_scaled(scale: u32) -> u32 {
+    with_default_font(|font| font.height(scale as f32))
 }
 
 /// Draw text centered horizontally on the screen
@@ -134,7 +137,7 @@ pub fn draw_text_boxed(
     padding: u32,
 ) {
     let text_w = text_width_scaled(text, scale);
-    let text_h = GLYPH_HEIGHT * scale;
+    let text_h = text_height_scaled(scale);
     let box_w = text_w + padding * 2;
     let box_h = text_h + padding * 2;
     let box_x = (buffer.width() - box_w) as i32 / 2;
@@ -0,0 +1,177 @@
+//! BDF (Glyph Bitmap Distribution Format) parser
+//!
+//! Reads the subset of BDF 2.1 that bitmap fonts actually use: the font
+//! bounding box, FONT_ASCENT/FONT_DESCENT and each glyph's ENCODING, DWIDTH,
+//! BBX and BITMAP. Encodings are taken as Unicode codepoints, which holds for
+//! ISO10646 fonts such as GNU Unifont and the X11 misc-fixed family.
+
+use super::{BitmapFace, Glyph};
+use std::collections::HashMap;
+
+/// Glyph being read between STARTCHAR and ENDCHAR
+#[derive(Default)]
+struct PendingGlyph {
+    encoding: Option<u32>,
+    advance: Option<i32>,
+    bbx: Option<(u32, u32, i32, i32)>,
+    rows: Vec<u8>,
+    in_bitmap: bool,
+}
+
+/// Parse a BDF font
+pub fn parse(source: &str) -> Result<BitmapFace, String> {
+    let mut bbox: Option<(u32, u32, i32, i32)> = None;
+    let mut ascent: Option<i32> = None;
+    let mut descent: Option<i32> = None;
+    let mut bitmaps = Vec::new();
+    let mut glyph: Option<PendingGlyph> = None;
+
+    for (number, line) in source.lines().enumerate() {
+        let mut words = line.split_whitespace();
+        let Some(keyword) = words.next() else {
+            continue;
+        };
+        let values: Vec<&str> = words.collect();
+        let context = |e: String| format!("line {}: {}", number + 1, e);
+
+        let Some(pending) = glyph.as_mut() else {
+            match keyword {
+                "STARTFONT" if !values.first().is_some_and(|v| v.starts_with('2')) => {
+                    return Err(format!("Unsupported BDF version {:?}", values.first()));
+                },
+                "FONTBOUNDINGBOX" => bbox = Some(parse_box(&values).map_err(context)?),
+                "FONT_ASCENT" => ascent = Some(parse_num(&values, 0).map_err(context)?),
+                "FONT_DESCENT" => descent = Some(parse_num(&values, 0).map_err(context)?),
+                "STARTCHAR" => glyph = Some(PendingGlyph::default()),
+                _ => {},
+            }
+            continue;
+        };
+
+        if pending.in_bitmap && keyword != "ENDCHAR" {
+            let row = (0..keyword.len() / 2)
+                .map(|i| u8::from_str_radix(&keyword[i * 2..i * 2 + 2], 16))
+                .collect::<Result<Vec<u8>, _>>()
+                .map_err(|e| context(format!("Bad bitmap row '{}': {}", keyword, e)))?;
+            let stride = pending.bbx.map_or(0, |(w, ..)| w.div_ceil(8) as usize);
+            pending
+                .rows
+                .extend((0..stride).map(|i| row.get(i).copied().unwrap_or(0)));
+            continue;
+        }
+        match keyword {
+            // Unencoded glyphs are -1; some fonts put the real code in a second field
+            "ENCODING" => {
+                let code: i64 = parse_num(&values, 0).map_err(context)?;
+                let alt: Option<i64> = parse_num(&values, 1).ok();
+                pending.encoding = u32::try_from(code)
+                    .ok()
+                    .or_else(|| alt.and_then(|c| u32::try_from(c).ok()));
+            },
+            "DWIDTH" => pending.advance = Some(parse_num(&values, 0).map_err(context)?),
+            "BBX" => pending.bbx = Some(parse_box(&values).map_err(context)?),
+            "BITMAP" => pending.in_bitmap = true,
+            "ENDCHAR" => {
+                if let Some(done) = glyph.take() {
+                    bitmaps.push(done);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    let (cell_w, cell_h, _, cell_y) = bbox.ok_or("Missing FONTBOUNDINGBOX")?;
+    let ascent = ascent.unwrap_or(cell_h as i32 + cell_y);
+    let descent = descent.unwrap_or(-cell_y);
+    let height = (ascent + descent).max(1) as u32;
+
+    let mut glyphs = HashMap::new();
+    for pending in bitmaps {
+        let Some(ch) = pending.encoding.and_then(char::from_u32) else {
+            continue;
+        };
+        let (w, h, x_off, y_off) = pending.bbx.unwrap_or((cell_w, cell_h, 0, cell_y));
+        let mut glyph = Glyph::from_rows(w, h, &pending.rows, w.div_ceil(8) as usize);
+        glyph.left = x_off;
+        glyph.top = ascent - (y_off + h as i32);
+        glyph.advance = pending.advance.unwrap_or(cell_w as i32) as f32;
+        glyphs.insert(ch, glyph);
+    }
+    if glyphs.is_empty() {
+        return Err("No encoded glyphs".to_string());
+    }
+
+    Ok(BitmapFace::new(height, ascent, cell_w, glyphs))
+}
+
+fn parse_num<T: std::str::FromStr>(values: &[&str], index: usize) -> Result<T, String> {
+    let value = values.get(index).ok_or("Missing value")?;
+    value
+        .parse()
+        .map_err(|_| format!("Invalid number '{}'", value))
+}
+
+fn parse_box(values: &[&str]) -> Result<(u32, u32, i32, i32), String> {
+    Ok((
+        parse_num(values, 0)?,
+        parse_num(values, 1)?,
+        parse_num(values, 2)?,
+        parse_num(values, 3)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Face;
+    use super::*;
+
+    const FONT: &str = "STARTFONT 2.1
+FONT -misc-test-medium-r-normal--10-100-75-75-c-60-iso10646-1
+SIZE 10 75 75
+FONTBOUNDINGBOX 6 10 0 -2
+STARTPROPERTIES 2
+FONT_ASCENT 8
+FONT_DESCENT 2
+ENDPROPERTIES
+CHARS 2
+STARTCHAR emdash
+ENCODING 8212
+SWIDTH 1000 0
+DWIDTH 6 0
+BBX 6 1 0 3
+BITMAP
+FC
+ENDCHAR
+STARTCHAR unencoded
+ENCODING -1
+DWIDTH 6 0
+BBX 1 1 0 0
+BITMAP
+80
+ENDCHAR
+ENDFONT
+";
+
+    #[test]
+    fn test_parse_bdf() {
+        let face = parse(FONT).unwrap();
+        assert_eq!(face.len(), 1);
+        assert_eq!(face.height(1.0), 10);
+        assert_eq!(face.ascent(1.0), 8);
+
+        let dash = face.glyph('\u{2014}', 1.0).unwrap();
+        assert_eq!((dash.width, dash.height), (6, 1));
+        // Bottom of the box sits 3 above the baseline
+        assert_eq!(dash.top, 4);
+        assert!(dash.coverage.iter().all(|&c| c == 255));
+
+        let big = face.glyph('\u{2014}', 2.0).unwrap();
+        assert_eq!((big.width, big.top, big.advance), (12, 8, 12.0));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("STARTFONT 2.1\nENDFONT\n").is_err());
+        assert!(parse(&FONT.replace("FC", "ZZ")).is_err());
+    }
+}
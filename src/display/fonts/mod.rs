@@ -0,0 +1,604 @@
+//! Loadable Fonts
+//!
+//! A `Font` is a fallback chain of faces: each character is drawn with the
+//! first face that has a glyph for it. The built-in font8x8 face always ends
+//! the chain, so loaded fonts only need to cover the characters they add.
+//!
+//! Supported formats:
+//! - BDF (`.bdf`) - X11 bitmap fonts, any cell size, Unicode encodings
+//! - PSF (`.psf`, `.psfu`) - Linux console fonts, PSF1 and PSF2 with Unicode tables
+//! - TrueType/OpenType (`.ttf`, `.otf`, `.ttc`) - outline fonts, anti-aliased
+//!
+//! `Font::cp437()` is a built-in 8x16 face with the IBM PC character set,
+//! for ANSI art and DOS-style text.
+//!
+//! Sizes are given as a scale relative to the 8px built-in font, so
+//! `scale = 2.0` is a 16px line in any font. Bitmap faces snap to whole
+//! multiples of their cell; outline faces render at any fractional size
+//! with proportional advances and kerning.
+//!
+//! Faces in a chain are aligned on their baselines. The free `draw_text*`
+//! functions in `display::font` render through the default font, which
+//! starts as the built-in one and can be replaced with `set_default_font`.
+
+mod bdf;
+pub mod cp437;
+mod outline;
+mod psf;
+
+pub use outline::OutlineFace;
+
+use super::font::{get_glyph, GLYPH_HEIGHT, GLYPH_WIDTH};
+use super::PixelBuffer;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError, RwLock};
+
+/// A rendered glyph: coverage bitmap plus placement metrics
+#[derive(Debug, Clone, PartialEq)]
+pub struct Glyph {
+    pub width: u32,
+    pub height: u32,
+    /// Bitmap offset right of the pen position
+    pub left: i32,
+    /// Bitmap offset below the top of the line
+    pub top: i32,
+    /// Pen movement after this glyph
+    pub advance: f32,
+    /// Row-major coverage, 0 (empty) to 255 (solid)
+    pub coverage: Vec<u8>,
+}
+
+impl Glyph {
+    /// Build a glyph from packed 1-bit rows (most significant bit leftmost)
+    fn from_rows(width: u32, height: u32, rows: &[u8], stride: usize) -> Self {
+        let mut coverage = vec![0; (width * height) as usize];
+        for row in 0..height as usize {
+            for col in 0..width as usize {
+                let byte = rows.get(row * stride + col / 8).copied().unwrap_or(0);
+                if byte & (0x80 >> (col % 8)) != 0 {
+                    coverage[row * width as usize + col] = 255;
+                }
+            }
+        }
+        Self {
+            width,
+            height,
+            left: 0,
+            top: 0,
+            advance: width as f32,
+            coverage,
+        }
+    }
+
+    /// Nearest-neighbor upscale
+    fn scaled(&self, scale: u32) -> Self {
+        let width = self.width * scale;
+        let height = self.height * scale;
+        let mut coverage = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            let row = (y / scale * self.width) as usize;
+            coverage.extend((0..width).map(|x| self.coverage[row + (x / scale) as usize]));
+        }
+        Self {
+            width,
+            height,
+            left: self.left * scale as i32,
+            top: self.top * scale as i32,
+            advance: self.advance * scale as f32,
+            coverage,
+        }
+    }
+
+    /// Coverage at a bitmap position
+    #[inline]
+    pub fn at(&self, x: u32, y: u32) -> u8 {
+        self.coverage[(y * self.width + x) as usize]
+    }
+
+    /// Bilinear coverage between pixel centres (0 outside the bitmap)
+    pub fn sample(&self, x: f32, y: f32) -> u8 {
+        let (fx, fy) = (x.floor(), y.floor());
+        let (tx, ty) = (x - fx, y - fy);
+        let texel = |x: i32, y: i32| {
+            if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+                0.0
+            } else {
+                f32::from(self.at(x as u32, y as u32))
+            }
+        };
+        let (x0, y0) = (fx as i32, fy as i32);
+        let top = texel(x0, y0) * (1.0 - tx) + texel(x0 + 1, y0) * tx;
+        let bottom = texel(x0, y0 + 1) * (1.0 - tx) + texel(x0 + 1, y0 + 1) * tx;
+        (top * (1.0 - ty) + bottom * ty).round() as u8
+    }
+}
+
+/// A source of glyphs. `scale` is relative to the 8px built-in font.
+pub trait Face: Send + Sync {
+    /// Line height in pixels
+    fn height(&self, scale: f32) -> u32;
+    /// Distance from the top of the line to the baseline
+    fn ascent(&self, scale: f32) -> i32;
+    /// Advance for characters no face in the chain has
+    fn cell_width(&self, scale: f32) -> f32;
+    /// Glyph for a character
+    fn glyph(&self, ch: char, scale: f32) -> Option<Arc<Glyph>>;
+    /// Adjustment to the advance between two characters (negative pulls them together)
+    fn kerning(&self, _left: char, _right: char, _scale: f32) -> f32 {
+        0.0
+    }
+}
+
+/// A bitmap face: glyphs keyed by Unicode codepoint
+pub struct BitmapFace {
+    height: u32,
+    ascent: i32,
+    cell_width: u32,
+    glyphs: HashMap<char, Arc<Glyph>>,
+    /// Upscaled glyphs, built on first use
+    scaled: Mutex<HashMap<(char, u32), Arc<Glyph>>>,
+}
+
+impl BitmapFace {
+    fn new(height: u32, ascent: i32, cell_width: u32, glyphs: HashMap<char, Glyph>) -> Self {
+        Self {
+            height,
+            ascent,
+            cell_width,
+            glyphs: glyphs
+                .into_iter()
+                .map(|(ch, g)| (ch, Arc::new(g)))
+                .collect(),
+            scaled: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The baked-in 8x8 font
+    pub fn builtin() -> Self {
+        let chars = (' '..='~').filter(|&ch| get_glyph(ch).is_some());
+        let glyphs = chars
+            .map(|ch| {
+                // font8x8 stores the leftmost pixel in the low bit
+                let rows: Vec<u8> = get_glyph(ch)
+                    .into_iter()
+                    .flatten()
+                    .map(|bits| bits.reverse_bits())
+                    .collect();
+                (ch, Glyph::from_rows(GLYPH_WIDTH, GLYPH_HEIGHT, &rows, 1))
+            })
+            .collect();
+        Self::new(GLYPH_HEIGHT, GLYPH_HEIGHT as i32 - 1, GLYPH_WIDTH, glyphs)
+    }
+
+    /// Number of glyphs in the face
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+
+    /// Whole-pixel multiple of the native cell: bitmaps only scale cleanly by integers.
+    /// Cells taller than the built-in font's count as one step of scale on their own,
+    /// so a 16px font at scale 2 is drawn at its native size.
+    fn pixel_scale(&self, scale: f32) -> u32 {
+        let steps = (self.height as f32 / GLYPH_HEIGHT as f32).round().max(1.0);
+        (scale / steps).round().max(1.0) as u32
+    }
+}
+
+impl Face for BitmapFace {
+    fn height(&self, scale: f32) -> u32 {
+        self.height * self.pixel_scale(scale)
+    }
+
+    fn ascent(&self, scale: f32) -> i32 {
+        self.ascent * self.pixel_scale(scale) as i32
+    }
+
+    fn cell_width(&self, scale: f32) -> f32 {
+        (self.cell_width * self.pixel_scale(scale)) as f32
+    }
+
+    fn glyph(&self, ch: char, scale: f32) -> Option<Arc<Glyph>> {
+        let glyph = self.glyphs.get(&ch)?;
+        let scale = self.pixel_scale(scale);
+        if scale == 1 {
+            return Some(Arc::clone(glyph));
+        }
+        let mut scaled = self.scaled.lock().unwrap_or_else(PoisonError::into_inner);
+        let glyph = scaled
+            .entry((ch, scale))
+            .or_insert_with(|| Arc::new(glyph.scaled(scale)));
+        Some(Arc::clone(glyph))
+    }
+}
+
+/// A character placed by `Font::layout`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlacedChar {
+    pub ch: char,
+    /// Pen position relative to the start of the string
+    pub x: f32,
+    pub advance: f32,
+}
+
+/// A fallback chain of faces
+#[derive(Clone)]
+pub struct Font {
+    faces: Vec<Arc<dyn Face>>,
+}
+
+impl Default for Font {
+    /// The current default font
+    fn default() -> Self {
+        default_font()
+    }
+}
+
+impl Font {
+    /// The built-in font8x8 font on its own
+    pub fn builtin() -> Self {
+        static BUILTIN: OnceLock<Arc<BitmapFace>> = OnceLock::new();
+        let face = BUILTIN.get_or_init(|| Arc::new(BitmapFace::builtin()));
+        Self {
+            faces: vec![Arc::clone(face) as Arc<dyn Face>],
+        }
+    }
+
+    /// The built-in CP437 8x16 font, falling back to font8x8
+    pub fn cp437() -> Self {
+        static CP437: OnceLock<Arc<BitmapFace>> = OnceLock::new();
+        let face = CP437.get_or_init(|| Arc::new(cp437::face()));
+        Self {
+            faces: vec![Arc::clone(face) as Arc<dyn Face>],
+        }
+        .with_fallback(&Self::builtin())
+    }
+
+    /// Load a font file (format chosen by extension), falling back to the built-in font
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        Self::load_chain(&[path])
+    }
+
+    /// Load several font files as one chain, in priority order, ending with the built-in font
+    pub fn load_chain<P: AsRef<Path>>(paths: &[P]) -> Result<Self, String> {
+        let mut faces = Vec::new();
+        for path in paths {
+            let path = path.as_ref();
+            let face = load_face(path).map_err(|e| format!("Font '{}': {}", path.display(), e))?;
+            faces.push(face);
+        }
+        Ok(Self { faces }.with_fallback(&Self::builtin()))
+    }
+
+    /// Build a chain from faces, in priority order
+    pub fn from_faces(faces: Vec<Arc<dyn Face>>) -> Self {
+        Self { faces }
+    }
+
+    /// Append another font's faces to the end of this chain
+    pub fn with_fallback(mut self, fallback: &Font) -> Self {
+        self.faces.extend(fallback.faces.iter().cloned());
+        self
+    }
+
+    /// Line height in pixels (set by the first face)
+    pub fn height(&self, scale: f32) -> u32 {
+        self.faces.first().map_or_else(
+            || (GLYPH_HEIGHT as f32 * scale).round() as u32,
+            |f| f.height(scale),
+        )
+    }
+
+    /// Find a glyph and the face it came from, with the vertical shift that
+    /// puts it on the first face's baseline
+    fn lookup(&self, ch: char, scale: f32) -> Option<(usize, Arc<Glyph>, i32)> {
+        let ascent = self.faces.first()?.ascent(scale);
+        self.faces.iter().enumerate().find_map(|(index, face)| {
+            face.glyph(ch, scale)
+                .map(|glyph| (index, glyph, ascent - face.ascent(scale)))
+        })
+    }
+
+    /// Pen movement for a character in pixels (without kerning)
+    pub fn advance(&self, ch: char, scale: f32) -> f32 {
+        self.lookup(ch, scale).map_or_else(
+            || {
+                self.faces
+                    .first()
+                    .map_or(GLYPH_WIDTH as f32 * scale, |f| f.cell_width(scale))
+            },
+            |(_, glyph, _)| glyph.advance,
+        )
+    }
+
+    /// Pen positions for each character of a string, including kerning
+    pub fn layout<'a>(
+        &'a self,
+        text: &'a str,
+        scale: f32,
+    ) -> impl Iterator<Item = PlacedChar> + 'a {
+        let mut pen = 0.0;
+        let mut prev: Option<(char, usize)> = None;
+        text.chars().map(move |ch| {
+            let found = self.lookup(ch, scale);
+            let face = found.as_ref().map(|(index, ..)| *index);
+            // Kerning only applies between two glyphs from the same face
+            if let (Some((left, left_face)), Some(index)) = (prev, face) {
+                if left_face == index {
+                    pen += self.faces[index].kerning(left, ch, scale);
+                }
+            }
+            prev = face.map(|index| (ch, index));
+
+            let advance = found.map_or_else(|| self.advance(ch, scale), |(_, g, _)| g.advance);
+            let placed = PlacedChar {
+                ch,
+                x: pen,
+                advance,
+            };
+            pen += advance;
+            placed
+        })
+    }
+
+    /// Width of a string in pixels
+    pub fn text_width(&self, text: &str, scale: f32) -> u32 {
+        self.layout(text, scale)
+            .last()
+            .map_or(0.0, |c| c.x + c.advance)
+            .ceil()
+            .max(0.0) as u32
+    }
+
+    /// Draw a character with its line top-left at (x, y). Returns the advance.
+    pub fn draw_char(
+        &self,
+        buffer: &mut PixelBuffer,
+        x: i32,
+        y: i32,
+        ch: char,
+        r: u8,
+        g: u8,
+        b: u8,
+        scale: f32,
+    ) -> f32 {
+        self.draw_char_with(buffer, x, y, ch, (r, g, b), scale, false)
+    }
+
+    /// Draw a character flipped vertically within its line (for reflections)
+    pub fn draw_char_flipped(
+        &self,
+        buffer: &mut PixelBuffer,
+        x: i32,
+        y: i32,
+        ch: char,
+        r: u8,
+        g: u8,
+        b: u8,
+        scale: f32,
+    ) -> f32 {
+        self.draw_char_with(buffer, x, y, ch, (r, g, b), scale, true)
+    }
+
+    fn draw_char_with(
+        &self,
+        buffer: &mut PixelBuffer,
+        x: i32,
+        y: i32,
+        ch: char,
+        color: (u8, u8, u8),
+        scale: f32,
+        flipped: bool,
+    ) -> f32 {
+        let Some((_, glyph, shift)) = self.lookup(ch, scale) else {
+            return self.advance(ch, scale);
+        };
+        let line_h = self.height(scale) as i32;
+        let (r, g, b) = color;
+        for gy in 0..glyph.height {
+            let row = glyph.top + shift + gy as i32;
+            let py = if flipped {
+                y + line_h - 1 - row
+            } else {
+                y + row
+            };
+            for gx in 0..glyph.width {
+                let px = x + glyph.left + gx as i32;
+                match glyph.at(gx, gy) {
+                    0 => {},
+                    255 => buffer.set_pixel(px, py, r, g, b),
+                    a => buffer.blend_pixel(px, py, r, g, b, a),
+                }
+            }
+        }
+        glyph.advance
+    }
+
+    /// Draw a character rotated by `angle` radians (clockwise on screen), with the
+    /// middle of its advance on the baseline at (cx, cy). Returns the advance.
+    /// Glyphs are sampled bilinearly, so rotated bitmap fonts stay smooth.
+    pub fn draw_char_rotated(
+        &self,
+        buffer: &mut PixelBuffer,
+        cx: f32,
+        cy: f32,
+        angle: f32,
+        ch: char,
+        color: (u8, u8, u8),
+        scale: f32,
+    ) -> f32 {
+        let (Some((_, glyph, shift)), Some(first)) = (self.lookup(ch, scale), self.faces.first())
+        else {
+            return self.advance(ch, scale);
+        };
+        if glyph.width == 0 || glyph.height == 0 {
+            return glyph.advance;
+        }
+
+        // Glyph box relative to the pivot, before rotation
+        let x0 = glyph.left as f32 - glyph.advance / 2.0;
+        let y0 = (glyph.top + shift - first.ascent(scale)) as f32;
+        let (x1, y1) = (x0 + glyph.width as f32, y0 + glyph.height as f32);
+        let (sin, cos) = angle.sin_cos();
+
+        // Screen-space bounds of the rotated box
+        let corners = [(x0, y0), (x1, y0), (x0, y1), (x1, y1)]
+            .map(|(x, y)| (cx + x * cos - y * sin, cy + x * sin + y * cos));
+        let min_x = corners.iter().map(|c| c.0).fold(f32::MAX, f32::min).floor() as i32;
+        let max_x = corners.iter().map(|c| c.0).fold(f32::MIN, f32::max).ceil() as i32;
+        let min_y = corners.iter().map(|c| c.1).fold(f32::MAX, f32::min).floor() as i32;
+        let max_y = corners.iter().map(|c| c.1).fold(f32::MIN, f32::max).ceil() as i32;
+
+        let (r, g, b) = color;
+        for py in min_y.max(0)..=max_y.min(buffer.height() as i32 - 1) {
+            for px in min_x.max(0)..=max_x.min(buffer.width() as i32 - 1) {
+                // Rotate the pixel centre back into glyph space
+                let dx = px as f32 + 0.5 - cx;
+                let dy = py as f32 + 0.5 - cy;
+                let u = dx * cos + dy * sin - x0 - 0.5;
+                let v = -dx * sin + dy * cos - y0 - 0.5;
+                match glyph.sample(u, v) {
+                    0 => {},
+                    255 => buffer.set_pixel(px, py, r, g, b),
+                    a => buffer.blend_pixel(px, py, r, g, b, a),
+                }
+            }
+        }
+        glyph.advance
+    }
+
+    /// Draw a string with its line top-left at (x, y). Returns the width drawn.
+    pub fn draw_text(
+        &self,
+        buffer: &mut PixelBuffer,
+        x: i32,
+        y: i32,
+        text: &str,
+        r: u8,
+        g: u8,
+        b: u8,
+        scale: f32,
+    ) -> i32 {
+        self.draw_text_with(buffer, x, y, text, (r, g, b), scale, false)
+    }
+
+    /// Draw a string flipped vertically (for reflections). Returns the width drawn.
+    pub fn draw_text_flipped(
+        &self,
+        buffer: &mut PixelBuffer,
+        x: i32,
+        y: i32,
+        text: &str,
+        r: u8,
+        g: u8,
+        b: u8,
+        scale: f32,
+    ) -> i32 {
+        self.draw_text_with(buffer, x, y, text, (r, g, b), scale, true)
+    }
+
+    fn draw_text_with(
+        &self,
+        buffer: &mut PixelBuffer,
+        x: i32,
+        y: i32,
+        text: &str,
+        color: (u8, u8, u8),
+        scale: f32,
+        flipped: bool,
+    ) -> i32 {
+        let mut width = 0.0;
+        for c in self.layout(text, scale) {
+            let cx = x + c.x.round() as i32;
+            self.draw_char_with(buffer, cx, y, c.ch, color, scale, flipped);
+            width = c.x + c.advance;
+        }
+        width.round() as i32
+    }
+}
+
+/// Load one face, choosing the parser by file extension
+fn load_face(path: &Path) -> Result<Arc<dyn Face>, String> {
+    let data = fs::read(path).map_err(|e| e.to_string())?;
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    match ext.as_str() {
+        "bdf" => Ok(Arc::new(bdf::parse(&String::from_utf8_lossy(&data))?)),
+        "psf" | "psfu" => Ok(Arc::new(psf::parse(&data)?)),
+        "ttf" | "otf" | "ttc" => Ok(Arc::new(OutlineFace::new(data)?)),
+        _ => Err(format!("Unsupported font format '{}'", ext)),
+    }
+}
+
+fn default_slot() -> &'static RwLock<Font> {
+    static DEFAULT: OnceLock<RwLock<Font>> = OnceLock::new();
+    DEFAULT.get_or_init(|| RwLock::new(Font::builtin()))
+}
+
+/// Replace the font used by the free `draw_text*` functions and new scrollers
+pub fn set_default_font(font: Font) {
+    *default_slot()
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = font;
+}
+
+/// The font used by the free `draw_text*` functions and new scrollers
+pub fn default_font() -> Font {
+    with_default_font(Font::clone)
+}
+
+/// Borrow the default font without cloning it
+pub fn with_default_font<T>(f: impl FnOnce(&Font) -> T) -> T {
+    f(&default_slot()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_matches_font8x8() {
+        let font = Font::builtin();
+        let (_, glyph, shift) = font.lookup('T', 1.0).unwrap();
+        assert_eq!(shift, 0);
+        // Top row of 'T' is 0x3F: the six leftmost pixels
+        let row: Vec<u8> = (0..8).map(|x| glyph.at(x, 0)).collect();
+        assert_eq!(row, [255, 255, 255, 255, 255, 255, 0, 0]);
+        assert_eq!(font.text_width("Hi", 3.0), 48);
+        assert_eq!(font.height(2.0), 16);
+        // Bitmaps snap to whole multiples
+        assert_eq!(font.height(2.4), 16);
+    }
+
+    #[test]
+    fn test_fallback_chain() {
+        let bdf = "STARTFONT 2.1\nFONTBOUNDINGBOX 6 12 0 -2\nSTARTPROPERTIES 2\n\
+                   FONT_ASCENT 10\nFONT_DESCENT 2\nENDPROPERTIES\nCHARS 1\n\
+                   STARTCHAR eacute\nENCODING 233\nDWIDTH 6 0\nBBX 4 2 1 8\nBITMAP\n\
+                   F0\n90\nENDCHAR\nENDFONT\n";
+        let face: Arc<dyn Face> = Arc::new(bdf::parse(bdf).unwrap());
+        let font = Font::from_faces(vec![face]).with_fallback(&Font::builtin());
+
+        assert_eq!(font.height(1.0), 12);
+        assert_eq!(font.advance('é', 1.0), 6.0);
+        // 'A' comes from font8x8, lowered so its baseline (7) meets the BDF one (10)
+        let (index, glyph, shift) = font.lookup('A', 1.0).unwrap();
+        assert_eq!((index, glyph.width), (1, 8));
+        assert_eq!(shift, 3);
+        // The 12px face counts as scale 2 of the 8px one
+        assert_eq!(font.height(2.0), 12);
+        assert_eq!(font.height(4.0), 24);
+        // Characters nobody has advance by the first face's cell width
+        assert_eq!(font.advance('\u{2603}', 1.0), 6.0);
+    }
+}
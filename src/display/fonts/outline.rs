@@ -0,0 +1,130 @@
+//! Outline (TrueType/OpenType) faces
+//!
+//! Glyphs are rasterized with coverage anti-aliasing on first use and cached
+//! per size. Scale 1 is an 8px line, matching the built-in font, so outline
+//! fonts drop into layouts sized for it; fractional scales give smooth sizes
+//! in between the steps bitmap fonts are limited to.
+
+use super::{Face, Glyph};
+use crate::display::GLYPH_HEIGHT;
+use ab_glyph::{point, Font as _, FontVec, GlyphId, PxScale, ScaleFont};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Cached glyphs are dropped past this many (sizes can animate)
+const MAX_CACHED: usize = 4096;
+
+/// Rendered glyphs by (character, scale bits); None if the face lacks the character
+type GlyphCache = HashMap<(char, u32), Option<Arc<Glyph>>>;
+
+/// A TrueType/OpenType face
+pub struct OutlineFace {
+    font: FontVec,
+    cache: Mutex<GlyphCache>,
+}
+
+impl OutlineFace {
+    /// Parse a font file (the first face of a collection)
+    pub fn new(data: Vec<u8>) -> Result<Self, String> {
+        let font = FontVec::try_from_vec(data).map_err(|e| e.to_string())?;
+        Ok(Self {
+            font,
+            cache: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn px_scale(scale: f32) -> PxScale {
+        PxScale::from(GLYPH_HEIGHT as f32 * scale.max(0.1))
+    }
+
+    fn id(&self, ch: char) -> Option<GlyphId> {
+        // Glyph 0 is .notdef: the face doesn't have the character
+        Some(self.font.glyph_id(ch)).filter(|id| id.0 != 0)
+    }
+
+    fn rasterize(&self, ch: char, scale: f32) -> Option<Glyph> {
+        let id = self.id(ch)?;
+        let scaled = self.font.as_scaled(Self::px_scale(scale));
+        let advance = scaled.h_advance(id);
+        let baseline = self.ascent(scale) as f32;
+        let outlined = self
+            .font
+            .outline_glyph(id.with_scale_and_position(scaled.scale(), point(0.0, baseline)));
+
+        // Blank glyphs (spaces) have no outline but still advance
+        let Some(outlined) = outlined else {
+            return Some(Glyph {
+                width: 0,
+                height: 0,
+                left: 0,
+                top: 0,
+                advance,
+                coverage: Vec::new(),
+            });
+        };
+        let bounds = outlined.px_bounds();
+        let width = bounds.width() as u32;
+        let height = bounds.height() as u32;
+        let mut coverage = vec![0; (width * height) as usize];
+        outlined.draw(|x, y, c| {
+            if x < width && y < height {
+                coverage[(y * width + x) as usize] = (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+            }
+        });
+        Some(Glyph {
+            width,
+            height,
+            left: bounds.min.x as i32,
+            top: bounds.min.y as i32,
+            advance,
+            coverage,
+        })
+    }
+}
+
+impl Face for OutlineFace {
+    fn height(&self, scale: f32) -> u32 {
+        self.font.as_scaled(Self::px_scale(scale)).height().ceil() as u32
+    }
+
+    fn ascent(&self, scale: f32) -> i32 {
+        self.font.as_scaled(Self::px_scale(scale)).ascent().round() as i32
+    }
+
+    fn cell_width(&self, scale: f32) -> f32 {
+        // Half an em, the usual width of a figure
+        Self::px_scale(scale).x * 0.5
+    }
+
+    fn glyph(&self, ch: char, scale: f32) -> Option<Arc<Glyph>> {
+        let key = (ch, scale.to_bits());
+        let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(glyph) = cache.get(&key) {
+            return glyph.clone();
+        }
+        if cache.len() >= MAX_CACHED {
+            cache.clear();
+        }
+        let glyph = self.rasterize(ch, scale).map(Arc::new);
+        cache.insert(key, glyph.clone());
+        glyph
+    }
+
+    fn kerning(&self, left: char, right: char, scale: f32) -> f32 {
+        match (self.id(left), self.id(right)) {
+            (Some(a), Some(b)) => self.font.as_scaled(Self::px_scale(scale)).kern(a, b),
+            _ => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_invalid_font() {
+        assert!(OutlineFace::new(b"not a font".to_vec()).is_err());
+        assert!(OutlineFace::new(Vec::new()).is_err());
+    }
+}
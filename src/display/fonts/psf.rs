@@ -0,0 +1,191 @@
+//! PC Screen Font (Linux console font) parser
+//!
+//! Handles PSF1 (8 pixels wide, 256 or 512 glyphs) and PSF2 (any size).
+//! With a Unicode table each glyph is mapped to every codepoint listed for
+//! it; without one, glyph N is codepoint N (Latin-1 for the first 256).
+
+use super::{BitmapFace, Glyph};
+use std::collections::HashMap;
+
+const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+const PSF1_MODE512: u8 = 0x01;
+const PSF1_MODEHASTAB: u8 = 0x02;
+const PSF1_MODESEQ: u8 = 0x04;
+const PSF1_SEPARATOR: u16 = 0xFFFF;
+const PSF1_STARTSEQ: u16 = 0xFFFE;
+
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xB5, 0x4A, 0x86];
+const PSF2_HAS_UNICODE_TABLE: u32 = 0x01;
+const PSF2_SEPARATOR: u8 = 0xFF;
+const PSF2_STARTSEQ: u8 = 0xFE;
+
+/// Parse a PSF1 or PSF2 font
+pub fn parse(data: &[u8]) -> Result<BitmapFace, String> {
+    if data.starts_with(&PSF2_MAGIC) {
+        parse_psf2(data)
+    } else if data.starts_with(&PSF1_MAGIC) {
+        parse_psf1(data)
+    } else {
+        Err("Not a PSF font".to_string())
+    }
+}
+
+fn parse_psf1(data: &[u8]) -> Result<BitmapFace, String> {
+    let mode = *data.get(2).ok_or("Truncated header")?;
+    let height = u32::from(*data.get(3).ok_or("Truncated header")?);
+    let count = if mode & PSF1_MODE512 != 0 { 512 } else { 256 };
+    let glyph_size = height as usize;
+    let table_start = 4 + count * glyph_size;
+    let bitmaps = data.get(4..table_start).ok_or("Truncated glyph data")?;
+
+    let mut codes: Vec<Vec<char>> = vec![Vec::new(); count];
+    if mode & (PSF1_MODEHASTAB | PSF1_MODESEQ) != 0 {
+        let mut entries = data[table_start..]
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]));
+        for chars in &mut codes {
+            let mut in_sequence = false;
+            for entry in entries.by_ref() {
+                match entry {
+                    PSF1_SEPARATOR => break,
+                    PSF1_STARTSEQ => in_sequence = true,
+                    // Multi-codepoint sequences (combining forms) aren't supported
+                    _ if in_sequence => {},
+                    code => chars.extend(char::from_u32(u32::from(code))),
+                }
+            }
+        }
+    } else {
+        identity_codes(&mut codes);
+    }
+
+    Ok(build_face(bitmaps, &codes, 8, height, 1))
+}
+
+fn parse_psf2(data: &[u8]) -> Result<BitmapFace, String> {
+    let field = |index: usize| {
+        data.get(index * 4..index * 4 + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or("Truncated header")
+    };
+    let header_size = field(2)? as usize;
+    let flags = field(3)?;
+    let count = field(4)? as usize;
+    let glyph_size = field(5)? as usize;
+    let height = field(6)?;
+    let width = field(7)?;
+
+    let stride = width.div_ceil(8) as usize;
+    let bitmap_size = stride.checked_mul(height as usize);
+    if width == 0 || height == 0 || bitmap_size.map_or(true, |size| glyph_size < size) {
+        return Err(format!("Invalid glyph size {}x{}", width, height));
+    }
+    let table_start = count
+        .checked_mul(glyph_size)
+        .and_then(|size| size.checked_add(header_size))
+        .ok_or("Glyph data too large")?;
+    let bitmaps = data
+        .get(header_size..table_start)
+        .ok_or("Truncated glyph data")?;
+
+    let mut codes: Vec<Vec<char>> = vec![Vec::new(); count];
+    if flags & PSF2_HAS_UNICODE_TABLE != 0 {
+        let mut table = data[table_start..].split(|&b| b == PSF2_SEPARATOR);
+        for (chars, entry) in codes.iter_mut().zip(table.by_ref()) {
+            // Single codepoints come first; anything after STARTSEQ is a sequence
+            let singles = entry.split(|&b| b == PSF2_STARTSEQ).next().unwrap_or(&[]);
+            chars.extend(String::from_utf8_lossy(singles).chars());
+        }
+    } else {
+        identity_codes(&mut codes);
+    }
+
+    Ok(build_face(bitmaps, &codes, width, height, glyph_size))
+}
+
+fn identity_codes(codes: &mut [Vec<char>]) {
+    for (index, chars) in codes.iter_mut().enumerate() {
+        chars.extend(char::from_u32(index as u32));
+    }
+}
+
+fn build_face(
+    bitmaps: &[u8],
+    codes: &[Vec<char>],
+    width: u32,
+    height: u32,
+    glyph_size: usize,
+) -> BitmapFace {
+    let stride = width.div_ceil(8) as usize;
+    let mut glyphs = HashMap::new();
+    for (rows, chars) in bitmaps.chunks_exact(glyph_size).zip(codes) {
+        for &ch in chars {
+            glyphs.insert(ch, Glyph::from_rows(width, height, rows, stride));
+        }
+    }
+    // Console fonts have no baseline; put it a sixth of the cell up from the bottom
+    let ascent = height as i32 - (height as i32 / 6).max(1);
+    BitmapFace::new(height, ascent, width, glyphs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Face;
+    use super::*;
+
+    /// A 2-glyph, 10x2 PSF2 font with a Unicode table
+    fn psf2_font() -> Vec<u8> {
+        let mut data = PSF2_MAGIC.to_vec();
+        for value in [0u32, 32, PSF2_HAS_UNICODE_TABLE, 2, 4, 2, 10] {
+            data.extend(value.to_le_bytes());
+        }
+        // Glyph 0: left column; glyph 1: rightmost column (second byte, bit 6)
+        data.extend([0x80, 0x00, 0x80, 0x00]);
+        data.extend([0x00, 0x40, 0x00, 0x40]);
+        // Glyph 0 is 'A' and 'Α' (Greek capital alpha); glyph 1 is 'ж'
+        data.extend("AΑ".as_bytes());
+        data.push(PSF2_SEPARATOR);
+        data.extend("ж".as_bytes());
+        data.push(PSF2_STARTSEQ);
+        data.extend("e\u{301}".as_bytes());
+        data.push(PSF2_SEPARATOR);
+        data
+    }
+
+    #[test]
+    fn test_parse_psf2_unicode() {
+        let face = parse(&psf2_font()).unwrap();
+        assert_eq!(face.len(), 3);
+        assert_eq!(face.height(1.0), 2);
+
+        let alpha = face.glyph('Α', 1.0).unwrap();
+        assert_eq!(alpha, face.glyph('A', 1.0).unwrap());
+        assert_eq!(alpha.at(0, 1), 255);
+
+        let zhe = face.glyph('ж', 1.0).unwrap();
+        assert_eq!((zhe.width, zhe.at(9, 0), zhe.at(8, 0)), (10, 255, 0));
+        assert!(face.glyph('e', 1.0).is_none());
+    }
+
+    #[test]
+    fn test_parse_psf1_without_table() {
+        let mut data = vec![PSF1_MAGIC[0], PSF1_MAGIC[1], 0, 1];
+        data.extend((0..=255u8).map(|i| if i == b'!' { 0x18 } else { 0 }));
+        let face = parse(&data).unwrap();
+        assert_eq!(face.len(), 256);
+        assert_eq!(face.glyph('!', 1.0).unwrap().at(3, 0), 255);
+        assert_eq!(face.glyph('é', 1.0).unwrap().width, 8);
+
+        assert!(parse(&data[..100]).is_err());
+        assert!(parse(b"not a font").is_err());
+    }
+
+    #[test]
+    fn test_parse_psf2_huge_header() {
+        let mut data = PSF2_MAGIC.to_vec();
+        for value in [0, u32::MAX, 0, u32::MAX, u32::MAX, 1, 8] {
+            data.extend(value.to_le_bytes());
+        }
+        assert!(parse(&data).is_err());
+    }
+}
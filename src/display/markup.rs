@@ -0,0 +1,243 @@
+//! Inline Text Markup
+//!
+//! Tags in braces style the text that follows them until a matching `{/}`:
+//!
+//! ```text
+//! {red}BREAKING{/} {rainbow}party{/} in the {yellow blink}main room{/} now
+//! ```
+//!
+//! - Colors: `red`, `green`, `blue`, `yellow`, `orange`, `cyan`, `magenta`,
+//!   `pink`, `purple`, `white`, `black`, `gray`, or `#rgb` / `#rrggbb`
+//! - Color effects: `rainbow`, `pulse`
+//! - Offsets: `wave`, `wobble`, `bounce`, `circle`, `steady` (no offset)
+//! - Visibility: `blink`, `strobe`, `glitch`, `solid` (always visible)
+//!
+//! Effects take an optional number after a colon for their main parameter,
+//! e.g. `{wave:8}` (amplitude in pixels) or `{blink:4}` (rate in Hz).
+//! Several tags can share one pair of braces, separated by spaces. Tags nest,
+//! with inner tags overriding outer ones; `{/}` closes the innermost.
+//! `{{` is a literal brace, and anything in braces that isn't a known tag is
+//! left in the text as written.
+
+use super::scroller::{ColorEffect, OffsetEffect, VisibilityEffect};
+
+/// Style for a run of text. Unset fields fall back to the scroller's own effects.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SpanStyle {
+    pub color: Option<(u8, u8, u8)>,
+    pub color_fx: Option<ColorEffect>,
+    pub offset: Option<OffsetEffect>,
+    pub visibility: Option<VisibilityEffect>,
+}
+
+impl SpanStyle {
+    /// Combine with an outer style: fields set on `self` win
+    fn or(self, outer: SpanStyle) -> SpanStyle {
+        SpanStyle {
+            color: self.color.or(outer.color),
+            color_fx: self.color_fx.or(outer.color_fx),
+            offset: self.offset.or(outer.offset),
+            visibility: self.visibility.or(outer.visibility),
+        }
+    }
+
+    /// Apply one tag like `red`, `#ff8800` or `wave:6`
+    fn apply(&mut self, tag: &str) -> Option<()> {
+        let (name, param) = match tag.split_once(':') {
+            Some((name, param)) => (name, Some(param.parse::<f32>().ok()?)),
+            None => (tag, None),
+        };
+        let param = |default: f32| param.unwrap_or(default);
+
+        match name.to_lowercase().as_str() {
+            "rainbow" => self.color_fx = Some(ColorEffect::Rainbow { speed: param(1.0) }),
+            "pulse" => {
+                self.color_fx = Some(ColorEffect::Pulse {
+                    speed: param(2.0),
+                    min_brightness: 0.3,
+                });
+            },
+            "wave" => {
+                self.offset = Some(OffsetEffect::Wave {
+                    amplitude: param(4.0),
+                    frequency: 3.0,
+                });
+            },
+            "wobble" => self.offset = Some(OffsetEffect::Wobble { amount: param(2.0) }),
+            "bounce" => {
+                self.offset = Some(OffsetEffect::Bounce {
+                    height: param(8.0),
+                    speed: 4.0,
+                });
+            },
+            "circle" => {
+                self.offset = Some(OffsetEffect::Circle {
+                    radius: param(3.0),
+                    speed: 3.0,
+                });
+            },
+            "steady" => self.offset = Some(OffsetEffect::None),
+            "blink" => self.visibility = Some(VisibilityEffect::Blink { rate: param(2.0) }),
+            "strobe" => self.visibility = Some(VisibilityEffect::Strobe { rate: param(2.0) }),
+            "glitch" => {
+                self.visibility = Some(VisibilityEffect::BlinkRandom { rate: param(8.0) });
+            },
+            "solid" => self.visibility = Some(VisibilityEffect::None),
+            color => {
+                // A plain color also stops any inherited color effect
+                self.color = Some(parse_color(color)?);
+                self.color_fx = Some(ColorEffect::None);
+            },
+        }
+        Some(())
+    }
+}
+
+/// A run of text with one style
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub style: SpanStyle,
+}
+
+/// Parse markup into styled spans. Never fails: anything unrecognised is kept as text.
+pub fn parse(markup: &str) -> Vec<Span> {
+    let mut spans: Vec<Span> = Vec::new();
+    let mut stack = vec![SpanStyle::default()];
+    let mut rest = markup;
+
+    while let Some(open) = rest.find('{') {
+        let style = stack.last().copied().unwrap_or_default();
+        push_text(&mut spans, &rest[..open], style);
+        let after = &rest[open + 1..];
+
+        if let Some(after) = after.strip_prefix('{') {
+            push_text(&mut spans, "{", style);
+            rest = after;
+            continue;
+        }
+        let Some(close) = after.find('}') else {
+            push_text(&mut spans, &rest[open..], style);
+            rest = "";
+            break;
+        };
+        let tag = &after[..close];
+
+        if tag.starts_with('/') {
+            if stack.len() > 1 {
+                stack.pop();
+            }
+        } else if let Some(tagged) = parse_tags(tag) {
+            stack.push(tagged.or(style));
+        } else {
+            push_text(&mut spans, &rest[open..open + close + 2], style);
+        }
+        rest = &after[close + 1..];
+    }
+    let style = stack.last().copied().unwrap_or_default();
+    push_text(&mut spans, rest, style);
+    spans
+}
+
+/// Append text, merging it into the last span if the style matches
+fn push_text(spans: &mut Vec<Span>, text: &str, style: SpanStyle) {
+    match spans.last_mut() {
+        Some(last) if last.style == style => last.text.push_str(text),
+        _ if text.is_empty() => {},
+        _ => spans.push(Span {
+            text: text.to_string(),
+            style,
+        }),
+    }
+}
+
+/// Plain text with the markup removed
+pub fn strip(markup: &str) -> String {
+    parse(markup).into_iter().map(|span| span.text).collect()
+}
+
+/// Parse the inside of a tag: one or more space-separated tags, all of which must be known
+fn parse_tags(tag: &str) -> Option<SpanStyle> {
+    let mut style = SpanStyle::default();
+    let mut any = false;
+    for word in tag.split_whitespace() {
+        style.apply(word)?;
+        any = true;
+    }
+    any.then_some(style)
+}
+
+fn parse_color(name: &str) -> Option<(u8, u8, u8)> {
+    if let Some(hex) = name.strip_prefix('#') {
+        let digit = |i: usize| u8::from_str_radix(hex.get(i..=i)?, 16).ok();
+        return match hex.len() {
+            3 => Some((digit(0)? * 17, digit(1)? * 17, digit(2)? * 17)),
+            6 => Some((
+                digit(0)? * 16 + digit(1)?,
+                digit(2)? * 16 + digit(3)?,
+                digit(4)? * 16 + digit(5)?,
+            )),
+            _ => None,
+        };
+    }
+    Some(match name {
+        "red" => (255, 40, 40),
+        "green" => (40, 255, 80),
+        "blue" => (60, 120, 255),
+        "yellow" => (255, 230, 40),
+        "orange" => (255, 140, 0),
+        "cyan" => (0, 255, 255),
+        "magenta" => (255, 0, 255),
+        "pink" => (255, 120, 200),
+        "purple" => (160, 60, 255),
+        "white" => (255, 255, 255),
+        "black" => (0, 0, 0),
+        "gray" | "grey" => (128, 128, 128),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spans() {
+        let spans = parse("{red}BREAKING{/} {rainbow}party{/} {blink}now{/}");
+        let texts: Vec<&str> = spans.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, ["BREAKING", " ", "party", " ", "now"]);
+        assert_eq!(spans[0].style.color, Some((255, 40, 40)));
+        assert_eq!(spans[1].style, SpanStyle::default());
+        assert_eq!(
+            spans[2].style.color_fx,
+            Some(ColorEffect::Rainbow { speed: 1.0 })
+        );
+        assert_eq!(
+            spans[4].style.visibility,
+            Some(VisibilityEffect::Blink { rate: 2.0 })
+        );
+    }
+
+    #[test]
+    fn test_nesting_and_params() {
+        let spans = parse("{#00ff00 wave:8}a{blink}b{/}c");
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[1].style.color, Some((0, 255, 0)));
+        assert!(spans[1].style.visibility.is_some());
+        assert_eq!(
+            spans[2].style.offset,
+            Some(OffsetEffect::Wave {
+                amplitude: 8.0,
+                frequency: 3.0
+            })
+        );
+        assert_eq!(spans[2].style.visibility, None);
+    }
+
+    #[test]
+    fn test_unknown_tags_stay_literal() {
+        assert_eq!(strip("{{red}} {name} {red"), "{red}} {name} {red");
+        assert_eq!(strip("a{/}{/}b"), "ab");
+        assert_eq!(strip("{wave:big}x"), "{wave:big}x");
+    }
+}
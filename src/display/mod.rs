@@ -1,20 +1,33 @@
 mod font;
+pub mod fonts;
+pub mod markup;
+mod path_text;
 mod pixel_buffer;
 mod scroller;
+pub mod stroke_font;
 pub mod text_fx;
+mod text_layout;
 
 #[allow(unused_imports)]
 pub use font::{
     draw_char_scaled, draw_text, draw_text_boxed, draw_text_centered, draw_text_centered_scaled,
-    draw_text_scaled, text_width, text_width_scaled, GLYPH_HEIGHT, GLYPH_WIDTH,
+    draw_text_scaled, text_height_scaled, text_width, text_width_scaled, GLYPH_HEIGHT, GLYPH_WIDTH,
 };
 #[allow(unused_imports)]
+pub use fonts::{default_font, set_default_font, Font};
+#[allow(unused_imports)]
+pub use path_text::{spline_points, PathScroller, TextPath};
+#[allow(unused_imports)]
 pub use pixel_buffer::{BlendMode, PixelBuffer};
 #[allow(unused_imports)]
 pub use scroller::{
     ColorEffect, LayerEffect, OffsetEffect, ScrollDirection, ScrollMode, Scroller, SineScroller,
     StyledScroller, Typewriter, VisibilityEffect,
 };
+#[allow(unused_imports)]
+pub use stroke_font::StrokeText;
+#[allow(unused_imports)]
+pub use text_layout::{fit_text, Align, LayoutOptions, TextBlock, VAlign};
 
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
@@ -29,12 +42,24 @@ pub const DEFAULT_HEIGHT: u32 = 480;
 
 pub struct Display {
     sdl_context: Sdl,
-    canvas: Canvas<Window>,
+    /// One canvas per window; the first sets the pace with vsync
+    canvases: Vec<Canvas<Window>>,
+    /// SDL window ids and desktop positions, matching `canvases`
+    windows: Vec<(u32, (i32, i32))>,
     event_pump: EventPump,
     width: u32,
     height: u32,
 }
 
+/// Placement of one output window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowSpec {
+    /// Desktop position for a borderless window; None = a centered, decorated one
+    pub position: Option<(i32, i32)>,
+    pub width: u32,
+    pub height: u32,
+}
+
 pub struct RenderTarget<'a> {
     texture: Texture<'a>,
     width: u32,
@@ -92,33 +117,62 @@ impl Display {
         height: u32,
         vsync: bool,
     ) -> Result<(Self, TextureCreator<WindowContext>), String> {
+        let window = WindowSpec {
+            position: None,
+            width,
+            height,
+        };
+        let (display, mut texture_creators) = Self::with_windows(title, &[window], vsync)?;
+        Ok((display, texture_creators.remove(0)))
+    }
+
+    /// Create one window per output, e.g. one on each projector's part of the
+    /// desktop. Returns a texture creator for each window, in order.
+    /// Only the first window waits for vsync, so several don't divide the frame rate.
+    pub fn with_windows(
+        title: &str,
+        specs: &[WindowSpec],
+        vsync: bool,
+    ) -> Result<(Self, Vec<TextureCreator<WindowContext>>), String> {
         let sdl_context = sdl2::init()?;
         let video_subsystem = sdl_context.video()?;
 
-        let window = video_subsystem
-            .window(title, width, height)
-            .position_centered()
-            .build()
-            .map_err(|e| e.to_string())?;
+        let mut canvases = Vec::with_capacity(specs.len());
+        let mut windows = Vec::with_capacity(specs.len());
+        for (i, spec) in specs.iter().enumerate() {
+            let mut builder = video_subsystem.window(title, spec.width, spec.height);
+            match spec.position {
+                Some((x, y)) => builder.position(x, y).borderless(),
+                None => builder.position_centered(),
+            };
+            let window = builder.build().map_err(|e| e.to_string())?;
+            windows.push((window.id(), spec.position.unwrap_or((0, 0))));
 
-        let mut canvas_builder = window.into_canvas().accelerated();
-        if vsync {
-            canvas_builder = canvas_builder.present_vsync();
+            let mut canvas_builder = window.into_canvas().accelerated();
+            if vsync && i == 0 {
+                canvas_builder = canvas_builder.present_vsync();
+            }
+            canvases.push(canvas_builder.build().map_err(|e| e.to_string())?);
         }
-        let canvas = canvas_builder.build().map_err(|e| e.to_string())?;
+        let first = specs.first().ok_or("No output windows")?;
+        let (width, height) = (first.width, first.height);
 
-        let texture_creator = canvas.texture_creator();
+        let texture_creators = canvases
+            .iter()
+            .map(Canvas::<Window>::texture_creator)
+            .collect();
         let event_pump = sdl_context.event_pump()?;
 
         Ok((
             Self {
                 sdl_context,
-                canvas,
+                canvases,
+                windows,
                 event_pump,
                 width,
                 height,
             },
-            texture_creator,
+            texture_creators,
         ))
     }
 
@@ -134,17 +188,37 @@ impl Display {
         &mut self,
         target: &mut RenderTarget,
         buffer: &PixelBuffer,
+    ) -> Result<(), String> {
+        self.present_to(0, target, buffer)
+    }
+
+    /// Show `buffer` in window `window` (see `with_windows`)
+    pub fn present_to(
+        &mut self,
+        window: usize,
+        target: &mut RenderTarget,
+        buffer: &PixelBuffer,
     ) -> Result<(), String> {
         target
             .texture
             .update(None, buffer.as_bytes(), (buffer.width() * 4) as usize)
             .map_err(|e| e.to_string())?;
 
-        self.canvas.copy(&target.texture, None, None)?;
-        self.canvas.present();
+        let canvas = self.canvases.get_mut(window).ok_or("No such window")?;
+        canvas.copy(&target.texture, None, None)?;
+        canvas.present();
         Ok(())
     }
 
+    /// Desktop position of SDL window `window_id`, so mouse positions from
+    /// every window share one coordinate space
+    fn window_offset(&self, window_id: u32) -> (i32, i32) {
+        self.windows
+            .iter()
+            .find(|&&(id, _)| id == window_id)
+            .map_or((0, 0), |&(_, offset)| offset)
+    }
+
     /// Show the mouse cursor
     pub fn show_cursor(&self) {
         self.sdl_context.mouse().show_cursor(true);
@@ -160,10 +234,13 @@ impl Display {
         self.sdl_context.mouse().is_cursor_showing()
     }
 
+    /// Pending input. Mouse positions are on the desktop: a window's own
+    /// coordinates plus its position (see `WindowSpec`).
     pub fn poll_events(&mut self) -> Vec<InputEvent> {
         let mut events = Vec::new();
 
-        for event in self.event_pump.poll_iter() {
+        let pending: Vec<Event> = self.event_pump.poll_iter().collect();
+        for event in pending {
             match event {
                 Event::Quit { .. } => events.push(InputEvent::Quit),
                 Event::KeyDown {
@@ -172,19 +249,45 @@ impl Display {
                 Event::KeyUp {
                     keycode: Some(k), ..
                 } => events.push(InputEvent::KeyUp(k)),
-                Event::MouseMotion { x, y, .. } => events.push(InputEvent::MouseMove { x, y }),
+                Event::MouseMotion {
+                    window_id, x, y, ..
+                } => {
+                    let (ox, oy) = self.window_offset(window_id);
+                    events.push(InputEvent::MouseMove {
+                        x: x + ox,
+                        y: y + oy,
+                    });
+                },
                 Event::MouseButtonDown {
-                    x, y, mouse_btn, ..
+                    window_id,
+                    x,
+                    y,
+                    mouse_btn,
+                    ..
                 } => {
+                    let (ox, oy) = self.window_offset(window_id);
                     if let Some(button) = map_mouse_button(mouse_btn) {
-                        events.push(InputEvent::MouseDown { x, y, button });
+                        events.push(InputEvent::MouseDown {
+                            x: x + ox,
+                            y: y + oy,
+                            button,
+                        });
                     }
                 },
                 Event::MouseButtonUp {
-                    x, y, mouse_btn, ..
+                    window_id,
+                    x,
+                    y,
+                    mouse_btn,
+                    ..
                 } => {
+                    let (ox, oy) = self.window_offset(window_id);
                     if let Some(button) = map_mouse_button(mouse_btn) {
-                        events.push(InputEvent::MouseUp { x, y, button });
+                        events.push(InputEvent::MouseUp {
+                            x: x + ox,
+                            y: y + oy,
+                            button,
+                        });
                     }
                 },
                 _ => {},
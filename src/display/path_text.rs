@@ -0,0 +1,347 @@
+//! Text on a Path
+//!
+//! `TextPath` is a polyline parameterised by arc length, built from points,
+//! a circle or a Catmull-Rom spline. `PathScroller` moves text along one,
+//! placing each glyph at its distance along the path and rotating it to the
+//! local direction, so text can run around a polygon's edges or a circle.
+//!
+//! Text reads in the direction the path is walked, with glyph tops on the
+//! left of the direction of travel. On screen (y down) that puts text on the
+//! outside of a clockwise loop and the inside of a counter-clockwise one.
+
+use super::fonts::Font;
+use super::PixelBuffer;
+use std::f32::consts::TAU;
+
+/// Samples per span when flattening splines
+const SPLINE_STEPS: usize = 16;
+
+/// A path sampled as a polyline, with cumulative arc lengths
+#[derive(Debug, Clone)]
+pub struct TextPath {
+    /// Vertices; closed paths repeat the first vertex at the end
+    points: Vec<(f32, f32)>,
+    /// Distance along the path to each vertex
+    lengths: Vec<f32>,
+    closed: bool,
+}
+
+impl TextPath {
+    /// Straight segments through `points`, joining the last to the first when `closed`
+    pub fn polyline(points: &[(f32, f32)], closed: bool) -> Self {
+        let mut points = points.to_vec();
+        if closed && points.len() > 1 {
+            points.push(points[0]);
+        }
+        let mut lengths = Vec::with_capacity(points.len());
+        let mut total = 0.0;
+        for (i, &(x, y)) in points.iter().enumerate() {
+            if let Some(&(px, py)) = i.checked_sub(1).and_then(|p| points.get(p)) {
+                total += (x - px).hypot(y - py);
+            }
+            lengths.push(total);
+        }
+        Self {
+            points,
+            lengths,
+            closed,
+        }
+    }
+
+    /// A circle, starting at `start_angle` (radians, 0 = right) and running clockwise on screen
+    pub fn circle(center: (f32, f32), radius: f32, start_angle: f32) -> Self {
+        // Short segments so glyph angles change smoothly
+        let segments = (radius * TAU / 4.0).clamp(32.0, 1024.0) as usize;
+        let points: Vec<(f32, f32)> = (0..segments)
+            .map(|i| {
+                let angle = start_angle + i as f32 / segments as f32 * TAU;
+                (
+                    center.0 + angle.cos() * radius,
+                    center.1 + angle.sin() * radius,
+                )
+            })
+            .collect();
+        Self::polyline(&points, true)
+    }
+
+    /// A smooth Catmull-Rom curve passing through every control point
+    pub fn spline(points: &[(f32, f32)], closed: bool) -> Self {
+        Self::polyline(&spline_points(points, closed, SPLINE_STEPS), closed)
+    }
+
+    /// The same path walked the other way (flips which side text sits on)
+    pub fn reversed(mut self) -> Self {
+        let total = self.length();
+        self.points.reverse();
+        self.lengths = self.lengths.iter().rev().map(|s| total - s).collect();
+        self
+    }
+
+    /// Total arc length in pixels
+    pub fn length(&self) -> f32 {
+        self.lengths.last().copied().unwrap_or(0.0)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Point at arc length `s`. Closed paths wrap around; open paths clamp to their ends.
+    pub fn point_at(&self, s: f32) -> Option<(f32, f32)> {
+        let total = self.length();
+        let s = if self.closed && total > 0.0 {
+            s.rem_euclid(total)
+        } else {
+            s.clamp(0.0, total)
+        };
+        // First vertex at or beyond `s`; the segment ends there
+        let end = self.lengths.partition_point(|&l| l < s);
+        let Some(start) = end.checked_sub(1) else {
+            return self.points.first().copied();
+        };
+        let (x1, y1) = self.points[start];
+        let (x2, y2) = *self.points.get(end)?;
+        let span = self.lengths[end] - self.lengths[start];
+        let t = if span > 0.0 {
+            (s - self.lengths[start]) / span
+        } else {
+            0.0
+        };
+        Some((x1 + (x2 - x1) * t, y1 + (y2 - y1) * t))
+    }
+
+    /// Direction of travel at `s` (radians, clockwise on screen), averaged over
+    /// `span` pixels so glyphs turn corners gradually instead of snapping
+    pub fn angle_at(&self, s: f32, span: f32) -> f32 {
+        let half = span.max(1.0) / 2.0;
+        match (self.point_at(s - half), self.point_at(s + half)) {
+            (Some((x1, y1)), Some((x2, y2))) if (x2 - x1).hypot(y2 - y1) > 0.001 => {
+                (y2 - y1).atan2(x2 - x1)
+            },
+            _ => 0.0,
+        }
+    }
+}
+
+/// Points along a uniform Catmull-Rom spline through `points`, `steps` per span
+pub fn spline_points(points: &[(f32, f32)], closed: bool, steps: usize) -> Vec<(f32, f32)> {
+    let n = points.len();
+    if n < 3 || steps == 0 {
+        return points.to_vec();
+    }
+    let at = |i: isize| {
+        if closed {
+            points[i.rem_euclid(n as isize) as usize]
+        } else {
+            points[i.clamp(0, n as isize - 1) as usize]
+        }
+    };
+
+    let spans = if closed { n } else { n - 1 };
+    let mut samples = Vec::with_capacity(spans * steps + 1);
+    for span in 0..spans as isize {
+        let (p0, p1, p2, p3) = (at(span - 1), at(span), at(span + 1), at(span + 2));
+        for step in 0..steps {
+            let t = step as f32 / steps as f32;
+            samples.push((
+                catmull_rom(p0.0, p1.0, p2.0, p3.0, t),
+                catmull_rom(p0.1, p1.1, p2.1, p3.1, t),
+            ));
+        }
+    }
+    if !closed {
+        samples.push(points[n - 1]);
+    }
+    samples
+}
+
+/// Uniform Catmull-Rom interpolation between p1 and p2
+fn catmull_rom(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+/// Text scrolling along a `TextPath`
+///
+/// On closed paths the text repeats as many times as fits around the loop,
+/// evenly spaced. On open paths it enters at the start and leaves at the end.
+pub struct PathScroller {
+    text: String,
+    /// Distance along the path of the start of the text
+    offset: f32,
+    speed: f32, // pixels per second, negative runs backwards
+    scale: f32,
+    color: (u8, u8, u8),
+    font: Font,
+    gap: f32,
+    margin: f32,
+    passes: u32,
+}
+
+impl PathScroller {
+    /// Create a new path scroller with default settings
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            offset: 0.0,
+            speed: 60.0,
+            scale: 1.0,
+            color: (255, 255, 255),
+            font: Font::default(),
+            gap: 32.0,
+            margin: 0.0,
+            passes: 0,
+        }
+    }
+
+    /// Set scroll speed (pixels per second along the path; negative reverses)
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Set text scale (1 = 8px line)
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = scale.max(0.1);
+        self
+    }
+
+    /// Set text color
+    pub fn color(mut self, r: u8, g: u8, b: u8) -> Self {
+        self.color = (r, g, b);
+        self
+    }
+
+    /// Set the font (defaults to the global default font)
+    pub fn font(mut self, font: Font) -> Self {
+        self.font = font;
+        self
+    }
+
+    /// Minimum space between repeats on closed paths, in pixels
+    pub fn gap(mut self, gap: f32) -> Self {
+        self.gap = gap.max(0.0);
+        self
+    }
+
+    /// Lift the baseline off the path by this many pixels, towards the glyph tops
+    pub fn margin(mut self, margin: f32) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Text length along the path in pixels
+    pub fn text_width(&self) -> f32 {
+        self.font.text_width(&self.text, self.scale) as f32
+    }
+
+    /// Number of times the text has gone all the way along (or around) the path
+    pub fn passes(&self) -> u32 {
+        self.passes
+    }
+
+    /// Advance the text along `path`
+    pub fn update(&mut self, dt: f32, path: &TextPath) {
+        // Open paths run from fully before the start to fully past the end
+        let period = if path.is_closed() {
+            path.length()
+        } else {
+            path.length() + self.text_width()
+        };
+        if period <= 0.0 {
+            return;
+        }
+        self.offset += self.speed * dt;
+        if self.offset >= period || self.offset < 0.0 {
+            self.offset = self.offset.rem_euclid(period);
+            self.passes += 1;
+        }
+    }
+
+    /// Draw the text along `path`
+    pub fn render(&self, buffer: &mut PixelBuffer, path: &TextPath) {
+        let length = path.length();
+        if length <= 0.0 || self.text.is_empty() {
+            return;
+        }
+        let width = self.text_width();
+        let starts: Vec<f32> = if path.is_closed() {
+            let copies = ((length / (width + self.gap)).floor() as usize).max(1);
+            let spacing = length / copies as f32;
+            (0..copies)
+                .map(|i| self.offset + i as f32 * spacing)
+                .collect()
+        } else {
+            vec![self.offset - width]
+        };
+
+        for start in starts {
+            for c in self.font.layout(&self.text, self.scale) {
+                let s = start + c.x + c.advance / 2.0;
+                if !path.is_closed() && !(0.0..=length).contains(&s) {
+                    continue;
+                }
+                let Some((x, y)) = path.point_at(s) else {
+                    continue;
+                };
+                let angle = path.angle_at(s, c.advance);
+                // Glyph "up" is a quarter turn anticlockwise from the direction of travel
+                let (sin, cos) = angle.sin_cos();
+                let (x, y) = (x + sin * self.margin, y - cos * self.margin);
+                self.font
+                    .draw_char_rotated(buffer, x, y, angle, c.ch, self.color, self.scale);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arc_length_sampling() {
+        let square = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+        let path = TextPath::polyline(&square, true);
+        assert_eq!(path.length(), 40.0);
+        assert_eq!(path.point_at(15.0), Some((10.0, 5.0)));
+        // Closed paths wrap in both directions
+        assert_eq!(path.point_at(45.0), Some((5.0, 0.0)));
+        assert_eq!(path.point_at(-5.0), Some((0.0, 5.0)));
+        // Down the right-hand edge
+        assert!((path.angle_at(15.0, 2.0) - TAU / 4.0).abs() < 1e-5);
+
+        let open = TextPath::polyline(&square, false);
+        assert_eq!(open.length(), 30.0);
+        assert_eq!(open.point_at(99.0), Some((0.0, 10.0)));
+
+        let back = TextPath::polyline(&square, false).reversed();
+        assert_eq!(back.point_at(5.0), Some((5.0, 10.0)));
+    }
+
+    #[test]
+    fn test_circle_and_spline() {
+        let circle = TextPath::circle((50.0, 50.0), 20.0, -TAU / 4.0);
+        assert!((circle.length() - TAU * 20.0).abs() < 0.5);
+        let (x, y) = circle.point_at(0.0).unwrap();
+        assert!((x - 50.0).abs() < 1e-3 && (y - 30.0).abs() < 1e-3);
+        // Clockwise from the top: heading right
+        assert!(circle.angle_at(0.0, 2.0).abs() < 0.05);
+
+        // Splines pass through their control points
+        let points = [(0.0, 0.0), (10.0, 10.0), (20.0, 0.0), (30.0, 10.0)];
+        let spline = TextPath::spline(&points, false);
+        let end = spline.point_at(spline.length()).unwrap();
+        assert_eq!(end, (30.0, 10.0));
+        assert!(spline.length() > TextPath::polyline(&points, false).length() * 0.99);
+        assert!(spline.points.contains(&(10.0, 10.0)));
+    }
+}
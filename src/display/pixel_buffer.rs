@@ -56,6 +56,8 @@ pub struct PixelBuffer {
     width: u32,
     height: u32,
     depth: Option<Vec<f32>>,
+    /// Left, top, right and bottom (exclusive) of where bounds-checked calls draw
+    clip: (i32, i32, i32, i32),
 }
 
 impl PixelBuffer {
@@ -71,6 +73,7 @@ impl PixelBuffer {
             width,
             height,
             depth: None,
+            clip: (0, 0, width as i32, height as i32),
         }
     }
 
@@ -82,6 +85,7 @@ impl PixelBuffer {
             width,
             height,
             depth: Some(vec![f32::INFINITY; pixel_count]),
+            clip: (0, 0, width as i32, height as i32),
         }
     }
 
@@ -95,10 +99,26 @@ impl PixelBuffer {
         self.height
     }
 
-    /// Check if coordinates are within bounds
+    /// Limit the bounds-checked pixel calls (`set_pixel`, `blend_pixel`, ...)
+    /// to a rectangle, so text drawn with them stays inside it. `None` lifts
+    /// the limit.
+    pub fn set_clip(&mut self, clip: Option<(i32, i32, u32, u32)>) {
+        let (w, h) = (self.width as i32, self.height as i32);
+        self.clip = clip.map_or((0, 0, w, h), |(x, y, width, height)| {
+            (
+                x.clamp(0, w),
+                y.clamp(0, h),
+                (x + width as i32).clamp(0, w),
+                (y + height as i32).clamp(0, h),
+            )
+        });
+    }
+
+    /// Check if coordinates are within bounds (and the clip rectangle)
     #[inline]
     fn in_bounds(&self, x: i32, y: i32) -> bool {
-        x >= 0 && x < self.width as i32 && y >= 0 && y < self.height as i32
+        let (x0, y0, x1, y1) = self.clip;
+        x >= x0 && x < x1 && y >= y0 && y < y1
     }
 
     /// Calculate byte offset for pixel at (x, y)
@@ -762,16 +782,63 @@ impl PixelBuffer {
     }
 
     /// Fill a polygon using scanline algorithm
-    /// Optimized: preallocates intersection buffer outside loop
     pub fn fill_polygon(&mut self, vertices: &[(f32, f32)], r: u8, g: u8, b: u8) {
-        if vertices.len() < 3 {
+        self.fill_contours(&[vertices], false, r, g, b);
+    }
+
+    /// Fill the area enclosed by several closed contours, so contours inside
+    /// others cut holes. With `nonzero` a point is inside when the contours
+    /// wind round it (holes must run against their outline); otherwise when
+    /// a ray from it crosses an odd number of edges.
+    pub fn fill_contours<C: AsRef<[(f32, f32)]>>(
+        &mut self,
+        contours: &[C],
+        nonzero: bool,
+        r: u8,
+        g: u8,
+        b: u8,
+    ) {
+        self.scan_contours(contours, nonzero, |buffer, x1, x2, y| {
+            buffer.hline(x1, x2, y, r, g, b);
+        });
+    }
+
+    /// Fill the area enclosed by several closed contours with alpha blending
+    pub fn fill_contours_blend<C: AsRef<[(f32, f32)]>>(
+        &mut self,
+        contours: &[C],
+        nonzero: bool,
+        r: u8,
+        g: u8,
+        b: u8,
+        a: u8,
+    ) {
+        self.scan_contours(contours, nonzero, |buffer, x1, x2, y| {
+            buffer.hline_blend(x1, x2, y, r, g, b, a);
+        });
+    }
+
+    /// Call `span(self, x1, x2, y)` for each run of pixels inside the contours
+    /// Optimized: preallocates intersection buffer outside loop
+    fn scan_contours<C: AsRef<[(f32, f32)]>>(
+        &mut self,
+        contours: &[C],
+        nonzero: bool,
+        mut span: impl FnMut(&mut Self, i32, i32, i32),
+    ) {
+        let contours: Vec<&[(f32, f32)]> = contours
+            .iter()
+            .map(AsRef::as_ref)
+            .filter(|contour| contour.len() >= 3)
+            .collect();
+        if contours.is_empty() {
             return;
         }
 
         // Find bounding box
         let mut min_y = f32::MAX;
         let mut max_y = f32::MIN;
-        for (_, y) in vertices {
+        for (_, y) in contours.iter().flat_map(|contour| contour.iter()) {
             min_y = min_y.min(*y);
             max_y = max_y.max(*y);
         }
@@ -779,9 +846,9 @@ impl PixelBuffer {
         let min_y = (min_y as i32).max(0);
         let max_y = (max_y as i32).min(self.height as i32 - 1);
 
-        // Preallocate intersection buffer (reused per scanline)
-        let mut intersections = Vec::with_capacity(vertices.len());
-        let n = vertices.len();
+        // Preallocate intersection buffer (reused per scanline): x and winding
+        let edges: usize = contours.iter().map(|contour| contour.len()).sum();
+        let mut intersections: Vec<(i32, i32)> = Vec::with_capacity(edges);
 
         // Scanline fill
         for y in min_y..=max_y {
@@ -789,22 +856,39 @@ impl PixelBuffer {
             let yf = y as f32 + 0.5;
 
             // Find all edge intersections with this scanline
-            for i in 0..n {
-                let (x1, y1) = vertices[i];
-                let (x2, y2) = vertices[(i + 1) % n];
-
-                // Check if edge crosses this scanline
-                if (y1 <= yf && y2 > yf) || (y2 <= yf && y1 > yf) {
-                    // Calculate x intersection
-                    let x = x1 + (yf - y1) / (y2 - y1) * (x2 - x1);
-                    intersections.push(x as i32);
+            for contour in &contours {
+                let n = contour.len();
+                for i in 0..n {
+                    let (x1, y1) = contour[i];
+                    let (x2, y2) = contour[(i + 1) % n];
+
+                    // Check if edge crosses this scanline
+                    if (y1 <= yf && y2 > yf) || (y2 <= yf && y1 > yf) {
+                        // Calculate x intersection
+                        let x = x1 + (yf - y1) / (y2 - y1) * (x2 - x1);
+                        intersections.push((x as i32, if y2 > y1 { 1 } else { -1 }));
+                    }
                 }
             }
 
-            // Sort intersections and fill between pairs
+            // Sort intersections and fill between pairs, or while wound round
             intersections.sort_unstable();
-            for pair in intersections.chunks_exact(2) {
-                self.hline(pair[0], pair[1], y, r, g, b);
+            if nonzero {
+                let mut winding = 0;
+                let mut start = 0;
+                for &(x, direction) in &intersections {
+                    if winding == 0 {
+                        start = x;
+                    }
+                    winding += direction;
+                    if winding == 0 {
+                        span(self, start, x, y);
+                    }
+                }
+            } else {
+                for pair in intersections.chunks_exact(2) {
+                    span(self, pair[0].0, pair[1].0, y);
+                }
             }
         }
     }
@@ -893,6 +977,47 @@ impl PixelBuffer {
         }
     }
 
+    /// Fill any shape given its horizontal runs: `spans(y)` returns the
+    /// (x_start, x_end) pairs inside the shape along the line `y`. Rows from
+    /// `min_y` to `max_y` are sampled through pixel centers, like `fill_polygon`.
+    pub fn fill_spans(
+        &mut self,
+        min_y: f32,
+        max_y: f32,
+        spans: impl Fn(f32) -> Vec<(f32, f32)>,
+        r: u8,
+        g: u8,
+        b: u8,
+    ) {
+        let min_y = (min_y as i32).max(0);
+        let max_y = (max_y as i32).min(self.height as i32 - 1);
+        for y in min_y..=max_y {
+            for (x1, x2) in spans(y as f32 + 0.5) {
+                self.hline(x1 as i32, x2 as i32, y, r, g, b);
+            }
+        }
+    }
+
+    /// Fill a shape given its horizontal runs, with alpha blending
+    pub fn fill_spans_blend(
+        &mut self,
+        min_y: f32,
+        max_y: f32,
+        spans: impl Fn(f32) -> Vec<(f32, f32)>,
+        r: u8,
+        g: u8,
+        b: u8,
+        a: u8,
+    ) {
+        let min_y = (min_y as i32).max(0);
+        let max_y = (max_y as i32).min(self.height as i32 - 1);
+        for y in min_y..=max_y {
+            for (x1, x2) in spans(y as f32 + 0.5) {
+                self.hline_blend(x1 as i32, x2 as i32, y, r, g, b, a);
+            }
+        }
+    }
+
     // ========================================================================
     // Buffer Operations
     // ========================================================================
@@ -949,6 +1074,41 @@ impl PixelBuffer {
         }
     }
 
+    /// Copy a rectangle out into a new buffer (areas outside this buffer stay black)
+    pub fn crop(&self, x: i32, y: i32, width: u32, height: u32) -> Self {
+        let mut cropped = Self::with_size(width, height);
+        for cy in 0..height as i32 {
+            for cx in 0..width as i32 {
+                if let Some((r, g, b)) = self.get_pixel(x + cx, y + cy) {
+                    cropped.set_pixel(cx, cy, r, g, b);
+                }
+            }
+        }
+        cropped
+    }
+
+    /// Fill this buffer with the same-sized rectangle of `src` at (x, y),
+    /// without allocating (areas outside `src` go black)
+    pub fn copy_rect_from(&mut self, src: &PixelBuffer, x: i32, y: i32) {
+        self.clear(0, 0, 0);
+        let x0 = x.max(0);
+        let x1 = (x + self.width as i32).min(src.width as i32);
+        if x1 <= x0 {
+            return;
+        }
+        let len = (x1 - x0) as usize * 4;
+        for dy in 0..self.height as i32 {
+            let sy = y + dy;
+            if sy < 0 || sy >= src.height as i32 {
+                continue;
+            }
+            let src_idx = src.pixel_index(x0 as u32, sy as u32);
+            let dst_idx = self.pixel_index((x0 - x) as u32, dy as u32);
+            self.pixels[dst_idx..dst_idx + len]
+                .copy_from_slice(&src.pixels[src_idx..src_idx + len]);
+        }
+    }
+
     /// Composite a source buffer onto this one using per-pixel source alpha.
     /// Supports Alpha (src-over), Additive, and Multiply blend modes.
     /// Skips fully transparent pixels; fast-copies fully opaque ones in Alpha mode.
@@ -1042,6 +1202,40 @@ impl PixelBuffer {
         }
     }
 
+    /// Rotate the hue of the entire buffer by `degrees`.
+    /// Uses the luminance-preserving hue rotation matrix (same as CSS hue-rotate)
+    /// in 8.8 fixed point, so brightness stays roughly constant.
+    pub fn hue_rotate(&mut self, degrees: f32) {
+        let degrees = degrees.rem_euclid(360.0);
+        if degrees < 0.5 || degrees > 359.5 {
+            return;
+        }
+
+        let (s, c) = degrees.to_radians().sin_cos();
+        let m = [
+            0.213 + c * 0.787 - s * 0.213,
+            0.715 - c * 0.715 - s * 0.715,
+            0.072 - c * 0.072 + s * 0.928,
+            0.213 - c * 0.213 + s * 0.143,
+            0.715 + c * 0.285 + s * 0.140,
+            0.072 - c * 0.072 - s * 0.283,
+            0.213 - c * 0.213 - s * 0.787,
+            0.715 - c * 0.715 + s * 0.715,
+            0.072 + c * 0.928 + s * 0.072,
+        ];
+        let k = m.map(|v| (v * 256.0) as i32);
+
+        for chunk in self.pixels.chunks_exact_mut(4) {
+            // ABGR layout: [0]=A, [1]=B, [2]=G, [3]=R
+            let r = chunk[3] as i32;
+            let g = chunk[2] as i32;
+            let b = chunk[1] as i32;
+            chunk[3] = ((k[0] * r + k[1] * g + k[2] * b) >> 8).clamp(0, 255) as u8;
+            chunk[2] = ((k[3] * r + k[4] * g + k[5] * b) >> 8).clamp(0, 255) as u8;
+            chunk[1] = ((k[6] * r + k[7] * g + k[8] * b) >> 8).clamp(0, 255) as u8;
+        }
+    }
+
     /// Copy contents from another buffer (must be same size)
     pub fn copy_from(&mut self, src: &PixelBuffer) {
         if self.pixels.len() == src.pixels.len() {
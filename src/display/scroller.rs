@@ -5,16 +5,15 @@
 //! Use `Scroller` for basic scrolling, or `StyledScroller` to combine
 //! scrolling with text_fx effects (rainbow, wave, shadow, etc.)
 
-use super::font::{
-    draw_char_scaled, draw_text_scaled, text_width_scaled, GLYPH_HEIGHT, GLYPH_WIDTH,
-};
-use super::text_fx::{
-    color as fx_color, offset as fx_offset, transform as fx_transform, visibility as fx_vis,
-};
+use super::font::{draw_text_scaled, text_width_scaled};
+use super::fonts::Font;
+use super::markup::{self, SpanStyle};
+use super::text_fx::{color as fx_color, offset as fx_offset, visibility as fx_vis};
 use super::{PixelBuffer, DEFAULT_WIDTH};
+use serde::{Deserialize, Serialize};
 
 /// Scroll direction
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ScrollDirection {
     /// Text moves from right to left (classic news ticker)
     Leftward,
@@ -38,12 +37,15 @@ pub struct Scroller {
     speed: f32, // pixels per second (always positive)
     direction: ScrollDirection,
     mode: ScrollMode,
-    scale: u32,
+    scale: f32,
     color: (u8, u8, u8),
+    font: Font,
     // For ping-pong mode
     ping_pong_dir: f32, // 1.0 or -1.0
     // Screen width (updated dynamically)
     screen_width: u32,
+    // Completed passes (wraps in loop mode, far-edge bounces in ping-pong mode)
+    passes: u32,
 }
 
 impl Scroller {
@@ -56,10 +58,12 @@ impl Scroller {
             speed: 100.0,
             direction: ScrollDirection::Leftward,
             mode: ScrollMode::Loop,
-            scale: 1,
+            scale: 1.0,
             color: (255, 255, 255),
+            font: Font::default(),
             ping_pong_dir: -1.0,
             screen_width: DEFAULT_WIDTH,
+            passes: 0,
         }
     }
 
@@ -92,7 +96,13 @@ impl Scroller {
 
     /// Set text scale
     pub fn scale(mut self, scale: u32) -> Self {
-        self.scale = scale.max(1);
+        self.scale = scale.max(1) as f32;
+        self
+    }
+
+    /// Set a fractional text scale (outline fonts render in between whole steps)
+    pub fn fractional_scale(mut self, scale: f32) -> Self {
+        self.scale = scale.max(0.1);
         self
     }
 
@@ -102,14 +112,20 @@ impl Scroller {
         self
     }
 
+    /// Set the font (default: the current default font)
+    pub fn font(mut self, font: Font) -> Self {
+        self.font = font;
+        self
+    }
+
     /// Get the pixel width of the text
     pub fn text_pixel_width(&self) -> u32 {
-        text_width_scaled(&self.text, self.scale)
+        self.font.text_width(&self.text, self.scale)
     }
 
     /// Get the pixel height of the text
     pub fn text_pixel_height(&self) -> u32 {
-        GLYPH_HEIGHT * self.scale
+        self.font.height(self.scale)
     }
 
     /// Get current X position
@@ -122,8 +138,13 @@ impl Scroller {
         &self.text
     }
 
-    /// Get scale
+    /// Get scale (rounded to a whole step)
     pub fn get_scale(&self) -> u32 {
+        self.scale.round() as u32
+    }
+
+    /// Get scale, including any fraction
+    pub fn scale_factor(&self) -> f32 {
         self.scale
     }
 
@@ -132,6 +153,16 @@ impl Scroller {
         self.color
     }
 
+    /// Get font
+    pub fn get_font(&self) -> &Font {
+        &self.font
+    }
+
+    /// Number of full passes completed since creation
+    pub fn passes(&self) -> u32 {
+        self.passes
+    }
+
     /// Update scroller position
     pub fn update(&mut self, dt: f32) {
         let text_w = self.text_pixel_width() as f32;
@@ -145,6 +176,7 @@ impl Scroller {
                         // Wrap: when text exits left, reset to right edge
                         if self.x < -text_w {
                             self.x = width;
+                            self.passes += 1;
                         }
                     },
                     ScrollDirection::Rightward => {
@@ -152,6 +184,7 @@ impl Scroller {
                         // Wrap: when text exits right, reset to left edge
                         if self.x > width {
                             self.x = -text_w;
+                            self.passes += 1;
                         }
                     },
                 }
@@ -166,9 +199,15 @@ impl Scroller {
                 if self.x <= min_x {
                     self.x = min_x;
                     self.ping_pong_dir = 1.0;
+                    if self.direction == ScrollDirection::Leftward {
+                        self.passes += 1;
+                    }
                 } else if self.x >= max_x {
                     self.x = max_x;
                     self.ping_pong_dir = -1.0;
+                    if self.direction == ScrollDirection::Rightward {
+                        self.passes += 1;
+                    }
                 }
             },
         }
@@ -195,7 +234,7 @@ impl Scroller {
 
     /// Render the scroller at the given Y position
     pub fn render(&self, buffer: &mut PixelBuffer, y: i32) {
-        draw_text_scaled(
+        self.font.draw_text(
             buffer,
             self.x as i32,
             y,
@@ -369,6 +408,12 @@ impl SineScroller {
         self
     }
 
+    /// Set a fractional text scale (outline fonts render in between whole steps)
+    pub fn fractional_scale(mut self, scale: f32) -> Self {
+        self.scroller = self.scroller.fractional_scale(scale);
+        self
+    }
+
     /// Set text color
     pub fn color(mut self, r: u8, g: u8, b: u8) -> Self {
         self.scroller = self.scroller.color(r, g, b);
@@ -388,17 +433,16 @@ impl SineScroller {
 
     /// Render with sine wave effect
     pub fn render(&self, buffer: &mut PixelBuffer, base_y: i32) {
-        use super::font::{draw_char_scaled, GLYPH_WIDTH};
-
-        let char_w = (GLYPH_WIDTH * self.scroller.scale) as i32;
+        let font = &self.scroller.font;
         let text_width = self.scroller.text_pixel_width() as f32;
         let screen_w = self.scroller.screen_width as i32;
 
         // Draw multiple copies to seamlessly fill the screen
         let mut base_x = self.scroller.x;
         while (base_x as i32) < screen_w {
-            for (i, ch) in self.scroller.text.chars().enumerate() {
-                let x = base_x as i32 + i as i32 * char_w;
+            for placed in font.layout(&self.scroller.text, self.scroller.scale) {
+                let x = (base_x + placed.x) as i32;
+                let char_w = placed.advance.ceil() as i32;
 
                 // Skip if off screen
                 if x + char_w < 0 {
@@ -412,11 +456,11 @@ impl SineScroller {
                 let phase = (x as f32 * 0.02) + (self.time * self.frequency);
                 let y_offset = (phase.sin() * self.amplitude) as i32;
 
-                draw_char_scaled(
+                font.draw_char(
                     buffer,
                     x,
                     base_y + y_offset,
-                    ch,
+                    placed.ch,
                     self.scroller.color.0,
                     self.scroller.color.1,
                     self.scroller.color.2,
@@ -433,7 +477,8 @@ impl SineScroller {
 // ============================================================================
 
 /// Offset effect type for styled scrollers
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
 pub enum OffsetEffect {
     None,
     Wave { amplitude: f32, frequency: f32 },
@@ -443,7 +488,8 @@ pub enum OffsetEffect {
 }
 
 /// Color effect type for styled scrollers
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
 pub enum ColorEffect {
     None,
     Rainbow {
@@ -460,7 +506,8 @@ pub enum ColorEffect {
 }
 
 /// Visibility effect type for styled scrollers
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
 pub enum VisibilityEffect {
     None,
     Blink { rate: f32 },
@@ -470,7 +517,8 @@ pub enum VisibilityEffect {
 }
 
 /// Layer effect type for styled scrollers
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
 pub enum LayerEffect {
     None,
     Shadow {
@@ -490,6 +538,10 @@ pub enum LayerEffect {
 /// A scroller with composable text effects
 pub struct StyledScroller {
     scroller: Scroller,
+    /// Text as given, including any markup
+    source: String,
+    /// Markup style of each character of the scrolled text
+    styles: Vec<SpanStyle>,
     time: f32,
     offset_effect: OffsetEffect,
     color_effect: ColorEffect,
@@ -498,10 +550,20 @@ pub struct StyledScroller {
 }
 
 impl StyledScroller {
-    /// Create a new styled scroller
+    /// Create a new styled scroller. Inline markup (see `display::markup`)
+    /// styles parts of the text on top of the scroller-wide effects.
     pub fn new(text: impl Into<String>) -> Self {
+        let source = text.into();
+        let spans = markup::parse(&source);
+        let plain: String = spans.iter().map(|span| span.text.as_str()).collect();
+        let styles = spans
+            .iter()
+            .flat_map(|span| span.text.chars().map(|_| span.style))
+            .collect();
         Self {
-            scroller: Scroller::new(text),
+            scroller: Scroller::new(plain),
+            source,
+            styles,
             time: 0.0,
             offset_effect: OffsetEffect::None,
             color_effect: ColorEffect::None,
@@ -534,12 +596,24 @@ impl StyledScroller {
         self
     }
 
+    /// Set a fractional text scale (outline fonts render in between whole steps)
+    pub fn fractional_scale(mut self, scale: f32) -> Self {
+        self.scroller = self.scroller.fractional_scale(scale);
+        self
+    }
+
     /// Set base text color
     pub fn color(mut self, r: u8, g: u8, b: u8) -> Self {
         self.scroller = self.scroller.color(r, g, b);
         self
     }
 
+    /// Set the font (default: the current default font)
+    pub fn font(mut self, font: Font) -> Self {
+        self.scroller = self.scroller.font(font);
+        self
+    }
+
     /// Set offset effect (wave, wobble, bounce, etc.)
     pub fn offset(mut self, effect: OffsetEffect) -> Self {
         self.offset_effect = effect;
@@ -575,9 +649,24 @@ impl StyledScroller {
         self.scroller.set_screen_width(width);
     }
 
+    /// Get the text being scrolled, as given (with any markup)
+    pub fn text(&self) -> &str {
+        &self.source
+    }
+
+    /// Number of full passes completed since creation
+    pub fn passes(&self) -> u32 {
+        self.scroller.passes()
+    }
+
+    /// Markup style of a character (default if it has none)
+    fn span_style(&self, index: usize) -> SpanStyle {
+        self.styles.get(index).copied().unwrap_or_default()
+    }
+
     /// Calculate offset for a character
     fn char_offset(&self, index: usize) -> (i32, i32) {
-        match self.offset_effect {
+        match self.span_style(index).offset.unwrap_or(self.offset_effect) {
             OffsetEffect::None => (0, 0),
             OffsetEffect::Wave {
                 amplitude,
@@ -595,25 +684,23 @@ impl StyledScroller {
 
     /// Calculate color for a character
     fn char_color(&self, index: usize, total: usize) -> (u8, u8, u8) {
-        match self.color_effect {
-            ColorEffect::None => self.scroller.get_color(),
+        let style = self.span_style(index);
+        let base = style.color.unwrap_or_else(|| self.scroller.get_color());
+        match style.color_fx.unwrap_or(self.color_effect) {
+            ColorEffect::None => base,
             ColorEffect::Rainbow { speed } => fx_color::rainbow(index, self.time, speed),
             ColorEffect::Gradient { start, end } => fx_color::gradient(index, total, start, end),
             ColorEffect::Pulse {
                 speed,
                 min_brightness,
-            } => fx_color::pulse_brightness(
-                self.scroller.get_color(),
-                self.time,
-                speed,
-                min_brightness,
-            ),
+            } => fx_color::pulse_brightness(base, self.time, speed, min_brightness),
         }
     }
 
     /// Calculate visibility for a character
     fn char_visibility(&self, index: usize) -> f32 {
-        match self.visibility_effect {
+        let visibility = self.span_style(index).visibility;
+        match visibility.unwrap_or(self.visibility_effect) {
             VisibilityEffect::None => 1.0,
             VisibilityEffect::Blink { rate } => fx_vis::blink(self.time, rate),
             VisibilityEffect::Strobe { rate } => fx_vis::strobe(self.time, rate),
@@ -626,8 +713,11 @@ impl StyledScroller {
 
     /// Render the styled scroller
     pub fn render(&self, buffer: &mut PixelBuffer, base_y: i32) {
-        let scale = self.scroller.get_scale();
-        let _char_w = (GLYPH_WIDTH * scale) as i32;
+        self.render_at(buffer, 0, base_y);
+    }
+
+    /// Render the styled scroller with its left edge at `x`
+    pub fn render_at(&self, buffer: &mut PixelBuffer, x: i32, base_y: i32) {
         let text = self.scroller.text();
         let _char_count = text.chars().count();
 
@@ -639,7 +729,7 @@ impl StyledScroller {
                 color,
             } => {
                 // Draw shadow first
-                self.render_chars(buffer, base_y, Some((offset_x, offset_y)), Some(color));
+                self.render_chars(buffer, x, base_y, Some((offset_x, offset_y)), Some(color));
             },
             LayerEffect::Outline { color } => {
                 // Draw outline in 8 directions
@@ -654,42 +744,42 @@ impl StyledScroller {
                     (1, 1),
                 ];
                 for (ox, oy) in offsets {
-                    self.render_chars(buffer, base_y, Some((ox, oy)), Some(color));
+                    self.render_chars(buffer, x, base_y, Some((ox, oy)), Some(color));
                 }
             },
             LayerEffect::Reflection { gap, fade } => {
                 // Draw main text first, then reflection below
-                self.render_chars(buffer, base_y, None, None);
-                self.render_reflection(buffer, base_y, gap, fade);
+                self.render_chars(buffer, x, base_y, None, None);
+                self.render_reflection(buffer, x, base_y, gap, fade);
                 return; // Already drew main text
             },
             LayerEffect::None => {},
         }
 
         // Draw main text
-        self.render_chars(buffer, base_y, None, None);
+        self.render_chars(buffer, x, base_y, None, None);
     }
 
     /// Render characters with optional offset and color override
     fn render_chars(
         &self,
         buffer: &mut PixelBuffer,
+        origin_x: i32,
         base_y: i32,
         pos_offset: Option<(i32, i32)>,
         color_override: Option<(u8, u8, u8)>,
     ) {
-        let scale = self.scroller.get_scale();
-        let char_w = (GLYPH_WIDTH * scale) as i32;
+        let scale = self.scroller.scale_factor();
+        let font = self.scroller.get_font();
         let text = self.scroller.text();
         let char_count = text.chars().count();
-        let mut x = self.scroller.x() as i32;
 
         let (ox, oy) = pos_offset.unwrap_or((0, 0));
 
-        for (i, ch) in text.chars().enumerate() {
+        for (i, placed) in font.layout(text, scale).enumerate() {
+            let x = origin_x + (self.scroller.x() + placed.x) as i32;
             let vis = self.char_visibility(i);
             if vis < 0.01 {
-                x += char_w;
                 continue;
             }
 
@@ -704,27 +794,32 @@ impl StyledScroller {
                 b = (b as f32 * vis) as u8;
             }
 
-            draw_char_scaled(buffer, x + dx + ox, base_y + dy + oy, ch, r, g, b, scale);
-
-            x += char_w;
+            let (cx, cy) = (x + dx + ox, base_y + dy + oy);
+            font.draw_char(buffer, cx, cy, placed.ch, r, g, b, scale);
         }
     }
 
     /// Render reflection (flipped text below)
-    fn render_reflection(&self, buffer: &mut PixelBuffer, base_y: i32, gap: i32, fade: f32) {
-        let scale = self.scroller.get_scale();
-        let char_w = (GLYPH_WIDTH * scale) as i32;
-        let text_height = (GLYPH_HEIGHT * scale) as i32;
+    fn render_reflection(
+        &self,
+        buffer: &mut PixelBuffer,
+        origin_x: i32,
+        base_y: i32,
+        gap: i32,
+        fade: f32,
+    ) {
+        let scale = self.scroller.scale_factor();
+        let font = self.scroller.get_font();
+        let text_height = font.height(scale) as i32;
         let text = self.scroller.text();
         let char_count = text.chars().count();
-        let mut x = self.scroller.x() as i32;
 
         let reflect_y = base_y + text_height + gap;
 
-        for (i, ch) in text.chars().enumerate() {
+        for (i, placed) in font.layout(text, scale).enumerate() {
+            let x = origin_x + (self.scroller.x() + placed.x) as i32;
             let vis = self.char_visibility(i) * fade;
             if vis < 0.01 {
-                x += char_w;
                 continue;
             }
 
@@ -736,18 +831,16 @@ impl StyledScroller {
             g = (g as f32 * vis) as u8;
             b = (b as f32 * vis) as u8;
 
-            fx_transform::draw_char_flipped(
+            font.draw_char_flipped(
                 buffer,
                 x + dx,
                 reflect_y - dy, // Invert dy for reflection
-                ch,
+                placed.ch,
                 r,
                 g,
                 b,
                 scale,
             );
-
-            x += char_w;
         }
     }
 
@@ -760,11 +853,12 @@ impl StyledScroller {
         padding: u32,
     ) {
         let strip_y = y - padding as i32;
-        let mut strip_h = (GLYPH_HEIGHT * self.scroller.get_scale()) + padding * 2;
+        let text_h = self.scroller.text_pixel_height();
+        let mut strip_h = text_h + padding * 2;
 
         // Extra space for reflection
         if let LayerEffect::Reflection { gap, .. } = self.layer_effect {
-            strip_h += (GLYPH_HEIGHT * self.scroller.get_scale()) + gap as u32;
+            strip_h += text_h + gap as u32;
         }
 
         // Extra space for wave amplitude
@@ -0,0 +1,331 @@
+//! Vector Stroke Font
+//!
+//! A Hershey-style single-stroke font. Every glyph is a few polylines on a
+//! small integer grid, so text can be drawn as lines at any size and angle,
+//! or pushed through `math3d::project` for the line-drawn vector look.
+//! `StrokeText` lays out a message as polylines in pixels; drawing is left
+//! to the caller or to `draw` / `draw_thick`.
+//!
+//! Glyphs use the Hershey encoding: each coordinate is a letter offset from
+//! `R` (`R` is 0, `F` is -12), the first pair is the glyph's left and right
+//! edges, and ` R` lifts the pen. Capitals run from y = -12 down to the
+//! baseline at y = 9; lowercase x-height is at -5 and descenders reach 16.
+
+use super::text_layout::Align;
+use super::PixelBuffer;
+use std::sync::OnceLock;
+
+/// Font units from the top of the capitals to the baseline
+pub const CAP_HEIGHT: f32 = 21.0;
+/// Font y of the top of the capitals
+const CAP_TOP: f32 = -12.0;
+/// Font units between baselines
+const LINE_PITCH: f32 = 32.0;
+
+/// Printable ASCII, space to tilde
+#[rustfmt::skip]
+const GLYPHS: [&str; 95] = [
+    "JZ", // space
+    "MWRFRT RRYQZR[SZRY", // !
+    "JZOFOM RUFUM", // "
+    "H]PFM[ RVFS[ RLMYM RKTXT", // #
+    "H\\XHVGTFQFOGMHLILKLMNOPPRPUPWRXSYUYWWYUZS[P[NZLX RRBR_", // $
+    "F^[FI[ RPJPLOMNNMNLNKMJLJJJHKGLFMFNFOGPHPJ RZWZYYZX[W[V[UZTYTWTUUTVSWSXSYTZUZW", // %
+    "E_[[NLMINGPFRGSIRLKTJWKZN[Q[UYYT[Q", // &
+    "NVRFRM", // '
+    "KYRGPKOQPVRZ", // (
+    "KYRGTKUPTVRZ", // )
+    "JZRFRR RMIWO RWIMO", // *
+    "E_RIR[ RIR[R", // +
+    "MWSZR[QZRYSZS\\R^Q_", // ,
+    "JZMRWR", // -
+    "MWRYQZR[SZRY", // .
+    "I[YDK^", // /
+    "H\\YPXUWXUZR[OZMXLUKQLLMIOGRFUGWIXLYP", // 0
+    "H\\NJPISFS[", // 1
+    "H\\LJMHOGRFTFVHXJXLWOK[Y[", // 2
+    "H\\MFXFRNRNUOWPXRXUXWVYT[R[OZMY", // 3
+    "H\\UFKVZV RUFU[", // 4
+    "H\\XFMFLONOQNSNUOWQXSXUXXVZT[Q[OZMY", // 5
+    "H\\UGSFPFNILLLPLTMX RXUXWWYT[R[P[MYLWLULSMQPOROTOWQXSXU", // 6
+    "H\\KFYFO[", // 7
+    "H\\XKWMVOTPRPPPNOMMLKMINGPFRFTFVGWIXK RXVXXWYT[R[P[MYLXLVLSMRPPRPTPWRXSXU", // 8
+    "H\\XLXNWPTRRRPRMPLNLLLJMHPFRFTFWHXJXL RXKXOXSWVVYS[Q[OZ", // 9
+    "MWRMQNROSNRM RRYQZR[SZRY", // :
+    "MWRMQNROSNRM RSZR[QZRYSZS\\R^Q_", // ;
+    "F^ZIJRZ[", // <
+    "E_IO[O RIU[U", // =
+    "F^JIZRJ[", // >
+    "I[LJMHOGQFSFVGWHXJXLWNRQRT RRYQZR[SZRY", // ?
+    "E`VSVUUVTWRXPWOVNUNSNQOPPORNTOUPVQVS RVOVUWWYW[U\\Q[MXJUHQHMIJLHOHSIWLZO\\S\\W[", // @
+    "I[J[RFZ[ RMTWT", // A
+    "G\\KFK[ RKFTFWGXHYJYLXNWOTP RKPTPWQXRYTYWXYWZT[K[", // B
+    "H]YIVGSFPGMILLKQLUMXPZS[VZYX", // C
+    "G\\KFK[ RKFRFRFUGWIXLYPXUWXUZR[K[", // D
+    "H[YFLFL[Y[ RLPTP", // E
+    "HZYFLFL[ RLPTP", // F
+    "H]YIVGSFPGMILMKQLUMXPZS[VZYX[T RUT[T", // G
+    "G]KFK[ RYFY[ RKPYP", // H
+    "NVRFR[", // I
+    "JZVFVVVXUYSZQ[P[NZMYLW", // J
+    "G\\KFK[ RYFKT RPOY[", // K
+    "HYLFL[X[", // L
+    "F^J[JFR[ZFZ[", // M
+    "G]K[KFY[YF", // N
+    "G]ZPYUXXUZR[OZLXKUJQKLLIOGRFUGXIYLZP", // O
+    "G\\K[KFSFSFUFWGYIYKYMWOUPSPKP", // P
+    "G]ZPYUXXUZR[OZLXKUJQKLLIOGRFUGXIYLZP RTVZ]", // Q
+    "G\\K[KFSFSFUFWGYIYKYMWOUPSPKP RRPY[", // R
+    "H\\XHVGTFQFOGMHLILKLMNOPPRPUPWRXSYUYWWYUZS[P[NZLX", // S
+    "JZRFR[ RKFYF", // T
+    "G]KFKULWMYO[R[U[WYXWYUYF", // U
+    "I[JFR[ZF", // V
+    "F^HFM[RLW[\\F", // W
+    "H\\KFY[ RYFK[", // X
+    "I[JFRPR[ RZFRP", // Y
+    "H\\KFYFK[Y[", // Z
+    "KYVDPDP^V^", // [
+    "I[KDY^", // \\
+    "KYNDTDT^N^", // ]
+    "JZMKRFWK", // ^
+    "JZJ^Z^", // _
+    "NVQFSJ", // `
+    "I\\XMX[ RUNSMQMONMPLSLUMXOZQ[S[UZ", // a
+    "H[LFL[ RONQMSMUNWPXSXUWXUZS[Q[OZ", // b
+    "I[UNSMQMONMPLSLUMXOZQ[S[UZ", // c
+    "I\\XFX[ RUNSMQMONMPLSLUMXOZQ[S[UZ", // d
+    "I[LTXTXQVOTNRMPNNOLQLTLWNYPZR[TZVY", // e
+    "LXWFTFRGQJQ[ RNMUM", // f
+    "I\\XMX]W`VaSbPbNa RUNSMQMONMPLSLUMXOZQ[S[UZ", // g
+    "H\\LFL[ RLQONQMTMVNWQW[", // h
+    "NVRMR[ RRFQGRHSGRF", // i
+    "MWSMS^RaPbNb RSFRGSHTGSF", // j
+    "HZLFL[ RVMLW RPSW[", // k
+    "NVRFR[", // l
+    "CaGMG[ RGQJNLMOMQNRQR[ RRQUNWMZM\\N]Q][", // m
+    "H\\LML[ RLQONQMTMVNWQW[", // n
+    "I[XTXWVYTZR[PZNYLWLTLQNOPNRMTNVOXQXT", // o
+    "H[LMLb RONQMSMUNWPXSXUWXUZS[Q[OZ", // p
+    "I\\XMXb RUNSMQMONMPLSLUMXOZQ[S[UZ", // q
+    "KXOMO[ ROSPPRNTMWM", // r
+    "JZVOUNSMRMPMNNMOMQMROSPTRTTTVUWVWWWXVZU[S[P[OZMY", // s
+    "LXRFRWSZU[W[ RNMVM", // t
+    "H\\LMLWMZO[R[TZWW RWMW[", // u
+    "JZLMR[XM", // v
+    "G]JMN[RMV[ZM", // w
+    "JZLMX[ RXML[", // x
+    "JZLMR[ RXMR[P_NaLbKb", // y
+    "JZLMXML[X[", // z
+    "KYUDREQGQNPPNQPRQTQ[R]U^", // {
+    "NVRDR^", // |
+    "KYODRESGSNTPVQTRSTS[R]O^", // }
+    "F^JSKQMPOPQQSSUTWTYSZQ", // ~
+];
+
+/// A decoded glyph, in font units
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrokeGlyph {
+    pub left: f32,
+    pub right: f32,
+    pub strokes: Vec<Vec<(f32, f32)>>,
+}
+
+impl StrokeGlyph {
+    fn decode(code: &str) -> Self {
+        let coord = |b: u8| f32::from(b) - f32::from(b'R');
+        let bytes = code.as_bytes();
+        let mut strokes: Vec<Vec<(f32, f32)>> = vec![Vec::new()];
+        for pair in bytes[2..].chunks_exact(2) {
+            match pair {
+                b" R" => strokes.push(Vec::new()),
+                &[x, y] => strokes
+                    .last_mut()
+                    .into_iter()
+                    .for_each(|s| s.push((coord(x), coord(y)))),
+                _ => {},
+            }
+        }
+        strokes.retain(|s| !s.is_empty());
+        Self {
+            left: coord(bytes[0]),
+            right: coord(bytes[1]),
+            strokes,
+        }
+    }
+
+    /// Pen movement in font units
+    pub fn advance(&self) -> f32 {
+        self.right - self.left
+    }
+}
+
+/// Glyph for a character; anything outside printable ASCII draws as `?`
+pub fn glyph(ch: char) -> &'static StrokeGlyph {
+    static DECODED: OnceLock<Vec<StrokeGlyph>> = OnceLock::new();
+    let glyphs = DECODED.get_or_init(|| GLYPHS.iter().map(|g| StrokeGlyph::decode(g)).collect());
+    let ch = if (' '..='~').contains(&ch) { ch } else { '?' };
+    &glyphs[ch as usize - ' ' as usize]
+}
+
+/// Width of one line of text in font units
+fn line_units(line: &str) -> f32 {
+    line.chars().map(|ch| glyph(ch).advance()).sum()
+}
+
+/// Text laid out as polylines, in pixels
+///
+/// The origin starts at the top-left of the block (the top of the first
+/// line's capitals); `centered` moves it to the middle.
+#[derive(Debug, Clone, Default)]
+pub struct StrokeText {
+    strokes: Vec<Vec<(f32, f32)>>,
+    width: f32,
+    height: f32,
+}
+
+impl StrokeText {
+    /// Lay out `text` with capitals `size` pixels tall. Lines split on `\n` and are
+    /// aligned within the widest one (`Justify` lines up on the left).
+    pub fn new(text: &str, size: f32, align: Align) -> Self {
+        let k = size / CAP_HEIGHT;
+        let lines: Vec<&str> = text.lines().collect();
+        let widest = lines.iter().map(|l| line_units(l)).fold(0.0, f32::max);
+
+        let mut strokes = Vec::new();
+        for (row, line) in lines.iter().enumerate() {
+            let slack = widest - line_units(line);
+            let mut pen = match align {
+                Align::Left | Align::Justify => 0.0,
+                Align::Center => slack / 2.0,
+                Align::Right => slack,
+            };
+            let top = row as f32 * LINE_PITCH - CAP_TOP;
+            for ch in line.chars() {
+                let g = glyph(ch);
+                strokes.extend(g.strokes.iter().map(|stroke| {
+                    stroke
+                        .iter()
+                        .map(|&(x, y)| ((pen + x - g.left) * k, (top + y) * k))
+                        .collect::<Vec<_>>()
+                }));
+                pen += g.advance();
+            }
+        }
+        let rows = lines.len().max(1) as f32;
+        Self {
+            strokes,
+            width: widest * k,
+            height: (CAP_HEIGHT + (rows - 1.0) * LINE_PITCH) * k,
+        }
+    }
+
+    /// Move the origin to the middle of the block
+    pub fn centered(mut self) -> Self {
+        let (dx, dy) = (self.width / 2.0, self.height / 2.0);
+        for point in self.strokes.iter_mut().flatten() {
+            point.0 -= dx;
+            point.1 -= dy;
+        }
+        self
+    }
+
+    /// Polylines in pixels, relative to the origin
+    pub fn strokes(&self) -> &[Vec<(f32, f32)>] {
+        &self.strokes
+    }
+
+    /// Width of the widest line in pixels
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    /// Capitals of the first line to the baseline of the last, in pixels
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    /// Segments with the origin at (x, y), rotated by `angle` radians (clockwise on screen)
+    fn segments(&self, x: f32, y: f32, angle: f32) -> impl Iterator<Item = [(f32, f32); 2]> + '_ {
+        let (sin, cos) = angle.sin_cos();
+        let place =
+            move |&(px, py): &(f32, f32)| (x + px * cos - py * sin, y + px * sin + py * cos);
+        self.strokes.iter().flat_map(move |stroke| {
+            stroke
+                .windows(2)
+                .map(move |pair| [place(&pair[0]), place(&pair[1])])
+        })
+    }
+
+    /// Draw with anti-aliased hairlines
+    pub fn draw(&self, buffer: &mut PixelBuffer, x: f32, y: f32, angle: f32, color: (u8, u8, u8)) {
+        let (r, g, b) = color;
+        for [(x0, y0), (x1, y1)] in self.segments(x, y, angle) {
+            buffer.line_aa(x0, y0, x1, y1, r, g, b);
+        }
+    }
+
+    /// Draw with round-capped lines `thickness` pixels wide
+    pub fn draw_thick(
+        &self,
+        buffer: &mut PixelBuffer,
+        x: f32,
+        y: f32,
+        angle: f32,
+        thickness: i32,
+        color: (u8, u8, u8),
+    ) {
+        let (r, g, b) = color;
+        for [(x0, y0), (x1, y1)] in self.segments(x, y, angle) {
+            buffer.line_thick_rounded(
+                x0.round() as i32,
+                y0.round() as i32,
+                x1.round() as i32,
+                y1.round() as i32,
+                thickness,
+                r,
+                g,
+                b,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_glyphs() {
+        let a = glyph('A');
+        assert_eq!((a.left, a.right), (-9.0, 9.0));
+        assert_eq!(a.strokes.len(), 2);
+        assert_eq!(a.strokes[0], [(-8.0, 9.0), (0.0, -12.0), (8.0, 9.0)]);
+        assert!(glyph(' ').strokes.is_empty());
+        assert_eq!(glyph('\u{e9}'), glyph('?'));
+        // Every glyph stays on the grid
+        for ch in ' '..='~' {
+            let g = glyph(ch);
+            assert!(g.left < g.right, "{ch:?}");
+            assert!(g
+                .strokes
+                .iter()
+                .flatten()
+                .all(|&(_, y)| (-16.0..=16.0).contains(&y)));
+        }
+    }
+
+    #[test]
+    fn test_layout() {
+        let text = StrokeText::new("AA", CAP_HEIGHT, Align::Left);
+        assert_eq!(text.width(), 36.0);
+        assert_eq!(text.height(), CAP_HEIGHT);
+        // The apex of the second A, measured from the top-left
+        assert_eq!(text.strokes()[2][1], (27.0, 0.0));
+
+        let two = StrokeText::new("I\nAA", CAP_HEIGHT * 2.0, Align::Center).centered();
+        assert_eq!(two.height(), (CAP_HEIGHT + LINE_PITCH) * 2.0);
+        // The I is centred over the wider line
+        let (x, _) = two.strokes()[0][0];
+        assert!(x.abs() < 1e-4);
+    }
+}
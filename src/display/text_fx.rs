@@ -9,7 +9,7 @@
 //! - `visibility` - Alpha/visibility modifiers (blink, pulse, strobe)
 //! - `transform` - Geometric transforms (flip for reflection)
 
-use super::font::{get_glyph, GLYPH_HEIGHT, GLYPH_WIDTH};
+use super::fonts::with_default_font;
 use super::PixelBuffer;
 
 /// Offset functions - return (dx, dy) position modifiers
@@ -211,9 +211,7 @@ pub mod transform {
         b: u8,
         scale: u32,
     ) {
-        if let Some(glyph) = get_glyph(ch) {
-            draw_glyph_flipped(buffer, x, y, glyph, r, g, b, scale);
-        }
+        with_default_font(|font| font.draw_char_flipped(buffer, x, y, ch, r, g, b, scale as f32));
     }
 
     /// Draw a glyph vertically flipped
@@ -258,12 +256,7 @@ pub mod transform {
         b: u8,
         scale: u32,
     ) {
-        let char_width = (GLYPH_WIDTH * scale) as i32;
-        let mut cursor_x = x;
-        for ch in text.chars() {
-            draw_char_flipped(buffer, cursor_x, y, ch, r, g, b, scale);
-            cursor_x += char_width;
-        }
+        with_default_font(|font| font.draw_text_flipped(buffer, x, y, text, r, g, b, scale as f32));
     }
 
     /// Draw text with reflection below (lake effect)
@@ -277,9 +270,9 @@ pub mod transform {
         gap: i32,
         reflection_fade: f32,
     ) {
-        use super::super::font::draw_text_scaled;
+        use super::super::font::{draw_text_scaled, text_height_scaled};
 
-        let text_height = (GLYPH_HEIGHT * scale) as i32;
+        let text_height = text_height_scaled(scale) as i32;
 
         // Draw main text
         draw_text_scaled(buffer, x, y, text, color.0, color.1, color.2, scale);
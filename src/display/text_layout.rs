@@ -0,0 +1,479 @@
+//! Text Layout in Shapes
+//!
+//! Word-wraps text into an arbitrary shape, described by the horizontal runs
+//! it covers at each y. Every line uses the widest run available across its
+//! full height, so text follows the curve of a circle or the slant of a
+//! polygon's edges. `fit_text` picks the largest scale at which all of the
+//! text fits.
+//!
+//! Words only break mid-word when they are wider than the shape's widest
+//! run; otherwise a line too narrow for the next word is left empty.
+//! Newlines start a new paragraph.
+
+use super::fonts::Font;
+use super::PixelBuffer;
+use serde::{Deserialize, Serialize};
+
+/// Scale steps tried when searching for the best fit
+const FIT_ITERATIONS: usize = 12;
+
+/// Horizontal alignment of each line within its run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Align {
+    Left,
+    #[default]
+    Center,
+    Right,
+    /// Stretch word gaps to fill the run (last line of a paragraph stays left-aligned)
+    Justify,
+}
+
+/// Vertical placement of the block within the shape
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VAlign {
+    Top,
+    #[default]
+    Middle,
+    Bottom,
+}
+
+/// Settings for `fit_text`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutOptions {
+    pub align: Align,
+    pub valign: VAlign,
+    /// Smallest scale to try (1 = 8px line); text is cut off if it doesn't fit at this size
+    pub min_scale: f32,
+    /// Largest scale to try
+    pub max_scale: f32,
+    /// Line pitch as a multiple of the line height
+    pub line_spacing: f32,
+    /// Space kept clear inside the shape's edges, in pixels
+    pub padding: f32,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        Self {
+            align: Align::default(),
+            valign: VAlign::default(),
+            min_scale: 1.0,
+            max_scale: 8.0,
+            line_spacing: 1.2,
+            padding: 4.0,
+        }
+    }
+}
+
+/// A word placed at a pen position
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacedWord {
+    pub text: String,
+    pub x: f32,
+}
+
+/// One line of laid-out text
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLine {
+    /// Top of the line
+    pub y: f32,
+    pub words: Vec<PlacedWord>,
+}
+
+/// Text wrapped into a shape at a chosen scale
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextBlock {
+    pub scale: f32,
+    pub lines: Vec<TextLine>,
+    /// False if the text was cut off at the minimum scale
+    pub complete: bool,
+}
+
+impl TextBlock {
+    /// Draw the block
+    pub fn render(&self, buffer: &mut PixelBuffer, font: &Font, color: (u8, u8, u8)) {
+        let (r, g, b) = color;
+        for line in &self.lines {
+            for word in &line.words {
+                let (x, y) = (word.x.round() as i32, line.y.round() as i32);
+                font.draw_text(buffer, x, y, &word.text, r, g, b, self.scale);
+            }
+        }
+    }
+}
+
+/// Wrap `text` into a shape at the largest scale that fits.
+///
+/// `top` and `bottom` bound the shape vertically; `spans(y)` returns the
+/// sorted (x_start, x_end) runs inside it along the line `y`.
+pub fn fit_text(
+    font: &Font,
+    text: &str,
+    top: f32,
+    bottom: f32,
+    spans: impl Fn(f32) -> Vec<(f32, f32)>,
+    options: &LayoutOptions,
+) -> TextBlock {
+    let min = options.min_scale.max(0.1);
+    let max = options.max_scale.max(min);
+    let area = Area {
+        top: top + options.padding,
+        bottom: bottom - options.padding,
+        padding: options.padding,
+        spans: &spans,
+    };
+
+    // Sizes that only fit by breaking words lose to smaller ones that don't
+    let best = layout_at(font, text, max, &area, options, false);
+    if best.complete {
+        return best;
+    }
+    let mut best = layout_at(font, text, min, &area, options, false);
+    if !best.complete {
+        return layout_at(font, text, min, &area, options, true);
+    }
+    let (mut low, mut high) = (min, max);
+    for _ in 0..FIT_ITERATIONS {
+        let mid = (low + high) / 2.0;
+        let block = layout_at(font, text, mid, &area, options, false);
+        if block.complete {
+            low = mid;
+            best = block;
+        } else {
+            high = mid;
+        }
+    }
+    best
+}
+
+/// The space text is laid out in
+struct Area<'a> {
+    top: f32,
+    bottom: f32,
+    padding: f32,
+    spans: &'a dyn Fn(f32) -> Vec<(f32, f32)>,
+}
+
+impl Area<'_> {
+    /// Widest run clear of the edges over the whole band from `y0` to `y1`
+    fn run(&self, y0: f32, y1: f32) -> Option<(f32, f32)> {
+        let mut runs = (self.spans)(y0 + 0.5);
+        for y in [(y0 + y1) / 2.0, y1 - 0.5] {
+            runs = intersect(&runs, &(self.spans)(y));
+        }
+        runs.into_iter()
+            .map(|(a, b)| (a + self.padding, b - self.padding))
+            .filter(|(a, b)| b > a)
+            .max_by(|a, b| (a.1 - a.0).total_cmp(&(b.1 - b.0)))
+    }
+}
+
+/// Overlap of two sorted run lists
+fn intersect(a: &[(f32, f32)], b: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    let mut out = Vec::new();
+    for &(a0, a1) in a {
+        for &(b0, b1) in b {
+            let (start, end) = (a0.max(b0), a1.min(b1));
+            if end > start {
+                out.push((start, end));
+            }
+        }
+    }
+    out
+}
+
+/// Lay out at one scale, applying vertical alignment
+fn layout_at(
+    font: &Font,
+    text: &str,
+    scale: f32,
+    area: &Area,
+    options: &LayoutOptions,
+    break_words: bool,
+) -> TextBlock {
+    let from_top = wrap(font, text, scale, area.top, area, options, break_words);
+    let slack = match (from_top.complete, from_top.lines.last()) {
+        (true, Some(last)) => area.bottom - (last.y + font.height(scale) as f32),
+        _ => return from_top,
+    };
+    let shift = match options.valign {
+        VAlign::Top => return from_top,
+        VAlign::Middle => slack / 2.0,
+        VAlign::Bottom => slack,
+    };
+    // Runs change with y, so the shifted block may wrap differently; back off if it no longer fits
+    [shift, shift / 2.0]
+        .into_iter()
+        .filter(|&s| s >= 1.0)
+        .map(|s| wrap(font, text, scale, area.top + s, area, options, break_words))
+        .find(|block| block.complete)
+        .unwrap_or(from_top)
+}
+
+/// Greedy word wrap starting at `start_y`. Without `break_words`, a word
+/// wider than every line leaves the layout incomplete.
+fn wrap(
+    font: &Font,
+    text: &str,
+    scale: f32,
+    start_y: f32,
+    area: &Area,
+    options: &LayoutOptions,
+    break_words: bool,
+) -> TextBlock {
+    let glyph_h = font.height(scale) as f32;
+    let pitch = glyph_h * options.line_spacing.max(0.5);
+    let space = font.advance(' ', scale);
+    let widest = widest_run(area, glyph_h, pitch);
+    let mut lines = Vec::new();
+    let mut y = start_y;
+
+    for paragraph in text.lines() {
+        let mut words: Vec<String> = paragraph.split_whitespace().map(String::from).collect();
+        words.reverse();
+        if words.is_empty() {
+            // Blank line between paragraphs
+            y += pitch;
+            continue;
+        }
+        while !words.is_empty() {
+            if y + glyph_h > area.bottom {
+                return TextBlock {
+                    scale,
+                    lines,
+                    complete: false,
+                };
+            }
+            let Some((x0, x1)) = area.run(y, y + glyph_h) else {
+                y += pitch;
+                continue;
+            };
+            let room = x1 - x0;
+
+            // Fill the line: (word, width) pairs
+            let mut line: Vec<(String, f32)> = Vec::new();
+            let mut used = 0.0;
+            while let Some(word) = words.pop() {
+                let width = font.text_width(&word, scale) as f32;
+                let needed = if line.is_empty() {
+                    width
+                } else {
+                    used + space + width
+                };
+                if needed <= room {
+                    used = needed;
+                    line.push((word, width));
+                } else if line.is_empty() && width > widest {
+                    if !break_words {
+                        return TextBlock {
+                            scale,
+                            lines,
+                            complete: false,
+                        };
+                    }
+                    // Too long for any line: break it where it overflows this one
+                    let (head, tail) = split_to_fit(font, &word, scale, room);
+                    used = font.text_width(&head, scale) as f32;
+                    line.push((head, used));
+                    words.push(tail);
+                    break;
+                } else {
+                    words.push(word);
+                    break;
+                }
+            }
+            if !line.is_empty() {
+                let last_in_paragraph = words.is_empty();
+                lines.push(TextLine {
+                    y,
+                    words: place_words(
+                        line,
+                        used,
+                        x0,
+                        room,
+                        space,
+                        options.align,
+                        last_in_paragraph,
+                    ),
+                });
+            }
+            y += pitch;
+        }
+    }
+    TextBlock {
+        scale,
+        lines,
+        complete: true,
+    }
+}
+
+/// Widest run at any line position in the area
+fn widest_run(area: &Area, glyph_h: f32, pitch: f32) -> f32 {
+    let mut widest: f32 = 0.0;
+    let mut y = area.top;
+    while y + glyph_h <= area.bottom {
+        if let Some((x0, x1)) = area.run(y, y + glyph_h) {
+            widest = widest.max(x1 - x0);
+        }
+        y += pitch;
+    }
+    widest
+}
+
+/// Split a word into the longest prefix that fits `room` (at least one character) and the rest
+fn split_to_fit(font: &Font, word: &str, scale: f32, room: f32) -> (String, String) {
+    let split = font
+        .layout(word, scale)
+        .enumerate()
+        .find(|(_, c)| c.x + c.advance > room)
+        .map_or_else(|| word.chars().count(), |(i, _)| i.max(1));
+    let at = word
+        .char_indices()
+        .nth(split)
+        .map_or(word.len(), |(i, _)| i);
+    (word[..at].to_string(), word[at..].to_string())
+}
+
+/// Position the words of a line within its run
+fn place_words(
+    line: Vec<(String, f32)>,
+    used: f32,
+    x0: f32,
+    room: f32,
+    space: f32,
+    align: Align,
+    last_in_paragraph: bool,
+) -> Vec<PlacedWord> {
+    let gaps = line.len().saturating_sub(1) as f32;
+    let (mut x, gap) = match align {
+        Align::Left => (x0, space),
+        Align::Center => (x0 + (room - used) / 2.0, space),
+        Align::Right => (x0 + room - used, space),
+        Align::Justify if last_in_paragraph || gaps == 0.0 => (x0, space),
+        Align::Justify => (x0, space + (room - used) / gaps),
+    };
+    line.into_iter()
+        .map(|(text, width)| {
+            let placed = PlacedWord { text, x };
+            x += width + gap;
+            placed
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x0: f32, x1: f32) -> impl Fn(f32) -> Vec<(f32, f32)> {
+        move |_| vec![(x0, x1)]
+    }
+
+    fn text_of(block: &TextBlock) -> Vec<String> {
+        block
+            .lines
+            .iter()
+            .map(|l| {
+                let words: Vec<&str> = l.words.iter().map(|w| w.text.as_str()).collect();
+                words.join(" ")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_wrap_and_align() {
+        let font = Font::builtin();
+        let options = LayoutOptions {
+            align: Align::Left,
+            valign: VAlign::Top,
+            min_scale: 1.0,
+            max_scale: 1.0,
+            line_spacing: 1.0,
+            padding: 0.0,
+        };
+        // 12 characters wide, 3 lines tall
+        let block = fit_text(
+            &font,
+            "the quick brown fox jumps",
+            0.0,
+            24.0,
+            rect(0.0, 96.0),
+            &options,
+        );
+        assert!(block.complete);
+        assert_eq!(text_of(&block), ["the quick", "brown fox", "jumps"]);
+        assert_eq!(block.lines[1].y, 8.0);
+
+        let justified = LayoutOptions {
+            align: Align::Justify,
+            ..options
+        };
+        let block = fit_text(
+            &font,
+            "the quick brown fox jumps",
+            0.0,
+            24.0,
+            rect(0.0, 96.0),
+            &justified,
+        );
+        // "the" + stretched gap puts "quick" flush right
+        assert_eq!(block.lines[0].words[1].x, 96.0 - 40.0);
+        assert_eq!(block.lines[2].words[0].x, 0.0);
+
+        let too_long = fit_text(
+            &font,
+            "a b c d e f g h",
+            0.0,
+            16.0,
+            rect(0.0, 16.0),
+            &options,
+        );
+        assert!(!too_long.complete);
+        assert_eq!(too_long.lines.len(), 2);
+
+        // Words wider than the shape are broken
+        let block = fit_text(&font, "abcdefgh", 0.0, 32.0, rect(0.0, 24.0), &options);
+        assert_eq!(text_of(&block), ["abc", "def", "gh"]);
+    }
+
+    #[test]
+    fn test_fit_and_center_in_circle() {
+        let font = Font::builtin();
+        let circle = |y: f32| {
+            let dy = y - 50.0;
+            let half = (50.0f32 * 50.0 - dy * dy).sqrt();
+            if half > 0.0 {
+                vec![(50.0 - half, 50.0 + half)]
+            } else {
+                Vec::new()
+            }
+        };
+        let block = fit_text(
+            &font,
+            "HELLO",
+            0.0,
+            100.0,
+            circle,
+            &LayoutOptions::default(),
+        );
+        assert!(block.complete);
+        assert_eq!(block.lines.len(), 1);
+        assert!(block.scale > 1.5);
+
+        // One line, centred both ways
+        let line_h = font.height(block.scale) as f32;
+        let width = font.text_width("HELLO", block.scale) as f32;
+        assert!((block.lines[0].y + line_h / 2.0 - 50.0).abs() <= 1.0);
+        assert!((block.lines[0].words[0].x + width / 2.0 - 50.0).abs() <= 1.0);
+        // Every line sits inside the circle
+        for line in &block.lines {
+            for y in [line.y, line.y + line_h] {
+                let (x0, x1) = circle(y)[0];
+                assert!(line.words[0].x >= x0);
+                assert!(line.words[0].x + width <= x1);
+            }
+        }
+    }
+}
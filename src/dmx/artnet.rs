@@ -0,0 +1,54 @@
+//! Art-Net ArtDmx packet encoding and decoding
+//!
+//! Only the ArtDmx opcode is handled; polls and other opcodes are ignored.
+
+const HEADER: &[u8; 8] = b"Art-Net\0";
+const OP_DMX: u16 = 0x5000;
+const PROTOCOL_VERSION: u16 = 14;
+const DATA_OFFSET: usize = 18;
+
+/// Decode an ArtDmx packet into (port-address, channel data).
+/// Returns None for anything that is not a well-formed ArtDmx packet.
+pub fn parse(packet: &[u8]) -> Option<(u16, &[u8])> {
+    if packet.len() < DATA_OFFSET || &packet[0..8] != HEADER {
+        return None;
+    }
+
+    // Opcode is little-endian, everything else is big-endian
+    let opcode = u16::from_le_bytes([packet[8], packet[9]]);
+    if opcode != OP_DMX {
+        return None;
+    }
+
+    let sub_uni = packet[14] as u16;
+    let net = (packet[15] & 0x7F) as u16;
+    let universe = (net << 8) | sub_uni;
+
+    let length = u16::from_be_bytes([packet[16], packet[17]]) as usize;
+    let end = DATA_OFFSET + length.min(512);
+    if packet.len() < end {
+        return None;
+    }
+
+    Some((universe, &packet[DATA_OFFSET..end]))
+}
+
+/// Build an ArtDmx packet for a 15-bit port-address.
+/// Data longer than 512 channels is truncated; odd lengths are padded with zero.
+pub fn build(universe: u16, sequence: u8, data: &[u8]) -> Vec<u8> {
+    let data = &data[..data.len().min(512)];
+    let length = (data.len() + (data.len() & 1)).max(2);
+
+    let mut packet = Vec::with_capacity(DATA_OFFSET + length);
+    packet.extend_from_slice(HEADER);
+    packet.extend_from_slice(&OP_DMX.to_le_bytes());
+    packet.extend_from_slice(&PROTOCOL_VERSION.to_be_bytes());
+    packet.push(sequence);
+    packet.push(0); // Physical port
+    packet.push((universe & 0xFF) as u8);
+    packet.push(((universe >> 8) & 0x7F) as u8);
+    packet.extend_from_slice(&(length as u16).to_be_bytes());
+    packet.extend_from_slice(data);
+    packet.resize(DATA_OFFSET + length, 0);
+    packet
+}
@@ -0,0 +1,95 @@
+//! Wallfacer DMX fixture profile
+//!
+//! Channel layout (relative to the start address):
+//!
+//! | Ch | Function       | Values                                             |
+//! |----|----------------|----------------------------------------------------|
+//! | 1  | Effect select  | 0-7 no change, then 8 values per effect slot       |
+//! | 2  | Master dimmer  | 0 = black, 255 = full                              |
+//! | 3  | Speed          | 0 = frozen, 128 = normal, 255 = ~2x                |
+//! | 4  | Hue shift      | 0-255 maps to 0-360 degrees                        |
+//! | 5  | Chyron preset  | 0-7 default text, then 8 values per preset         |
+//! | 6  | Strobe         | 0-7 off, 8-255 ramps 1-20 Hz                       |
+
+/// Number of DMX channels the fixture occupies
+pub const CHANNEL_COUNT: usize = 6;
+
+/// Width of each slot range on the effect and chyron channels
+const SLOT_WIDTH: u8 = 8;
+
+const MAX_STROBE_HZ: f32 = 20.0;
+
+/// Where the fixture is patched
+#[derive(Debug, Clone, Copy)]
+pub struct FixtureProfile {
+    /// Universe to listen on (Art-Net port-address or sACN universe)
+    pub universe: u16,
+    /// 1-based DMX start address
+    pub address: u16,
+}
+
+impl FixtureProfile {
+    pub fn new(universe: u16, address: u16) -> Self {
+        Self {
+            universe,
+            address: address.clamp(1, (512 - CHANNEL_COUNT + 1) as u16),
+        }
+    }
+
+    /// Decode the fixture channels from a universe's channel data.
+    /// Channels beyond the end of a short packet read as zero.
+    pub fn decode(&self, data: &[u8]) -> FixtureState {
+        let start = self.address as usize - 1;
+        let channel = |offset: usize| data.get(start + offset).copied().unwrap_or(0);
+
+        FixtureState {
+            effect: slot(channel(0)),
+            dimmer: channel(1) as f32 / 255.0,
+            speed: channel(2) as f32 / 128.0,
+            hue_shift: channel(3) as f32 / 256.0 * 360.0,
+            chyron_preset: slot(channel(4)),
+            strobe_hz: strobe_rate(channel(5)),
+        }
+    }
+}
+
+impl Default for FixtureProfile {
+    fn default() -> Self {
+        Self::new(1, 1)
+    }
+}
+
+/// Decoded fixture channel values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixtureState {
+    /// Effect index requested by the desk (None = leave as is)
+    pub effect: Option<usize>,
+    /// Master dimmer, 0.0 to 1.0
+    pub dimmer: f32,
+    /// Animation speed multiplier
+    pub speed: f32,
+    /// Hue rotation in degrees
+    pub hue_shift: f32,
+    /// Chyron preset index (None = default chyron)
+    pub chyron_preset: Option<usize>,
+    /// Strobe rate in Hz (0 = off)
+    pub strobe_hz: f32,
+}
+
+/// Map a channel value onto a slot index using fixed-width ranges
+fn slot(value: u8) -> Option<usize> {
+    if value < SLOT_WIDTH {
+        None
+    } else {
+        Some((value / SLOT_WIDTH) as usize - 1)
+    }
+}
+
+fn strobe_rate(value: u8) -> f32 {
+    if value < SLOT_WIDTH {
+        0.0
+    } else {
+        let t = (value - SLOT_WIDTH) as f32 / (255 - SLOT_WIDTH) as f32;
+        1.0 + t * (MAX_STROBE_HZ - 1.0)
+    }
+}
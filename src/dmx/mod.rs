@@ -0,0 +1,304 @@
+//! DMX input over Art-Net and sACN (E1.31)
+//!
+//! Listens for DMX universes on UDP in background threads and decodes the
+//! wallfacer fixture profile, so a lighting desk can patch the projector
+//! like any other fixture.
+
+pub mod artnet;
+mod fixture;
+pub mod sacn;
+
+#[allow(unused_imports)]
+pub use fixture::{FixtureProfile, FixtureState, CHANNEL_COUNT};
+
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// Standard Art-Net UDP port
+pub const ARTNET_PORT: u16 = 6454;
+/// Standard sACN UDP port
+pub const SACN_PORT: u16 = 5568;
+
+/// Which wire protocol a packet arrived on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Protocol {
+    ArtNet,
+    Sacn,
+}
+
+/// Listener configuration
+#[derive(Debug, Clone)]
+pub struct DmxOptions {
+    /// Address to bind both listeners on
+    pub bind: Ipv4Addr,
+    /// Art-Net port (None = don't listen for Art-Net)
+    pub artnet_port: Option<u16>,
+    /// sACN port (None = don't listen for sACN)
+    pub sacn_port: Option<u16>,
+    pub profile: FixtureProfile,
+}
+
+impl Default for DmxOptions {
+    fn default() -> Self {
+        Self {
+            bind: Ipv4Addr::UNSPECIFIED,
+            artnet_port: Some(ARTNET_PORT),
+            sacn_port: Some(SACN_PORT),
+            profile: FixtureProfile::default(),
+        }
+    }
+}
+
+/// DMX listener that decodes fixture state in background threads
+pub struct DmxInput {
+    receiver: Receiver<FixtureState>,
+    artnet_addr: Option<SocketAddr>,
+    sacn_addr: Option<SocketAddr>,
+    _threads: Vec<thread::JoinHandle<()>>,
+}
+
+impl DmxInput {
+    /// Bind the configured listeners.
+    /// Fails if none of the requested sockets could be bound.
+    pub fn new(options: &DmxOptions) -> Result<Self, String> {
+        let (sender, receiver) = mpsc::channel();
+        let mut threads = Vec::new();
+        let mut errors = Vec::new();
+
+        let mut artnet_addr = None;
+        let mut sacn_addr = None;
+
+        let listeners = [
+            (options.artnet_port, Protocol::ArtNet),
+            (options.sacn_port, Protocol::Sacn),
+        ];
+        for (port, protocol) in listeners {
+            let Some(port) = port else { continue };
+            match Self::spawn(options, port, protocol, &sender) {
+                Ok((addr, handle)) => {
+                    threads.push(handle);
+                    match protocol {
+                        Protocol::ArtNet => artnet_addr = Some(addr),
+                        Protocol::Sacn => sacn_addr = Some(addr),
+                    }
+                },
+                Err(e) => errors.push(format!("{:?} port {}: {}", protocol, port, e)),
+            }
+        }
+
+        if threads.is_empty() {
+            return Err(if errors.is_empty() {
+                "No DMX protocols enabled".to_string()
+            } else {
+                errors.join(", ")
+            });
+        }
+        for e in &errors {
+            eprintln!("DMX: Failed to bind {}", e);
+        }
+
+        Ok(Self {
+            receiver,
+            artnet_addr,
+            sacn_addr,
+            _threads: threads,
+        })
+    }
+
+    fn spawn(
+        options: &DmxOptions,
+        port: u16,
+        protocol: Protocol,
+        sender: &Sender<FixtureState>,
+    ) -> Result<(SocketAddr, thread::JoinHandle<()>), String> {
+        let socket = UdpSocket::bind((options.bind, port)).map_err(|e| e.to_string())?;
+        socket
+            .set_read_timeout(Some(Duration::from_millis(500)))
+            .map_err(|e| e.to_string())?;
+
+        if protocol == Protocol::Sacn {
+            // Join the universe's multicast group; unicast still works if this fails
+            let group = sacn::multicast_addr(options.profile.universe);
+            if let Err(e) = socket.join_multicast_v4(&group, &options.bind) {
+                eprintln!("DMX: Could not join sACN multicast {}: {}", group, e);
+            }
+        }
+
+        let addr = socket.local_addr().map_err(|e| e.to_string())?;
+        let profile = options.profile;
+        let sender = sender.clone();
+        let handle = thread::spawn(move || {
+            Self::listen_loop(&socket, protocol, profile, &sender);
+        });
+
+        Ok((addr, handle))
+    }
+
+    fn listen_loop(
+        socket: &UdpSocket,
+        protocol: Protocol,
+        profile: FixtureProfile,
+        sender: &Sender<FixtureState>,
+    ) {
+        let mut packet = [0u8; 1024];
+        let mut last_state: Option<FixtureState> = None;
+
+        loop {
+            let len = match socket.recv_from(&mut packet) {
+                Ok((len, _)) => len,
+                Err(e)
+                    if e.kind() == std::io::ErrorKind::WouldBlock
+                        || e.kind() == std::io::ErrorKind::TimedOut =>
+                {
+                    continue;
+                },
+                Err(_) => break,
+            };
+
+            let decoded = match protocol {
+                Protocol::ArtNet => artnet::parse(&packet[..len]),
+                Protocol::Sacn => sacn::parse(&packet[..len]),
+            };
+
+            if let Some((universe, data)) = decoded {
+                if universe != profile.universe {
+                    continue;
+                }
+                // Desks resend the full universe continuously - only forward changes
+                let state = profile.decode(data);
+                if last_state != Some(state) {
+                    last_state = Some(state);
+                    if sender.send(state).is_err() {
+                        // Main thread gone, exit
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Poll for the latest fixture state (non-blocking).
+    /// Returns the most recent state if any arrived, discarding older ones.
+    pub fn poll(&self) -> Option<FixtureState> {
+        let mut latest = None;
+        while let Ok(state) = self.receiver.try_recv() {
+            latest = Some(state);
+        }
+        latest
+    }
+
+    /// Bound Art-Net address, if listening
+    pub fn artnet_addr(&self) -> Option<SocketAddr> {
+        self.artnet_addr
+    }
+
+    /// Bound sACN address, if listening
+    pub fn sacn_addr(&self) -> Option<SocketAddr> {
+        self.sacn_addr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn local_input(universe: u16, address: u16) -> DmxInput {
+        DmxInput::new(&DmxOptions {
+            bind: Ipv4Addr::LOCALHOST,
+            artnet_port: Some(0),
+            sacn_port: Some(0),
+            profile: FixtureProfile::new(universe, address),
+        })
+        .unwrap()
+    }
+
+    fn wait_for_state(input: &DmxInput) -> Option<FixtureState> {
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while Instant::now() < deadline {
+            if let Some(state) = input.poll() {
+                return Some(state);
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        None
+    }
+
+    #[test]
+    fn test_artnet_roundtrip() {
+        let data = [1, 2, 3, 4, 5];
+        let packet = artnet::build(0x1234, 7, &data);
+        let (universe, decoded) = artnet::parse(&packet).unwrap();
+        assert_eq!(universe, 0x1234);
+        // Odd lengths are padded to even
+        assert_eq!(decoded, &[1, 2, 3, 4, 5, 0]);
+    }
+
+    #[test]
+    fn test_sacn_roundtrip() {
+        let data = [10, 20, 30];
+        let packet = sacn::build(42, 1, sacn::DEFAULT_PRIORITY, &[0; 16], "test", &data);
+        let (universe, decoded) = sacn::parse(&packet).unwrap();
+        assert_eq!(universe, 42);
+        assert_eq!(decoded, &data);
+    }
+
+    #[test]
+    fn test_rejects_garbage() {
+        assert!(artnet::parse(b"not an art-net packet").is_none());
+        assert!(sacn::parse(&[0u8; 200]).is_none());
+    }
+
+    #[test]
+    fn test_fixture_decode() {
+        let profile = FixtureProfile::new(1, 3);
+        let mut data = [0u8; 16];
+        data[2..8].copy_from_slice(&[16, 255, 128, 128, 7, 255]);
+        let state = profile.decode(&data);
+        assert_eq!(state.effect, Some(1));
+        assert_eq!(state.dimmer, 1.0);
+        assert_eq!(state.speed, 1.0);
+        assert_eq!(state.hue_shift, 180.0);
+        assert_eq!(state.chyron_preset, None);
+        assert_eq!(state.strobe_hz, 20.0);
+    }
+
+    #[test]
+    fn test_artnet_over_udp() {
+        let input = local_input(3, 1);
+        let sender = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+
+        // Packets for other universes are ignored
+        let other = artnet::build(4, 0, &[255; 6]);
+        sender
+            .send_to(&other, input.artnet_addr().unwrap())
+            .unwrap();
+
+        let packet = artnet::build(3, 1, &[24, 128, 0, 0, 0, 0]);
+        sender
+            .send_to(&packet, input.artnet_addr().unwrap())
+            .unwrap();
+
+        let state = wait_for_state(&input).unwrap();
+        assert_eq!(state.effect, Some(2));
+        assert!((state.dimmer - 128.0 / 255.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_sacn_over_udp() {
+        let input = local_input(7, 10);
+        let sender = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+
+        let mut data = [0u8; 20];
+        data[9..15].copy_from_slice(&[0, 255, 64, 0, 20, 0]);
+        let packet = sacn::build(7, 0, sacn::DEFAULT_PRIORITY, &[1; 16], "desk", &data);
+        sender.send_to(&packet, input.sacn_addr().unwrap()).unwrap();
+
+        let state = wait_for_state(&input).unwrap();
+        assert_eq!(state.effect, None);
+        assert_eq!(state.speed, 0.5);
+        assert_eq!(state.chyron_preset, Some(1));
+    }
+}
@@ -0,0 +1,107 @@
+//! sACN (ANSI E1.31) data packet encoding and decoding
+//!
+//! Handles the root, framing and DMP layers of a single data packet.
+//! Universe discovery and synchronization packets are ignored.
+
+const ACN_IDENTIFIER: &[u8; 12] = b"ASC-E1.17\0\0\0";
+const VECTOR_ROOT_DATA: u32 = 0x0000_0004;
+const VECTOR_FRAMING_DATA: u32 = 0x0000_0002;
+const VECTOR_DMP_SET_PROPERTY: u8 = 0x02;
+const OPTION_PREVIEW: u8 = 0x80;
+const OPTION_TERMINATED: u8 = 0x40;
+const DATA_OFFSET: usize = 126;
+
+/// Default sACN priority for transmitted packets
+pub const DEFAULT_PRIORITY: u8 = 100;
+
+/// Multicast group for a universe (239.255.hi.lo)
+pub fn multicast_addr(universe: u16) -> std::net::Ipv4Addr {
+    std::net::Ipv4Addr::new(239, 255, (universe >> 8) as u8, (universe & 0xFF) as u8)
+}
+
+/// Decode an E1.31 data packet into (universe, channel data).
+/// Preview packets, stream-terminated packets and non-zero start codes are skipped.
+pub fn parse(packet: &[u8]) -> Option<(u16, &[u8])> {
+    if packet.len() < DATA_OFFSET || &packet[4..16] != ACN_IDENTIFIER {
+        return None;
+    }
+
+    let root_vector = u32::from_be_bytes([packet[18], packet[19], packet[20], packet[21]]);
+    let framing_vector = u32::from_be_bytes([packet[40], packet[41], packet[42], packet[43]]);
+    if root_vector != VECTOR_ROOT_DATA
+        || framing_vector != VECTOR_FRAMING_DATA
+        || packet[117] != VECTOR_DMP_SET_PROPERTY
+    {
+        return None;
+    }
+
+    let options = packet[112];
+    if options & (OPTION_PREVIEW | OPTION_TERMINATED) != 0 {
+        return None;
+    }
+
+    // Property count includes the start code
+    let count = u16::from_be_bytes([packet[123], packet[124]]) as usize;
+    if count == 0 || packet[125] != 0 {
+        return None;
+    }
+
+    let end = DATA_OFFSET + (count - 1).min(512);
+    if packet.len() < end {
+        return None;
+    }
+
+    let universe = u16::from_be_bytes([packet[113], packet[114]]);
+    Some((universe, &packet[DATA_OFFSET..end]))
+}
+
+/// Build an E1.31 data packet.
+/// `cid` identifies the sender; `source` is truncated to 63 bytes.
+pub fn build(
+    universe: u16,
+    sequence: u8,
+    priority: u8,
+    cid: &[u8; 16],
+    source: &str,
+    data: &[u8],
+) -> Vec<u8> {
+    let data = &data[..data.len().min(512)];
+    let total = DATA_OFFSET + data.len();
+    let flags_len = |from: usize| (0x7000 | (total - from) as u16).to_be_bytes();
+
+    let mut packet = Vec::with_capacity(total);
+
+    // Root layer
+    packet.extend_from_slice(&0x0010u16.to_be_bytes());
+    packet.extend_from_slice(&0x0000u16.to_be_bytes());
+    packet.extend_from_slice(ACN_IDENTIFIER);
+    packet.extend_from_slice(&flags_len(16));
+    packet.extend_from_slice(&VECTOR_ROOT_DATA.to_be_bytes());
+    packet.extend_from_slice(cid);
+
+    // Framing layer
+    packet.extend_from_slice(&flags_len(38));
+    packet.extend_from_slice(&VECTOR_FRAMING_DATA.to_be_bytes());
+    let mut name = [0u8; 64];
+    let source = source.as_bytes();
+    let name_len = source.len().min(63);
+    name[..name_len].copy_from_slice(&source[..name_len]);
+    packet.extend_from_slice(&name);
+    packet.push(priority);
+    packet.extend_from_slice(&0u16.to_be_bytes()); // Sync address
+    packet.push(sequence);
+    packet.push(0); // Options
+    packet.extend_from_slice(&universe.to_be_bytes());
+
+    // DMP layer
+    packet.extend_from_slice(&flags_len(115));
+    packet.push(VECTOR_DMP_SET_PROPERTY);
+    packet.push(0xA1); // Address and data type
+    packet.extend_from_slice(&0u16.to_be_bytes()); // First property address
+    packet.extend_from_slice(&1u16.to_be_bytes()); // Address increment
+    packet.extend_from_slice(&(data.len() as u16 + 1).to_be_bytes());
+    packet.push(0); // DMX start code
+    packet.extend_from_slice(data);
+
+    packet
+}
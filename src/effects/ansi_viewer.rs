@@ -0,0 +1,197 @@
+//! ANSI Art Slideshow
+//!
+//! Shows every `.ans` file in a directory in turn, drawn with the CP437
+//! 8x16 font and scaled to the width of the wall. Each piece is revealed at
+//! modem speed in the order it was drawn, the view smoothly scrolling to
+//! follow the cursor down tall pieces, then held before the next file.
+//! The directory is rescanned after each pass, so new files can be dropped
+//! in while it runs.
+
+use super::Effect;
+use crate::ansi::AnsiArt;
+use crate::display::fonts::cp437;
+use crate::display::PixelBuffer;
+use crate::regions::Scene;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory scanned when none is configured
+pub const DEFAULT_ANSI_DIR: &str = "ansi";
+
+/// Modem speed for the reveal, in bits per second (10 bits per byte on the wire)
+const BAUD: f32 = 14_400.0;
+/// Seconds to show a finished piece
+const HOLD_SECONDS: f32 = 6.0;
+/// How quickly the view catches up with the cursor (per second)
+const SCROLL_EASE: f32 = 3.0;
+/// Non-iCE blink rate in Hz
+const BLINK_HZ: f32 = 1.875;
+
+/// Shown when the directory has no art
+const PLACEHOLDER: &[u8] = b"\x1b[1;36m\xc9\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xbb\r\n\
+\xba \x1b[0;33mNo .ans files found    \x1b[1;36m\xba\r\n\
+\xc8\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xcd\xbc\r\n";
+
+/// Slideshow of ANSI art files
+pub struct AnsiViewer {
+    dir: PathBuf,
+    files: Vec<PathBuf>,
+    index: usize,
+    art: AnsiArt,
+    /// Bytes drawn so far
+    revealed: f32,
+    /// Art pixel row at the top of the screen
+    scroll: f32,
+    hold: f32,
+    time: f32,
+    /// Art pixels per screen pixel, from the last update
+    scale: f32,
+    screen_height: u32,
+}
+
+impl AnsiViewer {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let mut viewer = Self {
+            dir: dir.into(),
+            files: Vec::new(),
+            index: 0,
+            art: AnsiArt::default(),
+            revealed: 0.0,
+            scroll: 0.0,
+            hold: 0.0,
+            time: 0.0,
+            scale: 1.0,
+            screen_height: 0,
+        };
+        viewer.files = scan(&viewer.dir);
+        viewer.load_current();
+        viewer
+    }
+
+    /// Load the file at `index`, skipping any that fail to parse
+    fn load_current(&mut self) {
+        self.revealed = 0.0;
+        self.scroll = 0.0;
+        self.hold = 0.0;
+        while self.index < self.files.len() {
+            match AnsiArt::load(&self.files[self.index]) {
+                Ok(art) => {
+                    self.art = art;
+                    return;
+                },
+                Err(e) => {
+                    eprintln!("ANSI: {}: {}", self.files[self.index].display(), e);
+                    self.files.remove(self.index);
+                },
+            }
+        }
+        self.index = 0;
+        self.art = AnsiArt::parse(PLACEHOLDER).unwrap_or_default();
+    }
+
+    fn next_file(&mut self) {
+        self.index += 1;
+        if self.index >= self.files.len() {
+            self.files = scan(&self.dir);
+            self.index = 0;
+        }
+        self.load_current();
+    }
+
+    /// Art size in pixels
+    fn art_size(&self) -> (u32, u32) {
+        (
+            self.art.columns() as u32 * cp437::WIDTH,
+            self.art.row_count() as u32 * cp437::HEIGHT,
+        )
+    }
+}
+
+/// `.ans` files in a directory, sorted by name
+fn scan(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("ans"))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+impl Effect for AnsiViewer {
+    fn update(&mut self, dt: f32, width: u32, height: u32, _scene: &Scene) {
+        self.time += dt;
+        let (art_width, art_height) = self.art_size();
+        self.scale = art_width.max(1) as f32 / width.max(1) as f32;
+        self.screen_height = height;
+
+        self.revealed = (self.revealed + dt * BAUD / 10.0).min(self.art.len() as f32);
+        let done = self.revealed >= self.art.len() as f32;
+
+        // Keep the cursor's row a line above the bottom of the screen
+        let view = height as f32 * self.scale;
+        let cursor = self.art.cursor_row_at(self.revealed as usize) as f32;
+        let bottom = if done {
+            art_height as f32
+        } else {
+            (cursor + 2.0) * cp437::HEIGHT as f32
+        };
+        let target = (bottom - view).clamp(0.0, (art_height as f32 - view).max(0.0));
+        self.scroll += (target - self.scroll) * (1.0 - (-SCROLL_EASE * dt).exp());
+
+        if done {
+            self.hold += dt;
+            if self.hold >= HOLD_SECONDS {
+                self.next_file();
+            }
+        }
+    }
+
+    fn render(&self, buffer: &mut PixelBuffer) {
+        buffer.clear(0, 0, 0);
+        let (art_width, art_height) = self.art_size();
+        if art_width == 0 {
+            return;
+        }
+        let revealed = self.revealed as usize;
+        let blink_on = (self.time * BLINK_HZ * 2.0) as u32 % 2 == 0;
+
+        // Source column for every screen column
+        let columns: Vec<u32> = (0..buffer.width())
+            .map(|x| ((x as f32 + 0.5) * self.scale) as u32)
+            .take_while(|&px| px < art_width)
+            .collect();
+
+        for y in 0..self.screen_height.min(buffer.height()) {
+            let py = (self.scroll + (y as f32 + 0.5) * self.scale) as u32;
+            if py >= art_height {
+                break;
+            }
+            let row = (py / cp437::HEIGHT) as usize;
+            let line = (py % cp437::HEIGHT) as usize;
+            for (x, &px) in columns.iter().enumerate() {
+                let cell = self.art.cell((px / cp437::WIDTH) as usize, row);
+                if cell.offset > revealed {
+                    continue;
+                }
+                let bits = cp437::GLYPHS[usize::from(cell.ch)][line];
+                let lit = bits & (0x80 >> (px % cp437::WIDTH)) != 0;
+                let (r, g, b) = if lit && (blink_on || !cell.blink) {
+                    cell.fg
+                } else {
+                    cell.bg
+                };
+                buffer.set_pixel(x as i32, y as i32, r, g, b);
+            }
+        }
+    }
+
+    fn name(&self) -> &str {
+        "ANSI Art"
+    }
+}
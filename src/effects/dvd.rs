@@ -64,7 +64,7 @@ impl Dvd {
 
         for region in &scene.regions {
             let collision = match region.get_shape() {
-                Shape::Polygon(p) => {
+                Shape::Polygon(p) if p.holes.is_empty() => {
                     let verts = p.as_tuples();
                     rect_polygon_collision(
                         self.x,
@@ -90,6 +90,12 @@ impl Dvd {
                         None
                     }
                 }
+                shape => shape.rect_collision(
+                    self.x,
+                    self.y,
+                    self.logo_width as f32,
+                    self.logo_height as f32,
+                ),
             };
 
             if collision.is_some() {
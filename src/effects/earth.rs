@@ -304,7 +304,7 @@ impl Effect for Earth {
         // Region collision (circle around globe center)
         for region in &scene.regions {
             let collision = match region.get_shape() {
-                Shape::Polygon(p) => {
+                Shape::Polygon(p) if p.holes.is_empty() => {
                     let verts = p.as_tuples();
                     circle_polygon_collision(self.pos_x, self.pos_y, visual_radius, &verts)
                 }
@@ -318,6 +318,7 @@ impl Effect for Earth {
                         c.radius,
                     )
                 }
+                shape => shape.circle_collision(self.pos_x, self.pos_y, visual_radius),
             };
             if let Some((nx, ny, penetration)) = collision {
                 // Only reflect if moving INTO the region (prevents oscillation)
@@ -5,7 +5,6 @@
 
 use super::Effect;
 use crate::display::PixelBuffer;
-use crate::geometry::circle_polygon_collision;
 use crate::regions::{Scene, Shape};
 use crate::util::Rng;
 
@@ -138,22 +137,6 @@ impl Effect for GravityBalls {
             // Bounce off regions
             for region in &scene.regions {
                 match region.get_shape() {
-                    Shape::Polygon(poly) => {
-                        // Check circle-polygon collision with proper normal
-                        let verts = poly.as_tuples();
-                        if let Some((nx, ny, penetration)) =
-                            circle_polygon_collision(ball.x, ball.y, ball.radius, &verts)
-                        {
-                            // Push ball out along normal
-                            ball.x += nx * (penetration + 1.0);
-                            ball.y += ny * (penetration + 1.0);
-
-                            // Reflect velocity off the surface normal
-                            let dot = ball.vx * nx + ball.vy * ny;
-                            ball.vx = (ball.vx - 2.0 * dot * nx) * BOUNCE_DAMPING;
-                            ball.vy = (ball.vy - 2.0 * dot * ny) * BOUNCE_DAMPING;
-                        }
-                    }
                     Shape::Circle(circle) => {
                         let dx = ball.x - circle.center.x;
                         let dy = ball.y - circle.center.y;
@@ -173,6 +156,21 @@ impl Effect for GravityBalls {
                             ball.vy = (ball.vy - 2.0 * dot * ny) * BOUNCE_DAMPING;
                         }
                     }
+                    shape => {
+                        // Check collision with the outline, with proper normal
+                        if let Some((nx, ny, penetration)) =
+                            shape.circle_collision(ball.x, ball.y, ball.radius)
+                        {
+                            // Push ball out along normal
+                            ball.x += nx * (penetration + 1.0);
+                            ball.y += ny * (penetration + 1.0);
+
+                            // Reflect velocity off the surface normal
+                            let dot = ball.vx * nx + ball.vy * ny;
+                            ball.vx = (ball.vx - 2.0 * dot * nx) * BOUNCE_DAMPING;
+                            ball.vy = (ball.vy - 2.0 * dot * ny) * BOUNCE_DAMPING;
+                        }
+                    }
                 }
             }
 
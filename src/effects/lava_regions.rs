@@ -140,13 +140,6 @@ impl Effect for LavaRegions {
             let spawn_chance = dt * 30.0; // ~30 per second per region
             if self.rng.next_f32() < spawn_chance {
                 match region.get_shape() {
-                    Shape::Polygon(poly) => {
-                        if let Some((min_x, min_y, max_x, _max_y)) = poly.bounds() {
-                            // Spawn near top edge
-                            let x = min_x + self.rng.next_f32() * (max_x - min_x);
-                            self.spawn_ember(x, min_y);
-                        }
-                    }
                     Shape::Circle(circle) => {
                         // Spawn from top of circle
                         let angle = -std::f32::consts::PI * 0.5
@@ -155,6 +148,13 @@ impl Effect for LavaRegions {
                         let y = circle.center.y + angle.sin() * circle.radius;
                         self.spawn_ember(x, y);
                     }
+                    shape => {
+                        if let Some((min_x, min_y, max_x, _max_y)) = shape.bounds() {
+                            // Spawn near top edge
+                            let x = min_x + self.rng.next_f32() * (max_x - min_x);
+                            self.spawn_ember(x, min_y);
+                        }
+                    }
                 }
             }
         }
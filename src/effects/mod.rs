@@ -1,3 +1,4 @@
+mod ansi_viewer;
 mod bobs;
 mod copper_bars;
 mod dot_tunnel;
@@ -24,9 +25,11 @@ mod testpattern;
 mod text_fx_demo;
 mod tunnel;
 mod vector_balls;
+mod vector_text;
 mod vortex;
 mod worms;
 
+pub use ansi_viewer::{AnsiViewer, DEFAULT_ANSI_DIR};
 pub use bobs::Bobs;
 pub use copper_bars::CopperBars;
 pub use dot_tunnel::DotTunnel;
@@ -53,6 +56,7 @@ pub use testpattern::TestPattern;
 pub use text_fx_demo::TextFxDemo;
 pub use tunnel::Tunnel;
 pub use vector_balls::VectorBalls;
+pub use vector_text::VectorText;
 pub use vortex::Vortex;
 pub use worms::Worms;
 
@@ -0,0 +1,159 @@
+//! Vector Text Effect
+//!
+//! Messages drawn in the stroke font, extruded into 3D and spun in front
+//! of the camera. The back face and the depth edges are drawn dim and the
+//! front face glows, for the vector-monitor look. Each message flies in,
+//! turns for a while, then flies out for the next.
+
+use super::Effect;
+use crate::display::{Align, PixelBuffer, StrokeText};
+use crate::math3d::{project, Vec3};
+use crate::regions::Scene;
+use crate::util::hsv_to_rgb;
+
+const MESSAGES: &[&str] = &["WALLFACER", "VECTOR\nTEXT", "2389\nRESEARCH"];
+
+/// Seconds each message is shown, including flying in and out
+const MESSAGE_SECONDS: f32 = 9.0;
+/// Seconds spent flying in (and out)
+const FLY_SECONDS: f32 = 1.2;
+/// Extrusion depth as a fraction of the capital height
+const DEPTH: f32 = 0.35;
+/// Camera distance as a multiple of the screen width
+const CAMERA_DISTANCE: f32 = 1.2;
+
+/// Spinning, extruded stroke-font text
+pub struct VectorText {
+    time: f32,
+    index: usize,
+    text: StrokeText,
+    /// Capital height the layout was built for
+    size: f32,
+    /// Screen size the layout was built for
+    layout_for: (u32, u32),
+}
+
+impl VectorText {
+    pub fn new() -> Self {
+        Self {
+            time: 0.0,
+            index: 0,
+            text: StrokeText::default(),
+            size: 0.0,
+            layout_for: (0, 0),
+        }
+    }
+
+    /// Lay out the current message to fill most of the screen
+    fn layout(&mut self, width: u32, height: u32) {
+        let message = MESSAGES[self.index];
+        let unit = StrokeText::new(message, 1.0, Align::Center);
+        let size = (width as f32 * 0.8 / unit.width().max(0.01))
+            .min(height as f32 * 0.6 / unit.height().max(0.01));
+        self.text = StrokeText::new(message, size, Align::Center).centered();
+        self.size = size;
+        self.layout_for = (width, height);
+    }
+
+    /// How far through the current message, 0..1 for flying in, 1 while shown, 1..2 flying out
+    fn flight(&self) -> f32 {
+        let t = self.time % MESSAGE_SECONDS;
+        if t < FLY_SECONDS {
+            t / FLY_SECONDS
+        } else if t > MESSAGE_SECONDS - FLY_SECONDS {
+            1.0 + (t - (MESSAGE_SECONDS - FLY_SECONDS)) / FLY_SECONDS
+        } else {
+            1.0
+        }
+    }
+}
+
+impl Default for VectorText {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Effect for VectorText {
+    fn update(&mut self, dt: f32, width: u32, height: u32, _scene: &Scene) {
+        self.time += dt;
+        let index = (self.time / MESSAGE_SECONDS) as usize % MESSAGES.len();
+        if index != self.index || self.layout_for != (width, height) {
+            self.index = index;
+            self.layout(width, height);
+        }
+    }
+
+    fn render(&self, buffer: &mut PixelBuffer) {
+        buffer.clear(0, 0, 0);
+        let width = buffer.width() as f32;
+        let cx = width / 2.0;
+        let cy = buffer.height() as f32 / 2.0;
+        let camera_z = width * CAMERA_DISTANCE;
+        // Text at depth zero is drawn at its laid-out size
+        let fov = camera_z;
+
+        // Fly in from far away and out past the camera's side
+        let flight = self.flight();
+        let ease = |t: f32| t * t * (3.0 - 2.0 * t);
+        let (distance, drift) = if flight <= 1.0 {
+            ((1.0 - ease(flight)) * camera_z * 6.0, 0.0)
+        } else {
+            (0.0, ease(flight - 1.0) * width * 1.5)
+        };
+
+        let spin = self.time * 0.8;
+        let tilt = (self.time * 0.5).sin() * 0.35;
+        let roll = (self.time * 0.3).sin() * 0.15;
+        let half_depth = self.size * DEPTH / 2.0;
+        let transform = |(x, y): (f32, f32), z: f32| {
+            let p = Vec3::new(x, y, z)
+                .rotate_y(spin)
+                .rotate_x(tilt)
+                .rotate_z(roll);
+            project(p + Vec3::new(drift, 0.0, camera_z + distance), fov, cx, cy)
+        };
+
+        let hue = (self.time * 40.0) % 360.0;
+        let (r, g, b) = hsv_to_rgb(hue, 0.7, 1.0);
+        let dim = (r / 4, g / 4, b / 4);
+        let thickness = (self.size / 12.0).clamp(2.0, 12.0) as i32;
+
+        for stroke in self.text.strokes() {
+            let front: Vec<Option<(f32, f32)>> =
+                stroke.iter().map(|&p| transform(p, -half_depth)).collect();
+            let back: Vec<Option<(f32, f32)>> =
+                stroke.iter().map(|&p| transform(p, half_depth)).collect();
+
+            // Back face and depth edges
+            for pair in back.windows(2) {
+                if let [Some((x0, y0)), Some((x1, y1))] = *pair {
+                    buffer.line_aa_additive(x0, y0, x1, y1, dim.0, dim.1, dim.2);
+                }
+            }
+            for (f, b) in front.iter().zip(&back) {
+                if let (Some((x0, y0)), Some((x1, y1))) = (*f, *b) {
+                    buffer.line_aa_additive(x0, y0, x1, y1, dim.0, dim.1, dim.2);
+                }
+            }
+
+            // Front face: soft halo, then a bright core
+            for pair in front.windows(2) {
+                if let [Some((x0, y0)), Some((x1, y1))] = *pair {
+                    buffer.line_thick_rounded(
+                        x0 as i32, y0 as i32, x1 as i32, y1 as i32, thickness, dim.0, dim.1, dim.2,
+                    );
+                }
+            }
+            for pair in front.windows(2) {
+                if let [Some((x0, y0)), Some((x1, y1))] = *pair {
+                    buffer.line_aa_additive(x0, y0, x1, y1, r, g, b);
+                }
+            }
+        }
+    }
+
+    fn name(&self) -> &str {
+        "Vector Text"
+    }
+}
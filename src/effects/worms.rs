@@ -142,7 +142,7 @@ impl Worm {
         let half_size = head_size / 2.0;
         for region in &scene.regions {
             let collision = match region.get_shape() {
-                Shape::Polygon(p) => {
+                Shape::Polygon(p) if p.holes.is_empty() => {
                     let verts = p.as_tuples();
                     rect_polygon_collision(
                         new_x - half_size,
@@ -168,6 +168,12 @@ impl Worm {
                         None
                     }
                 }
+                shape => shape.rect_collision(
+                    new_x - half_size,
+                    new_y - half_size,
+                    head_size,
+                    head_size,
+                ),
             };
 
             if let Some((nx, ny, dist)) = collision {
@@ -0,0 +1,450 @@
+//! Local data feeds for the chyron
+//!
+//! Feeds read RSS/Atom files, JSON files or the output of a local command
+//! every so often and turn each item into a chyron message, queued the same
+//! way as messages pushed over MQTT. Configured in the config file:
+//!
+//! ```json
+//! "feeds": [
+//!   { "name": "news", "type": "Rss", "path": "/var/cache/news.xml",
+//!     "template": "{yellow}NEWS{/} {title}", "interval": 600 },
+//!   { "name": "bar", "type": "Json", "path": "drinks.json", "select": "$.specials[*]",
+//!     "template": "{name}: {price}", "message": { "target": "bottom", "style": "info" } },
+//!   { "name": "np", "type": "Command", "command": "mpc", "args": ["current"],
+//!     "interval": 15, "message": { "priority": 5 } }
+//! ]
+//! ```
+//!
+//! Each item is a set of text fields: RSS and Atom items have `title`,
+//! `link`, `summary`, `date` and `id` plus their other child elements; JSON
+//! objects are flattened with dotted keys (`author.name`); plain values and
+//! command output lines are `text`. `{field}` in the template is replaced by
+//! the item's field, and anything else in braces is left for chyron markup.
+//!
+//! `message` holds any other chyron message fields (see `chyron::message`).
+//! Items keep their place in the queue while they stay in the feed: an item
+//! is only sent again when its text changes or its message would expire
+//! before the next refresh. TTL defaults to three intervals, so items ride
+//! out a couple of failed refreshes.
+
+mod select;
+mod xml;
+
+use crate::chyron::ChyronMessage;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Text fields of one feed item
+pub type Fields = BTreeMap<String, String>;
+
+/// Shortest refresh interval, in seconds
+const MIN_INTERVAL: f32 = 1.0;
+/// Default message TTL, in refresh intervals
+const TTL_INTERVALS: f32 = 3.0;
+
+/// Where a feed's items come from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum FeedSource {
+    /// An RSS or Atom file
+    Rss { path: PathBuf },
+    /// A JSON file; `select` picks the items (arrays are expanded)
+    Json {
+        path: PathBuf,
+        #[serde(default = "default_select")]
+        select: String,
+    },
+    /// A program's standard output, one item per line, or JSON if `select`
+    /// is set. Run directly, not through a shell; it should exit promptly.
+    Command {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        select: Option<String>,
+    },
+}
+
+fn default_select() -> String {
+    "$".to_string()
+}
+
+/// One feed, as configured
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeedConfig {
+    /// Used in message IDs and error messages
+    pub name: String,
+    #[serde(flatten)]
+    pub source: FeedSource,
+    /// Seconds between refreshes
+    #[serde(default = "default_interval")]
+    pub interval: f32,
+    /// Message text, with `{field}` replaced by the item's fields
+    /// (default: `{title}` for RSS, `{text}` otherwise)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Field that identifies an item across refreshes (default: the first of
+    /// `id`, `guid`, `link` and `title` that's set, else the message text)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Most items taken from the top of the feed
+    #[serde(default = "default_max_items")]
+    pub max_items: usize,
+    /// Other chyron message fields for every item
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub message: Map<String, Value>,
+}
+
+fn default_interval() -> f32 {
+    300.0
+}
+
+fn default_max_items() -> usize {
+    10
+}
+
+impl FeedConfig {
+    pub fn new(name: impl Into<String>, source: FeedSource) -> Self {
+        Self {
+            name: name.into(),
+            source,
+            interval: default_interval(),
+            template: None,
+            key: None,
+            max_items: default_max_items(),
+            message: Map::new(),
+        }
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs_f32(self.interval.max(MIN_INTERVAL))
+    }
+
+    fn template(&self) -> &str {
+        self.template.as_deref().unwrap_or(match self.source {
+            FeedSource::Rss { .. } => "{title}",
+            _ => "{text}",
+        })
+    }
+}
+
+/// Fill in `{field}` placeholders. Field values have their braces escaped so
+/// they show as written; unknown names (markup tags) are left alone.
+pub fn render_template(template: &str, fields: &Fields) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        rest = &rest[open..];
+        if let Some(after) = rest.strip_prefix("{{") {
+            out.push_str("{{");
+            rest = after;
+            continue;
+        }
+        let value = rest
+            .find('}')
+            .and_then(|close| Some((fields.get(&rest[1..close])?, close)));
+        match value {
+            Some((value, close)) => {
+                let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+                out.push_str(&value.replace('{', "{{"));
+                rest = &rest[close + 1..];
+            },
+            None => {
+                out.push('{');
+                rest = &rest[1..];
+            },
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// A feed and what it has already sent
+pub struct Feed {
+    pub config: FeedConfig,
+    /// Message text and send time by item key
+    sent: HashMap<String, (String, Instant)>,
+}
+
+impl Feed {
+    pub fn new(config: FeedConfig) -> Self {
+        Self {
+            config,
+            sent: HashMap::new(),
+        }
+    }
+
+    /// Read the source's items
+    pub fn fetch(&self) -> Result<Vec<Fields>, String> {
+        let read = |path: &PathBuf| {
+            fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))
+        };
+        let from_json = |text: &str, path: &str| -> Result<Vec<Fields>, String> {
+            let value: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+            Ok(select::select(&value, path)?
+                .into_iter()
+                .flat_map(|v| match v {
+                    Value::Array(items) => items.iter().collect(),
+                    _ => vec![v],
+                })
+                .map(select::fields)
+                .collect())
+        };
+        match &self.config.source {
+            FeedSource::Rss { path } => Ok(xml::feed_items(&xml::parse(&read(path)?)?)),
+            FeedSource::Json { path, select } => from_json(&read(path)?, select),
+            FeedSource::Command {
+                command,
+                args,
+                select,
+            } => {
+                let output = process::Command::new(command)
+                    .args(args)
+                    .stdin(process::Stdio::null())
+                    .output()
+                    .map_err(|e| format!("{}: {}", command, e))?;
+                if !output.status.success() {
+                    return Err(format!("{}: {}", command, output.status));
+                }
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                select.as_ref().map_or_else(
+                    || {
+                        Ok(stdout
+                            .lines()
+                            .filter(|line| !line.trim().is_empty())
+                            .map(|line| Fields::from([("text".to_string(), line.to_string())]))
+                            .collect())
+                    },
+                    |select| from_json(&stdout, select),
+                )
+            },
+        }
+    }
+
+    /// The chyron message for an item, with its key
+    fn message(&self, fields: &Fields) -> Result<(String, ChyronMessage), String> {
+        let text = render_template(self.config.template(), fields);
+        let key = self
+            .config
+            .key
+            .as_ref()
+            .map_or_else(
+                || {
+                    ["id", "guid", "link", "title"]
+                        .iter()
+                        .find_map(|k| fields.get(*k).filter(|v| !v.is_empty()).cloned())
+                },
+                |key| fields.get(key).cloned(),
+            )
+            .unwrap_or_else(|| text.clone());
+
+        let mut message = self.config.message.clone();
+        message
+            .entry("ttl")
+            .or_insert_with(|| (self.config.interval().as_secs_f32() * TTL_INTERVALS).into());
+        message.insert("text".to_string(), text.into());
+        message.insert(
+            "id".to_string(),
+            format!("feed:{}:{}", self.config.name, key).into(),
+        );
+        let message = serde_json::from_value(Value::Object(message))
+            .map_err(|e| format!("Bad message fields: {}", e))?;
+        Ok((key, message))
+    }
+
+    /// Fetch the feed and return messages for new, changed and expiring items
+    pub fn refresh(&mut self, now: Instant) -> Result<Vec<ChyronMessage>, String> {
+        let items = self.fetch()?;
+        let next_refresh = now + self.config.interval();
+        let mut messages = Vec::new();
+        let mut current = HashMap::new();
+        for fields in items.iter().take(self.config.max_items) {
+            let (key, message) = self.message(fields)?;
+            if message.text.trim().is_empty() || current.contains_key(&key) {
+                continue;
+            }
+            let expires = |sent: Instant| sent + Duration::from_secs_f32(message.ttl.max(0.0));
+            let entry = match self.sent.get(&key) {
+                Some((text, sent)) if *text == message.text && expires(*sent) > next_refresh => {
+                    (text.clone(), *sent)
+                },
+                _ => {
+                    let entry = (message.text.clone(), now);
+                    messages.push(message);
+                    entry
+                },
+            };
+            current.insert(key, entry);
+        }
+        // Forget items that left the feed so they're sent again if they return
+        self.sent = current;
+        Ok(messages)
+    }
+}
+
+/// Refreshes feeds on a background thread and hands over their messages
+pub struct FeedReader {
+    receiver: Receiver<ChyronMessage>,
+    _thread: thread::JoinHandle<()>,
+}
+
+impl FeedReader {
+    /// Start reading feeds. None if there are none.
+    pub fn start(configs: &[FeedConfig]) -> Option<Self> {
+        if configs.is_empty() {
+            return None;
+        }
+        let feeds: Vec<Feed> = configs.iter().cloned().map(Feed::new).collect();
+        let (sender, receiver) = mpsc::channel();
+        let handle = thread::spawn(move || run(feeds, &sender));
+        Some(Self {
+            receiver,
+            _thread: handle,
+        })
+    }
+
+    /// Get any messages from refreshes since the last poll (non-blocking)
+    pub fn poll(&self) -> Vec<ChyronMessage> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+fn run(mut feeds: Vec<Feed>, sender: &Sender<ChyronMessage>) {
+    let start = Instant::now();
+    let mut due = vec![start; feeds.len()];
+    loop {
+        let now = Instant::now();
+        for (feed, due) in feeds.iter_mut().zip(&mut due) {
+            if *due > now {
+                continue;
+            }
+            match feed.refresh(now) {
+                Ok(messages) => {
+                    for message in messages {
+                        if sender.send(message).is_err() {
+                            return;
+                        }
+                    }
+                },
+                Err(e) => eprintln!("Feed '{}': {}", feed.config.name, e),
+            }
+            *due = now + feed.config.interval();
+        }
+        let next = due.iter().min().copied().unwrap_or(now);
+        thread::sleep(next.saturating_duration_since(Instant::now()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn temp_file(name: &str, contents: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("wallfacer-{}-{}", process::id(), name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_render_template() {
+        let fields = Fields::from([
+            ("title".to_string(), "Set {times}\n changed".to_string()),
+            ("room".to_string(), "Main".to_string()),
+        ]);
+        assert_eq!(
+            render_template("{red}{room}{/}: {title} {{x}} {missing", &fields),
+            "{red}Main{/}: Set {{times} changed {{x}} {missing"
+        );
+    }
+
+    #[test]
+    fn test_json_feed() {
+        let path = temp_file(
+            "drinks.json",
+            r#"{"specials": [{"name": "Stout", "price": 4.5}, {"name": "Cider", "price": 4}]}"#,
+        );
+        let mut config = FeedConfig::new(
+            "bar",
+            FeedSource::Json {
+                path: path.clone(),
+                select: "$.specials".to_string(),
+            },
+        );
+        config.template = Some("{name}: {price}".to_string());
+        config.key = Some("name".to_string());
+        config.message = serde_json::from_str(r#"{"target": "bottom", "priority": 3}"#).unwrap();
+        let mut feed = Feed::new(config);
+
+        let now = Instant::now();
+        let messages = feed.refresh(now).unwrap();
+        let texts: Vec<_> = messages.iter().map(|m| m.text.as_str()).collect();
+        assert_eq!(texts, ["Stout: 4.5", "Cider: 4"]);
+        assert_eq!(messages[0].id.as_deref(), Some("feed:bar:Stout"));
+        assert_eq!(messages[0].priority, 3);
+        assert_eq!(messages[0].ttl, 900.0);
+
+        // Unchanged items aren't sent again until they'd expire
+        assert!(feed
+            .refresh(now + Duration::from_secs(300))
+            .unwrap()
+            .is_empty());
+        let later = feed.refresh(now + Duration::from_secs(600)).unwrap();
+        assert_eq!(later.len(), 2);
+
+        // A changed price is sent straight away
+        fs::write(&path, r#"{"specials": [{"name": "Stout", "price": 5}]}"#).unwrap();
+        let changed = feed.refresh(now + Duration::from_secs(700)).unwrap();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].text, "Stout: 5");
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_rss_feed() {
+        let path = temp_file(
+            "news.xml",
+            "<rss><channel>
+                <item><title>One</title><guid>1</guid></item>
+                <item><title>Two</title><guid>2</guid></item>
+                <item><title>Three</title><guid>3</guid></item>
+            </channel></rss>",
+        );
+        let mut config = FeedConfig::new("news", FeedSource::Rss { path: path.clone() });
+        config.max_items = 2;
+        let messages = Feed::new(config).refresh(Instant::now()).unwrap();
+        let texts: Vec<_> = messages.iter().map(|m| m.text.as_str()).collect();
+        assert_eq!(texts, ["One", "Two"]);
+        assert_eq!(messages[1].id.as_deref(), Some("feed:news:2"));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_config_json() {
+        let feeds: Vec<FeedConfig> = serde_json::from_str(
+            r#"[{"name": "np", "type": "Command", "command": "mpc", "args": ["current"], "interval": 15}]"#,
+        )
+        .unwrap();
+        assert_eq!(feeds[0].interval, 15.0);
+        assert_eq!(feeds[0].template(), "{text}");
+        assert!(
+            matches!(&feeds[0].source, FeedSource::Command { args, .. } if args == &["current"])
+        );
+
+        let mut bad = Feed::new(FeedConfig::new(
+            "missing",
+            FeedSource::Rss {
+                path: "/nonexistent/feed.xml".into(),
+            },
+        ));
+        assert!(bad.refresh(Instant::now()).is_err());
+    }
+}
@@ -0,0 +1,181 @@
+//! JSONPath-style selectors
+//!
+//! A small subset of JSONPath for picking feed items out of a document:
+//!
+//! ```text
+//! $.data.items[*]      every element of data.items
+//! $.results[0].title   one value
+//! $["odd key"].*       every value of an object
+//! ```
+//!
+//! `$` is optional. There's no recursive descent, filters or slices.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Key(String),
+    Index(i64),
+    Wildcard,
+}
+
+fn parse(path: &str) -> Result<Vec<Step>, String> {
+    let mut steps = Vec::new();
+    let mut rest = path.trim();
+    rest = rest.strip_prefix('$').unwrap_or(rest);
+    // A bare key is allowed at the start: "items[*]" means "$.items[*]"
+    if !rest.is_empty() && !rest.starts_with(['.', '[']) {
+        let end = rest.find(['.', '[']).unwrap_or(rest.len());
+        steps.push(Step::Key(rest[..end].to_string()));
+        rest = &rest[end..];
+    }
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            if after.starts_with('.') {
+                return Err("Recursive descent (..) isn't supported".to_string());
+            }
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            let key = &after[..end];
+            steps.push(match key {
+                "" => return Err(format!("Empty key in '{}'", path)),
+                "*" => Step::Wildcard,
+                _ => Step::Key(key.to_string()),
+            });
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let (step, after) =
+                parse_bracket(after).ok_or_else(|| format!("Bad selector '{}'", path))?;
+            steps.push(step);
+            rest = after;
+        } else {
+            return Err(format!("Bad selector '{}'", path));
+        }
+    }
+    Ok(steps)
+}
+
+/// Parse `*]`, `3]`, `-1]`, `'key']` or `"key"]` after the `[`
+fn parse_bracket(text: &str) -> Option<(Step, &str)> {
+    if let Some(quote) = text.chars().next().filter(|&c| c == '"' || c == '\'') {
+        let close = text[1..].find(quote)? + 1;
+        let after = text[close + 1..].strip_prefix(']')?;
+        return Some((Step::Key(text[1..close].to_string()), after));
+    }
+    let close = text.find(']')?;
+    let inner = text[..close].trim();
+    let step = if inner == "*" {
+        Step::Wildcard
+    } else {
+        Step::Index(inner.parse().ok()?)
+    };
+    Some((step, &text[close + 1..]))
+}
+
+/// Every value `path` picks out of `value`. Missing keys and indices select
+/// nothing rather than failing, so one odd item doesn't sink a feed.
+pub fn select<'a>(value: &'a Value, path: &str) -> Result<Vec<&'a Value>, String> {
+    let mut current = vec![value];
+    for step in parse(path)? {
+        current = current
+            .into_iter()
+            .flat_map(|value| -> Vec<&Value> {
+                match (&step, value) {
+                    (Step::Key(key), Value::Object(map)) => map.get(key).into_iter().collect(),
+                    (Step::Index(index), Value::Array(items)) => {
+                        let index = if *index < 0 {
+                            items.len() as i64 + index
+                        } else {
+                            *index
+                        };
+                        usize::try_from(index)
+                            .ok()
+                            .and_then(|i| items.get(i))
+                            .into_iter()
+                            .collect()
+                    },
+                    (Step::Wildcard, Value::Array(items)) => items.iter().collect(),
+                    (Step::Wildcard, Value::Object(map)) => map.values().collect(),
+                    _ => Vec::new(),
+                }
+            })
+            .collect();
+    }
+    Ok(current)
+}
+
+/// A selected value as text fields. Objects are flattened with dotted keys
+/// (`author.name`, `tags.0`); anything else is the single field `text`.
+pub fn fields(value: &Value) -> BTreeMap<String, String> {
+    let mut fields = BTreeMap::new();
+    match value {
+        Value::Object(_) => flatten("", value, &mut fields),
+        _ => flatten("text", value, &mut fields),
+    }
+    fields
+}
+
+fn flatten(prefix: &str, value: &Value, fields: &mut BTreeMap<String, String>) {
+    let join = |key: &str| {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", prefix, key)
+        }
+    };
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                flatten(&join(key), value, fields);
+            }
+        },
+        Value::Array(items) => {
+            for (i, value) in items.iter().enumerate() {
+                flatten(&join(&i.to_string()), value, fields);
+            }
+        },
+        Value::String(text) => {
+            fields.insert(prefix.to_string(), text.clone());
+        },
+        Value::Null => {
+            fields.insert(prefix.to_string(), String::new());
+        },
+        Value::Bool(_) | Value::Number(_) => {
+            fields.insert(prefix.to_string(), value.to_string());
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_select() {
+        let doc = json!({
+            "data": {"items": [{"title": "a"}, {"title": "b"}, {"name": "c"}]},
+            "odd key": {"x": 1, "y": 2}
+        });
+        let titles: Vec<_> = select(&doc, "$.data.items[*].title").unwrap();
+        assert_eq!(titles, [&json!("a"), &json!("b")]);
+        assert_eq!(select(&doc, "data.items[-1].name").unwrap(), [&json!("c")]);
+        assert_eq!(select(&doc, "$['odd key'].*").unwrap().len(), 2);
+        assert_eq!(select(&doc, "$").unwrap(), [&doc]);
+        assert!(select(&doc, "$.data.missing[0]").unwrap().is_empty());
+        assert!(select(&doc, "$..title").is_err());
+        assert!(select(&doc, "$.data[x]").is_err());
+    }
+
+    #[test]
+    fn test_fields() {
+        let item = json!({"title": "Hi", "score": 3, "author": {"name": "Ann"}, "tags": ["x", "y"], "gone": null});
+        let fields = fields(&item);
+        assert_eq!(fields["title"], "Hi");
+        assert_eq!(fields["score"], "3");
+        assert_eq!(fields["author.name"], "Ann");
+        assert_eq!(fields["tags.1"], "y");
+        assert_eq!(fields["gone"], "");
+        assert_eq!(super::fields(&json!("plain"))["text"], "plain");
+    }
+}
@@ -0,0 +1,356 @@
+//! Just enough XML for RSS and Atom
+//!
+//! Reads elements, attributes, text and CDATA, and skips comments,
+//! processing instructions and doctypes. No namespaces beyond stripping the
+//! prefix, no DTD entities beyond the predefined and numeric ones.
+
+use std::collections::BTreeMap;
+
+/// An XML element and its contents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    Element(Element),
+    Text(String),
+}
+
+impl Element {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            attributes: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Name without any namespace prefix
+    pub fn local_name(&self) -> &str {
+        self.name.rsplit(':').next().unwrap_or(&self.name)
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|node| match node {
+            Node::Element(element) => Some(element),
+            Node::Text(_) => None,
+        })
+    }
+
+    /// All text inside the element, nested elements included
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        self.collect_text(&mut text);
+        text
+    }
+
+    fn collect_text(&self, text: &mut String) {
+        for node in &self.children {
+            match node {
+                Node::Text(t) => text.push_str(t),
+                Node::Element(element) => element.collect_text(text),
+            }
+        }
+    }
+}
+
+/// Parse a document into its root element
+pub fn parse(xml: &str) -> Result<Element, String> {
+    let mut stack: Vec<Element> = Vec::new();
+    let mut root = None;
+    let mut rest = xml;
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = skip_past(after, "-->")?;
+        } else if let Some(after) = rest.strip_prefix("<![CDATA[") {
+            let end = after.find("]]>").ok_or("Unterminated CDATA")?;
+            if let Some(parent) = stack.last_mut() {
+                parent.children.push(Node::Text(after[..end].to_string()));
+            }
+            rest = &after[end + 3..];
+        } else if let Some(after) = rest.strip_prefix("<?") {
+            rest = skip_past(after, "?>")?;
+        } else if let Some(after) = rest.strip_prefix("<!") {
+            // Doctype, possibly with an internal subset in brackets
+            let bracket = after.find('[');
+            let close = after.find('>').ok_or("Unterminated declaration")?;
+            rest = match bracket {
+                Some(open) if open < close => skip_past(after, "]>")?,
+                _ => &after[close + 1..],
+            };
+        } else if let Some(after) = rest.strip_prefix("</") {
+            let close = after.find('>').ok_or("Unterminated end tag")?;
+            let name = after[..close].trim();
+            let element = stack
+                .pop()
+                .ok_or_else(|| format!("Unexpected </{}>", name))?;
+            if element.name != name {
+                return Err(format!("Expected </{}>, found </{}>", element.name, name));
+            }
+            rest = &after[close + 1..];
+            close_element(&mut stack, &mut root, element);
+        } else if let Some(after) = rest.strip_prefix('<') {
+            let (element, self_closing, after) = parse_start_tag(after)?;
+            rest = after;
+            if self_closing {
+                close_element(&mut stack, &mut root, element);
+            } else {
+                stack.push(element);
+            }
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            if let Some(parent) = stack.last_mut() {
+                parent
+                    .children
+                    .push(Node::Text(decode_entities(&rest[..end])));
+            }
+            rest = &rest[end..];
+        }
+    }
+
+    if let Some(open) = stack.last() {
+        return Err(format!("Unclosed <{}>", open.name));
+    }
+    root.ok_or_else(|| "No root element".to_string())
+}
+
+fn skip_past<'a>(text: &'a str, end: &str) -> Result<&'a str, String> {
+    text.find(end)
+        .map(|i| &text[i + end.len()..])
+        .ok_or_else(|| format!("Missing '{}'", end))
+}
+
+fn close_element(stack: &mut [Element], root: &mut Option<Element>, element: Element) {
+    match stack.last_mut() {
+        Some(parent) => parent.children.push(Node::Element(element)),
+        None => *root = root.take().or(Some(element)),
+    }
+}
+
+/// Parse `name attr="value" ...>` or `.../>` after the `<`
+fn parse_start_tag(text: &str) -> Result<(Element, bool, &str), String> {
+    let is_name_end = |c: char| c.is_whitespace() || c == '>' || c == '/';
+    let end = text.find(is_name_end).ok_or("Unterminated tag")?;
+    if end == 0 {
+        return Err("Empty tag name".to_string());
+    }
+    let mut element = Element::new(&text[..end]);
+    let mut rest = text[end..].trim_start();
+    loop {
+        if let Some(after) = rest.strip_prefix("/>") {
+            return Ok((element, true, after));
+        }
+        if let Some(after) = rest.strip_prefix('>') {
+            return Ok((element, false, after));
+        }
+        let eq = rest
+            .find('=')
+            .ok_or_else(|| format!("Bad attribute in <{}>", element.name))?;
+        let name = rest[..eq].trim().to_string();
+        let value = rest[eq + 1..].trim_start();
+        let quote = value
+            .chars()
+            .next()
+            .filter(|&c| c == '"' || c == '\'')
+            .ok_or_else(|| format!("Unquoted attribute '{}' in <{}>", name, element.name))?;
+        let close = value[1..]
+            .find(quote)
+            .ok_or_else(|| format!("Unterminated attribute '{}'", name))?;
+        element
+            .attributes
+            .push((name, decode_entities(&value[1..=close])));
+        rest = value[close + 2..].trim_start();
+    }
+}
+
+/// Replace `&amp;`-style and numeric character references
+pub fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .map_or_else(
+                        || entity.strip_prefix('#')?.parse().ok(),
+                        |hex| u32::from_str_radix(hex, 16).ok(),
+                    )
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            },
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            },
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Plain text from a field that may hold escaped HTML: tags dropped,
+/// entities decoded and whitespace collapsed
+pub fn clean_text(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                plain.push(' ');
+            },
+            _ if !in_tag => plain.push(c),
+            _ => {},
+        }
+    }
+    decode_entities(&plain)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Fields every RSS or Atom item has, even if empty
+const ITEM_FIELDS: [&str; 5] = ["title", "link", "summary", "date", "id"];
+
+/// Items of an RSS (0.9x, 1.0, 2.0) or Atom feed, as text fields.
+/// Child elements become fields by local name, with the common ones also
+/// under `title`, `link`, `summary`, `date` and `id` whichever format it is.
+pub fn feed_items(root: &Element) -> Vec<BTreeMap<String, String>> {
+    let mut items = Vec::new();
+    collect_items(root, &mut items);
+    items
+}
+
+fn collect_items(element: &Element, items: &mut Vec<BTreeMap<String, String>>) {
+    for child in element.elements() {
+        if matches!(child.local_name(), "item" | "entry") {
+            items.push(item_fields(child));
+        } else {
+            collect_items(child, items);
+        }
+    }
+}
+
+fn item_fields(item: &Element) -> BTreeMap<String, String> {
+    let mut fields = BTreeMap::new();
+    for child in item.elements() {
+        let name = child.local_name();
+        // Atom links are attributes; the first alternate (or unmarked) one wins
+        let value = if name == "link" && child.attribute("href").is_some() {
+            if child.attribute("rel").is_some_and(|rel| rel != "alternate") {
+                continue;
+            }
+            child.attribute("href").unwrap_or_default().to_string()
+        } else {
+            clean_text(&child.text())
+        };
+        let alias = match name {
+            "description" | "content" | "encoded" => Some("summary"),
+            "pubDate" | "updated" | "published" => Some("date"),
+            "guid" => Some("id"),
+            _ => None,
+        };
+        if let Some(alias) = alias {
+            fields
+                .entry(alias.to_string())
+                .or_insert_with(|| value.clone());
+        }
+        fields.entry(name.to_string()).or_insert(value);
+    }
+    for name in ITEM_FIELDS {
+        fields.entry(name.to_string()).or_default();
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let root = parse(
+            r#"<?xml version="1.0"?>
+            <!DOCTYPE note [<!ENTITY x "y">]>
+            <!-- comment -->
+            <note lang='en' to="A &amp; B"><body>Fish &lt;&#38;&#x21;&gt; <b>chips</b></body><empty/>
+            <code><![CDATA[if a < b]]></code></note>"#,
+        )
+        .unwrap();
+        assert_eq!(root.name, "note");
+        assert_eq!(root.attribute("to"), Some("A & B"));
+        assert_eq!(root.attribute("lang"), Some("en"));
+        let names: Vec<_> = root.elements().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["body", "empty", "code"]);
+        let body = root.elements().next().unwrap();
+        assert_eq!(body.text(), "Fish <&!> chips");
+        assert!(root.text().contains("if a < b"));
+
+        assert!(parse("<a><b></a>").is_err());
+        assert!(parse("<a>").is_err());
+        assert!(parse("just text").is_err());
+        assert_eq!(decode_entities("R&D &bogus; &#65;"), "R&D &bogus; A");
+    }
+
+    #[test]
+    fn test_feed_items() {
+        let rss = parse(
+            r#"<rss version="2.0"><channel><title>News</title>
+            <item><title>First</title><link>https://example.com/1</link>
+              <description>&lt;p&gt;Fish &amp;amp;
+                chips&lt;/p&gt;</description><guid>one</guid></item>
+            <item><title>Second</title></item>
+            </channel></rss>"#,
+        )
+        .unwrap();
+        let items = feed_items(&rss);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0]["summary"], "Fish & chips");
+        assert_eq!(items[0]["id"], "one");
+        assert_eq!(items[1]["link"], "");
+
+        let atom = parse(
+            r#"<feed xmlns="http://www.w3.org/2005/Atom"><entry>
+              <title type="html">Big &amp;amp; bold</title>
+              <link rel="self" href="https://example.com/self"/>
+              <link href="https://example.com/post"/>
+              <id>urn:1</id><updated>2024-05-01T12:00:00Z</updated>
+            </entry></feed>"#,
+        )
+        .unwrap();
+        let items = feed_items(&atom);
+        assert_eq!(items[0]["title"], "Big & bold");
+        assert_eq!(items[0]["link"], "https://example.com/post");
+        assert_eq!(items[0]["date"], "2024-05-01T12:00:00Z");
+    }
+}
@@ -439,6 +439,20 @@ pub fn circle_polygon_collision(
         }
     }
 
+    circle_edge_collision(cx, cy, radius, vertices)
+}
+
+/// Check if a circle overlaps a polygon's edges, from whichever side.
+/// Returns (normal_x, normal_y, penetration_depth) pointing from the closest edge toward
+/// the circle center.
+pub fn circle_edge_collision(
+    cx: f32,
+    cy: f32,
+    radius: f32,
+    vertices: &[(f32, f32)],
+) -> Option<(f32, f32, f32)> {
+    let n = vertices.len();
+
     // Find closest point on any polygon edge to circle center
     let mut closest_dist = f32::MAX;
     let mut closest_nx = 0.0_f32;
@@ -506,3 +520,561 @@ pub fn circle_circle_collision(
         None
     }
 }
+
+/// Total length of a polyline (including the closing edge when `closed`)
+pub fn polyline_length(points: &[(f32, f32)], closed: bool) -> f32 {
+    let n = points.len();
+    if n < 2 {
+        return 0.0;
+    }
+    let edges = if closed { n } else { n - 1 };
+    (0..edges)
+        .map(|i| {
+            let (x1, y1) = points[i];
+            let (x2, y2) = points[(i + 1) % n];
+            length(x2 - x1, y2 - y1)
+        })
+        .sum()
+}
+
+/// Point at arc-length distance `s` along a polyline.
+/// Returns (x, y, tangent_x, tangent_y). Closed polylines wrap `s` around;
+/// open polylines clamp it to the endpoints.
+pub fn point_along_polyline(
+    points: &[(f32, f32)],
+    closed: bool,
+    s: f32,
+) -> Option<(f32, f32, f32, f32)> {
+    let n = points.len();
+    if n < 2 {
+        return points.first().map(|&(x, y)| (x, y, 1.0, 0.0));
+    }
+
+    let total = polyline_length(points, closed);
+    let mut s = if closed && total > 0.0 {
+        s.rem_euclid(total)
+    } else {
+        s.clamp(0.0, total)
+    };
+
+    let edges = if closed { n } else { n - 1 };
+    let mut last = (points[0].0, points[0].1, 1.0, 0.0);
+    for i in 0..edges {
+        let (x1, y1) = points[i];
+        let (x2, y2) = points[(i + 1) % n];
+        let len = length(x2 - x1, y2 - y1);
+        if len < 0.0001 {
+            continue;
+        }
+        let (tx, ty) = ((x2 - x1) / len, (y2 - y1) / len);
+        if s <= len {
+            return Some((x1 + tx * s, y1 + ty * s, tx, ty));
+        }
+        s -= len;
+        last = (x2, y2, tx, ty);
+    }
+    Some(last)
+}
+
+/// Resample a polyline into `count` evenly spaced points by arc length.
+/// Open polylines include both endpoints; closed ones space points around the loop.
+pub fn resample_polyline(points: &[(f32, f32)], closed: bool, count: usize) -> Vec<(f32, f32)> {
+    if count == 0 || points.is_empty() {
+        return Vec::new();
+    }
+    let total = polyline_length(points, closed);
+    let spacing = if closed {
+        total / count as f32
+    } else if count > 1 {
+        total / (count - 1) as f32
+    } else {
+        0.0
+    };
+
+    (0..count)
+        .filter_map(|i| point_along_polyline(points, closed, i as f32 * spacing))
+        .map(|(x, y, _, _)| (x, y))
+        .collect()
+}
+
+/// Check if a circle collides with a ring (annulus).
+/// Returns (normal_x, normal_y, penetration_depth) pushing the circle out of the
+/// band: inward through the hole or outward past the rim, whichever is nearer.
+pub fn circle_ring_collision(
+    cx: f32,
+    cy: f32,
+    radius: f32,
+    rx: f32,
+    ry: f32,
+    inner: f32,
+    outer: f32,
+) -> Option<(f32, f32, f32)> {
+    let dx = cx - rx;
+    let dy = cy - ry;
+    let dist = (dx * dx + dy * dy).sqrt();
+    if dist + radius <= inner || dist - radius >= outer {
+        return None;
+    }
+    let (nx, ny) = if dist > 0.001 {
+        (dx / dist, dy / dist)
+    } else {
+        (1.0, 0.0)
+    };
+    // Out through whichever edge is closer to the circle's center
+    if dist - inner < outer - dist {
+        Some((-nx, -ny, dist + radius - inner))
+    } else {
+        Some((nx, ny, outer - dist + radius))
+    }
+}
+
+/// Points along a chain of cubic Bezier segments, `steps` per segment.
+/// `points` runs anchor, control, control, anchor, ...; a closed chain's
+/// last segment ends back at the first anchor. Trailing points that don't
+/// make a whole segment are ignored.
+pub fn flatten_bezier(points: &[(f32, f32)], closed: bool, steps: usize) -> Vec<(f32, f32)> {
+    let n = points.len();
+    let segments = if closed {
+        n / 3
+    } else {
+        n.saturating_sub(1) / 3
+    };
+    if segments == 0 || steps == 0 {
+        return points.to_vec();
+    }
+    let mut samples = Vec::with_capacity(segments * steps + 1);
+    for segment in 0..segments {
+        let i = segment * 3;
+        let (p0, p1, p2) = (points[i], points[i + 1], points[i + 2]);
+        let p3 = if closed && segment == segments - 1 {
+            points[0]
+        } else {
+            points[i + 3]
+        };
+        for step in 0..steps {
+            let t = step as f32 / steps as f32;
+            let u = 1.0 - t;
+            let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+            samples.push((
+                a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0,
+                a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1,
+            ));
+        }
+    }
+    if !closed {
+        samples.push(points[segments * 3]);
+    }
+    samples
+}
+
+/// Shoelace area of a closed contour; positive for clockwise winding with
+/// y pointing down
+pub fn signed_area(vertices: &[(f32, f32)]) -> f32 {
+    let n = vertices.len();
+    (0..n)
+        .map(|i| {
+            let ((x1, y1), (x2, y2)) = (vertices[i], vertices[(i + 1) % n]);
+            x1 * y2 - x2 * y1
+        })
+        .sum::<f32>()
+        / 2.0
+}
+
+/// How many times a closed contour winds round a point, clockwise (with y
+/// pointing down) counting positive like `signed_area`
+pub fn winding_number(px: f32, py: f32, vertices: &[(f32, f32)]) -> i32 {
+    let n = vertices.len();
+    let mut winding = 0;
+    for i in 0..n {
+        let (x1, y1) = vertices[i];
+        let (x2, y2) = vertices[(i + 1) % n];
+        if (y1 <= py) != (y2 <= py) && px < x1 + (py - y1) / (y2 - y1) * (x2 - x1) {
+            winding += if y2 > y1 { 1 } else { -1 };
+        }
+    }
+    winding
+}
+
+/// Points closer than this (in pixels) are the same point when tracing
+const SAME_POINT: f32 = 1e-3;
+
+/// How far either side of an edge `boundary_contours` looks
+const SAMPLE_OFFSET: f32 = 0.05;
+
+fn same_point(a: (f32, f32), b: (f32, f32)) -> bool {
+    distance_squared(a.0, a.1, b.0, b.1) <= SAME_POINT * SAME_POINT
+}
+
+/// Trace the edge of the area where `inside` holds, given contours whose
+/// edges run along all of it (and possibly elsewhere). Every edge is split
+/// wherever another meets it, and the pieces with `inside` true on exactly
+/// one side are chained into closed contours with the area on their left:
+/// outlines come out with positive `signed_area`, holes negative. Looking
+/// either side of each piece, rather than tracking where edges enter and
+/// leave, keeps shared edges and touching corners from upsetting the result.
+pub fn boundary_contours<C: AsRef<[(f32, f32)]>>(
+    contours: &[C],
+    inside: impl Fn(f32, f32) -> bool,
+) -> Vec<Vec<(f32, f32)>> {
+    let mut segments = Vec::new();
+    for contour in contours.iter().map(AsRef::as_ref) {
+        for i in 0..contour.len() {
+            let (p, q) = (contour[i], contour[(i + 1) % contour.len()]);
+            if !same_point(p, q) {
+                segments.push((p, q));
+            }
+        }
+    }
+
+    // Split every segment wherever another one meets it
+    let mut splits: Vec<Vec<(f32, (f32, f32))>> = vec![Vec::new(); segments.len()];
+    for i in 0..segments.len() {
+        for j in i + 1..segments.len() {
+            for (t, u, point) in meeting_points(segments[i], segments[j]) {
+                splits[i].push((t, point));
+                splits[j].push((u, point));
+            }
+        }
+    }
+
+    // Keep the pieces with the area on exactly one side, turned to its left
+    let mut edges: Vec<((f32, f32), (f32, f32))> = Vec::new();
+    for (&(start, end), mut cuts) in segments.iter().zip(splits) {
+        cuts.sort_by(|x, y| x.0.total_cmp(&y.0));
+        let mut from = start;
+        for to in cuts.into_iter().map(|(_, point)| point).chain([end]) {
+            if same_point(from, to) {
+                continue;
+            }
+            let (nx, ny) = normalize(from.1 - to.1, to.0 - from.0);
+            let (mx, my) = ((from.0 + to.0) / 2.0, (from.1 + to.1) / 2.0);
+            let left = inside(mx + nx * SAMPLE_OFFSET, my + ny * SAMPLE_OFFSET);
+            let right = inside(mx - nx * SAMPLE_OFFSET, my - ny * SAMPLE_OFFSET);
+            let edge = if left { (from, to) } else { (to, from) };
+            let duplicate = edges
+                .iter()
+                .any(|&(p, q)| same_point(p, edge.0) && same_point(q, edge.1));
+            if left != right && !duplicate {
+                edges.push(edge);
+            }
+            from = to;
+        }
+    }
+
+    // Chain them end to start, dropping any that don't close
+    let mut traced = Vec::new();
+    while let Some((start, mut at)) = edges.pop() {
+        let mut contour = vec![start];
+        while !same_point(at, start) {
+            let Some(next) = edges.iter().position(|&(p, _)| same_point(p, at)) else {
+                break;
+            };
+            contour.push(at);
+            at = edges.swap_remove(next).1;
+        }
+        if same_point(at, start) && contour.len() >= 3 {
+            traced.push(contour);
+        }
+    }
+    traced
+}
+
+/// Where two segments meet, as (t along the first, u along the second,
+/// point). Points land exactly on segment ends when they are close enough,
+/// so the pieces either side of a split join up again.
+fn meeting_points(
+    (a1, a2): ((f32, f32), (f32, f32)),
+    (b1, b2): ((f32, f32), (f32, f32)),
+) -> Vec<(f32, f32, (f32, f32))> {
+    let (dax, day) = (a2.0 - a1.0, a2.1 - a1.1);
+    let (dbx, dby) = (b2.0 - b1.0, b2.1 - b1.1);
+    let (len_a, len_b) = (length(dax, day), length(dbx, dby));
+    let cross = dax * dby - day * dbx;
+    // How far along a segment from `o` in direction `d` the point `p` lies
+    let along = |p: (f32, f32), o: (f32, f32), dx: f32, dy: f32| {
+        ((p.0 - o.0) * dx + (p.1 - o.1) * dy) / (dx * dx + dy * dy)
+    };
+
+    if cross.abs() <= 1e-6 * len_a * len_b {
+        // Parallel: only overlapping collinear segments meet, at each other's ends
+        let offset = ((b1.0 - a1.0) * day - (b1.1 - a1.1) * dax).abs() / len_a;
+        if offset > SAME_POINT {
+            return Vec::new();
+        }
+        let within = |s: f32, len: f32| s * len > SAME_POINT && (1.0 - s) * len > SAME_POINT;
+        let mut points = Vec::new();
+        for end in [b1, b2] {
+            let t = along(end, a1, dax, day);
+            if within(t, len_a) {
+                points.push((t, along(end, b1, dbx, dby), end));
+            }
+        }
+        for end in [a1, a2] {
+            let u = along(end, b1, dbx, dby);
+            if within(u, len_b) {
+                points.push((along(end, a1, dax, day), u, end));
+            }
+        }
+        return points;
+    }
+
+    let (ox, oy) = (b1.0 - a1.0, b1.1 - a1.1);
+    let t = (ox * dby - oy * dbx) / cross;
+    let u = (ox * day - oy * dax) / cross;
+    let (tol_a, tol_b) = (SAME_POINT / len_a, SAME_POINT / len_b);
+    if t < -tol_a || t > 1.0 + tol_a || u < -tol_b || u > 1.0 + tol_b {
+        return Vec::new();
+    }
+    let point = [(t * len_a, a1), ((1.0 - t) * len_a, a2)]
+        .into_iter()
+        .chain([(u * len_b, b1), ((1.0 - u) * len_b, b2)])
+        .find(|(distance, _)| distance.abs() <= SAME_POINT)
+        .map_or((a1.0 + t * dax, a1.1 + t * day), |(_, p)| p);
+    vec![(t.clamp(0.0, 1.0), u.clamp(0.0, 1.0), point)]
+}
+
+/// How the corners of an offset outline are filled in where the moved
+/// edges part
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Join {
+    /// The edges carried on until they meet, beveled instead when that
+    /// point is more than the given multiple of the offset from the corner
+    Miter(f32),
+    /// An arc around the corner
+    Round,
+    /// Straight across
+    Bevel,
+}
+
+/// Largest gap between a round join's segments and the true arc, in pixels
+const ROUND_TOLERANCE: f32 = 0.25;
+
+/// Grow (positive `distance`) or shrink (negative) a polygon by moving every
+/// edge `distance` along its normal. See `offset_contours`.
+pub fn offset_polygon(vertices: &[(f32, f32)], distance: f32, join: Join) -> Vec<Vec<(f32, f32)>> {
+    offset_contours(&[vertices], distance, join)
+}
+
+/// Grow or shrink the area the contours enclose under the even-odd rule
+/// (holes shrink as the outline grows). Each contour is moved out, corners
+/// joined with `join`, and the loops left where moved edges cross over, or
+/// where a narrow part shrinks away, are cut off. Returns contours with the
+/// area on their left, as `boundary_contours` does: a shape may split into
+/// several outlines when shrunk, or vanish altogether.
+pub fn offset_contours<C: AsRef<[(f32, f32)]>>(
+    contours: &[C],
+    distance: f32,
+    join: Join,
+) -> Vec<Vec<(f32, f32)>> {
+    let contours: Vec<Vec<(f32, f32)>> = contours
+        .iter()
+        .map(|contour| {
+            let mut points: Vec<(f32, f32)> = Vec::with_capacity(contour.as_ref().len());
+            for &p in contour.as_ref() {
+                if points.last().map_or(true, |&last| !same_point(last, p)) {
+                    points.push(p);
+                }
+            }
+            while points.len() > 1 && same_point(points[0], points[points.len() - 1]) {
+                points.pop();
+            }
+            points
+        })
+        .filter(|contour| contour.len() >= 3)
+        .collect();
+    let even_odd = |x: f32, y: f32| {
+        contours
+            .iter()
+            .filter(|contour| point_in_polygon(x, y, contour))
+            .count()
+            % 2
+            == 1
+    };
+
+    let moved: Vec<Vec<(f32, f32)>> = contours
+        .iter()
+        .map(|contour| {
+            // Run the contour with the area on its left, so its right is out
+            let longest = (0..contour.len())
+                .max_by(|&i, &j| {
+                    let edge = |k: usize| {
+                        let (p, q) = (contour[k], contour[(k + 1) % contour.len()]);
+                        distance_squared(p.0, p.1, q.0, q.1)
+                    };
+                    edge(i).total_cmp(&edge(j))
+                })
+                .unwrap_or(0);
+            let (p, q) = (contour[longest], contour[(longest + 1) % contour.len()]);
+            let (nx, ny) = normalize(p.1 - q.1, q.0 - p.0);
+            let (mx, my) = ((p.0 + q.0) / 2.0, (p.1 + q.1) / 2.0);
+            if even_odd(mx + nx * SAMPLE_OFFSET, my + ny * SAMPLE_OFFSET) {
+                offset_loop(contour.iter().copied(), distance, join)
+            } else {
+                offset_loop(contour.iter().rev().copied(), distance, join)
+            }
+        })
+        .collect();
+
+    // Wherever the moved loops wind round positively is inside; a shrunk
+    // part that turned inside out winds the other way
+    let winding = |x: f32, y: f32| moved.iter().map(|l| winding_number(x, y, l)).sum::<i32>();
+    boundary_contours(&moved, |x, y| winding(x, y) > 0)
+}
+
+/// One contour, area on its left, with its edges moved `distance` to the
+/// right. Where moved edges part they are joined; where they overlap they
+/// are linked through the original corner, making a loop that
+/// `offset_contours` cuts away.
+fn offset_loop(
+    contour: impl Iterator<Item = (f32, f32)>,
+    distance: f32,
+    join: Join,
+) -> Vec<(f32, f32)> {
+    let contour: Vec<(f32, f32)> = contour.collect();
+    let n = contour.len();
+    // The right-hand unit normal of the edge leaving each point
+    let normals: Vec<(f32, f32)> = (0..n)
+        .map(|i| {
+            let (p, q) = (contour[i], contour[(i + 1) % n]);
+            normalize(q.1 - p.1, p.0 - q.0)
+        })
+        .collect();
+
+    let mut moved = Vec::with_capacity(n * 2);
+    for (i, &corner) in contour.iter().enumerate() {
+        let n1 = normals[(i + n - 1) % n];
+        let n2 = normals[i];
+        let at = |(nx, ny): (f32, f32), d: f32| (corner.0 + nx * d, corner.1 + ny * d);
+        let (p1, p2) = (at(n1, distance), at(n2, distance));
+        let sin = n1.0 * n2.1 - n1.1 * n2.0;
+        let cos = n1.0 * n2.0 + n1.1 * n2.1;
+
+        if sin.abs() < 1e-4 && cos > 0.0 {
+            // Straight on
+            moved.push(p1);
+        } else if sin * distance < 0.0 {
+            // The moved edges overlap
+            moved.extend([p1, corner, p2]);
+        } else {
+            match join {
+                Join::Miter(limit) => {
+                    // The miter point is 1/cos(half the turn) offsets out
+                    let half_cos = ((1.0 + cos) / 2.0).sqrt();
+                    if half_cos * limit >= 1.0 {
+                        let bisector = normalize(n1.0 + n2.0, n1.1 + n2.1);
+                        moved.push(at(bisector, distance / half_cos));
+                    } else {
+                        moved.extend([p1, p2]);
+                    }
+                },
+                Join::Bevel => moved.extend([p1, p2]),
+                Join::Round => {
+                    let radius = distance.abs();
+                    let mut sweep = sin.atan2(cos);
+                    if sweep * distance < 0.0 {
+                        // Doubling straight back: go round the outside
+                        sweep = -sweep;
+                    }
+                    let step = if radius > ROUND_TOLERANCE {
+                        2.0 * (1.0 - ROUND_TOLERANCE / radius).acos()
+                    } else {
+                        std::f32::consts::PI
+                    };
+                    let steps = (sweep.abs() / step).ceil().max(1.0) as usize;
+                    let start = (n1.1 * distance).atan2(n1.0 * distance);
+                    for k in 0..=steps {
+                        let angle = start + sweep * k as f32 / steps as f32;
+                        moved.push(at((angle.cos(), angle.sin()), radius));
+                    }
+                },
+            }
+        }
+    }
+    moved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(contours: &[Vec<(f32, f32)>]) -> f32 {
+        contours.iter().map(|c| signed_area(c)).sum()
+    }
+
+    #[test]
+    fn test_offset_square() {
+        // Clockwise in y-down screen space; the offset doesn't mind which way
+        let square = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+        let mitered = offset_polygon(&square, 2.0, Join::Miter(2.0));
+        assert_eq!(mitered.len(), 1);
+        assert!((area(&mitered) - 196.0).abs() < 0.01);
+        assert!(point_in_polygon(-1.9, -1.9, &mitered[0]));
+
+        // A tight miter limit bevels the corners instead
+        let beveled = offset_polygon(&square, 2.0, Join::Miter(1.1));
+        assert!((area(&beveled) - (196.0 - 8.0)).abs() < 0.01);
+        assert_eq!(offset_polygon(&square, 2.0, Join::Bevel), beveled);
+
+        // Round corners are quarter circles, to within a quarter pixel
+        let rounded = offset_polygon(&square, 20.0, Join::Round);
+        let expected = 100.0 + 4.0 * 200.0 + std::f32::consts::PI * 400.0;
+        assert!(area(&rounded) < expected && area(&rounded) > expected - 40.0 * 0.25 * 4.0);
+        assert!(rounded[0].iter().all(|&(x, y)| {
+            let (dx, dy) = (x - x.clamp(0.0, 10.0), y - y.clamp(0.0, 10.0));
+            (length(dx, dy) - 20.0).abs() < 0.01
+        }));
+
+        let inset = offset_polygon(&square, -2.0, Join::Round);
+        assert!((area(&inset) - 36.0).abs() < 0.01);
+        assert!(offset_polygon(&square, -6.0, Join::Miter(2.0)).is_empty());
+    }
+
+    #[test]
+    fn test_offset_concave_shapes() {
+        // An L: the inner corner stays sharp, no loop is left behind
+        let l_shape = [
+            (0.0, 0.0),
+            (30.0, 0.0),
+            (30.0, 10.0),
+            (10.0, 10.0),
+            (10.0, 30.0),
+            (0.0, 30.0),
+        ];
+        let grown = offset_polygon(&l_shape, 2.0, Join::Miter(2.0));
+        assert_eq!(grown.len(), 1);
+        assert_eq!(grown[0].len(), 6);
+        assert!(point_in_polygon(11.9, 11.9, &grown[0]));
+        assert!(!point_in_polygon(12.1, 12.1, &grown[0]));
+        assert!(!point_in_polygon(15.0, 15.0, &grown[0]));
+
+        // A dumbbell shrinks apart into two pieces
+        let dumbbell = [
+            (0.0, 0.0),
+            (20.0, 0.0),
+            (20.0, 8.0),
+            (40.0, 8.0),
+            (40.0, 0.0),
+            (60.0, 0.0),
+            (60.0, 20.0),
+            (40.0, 20.0),
+            (40.0, 12.0),
+            (20.0, 12.0),
+            (20.0, 20.0),
+            (0.0, 20.0),
+        ];
+        let shrunk = offset_polygon(&dumbbell, -3.0, Join::Miter(2.0));
+        assert_eq!(shrunk.len(), 2);
+        assert!((area(&shrunk) - 2.0 * 14.0 * 14.0).abs() < 0.01);
+
+        // Growing a frame shrinks its window
+        let frame = [
+            vec![(0.0, 0.0), (30.0, 0.0), (30.0, 30.0), (0.0, 30.0)],
+            vec![(10.0, 10.0), (20.0, 10.0), (20.0, 20.0), (10.0, 20.0)],
+        ];
+        let grown = offset_contours(&frame, 2.0, Join::Miter(2.0));
+        assert!((area(&grown) - (34.0 * 34.0 - 36.0)).abs() < 0.01);
+        let filled = offset_contours(&frame, 6.0, Join::Miter(2.0));
+        assert_eq!(filled.len(), 1);
+        assert!((area(&filled) - 42.0 * 42.0).abs() < 0.01);
+    }
+}
@@ -1,5 +1,52 @@
 use crate::display::{InputEvent, MouseButtonKind, PixelBuffer};
-use crate::regions::{Circle, Point, Polygon, Region, Scene, Shape};
+use crate::regions::{
+    BoolOp, Circle, Curve, CurvePath, Ellipse, Point, Polygon, Rect, Region, Ring, RoundedRect,
+    Scene, Shape,
+};
+
+/// Smallest width, height or radius a drawn or dragged shape may have
+const MIN_SIZE: f32 = 10.0;
+
+/// What clicking on empty space starts drawing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    /// Click each vertex, click the first again to close
+    Polygon,
+    /// Drag from corner to corner
+    Rect,
+    Ellipse,
+    RoundedRect,
+    /// Drag from the center to the outer edge
+    Ring,
+    /// Click points a smooth curve passes through
+    Spline,
+    /// Click anchors, then pull their control points about
+    Bezier,
+}
+
+impl Tool {
+    const ALL: [Tool; 7] = [
+        Tool::Polygon,
+        Tool::Rect,
+        Tool::Ellipse,
+        Tool::RoundedRect,
+        Tool::Ring,
+        Tool::Spline,
+        Tool::Bezier,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Tool::Polygon => "polygon",
+            Tool::Rect => "rectangle",
+            Tool::Ellipse => "ellipse",
+            Tool::RoundedRect => "rounded rectangle",
+            Tool::Ring => "ring",
+            Tool::Spline => "spline path",
+            Tool::Bezier => "bezier path",
+        }
+    }
+}
 
 /// State machine for calibration mode
 #[derive(Debug, Clone)]
@@ -8,8 +55,10 @@ enum State {
     Idle,
     /// Drawing a new polygon
     Drawing { vertices: Vec<Point> },
-    /// Drawing a circle (shift+click to start, drag to set radius)
-    DrawingCircle { center: Point },
+    /// Drawing a circle (shift+click to start, drag to set radius), or a ring
+    DrawingCircle { center: Point, ring: bool },
+    /// Drawing a rectangle, ellipse or rounded rectangle from corner to corner
+    DrawingBox { start: Point },
     /// A region is selected for editing
     Selected { region_index: usize },
     /// Dragging a vertex of the selected region (polygon only)
@@ -21,11 +70,19 @@ enum State {
     DraggingCircleCenter { region_index: usize },
     /// Resizing a circle region (dragging edge)
     ResizingCircle { region_index: usize },
+    /// Dragging one of the handles of another kind of shape (see `shape_handles`)
+    DraggingHandle {
+        region_index: usize,
+        handle_index: usize,
+    },
 }
 
 /// Calibration mode for defining scene regions with the mouse
 pub struct CalibrationMode {
     state: State,
+    tool: Tool,
+    /// Region picked as the first operand of a boolean operation
+    operand: Option<usize>,
     scene: Scene,
     mouse_pos: (i32, i32),
     mouse_down: bool,
@@ -44,6 +101,8 @@ impl CalibrationMode {
         }
         Self {
             state: State::Idle,
+            tool: Tool::Polygon,
+            operand: None,
             scene,
             mouse_pos: (0, 0),
             mouse_down: false,
@@ -62,6 +121,62 @@ impl CalibrationMode {
         &mut self.scene
     }
 
+    pub fn tool(&self) -> Tool {
+        self.tool
+    }
+
+    /// Switch to the next drawing tool, dropping any shape half drawn
+    pub fn next_tool(&mut self) -> Tool {
+        let index = Tool::ALL.iter().position(|&t| t == self.tool).unwrap_or(0);
+        self.tool = Tool::ALL[(index + 1) % Tool::ALL.len()];
+        if matches!(
+            self.state,
+            State::Drawing { .. } | State::DrawingCircle { .. } | State::DrawingBox { .. }
+        ) {
+            self.state = State::Idle;
+        }
+        self.tool
+    }
+
+    /// Pick the selected region as the first operand of `combine_with_operand`,
+    /// returning its name
+    pub fn mark_operand(&mut self) -> Option<&str> {
+        let index = self.selected_index()?;
+        self.operand = Some(index);
+        self.scene.regions.get(index).map(|r| r.name.as_str())
+    }
+
+    /// Replace the marked operand and the selected region with `op` applied
+    /// to them (operand first), returning the names of the regions made
+    pub fn combine_with_operand(&mut self, op: BoolOp) -> Result<Vec<String>, String> {
+        let operand = self
+            .operand
+            .filter(|&i| i < self.scene.regions.len())
+            .ok_or("no region marked to combine with")?;
+        let selected = self.selected_index().ok_or("no region selected")?;
+        if selected == operand {
+            return Err("select a different region to combine with".to_string());
+        }
+
+        let names = self.scene.combine_regions(&[operand, selected], op, None)?;
+        self.operand = None;
+        self.state = State::Selected {
+            region_index: operand - usize::from(selected < operand),
+        };
+        Ok(names)
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        match self.state {
+            State::Selected { region_index }
+            | State::DraggingVertex { region_index, .. }
+            | State::DraggingCircleCenter { region_index }
+            | State::ResizingCircle { region_index }
+            | State::DraggingHandle { region_index, .. } => Some(region_index),
+            _ => None,
+        }
+    }
+
     pub fn handle_event(&mut self, event: &InputEvent) {
         match event {
             InputEvent::MouseMove { x, y } => {
@@ -137,6 +252,20 @@ impl CalibrationMode {
                     }
                 }
             }
+            State::DraggingHandle {
+                region_index,
+                handle_index,
+            } if self.mouse_down => {
+                let mouse = Point::new(self.mouse_pos.0 as f32, self.mouse_pos.1 as f32);
+                if let Some(shape) = self
+                    .scene
+                    .regions
+                    .get_mut(region_index)
+                    .and_then(|region| region.shape.as_mut())
+                {
+                    move_handle(shape, handle_index, mouse);
+                }
+            }
             _ => {}
         }
     }
@@ -163,6 +292,11 @@ impl CalibrationMode {
                                 self.state = State::Selected { region_index: idx };
                             }
                         }
+                    } else if let Some(handle_index) = self.find_handle_at(idx, click.x, click.y) {
+                        self.state = State::DraggingHandle {
+                            region_index: idx,
+                            handle_index,
+                        };
                     } else {
                         // Polygon region
                         if let Some(vidx) = self.find_vertex_at(idx, click.x, click.y) {
@@ -174,14 +308,8 @@ impl CalibrationMode {
                             self.state = State::Selected { region_index: idx };
                         }
                     }
-                } else if self.shift_held {
-                    // Shift+click on empty space - start drawing circle
-                    self.state = State::DrawingCircle { center: click };
                 } else {
-                    // Start drawing new polygon
-                    self.state = State::Drawing {
-                        vertices: vec![click],
-                    };
+                    self.start_drawing(click);
                 }
             }
 
@@ -190,15 +318,15 @@ impl CalibrationMode {
                 if vertices.len() >= 3 {
                     let first = &vertices[0];
                     if click.distance_to(first) < self.snap_distance {
-                        // Close the polygon and create region
-                        let polygon = Polygon::from_vertices(vertices.clone());
-                        let region = Region::new(self.next_region_name.clone(), polygon);
-                        let new_idx = self.scene.regions.len();
-                        self.scene.add_region(region);
-                        self.auto_increment_name();
-                        self.state = State::Selected {
-                            region_index: new_idx,
+                        // Close the outline and create region
+                        let shape = match self.tool {
+                            Tool::Spline => {
+                                Shape::Path(CurvePath::new(vertices.clone(), Curve::CatmullRom))
+                            }
+                            Tool::Bezier => Shape::Path(CurvePath::smooth_bezier(vertices)),
+                            _ => Shape::Polygon(Polygon::from_vertices(vertices.clone())),
                         };
+                        self.add_shape(shape);
                         return;
                     }
                 }
@@ -210,8 +338,8 @@ impl CalibrationMode {
                 };
             }
 
-            State::DrawingCircle { .. } => {
-                // Circle is finalized on mouse up, not click
+            State::DrawingCircle { .. } | State::DrawingBox { .. } => {
+                // Circle, ring and box shapes are finalized on mouse up, not click
             }
 
             State::Selected { region_index } => {
@@ -235,6 +363,12 @@ impl CalibrationMode {
                         vertex_index: vidx,
                     };
                     return;
+                } else if let Some(handle_index) = self.find_handle_at(idx, click.x, click.y) {
+                    self.state = State::DraggingHandle {
+                        region_index: idx,
+                        handle_index,
+                    };
+                    return;
                 }
 
                 // Check if clicking on a different region
@@ -244,23 +378,64 @@ impl CalibrationMode {
                             region_index: new_idx,
                         };
                     }
-                } else if self.shift_held {
-                    self.state = State::DrawingCircle { center: click };
                 } else {
-                    self.state = State::Drawing {
-                        vertices: vec![click],
-                    };
+                    self.start_drawing(click);
                 }
             }
 
             State::DraggingVertex { .. }
             | State::DraggingCircleCenter { .. }
-            | State::ResizingCircle { .. } => {
+            | State::ResizingCircle { .. }
+            | State::DraggingHandle { .. } => {
                 // Shouldn't happen - drag starts on mouse down
             }
         }
     }
 
+    /// Start a new shape at `click` with the current tool (shift: a circle)
+    fn start_drawing(&mut self, click: Point) {
+        self.state = match self.tool {
+            _ if self.shift_held => State::DrawingCircle {
+                center: click,
+                ring: false,
+            },
+            Tool::Polygon | Tool::Spline | Tool::Bezier => State::Drawing {
+                vertices: vec![click],
+            },
+            Tool::Rect | Tool::Ellipse | Tool::RoundedRect => State::DrawingBox { start: click },
+            Tool::Ring => State::DrawingCircle {
+                center: click,
+                ring: true,
+            },
+        };
+    }
+
+    /// Add a finished shape as a new region and select it
+    fn add_shape(&mut self, shape: Shape) {
+        let region = Region::new_shape(self.next_region_name.clone(), shape);
+        let new_idx = self.scene.regions.len();
+        self.scene.add_region(region);
+        self.auto_increment_name();
+        self.state = State::Selected {
+            region_index: new_idx,
+        };
+    }
+
+    /// The rectangle, ellipse or rounded rectangle spanning `start` to `end`
+    fn box_shape(tool: Tool, start: Point, end: Point) -> Option<Shape> {
+        let (width, height) = ((end.x - start.x).abs(), (end.y - start.y).abs());
+        let center = Point::new((start.x + end.x) / 2.0, (start.y + end.y) / 2.0);
+        match tool {
+            Tool::Rect => Some(Shape::Rect(Rect::new(center, width, height))),
+            Tool::Ellipse => Some(Shape::Ellipse(Ellipse::new(center, width / 2.0, height / 2.0))),
+            Tool::RoundedRect => {
+                let radius = width.min(height) * 0.2;
+                Some(Shape::RoundedRect(RoundedRect::new(center, width, height, radius)))
+            }
+            _ => None,
+        }
+    }
+
     fn on_right_click(&mut self) {
         // Cancel/deselect - return to idle from any active state
         match &self.state {
@@ -275,27 +450,32 @@ impl CalibrationMode {
         match self.state {
             State::DraggingVertex { region_index, .. }
             | State::DraggingCircleCenter { region_index }
-            | State::ResizingCircle { region_index } => {
+            | State::ResizingCircle { region_index }
+            | State::DraggingHandle { region_index, .. } => {
                 self.state = State::Selected { region_index };
             }
-            State::DrawingCircle { center } => {
+            State::DrawingCircle { center, ring } => {
                 // Finalize circle with radius from center to current mouse position
                 let dx = self.mouse_pos.0 as f32 - center.x;
                 let dy = self.mouse_pos.1 as f32 - center.y;
                 let radius = (dx * dx + dy * dy).sqrt();
 
-                if radius >= 10.0 {
-                    let circle = Circle::new(center, radius);
-                    let region = Region::new_circle(self.next_region_name.clone(), circle);
-                    let new_idx = self.scene.regions.len();
-                    self.scene.add_region(region);
-                    self.auto_increment_name();
-                    self.state = State::Selected {
-                        region_index: new_idx,
-                    };
-                } else {
+                if radius < MIN_SIZE {
                     // Too small, cancel
                     self.state = State::Idle;
+                } else if ring {
+                    self.add_shape(Shape::Ring(Ring::new(center, radius / 2.0, radius)));
+                } else {
+                    self.add_shape(Shape::Circle(Circle::new(center, radius)));
+                }
+            }
+            State::DrawingBox { start } => {
+                let end = Point::new(self.mouse_pos.0 as f32, self.mouse_pos.1 as f32);
+                let big_enough =
+                    (end.x - start.x).abs() >= MIN_SIZE && (end.y - start.y).abs() >= MIN_SIZE;
+                match Self::box_shape(self.tool, start, end) {
+                    Some(shape) if big_enough => self.add_shape(shape),
+                    _ => self.state = State::Idle,
                 }
             }
             _ => {}
@@ -307,11 +487,13 @@ impl CalibrationMode {
         if let State::Selected { region_index }
         | State::DraggingVertex { region_index, .. }
         | State::DraggingCircleCenter { region_index }
-        | State::ResizingCircle { region_index } = self.state
+        | State::ResizingCircle { region_index }
+        | State::DraggingHandle { region_index, .. } = self.state
         {
             if region_index < self.scene.regions.len() {
                 self.scene.remove_region(region_index);
             }
+            self.operand = None;
             self.state = State::Idle;
         }
     }
@@ -341,6 +523,14 @@ impl CalibrationMode {
         None
     }
 
+    fn find_handle_at(&self, region_index: usize, x: f32, y: f32) -> Option<usize> {
+        let shape = self.scene.regions.get(region_index)?.shape.as_ref()?;
+        let click = Point::new(x, y);
+        shape_handles(shape)
+            .iter()
+            .position(|h| click.distance_to(h) < self.vertex_handle_size)
+    }
+
     fn auto_increment_name(&mut self) {
         if let Some(pos) = self.next_region_name.rfind('_') {
             if let Ok(num) = self.next_region_name[pos + 1..].parse::<u32>() {
@@ -355,7 +545,8 @@ impl CalibrationMode {
             State::Selected { region_index }
             | State::DraggingVertex { region_index, .. }
             | State::DraggingCircleCenter { region_index }
-            | State::ResizingCircle { region_index } => Some(*region_index),
+            | State::ResizingCircle { region_index }
+            | State::DraggingHandle { region_index, .. } => Some(*region_index),
             _ => None,
         };
 
@@ -373,6 +564,8 @@ impl CalibrationMode {
 
             let outline_color = if is_selected {
                 (100, 150, 255)
+            } else if self.operand == Some(i) {
+                (255, 160, 40)
             } else {
                 (80, 120, 80)
             };
@@ -380,7 +573,10 @@ impl CalibrationMode {
             match region.get_shape() {
                 Shape::Polygon(poly) => {
                     // Fill with solid black (masked area)
-                    buffer.fill_polygon(&poly.as_tuples(), 0, 0, 0);
+                    poly.fill(buffer, 0, 0, 0);
+                    for hole in &poly.holes {
+                        draw_outline(buffer, hole, true, outline_color);
+                    }
 
                     // Draw outline
                     let vertices = &poly.vertices;
@@ -505,6 +701,63 @@ impl CalibrationMode {
                         );
                     }
                 }
+                shape => {
+                    shape.fill(buffer, 0, 0, 0);
+                    if let Shape::Ring(ring) = shape {
+                        for radius in [ring.inner_radius, ring.outer_radius] {
+                            buffer.draw_circle(
+                                ring.center.x as i32,
+                                ring.center.y as i32,
+                                radius as i32,
+                                outline_color.0,
+                                outline_color.1,
+                                outline_color.2,
+                            );
+                        }
+                    } else {
+                        draw_outline(buffer, &shape.outline(), true, outline_color);
+                    }
+
+                    if is_selected {
+                        // Bezier control points hang off their anchors
+                        if let Shape::Path(path) = shape {
+                            if path.curve() == Curve::Bezier {
+                                for pair in path.points().chunks(3) {
+                                    draw_outline(buffer, pair, false, (90, 90, 140));
+                                }
+                            }
+                        }
+
+                        let mouse = Point::new(self.mouse_pos.0 as f32, self.mouse_pos.1 as f32);
+                        for (hidx, h) in shape_handles(shape).iter().enumerate() {
+                            let is_dragging = matches!(
+                                self.state,
+                                State::DraggingHandle { region_index, handle_index }
+                                    if region_index == i && handle_index == hidx
+                            );
+                            let is_hovered =
+                                !is_dragging && mouse.distance_to(h) < self.vertex_handle_size;
+
+                            let (size, color) = if is_dragging {
+                                (6, (255, 255, 100))
+                            } else if is_hovered {
+                                (5, (255, 200, 100))
+                            } else {
+                                (4, (200, 200, 255))
+                            };
+
+                            buffer.fill_rect(
+                                h.x as i32 - size,
+                                h.y as i32 - size,
+                                (size * 2 + 1) as u32,
+                                (size * 2 + 1) as u32,
+                                color.0,
+                                color.1,
+                                color.2,
+                            );
+                        }
+                    }
+                }
             }
         }
 
@@ -565,8 +818,8 @@ impl CalibrationMode {
             }
         }
 
-        // Draw in-progress circle
-        if let State::DrawingCircle { center } = &self.state {
+        // Draw in-progress circle or ring
+        if let State::DrawingCircle { center, ring } = &self.state {
             let dx = self.mouse_pos.0 as f32 - center.x;
             let dy = self.mouse_pos.1 as f32 - center.y;
             let radius = (dx * dx + dy * dy).sqrt();
@@ -580,6 +833,16 @@ impl CalibrationMode {
                     220,
                     0,
                 );
+                if *ring {
+                    buffer.draw_circle(
+                        center.x as i32,
+                        center.y as i32,
+                        (radius / 2.0) as i32,
+                        255,
+                        220,
+                        0,
+                    );
+                }
             }
 
             // Draw center point
@@ -594,6 +857,14 @@ impl CalibrationMode {
             );
         }
 
+        // Draw in-progress rectangle, ellipse or rounded rectangle
+        if let State::DrawingBox { start } = &self.state {
+            let end = Point::new(self.mouse_pos.0 as f32, self.mouse_pos.1 as f32);
+            if let Some(shape) = Self::box_shape(self.tool, *start, end) {
+                draw_outline(buffer, &shape.outline(), true, (255, 220, 0));
+            }
+        }
+
         // Draw cursor crosshair
         let (mx, my) = self.mouse_pos;
         buffer.line(mx - 15, my, mx - 5, my, 255, 255, 255);
@@ -607,3 +878,132 @@ impl CalibrationMode {
         }
     }
 }
+
+/// Draw lines through `points`, back to the first one if `closed`
+fn draw_outline(buffer: &mut PixelBuffer, points: &[Point], closed: bool, color: (u8, u8, u8)) {
+    let segments = if closed { points.len() } else { points.len().saturating_sub(1) };
+    for j in 0..segments {
+        let p1 = &points[j];
+        let p2 = &points[(j + 1) % points.len()];
+        buffer.line(
+            p1.x as i32,
+            p1.y as i32,
+            p2.x as i32,
+            p2.y as i32,
+            color.0,
+            color.1,
+            color.2,
+        );
+    }
+}
+
+/// Center, right edge and bottom edge of a turned box
+fn box_handles(center: Point, width: f32, height: f32, angle: f32) -> Vec<Point> {
+    let (sin, cos) = angle.sin_cos();
+    let (hw, hh) = (width / 2.0, height / 2.0);
+    vec![
+        center,
+        Point::new(center.x + hw * cos, center.y + hw * sin),
+        Point::new(center.x - hh * sin, center.y + hh * cos),
+    ]
+}
+
+/// Move handle `handle` of a box (see `box_handles`) to `to`: the center
+/// moves it, the right edge sets its width and turn, the bottom its height
+fn drag_box(
+    center: &mut Point,
+    width: &mut f32,
+    height: &mut f32,
+    angle: &mut f32,
+    handle: usize,
+    to: Point,
+) {
+    let (dx, dy) = (to.x - center.x, to.y - center.y);
+    match handle {
+        0 => *center = to,
+        1 => {
+            *width = ((dx * dx + dy * dy).sqrt() * 2.0).max(MIN_SIZE);
+            *angle = dy.atan2(dx);
+        }
+        _ => {
+            let (sin, cos) = angle.sin_cos();
+            *height = ((cos * dy - sin * dx).abs() * 2.0).max(MIN_SIZE);
+        }
+    }
+}
+
+/// Drag handles of the shapes that aren't edited by vertex or circle handles
+fn shape_handles(shape: &Shape) -> Vec<Point> {
+    match shape {
+        Shape::Polygon(_) | Shape::Circle(_) => Vec::new(),
+        Shape::Rect(rect) => box_handles(rect.center, rect.width, rect.height, rect.angle),
+        Shape::Ellipse(e) => box_handles(e.center, e.radius_x * 2.0, e.radius_y * 2.0, e.angle),
+        Shape::RoundedRect(rect) => {
+            let mut handles = box_handles(rect.center, rect.width, rect.height, rect.angle);
+            // Where the top edge's straight part ends
+            let (sin, cos) = rect.angle.sin_cos();
+            let (x, y) = (rect.width / 2.0 - rect.corner_radius(), -rect.height / 2.0);
+            handles.push(Point::new(
+                rect.center.x + x * cos - y * sin,
+                rect.center.y + x * sin + y * cos,
+            ));
+            handles
+        }
+        Shape::Ring(ring) => vec![
+            ring.center,
+            Point::new(ring.center.x + ring.outer_radius, ring.center.y),
+            Point::new(ring.center.x + ring.inner_radius, ring.center.y),
+        ],
+        Shape::Path(path) => path.points().to_vec(),
+    }
+}
+
+/// Move handle `handle` of `shape` (see `shape_handles`) to `to`
+fn move_handle(shape: &mut Shape, handle: usize, to: Point) {
+    match shape {
+        Shape::Polygon(_) | Shape::Circle(_) => {}
+        Shape::Rect(rect) => drag_box(
+            &mut rect.center,
+            &mut rect.width,
+            &mut rect.height,
+            &mut rect.angle,
+            handle,
+            to,
+        ),
+        Shape::Ellipse(e) => {
+            let (mut width, mut height) = (e.radius_x * 2.0, e.radius_y * 2.0);
+            drag_box(&mut e.center, &mut width, &mut height, &mut e.angle, handle, to);
+            e.radius_x = width / 2.0;
+            e.radius_y = height / 2.0;
+        }
+        Shape::RoundedRect(rect) if handle == 3 => {
+            // Distance in from the right end of the top edge
+            let (sin, cos) = rect.angle.sin_cos();
+            let x = (to.x - rect.center.x) * cos + (to.y - rect.center.y) * sin;
+            rect.radius = (rect.width / 2.0 - x).clamp(0.0, rect.width.min(rect.height) / 2.0);
+        }
+        Shape::RoundedRect(rect) => drag_box(
+            &mut rect.center,
+            &mut rect.width,
+            &mut rect.height,
+            &mut rect.angle,
+            handle,
+            to,
+        ),
+        Shape::Ring(ring) => {
+            let distance = ring.center.distance_to(&to);
+            match handle {
+                0 => ring.center = to,
+                1 => ring.outer_radius = distance.max(ring.inner_radius + MIN_SIZE / 2.0),
+                _ => {
+                    ring.inner_radius = distance.min(ring.outer_radius - MIN_SIZE / 2.0).max(0.0);
+                }
+            }
+        }
+        Shape::Path(path) => {
+            if let Some(point) = path.points_mut().get_mut(handle) {
+                *point = to;
+            }
+        }
+    }
+}
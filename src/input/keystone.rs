@@ -0,0 +1,193 @@
+use crate::display::{InputEvent, MouseButtonKind, PixelBuffer};
+use crate::output::CornerPin;
+
+/// Corner handles grab the mouse within this many output pixels
+const GRAB_DISTANCE: f32 = 24.0;
+/// How far corners may be dragged past the output edge, as a fraction of its size
+const OVERSHOOT: f32 = 0.5;
+
+/// Interactive corner-pin editing for keystone correction.
+/// Works in output pixels: events and drawing happen after the warp.
+pub struct KeystoneEditor {
+    /// Corner that drags and nudges move (0 = top left, clockwise)
+    selected: usize,
+    dragging: bool,
+    mouse_pos: (i32, i32),
+}
+
+impl KeystoneEditor {
+    pub fn new() -> Self {
+        Self {
+            selected: 0,
+            dragging: false,
+            mouse_pos: (0, 0),
+        }
+    }
+
+    /// Handle a mouse event in output coordinates. Returns true if `pin` changed.
+    pub fn handle_event(
+        &mut self,
+        event: &InputEvent,
+        pin: &mut CornerPin,
+        width: u32,
+        height: u32,
+    ) -> bool {
+        match *event {
+            InputEvent::MouseMove { x, y } => {
+                self.mouse_pos = (x, y);
+                self.dragging && self.move_corner(pin, x as f32, y as f32, width, height)
+            },
+            InputEvent::MouseDown { x, y, button } => {
+                self.mouse_pos = (x, y);
+                let Some(corner) = self.corner_at(pin, x as f32, y as f32, width, height) else {
+                    return false;
+                };
+                self.selected = corner;
+                match button {
+                    MouseButtonKind::Left => {
+                        self.dragging = true;
+                        false
+                    },
+                    // Right click puts a corner back where it started
+                    MouseButtonKind::Right => {
+                        pin.corners[corner] = CornerPin::default().corners[corner];
+                        true
+                    },
+                    _ => false,
+                }
+            },
+            InputEvent::MouseUp { .. } => {
+                self.dragging = false;
+                false
+            },
+            _ => false,
+        }
+    }
+
+    /// Move the selected corner by (dx, dy) output pixels
+    pub fn nudge(&self, pin: &mut CornerPin, dx: i32, dy: i32, width: u32, height: u32) -> bool {
+        let (x, y) = pin.corners_px(width, height)[self.selected];
+        self.move_corner(pin, x + dx as f32, y + dy as f32, width, height)
+    }
+
+    /// Select the next corner clockwise
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % 4;
+    }
+
+    /// Put the selected corner at (x, y) unless that would fold the quad
+    fn move_corner(&self, pin: &mut CornerPin, x: f32, y: f32, width: u32, height: u32) -> bool {
+        let fraction = |v: f32, size: u32| (v / size as f32).clamp(-OVERSHOOT, 1.0 + OVERSHOOT);
+        let mut moved = *pin;
+        moved.corners[self.selected] = (fraction(x, width), fraction(y, height));
+        if moved == *pin || !moved.is_convex() {
+            return false;
+        }
+        *pin = moved;
+        true
+    }
+
+    fn corner_at(&self, pin: &CornerPin, x: f32, y: f32, width: u32, height: u32) -> Option<usize> {
+        pin.corners_px(width, height)
+            .iter()
+            .map(|&(cx, cy)| ((cx - x).powi(2) + (cy - y).powi(2)).sqrt())
+            .enumerate()
+            .filter(|&(_, distance)| distance < GRAB_DISTANCE)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    /// Draw the output quad and its corner handles over the warped frame
+    pub fn render(&self, buffer: &mut PixelBuffer, pin: &CornerPin) {
+        let corners = pin.corners_px(buffer.width(), buffer.height());
+        for i in 0..4 {
+            let (a, b) = (corners[i], corners[(i + 1) % 4]);
+            buffer.line(a.0 as i32, a.1 as i32, b.0 as i32, b.1 as i32, 255, 160, 0);
+        }
+        // Diagonals show the warped center
+        for (a, b) in [(corners[0], corners[2]), (corners[1], corners[3])] {
+            buffer.line(a.0 as i32, a.1 as i32, b.0 as i32, b.1 as i32, 120, 80, 0);
+        }
+
+        let hovered = self.corner_at(
+            pin,
+            self.mouse_pos.0 as f32,
+            self.mouse_pos.1 as f32,
+            buffer.width(),
+            buffer.height(),
+        );
+        for (i, &(x, y)) in corners.iter().enumerate() {
+            let (size, color) = if i == self.selected && self.dragging {
+                (6, (255, 255, 100))
+            } else if hovered == Some(i) {
+                (5, (255, 200, 100))
+            } else if i == self.selected {
+                (5, (255, 160, 0))
+            } else {
+                (4, (200, 200, 255))
+            };
+            // Keep handles on screen when a corner sits at the very edge
+            let x = (x as i32).clamp(size, buffer.width() as i32 - 1 - size);
+            let y = (y as i32).clamp(size, buffer.height() as i32 - 1 - size);
+            buffer.fill_rect(
+                x - size,
+                y - size,
+                (size * 2 + 1) as u32,
+                (size * 2 + 1) as u32,
+                color.0,
+                color.1,
+                color.2,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drag_and_nudge() {
+        let mut editor = KeystoneEditor::new();
+        let mut pin = CornerPin::default();
+        let down = InputEvent::MouseDown {
+            x: 635,
+            y: 5,
+            button: MouseButtonKind::Left,
+        };
+        assert!(!editor.handle_event(&down, &mut pin, 640, 480));
+        assert!(editor.handle_event(&InputEvent::MouseMove { x: 576, y: 48 }, &mut pin, 640, 480));
+        assert_eq!(pin.corners[1], (0.9, 0.1));
+
+        // Dragging across the opposite edge would fold the quad, so it's refused
+        assert!(!editor.handle_event(&InputEvent::MouseMove { x: 0, y: 470 }, &mut pin, 640, 480));
+        editor.handle_event(
+            &InputEvent::MouseUp {
+                x: 0,
+                y: 470,
+                button: MouseButtonKind::Left,
+            },
+            &mut pin,
+            640,
+            480,
+        );
+        assert!(!editor.handle_event(
+            &InputEvent::MouseMove { x: 100, y: 100 },
+            &mut pin,
+            640,
+            480
+        ));
+
+        editor.select_next();
+        assert!(editor.nudge(&mut pin, -64, 0, 640, 480));
+        assert_eq!(pin.corners[2], (0.9, 1.0));
+
+        let reset = InputEvent::MouseDown {
+            x: 576,
+            y: 480,
+            button: MouseButtonKind::Right,
+        };
+        assert!(editor.handle_event(&reset, &mut pin, 640, 480));
+        assert_eq!(pin.corners[2], (1.0, 1.0));
+    }
+}
@@ -0,0 +1,375 @@
+use crate::display::{InputEvent, MouseButtonKind, PixelBuffer};
+use crate::output::WarpMesh;
+
+/// Control points grab the mouse within this many output pixels
+const GRAB_DISTANCE: f32 = 16.0;
+/// How far points may be dragged past the output edge, as a fraction of its size
+const OVERSHOOT: f32 = 0.5;
+/// Line segments drawn per mesh cell, so bicubic grid lines look curved
+const SEGMENTS_PER_CELL: usize = 8;
+
+enum Drag {
+    /// Moving the selected points; the last mouse position
+    Points((i32, i32)),
+    /// Rubber-band selection from this corner
+    Box((i32, i32)),
+}
+
+/// Interactive mesh-warp editing: drag, nudge and subdivide control points.
+/// Works in output pixels: events and drawing happen after the warp.
+pub struct MeshEditor {
+    /// Selected point indices, in the order they were picked
+    selected: Vec<usize>,
+    drag: Option<Drag>,
+    mouse_pos: (i32, i32),
+}
+
+impl MeshEditor {
+    pub fn new() -> Self {
+        Self {
+            selected: vec![0],
+            drag: None,
+            mouse_pos: (0, 0),
+        }
+    }
+
+    /// Handle a mouse event in output coordinates. With `add` (shift held)
+    /// clicks toggle points in the selection instead of replacing it.
+    /// Returns true if `mesh` changed.
+    pub fn handle_event(
+        &mut self,
+        event: &InputEvent,
+        mesh: &mut WarpMesh,
+        add: bool,
+        width: u32,
+        height: u32,
+    ) -> bool {
+        if !mesh.is_valid() {
+            return false;
+        }
+        match *event {
+            InputEvent::MouseMove { x, y } => {
+                let (last_x, last_y) = self.mouse_pos;
+                self.mouse_pos = (x, y);
+                let Some(Drag::Points(last)) = self.drag.as_mut() else {
+                    return false;
+                };
+                *last = (x, y);
+                self.move_selected(mesh, x - last_x, y - last_y, width, height)
+            },
+            InputEvent::MouseDown { x, y, button } => {
+                self.mouse_pos = (x, y);
+                let point = Self::point_at(mesh, x as f32, y as f32, width, height);
+                match (button, point) {
+                    (MouseButtonKind::Left, Some(point)) => {
+                        let position = self.selected.iter().position(|&i| i == point);
+                        match (add, position) {
+                            (true, Some(position)) => {
+                                self.selected.remove(position);
+                                return false;
+                            },
+                            (true, None) => self.selected.push(point),
+                            (false, Some(_)) => {},
+                            (false, None) => self.selected = vec![point],
+                        }
+                        self.drag = Some(Drag::Points((x, y)));
+                        false
+                    },
+                    (MouseButtonKind::Left, None) => {
+                        if !add {
+                            self.selected.clear();
+                        }
+                        self.drag = Some(Drag::Box((x, y)));
+                        false
+                    },
+                    // Right click puts a point back where it started
+                    (MouseButtonKind::Right, Some(point)) => {
+                        let (i, j) = (point % mesh.columns, point / mesh.columns);
+                        let home = (
+                            i as f32 / (mesh.columns - 1) as f32,
+                            j as f32 / (mesh.rows - 1) as f32,
+                        );
+                        let changed = mesh.points[point] != home;
+                        mesh.points[point] = home;
+                        changed
+                    },
+                    (MouseButtonKind::Right, None) => {
+                        self.selected.clear();
+                        false
+                    },
+                    _ => false,
+                }
+            },
+            InputEvent::MouseUp { x, y, .. } => {
+                self.mouse_pos = (x, y);
+                if let Some(Drag::Box(start)) = self.drag.take() {
+                    let (left, right) = (start.0.min(x) as f32, start.0.max(x) as f32);
+                    let (top, bottom) = (start.1.min(y) as f32, start.1.max(y) as f32);
+                    for (i, &(px, py)) in mesh.points.iter().enumerate() {
+                        let (px, py) = (px * width as f32, py * height as f32);
+                        let inside = px >= left && px <= right && py >= top && py <= bottom;
+                        if inside && !self.selected.contains(&i) {
+                            self.selected.push(i);
+                        }
+                    }
+                }
+                false
+            },
+            _ => false,
+        }
+    }
+
+    /// Move the selected points by (dx, dy) output pixels
+    pub fn nudge(&self, mesh: &mut WarpMesh, dx: i32, dy: i32, width: u32, height: u32) -> bool {
+        self.move_selected(mesh, dx, dy, width, height)
+    }
+
+    pub fn select_all(&mut self, mesh: &WarpMesh) {
+        self.selected = (0..mesh.points.len()).collect();
+    }
+
+    /// Select only the point after the last one picked, row by row
+    pub fn select_next(&mut self, mesh: &WarpMesh) {
+        if mesh.points.is_empty() {
+            return;
+        }
+        let next = self
+            .selected
+            .last()
+            .map_or(0, |&i| (i + 1) % mesh.points.len());
+        self.selected = vec![next];
+    }
+
+    /// Double the grid resolution, keeping the same points selected.
+    /// Returns false once the mesh is as fine as it goes.
+    pub fn subdivide(&mut self, mesh: &mut WarpMesh) -> bool {
+        let Some(finer) = mesh.subdivide() else {
+            return false;
+        };
+        // Old point (i, j) is now at (2i, 2j)
+        for index in &mut self.selected {
+            let (i, j) = (*index % mesh.columns, *index / mesh.columns);
+            *index = j * 2 * finer.columns + i * 2;
+        }
+        *mesh = finer;
+        true
+    }
+
+    /// Forget the selection, e.g. when the mesh is replaced
+    pub fn reset(&mut self) {
+        self.selected = vec![0];
+        self.drag = None;
+    }
+
+    fn move_selected(
+        &self,
+        mesh: &mut WarpMesh,
+        dx: i32,
+        dy: i32,
+        width: u32,
+        height: u32,
+    ) -> bool {
+        if (dx, dy) == (0, 0) {
+            return false;
+        }
+        let limit = |v: f32| v.clamp(-OVERSHOOT, 1.0 + OVERSHOOT);
+        let (dx, dy) = (dx as f32 / width as f32, dy as f32 / height as f32);
+        let mut changed = false;
+        for &i in &self.selected {
+            let Some(point) = mesh.points.get_mut(i) else {
+                continue;
+            };
+            let moved = (limit(point.0 + dx), limit(point.1 + dy));
+            changed |= moved != *point;
+            *point = moved;
+        }
+        changed
+    }
+
+    fn point_at(mesh: &WarpMesh, x: f32, y: f32, width: u32, height: u32) -> Option<usize> {
+        mesh.points
+            .iter()
+            .map(|&(px, py)| {
+                ((px * width as f32 - x).powi(2) + (py * height as f32 - y).powi(2)).sqrt()
+            })
+            .enumerate()
+            .filter(|&(_, distance)| distance < GRAB_DISTANCE)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    /// Draw the warped grid, its control points and any selection box over
+    /// the warped frame
+    pub fn render(&self, buffer: &mut PixelBuffer, mesh: &WarpMesh) {
+        if !mesh.is_valid() {
+            return;
+        }
+        let (w, h) = (buffer.width() as f32, buffer.height() as f32);
+        let to_px = |(x, y): (f32, f32)| ((x * w) as i32, (y * h) as i32);
+
+        // Grid lines follow the surface between points
+        let curve = |buffer: &mut PixelBuffer, cells: usize, at: &dyn Fn(f32) -> (f32, f32)| {
+            let steps = cells * SEGMENTS_PER_CELL;
+            let mut last = to_px(at(0.0));
+            for k in 1..=steps {
+                let next = to_px(at(k as f32 / steps as f32));
+                buffer.line(last.0, last.1, next.0, next.1, 0, 200, 255);
+                last = next;
+            }
+        };
+        for j in 0..mesh.rows {
+            let t = j as f32 / (mesh.rows - 1) as f32;
+            curve(buffer, mesh.columns - 1, &|s| mesh.map(s, t));
+        }
+        for i in 0..mesh.columns {
+            let s = i as f32 / (mesh.columns - 1) as f32;
+            curve(buffer, mesh.rows - 1, &|t| mesh.map(s, t));
+        }
+
+        let (mx, my) = (self.mouse_pos.0 as f32, self.mouse_pos.1 as f32);
+        let hovered = Self::point_at(mesh, mx, my, buffer.width(), buffer.height());
+        for (i, &point) in mesh.points.iter().enumerate() {
+            let selected = self.selected.contains(&i);
+            let (size, color) = if selected && matches!(self.drag, Some(Drag::Points(_))) {
+                (4, (255, 255, 100))
+            } else if hovered == Some(i) {
+                (4, (255, 200, 100))
+            } else if selected {
+                (3, (255, 160, 0))
+            } else {
+                (2, (200, 200, 255))
+            };
+            let (x, y) = to_px(point);
+            let x = x.clamp(size, buffer.width() as i32 - 1 - size);
+            let y = y.clamp(size, buffer.height() as i32 - 1 - size);
+            buffer.fill_rect(
+                x - size,
+                y - size,
+                (size * 2 + 1) as u32,
+                (size * 2 + 1) as u32,
+                color.0,
+                color.1,
+                color.2,
+            );
+        }
+
+        if let Some(Drag::Box(start)) = self.drag {
+            let (x0, y0) = start;
+            let (x1, y1) = self.mouse_pos;
+            for (a, b) in [
+                ((x0, y0), (x1, y0)),
+                ((x1, y0), (x1, y1)),
+                ((x1, y1), (x0, y1)),
+                ((x0, y1), (x0, y0)),
+            ] {
+                buffer.line(a.0, a.1, b.0, b.1, 255, 255, 255);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn click(x: i32, y: i32, button: MouseButtonKind) -> [InputEvent; 2] {
+        [
+            InputEvent::MouseDown { x, y, button },
+            InputEvent::MouseUp { x, y, button },
+        ]
+    }
+
+    #[test]
+    fn test_select_and_drag() {
+        let mut editor = MeshEditor::new();
+        let mut mesh = WarpMesh::new(3, 3);
+
+        // Click the center, shift-click the top middle, then drag both down
+        for event in click(320, 240, MouseButtonKind::Left) {
+            editor.handle_event(&event, &mut mesh, false, 640, 480);
+        }
+        let down = InputEvent::MouseDown {
+            x: 318,
+            y: 2,
+            button: MouseButtonKind::Left,
+        };
+        assert!(!editor.handle_event(&down, &mut mesh, true, 640, 480));
+        assert!(editor.handle_event(
+            &InputEvent::MouseMove { x: 318, y: 50 },
+            &mut mesh,
+            true,
+            640,
+            480
+        ));
+        assert_eq!(mesh.points[1], (0.5, 0.1));
+        assert_eq!(mesh.points[4], (0.5, 0.6));
+        assert_eq!(mesh.points[0], (0.0, 0.0));
+
+        // Rubber band round the bottom row replaces the selection
+        let up = InputEvent::MouseUp {
+            x: 318,
+            y: 50,
+            button: MouseButtonKind::Left,
+        };
+        editor.handle_event(&up, &mut mesh, false, 640, 480);
+        let start = InputEvent::MouseDown {
+            x: -10,
+            y: 400,
+            button: MouseButtonKind::Left,
+        };
+        editor.handle_event(&start, &mut mesh, false, 640, 480);
+        let end = InputEvent::MouseUp {
+            x: 700,
+            y: 500,
+            button: MouseButtonKind::Left,
+        };
+        editor.handle_event(&end, &mut mesh, false, 640, 480);
+        assert_eq!(editor.selected, vec![6, 7, 8]);
+        assert!(editor.nudge(&mut mesh, 0, -48, 640, 480));
+        assert_eq!(mesh.points[7], (0.5, 0.9));
+
+        // Right click resets a point
+        for event in click(320, 432, MouseButtonKind::Right) {
+            editor.handle_event(&event, &mut mesh, false, 640, 480);
+        }
+        assert_eq!(mesh.points[7], (0.5, 1.0));
+    }
+
+    #[test]
+    fn test_subdivide_keeps_selection() {
+        let mut editor = MeshEditor::new();
+        let mut mesh = WarpMesh::new(3, 3);
+        editor.select_next(&mesh);
+        editor.select_next(&mesh);
+        editor.select_next(&mesh);
+        assert_eq!(editor.selected, vec![3]);
+        assert!(editor.subdivide(&mut mesh));
+        assert_eq!((mesh.columns, editor.selected.clone()), (5, vec![10]));
+        editor.select_all(&mesh);
+        assert_eq!(editor.selected.len(), 25);
+    }
+
+    #[test]
+    fn test_oversized_and_invalid_meshes() {
+        let mut editor = MeshEditor::new();
+        // Loaded from a file, so bigger than `new` would make
+        let mut mesh = WarpMesh::new(2, 2);
+        mesh.columns = 40;
+        mesh.points = (0..80)
+            .map(|k| ((k % 40) as f32 / 39.0, (k / 40) as f32))
+            .collect();
+        let home = mesh.points[79];
+        mesh.points[79] = (0.9, 0.9);
+        for event in click(576, 432, MouseButtonKind::Right) {
+            editor.handle_event(&event, &mut mesh, false, 640, 480);
+        }
+        assert_eq!(mesh.points[79], home);
+
+        // A mesh whose points don't match its grid is left alone
+        mesh.points.clear();
+        for event in click(0, 0, MouseButtonKind::Left) {
+            assert!(!editor.handle_event(&event, &mut mesh, false, 640, 480));
+        }
+        editor.select_next(&mesh);
+    }
+}
@@ -1,3 +1,7 @@
 mod calibration;
+mod keystone;
+mod mesh_editor;
 
 pub use calibration::CalibrationMode;
+pub use keystone::KeystoneEditor;
+pub use mesh_editor::MeshEditor;
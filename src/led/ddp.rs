@@ -0,0 +1,49 @@
+//! DDP (Distributed Display Protocol) packet encoding
+//!
+//! The protocol WLED and most pixel controllers accept: a 10-byte header
+//! followed by raw RGB data, split across packets with the PUSH flag set on
+//! the last one so the controller latches the whole frame at once.
+
+/// Standard DDP UDP port
+pub const DDP_PORT: u16 = 4048;
+
+/// Maximum payload per packet (480 RGB pixels, keeps packets under the MTU)
+pub const MAX_PAYLOAD: usize = 1440;
+
+const FLAG_VERSION_1: u8 = 0x40;
+const FLAG_PUSH: u8 = 0x01;
+const DATA_TYPE_RGB8: u8 = 0x0B;
+const DEVICE_DEFAULT_OUTPUT: u8 = 1;
+
+/// Split a frame of channel data into DDP packets.
+/// `sequence` is a 4-bit counter (0 disables duplicate detection on the receiver).
+pub fn build_frame(sequence: u8, data: &[u8]) -> Vec<Vec<u8>> {
+    let chunks: Vec<&[u8]> = if data.is_empty() {
+        vec![&[]]
+    } else {
+        data.chunks(MAX_PAYLOAD).collect()
+    };
+    let last = chunks.len() - 1;
+
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            let offset = (i * MAX_PAYLOAD) as u32;
+            let mut flags = FLAG_VERSION_1;
+            if i == last {
+                flags |= FLAG_PUSH;
+            }
+
+            let mut packet = Vec::with_capacity(10 + chunk.len());
+            packet.push(flags);
+            packet.push(sequence & 0x0F);
+            packet.push(DATA_TYPE_RGB8);
+            packet.push(DEVICE_DEFAULT_OUTPUT);
+            packet.extend_from_slice(&offset.to_be_bytes());
+            packet.extend_from_slice(&(chunk.len() as u16).to_be_bytes());
+            packet.extend_from_slice(chunk);
+            packet
+        })
+        .collect()
+}
@@ -0,0 +1,296 @@
+//! LED mapping file format
+//!
+//! Describes where each LED sits in content space and how the strip is
+//! addressed on the wire. LED positions are re-resolved whenever the regions
+//! they follow change, so strips on region edges track calibration changes.
+//!
+//! ```json
+//! {
+//!   "protocol": "ddp",
+//!   "host": "192.168.1.50",
+//!   "gamma": 2.2,
+//!   "brightness": 0.6,
+//!   "strips": [
+//!     { "type": "Region", "region": "frame_1", "leds": 120 },
+//!     { "type": "Polyline", "points": [{"x": 0, "y": 10}, {"x": 640, "y": 10}], "leds": 60 },
+//!     { "type": "Points", "points": [{"x": 320, "y": 240}] }
+//!   ]
+//! }
+//! ```
+
+use crate::geometry::resample_polyline;
+use crate::regions::{Point, Region, Scene, Shape};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Wire protocol used to reach the LED controller
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LedProtocol {
+    Sacn,
+    Artnet,
+    Ddp,
+}
+
+impl LedProtocol {
+    /// Standard UDP port for the protocol
+    pub fn default_port(self) -> u16 {
+        match self {
+            LedProtocol::Sacn => crate::dmx::SACN_PORT,
+            LedProtocol::Artnet => crate::dmx::ARTNET_PORT,
+            LedProtocol::Ddp => super::ddp::DDP_PORT,
+        }
+    }
+}
+
+/// Channel order expected by the LED hardware
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorOrder {
+    #[default]
+    Rgb,
+    Grb,
+    Brg,
+    Bgr,
+}
+
+impl ColorOrder {
+    /// Reorder an (r, g, b) triple for the wire
+    #[inline]
+    pub fn apply(self, (r, g, b): (u8, u8, u8)) -> [u8; 3] {
+        match self {
+            ColorOrder::Rgb => [r, g, b],
+            ColorOrder::Grb => [g, r, b],
+            ColorOrder::Brg => [b, r, g],
+            ColorOrder::Bgr => [b, g, r],
+        }
+    }
+}
+
+/// One run of LEDs in the mapping
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum LedStrip {
+    /// LEDs evenly spaced along a region's outline, starting at its first vertex
+    Region {
+        region: String,
+        leds: usize,
+        /// Walk the outline in reverse (for strips wired counter-clockwise)
+        #[serde(default)]
+        reverse: bool,
+    },
+    /// LEDs evenly spaced along a polyline
+    Polyline {
+        points: Vec<Point>,
+        leds: usize,
+        #[serde(default)]
+        closed: bool,
+    },
+    /// One LED per listed point
+    Points { points: Vec<Point> },
+}
+
+impl LedStrip {
+    /// Number of LEDs in this strip
+    pub fn led_count(&self) -> usize {
+        match self {
+            LedStrip::Region { leds, .. } | LedStrip::Polyline { leds, .. } => *leds,
+            LedStrip::Points { points } => points.len(),
+        }
+    }
+
+    /// Resolve LED positions, appending to `out`.
+    /// Region strips whose region is missing keep their slots at (-1, -1) so
+    /// later strips stay at the same addresses.
+    fn resolve_into(&self, scene: &Scene, out: &mut Vec<(f32, f32)>) {
+        match self {
+            LedStrip::Region {
+                region,
+                leds,
+                reverse,
+            } => {
+                let outline: Vec<(f32, f32)> = scene
+                    .regions
+                    .iter()
+                    .find(|r| &r.name == region)
+                    .map(|r| r.get_shape().outline().iter().map(|p| (p.x, p.y)).collect())
+                    .unwrap_or_default();
+                let mut positions = resample_polyline(&outline, true, *leds);
+                if *reverse {
+                    positions.reverse();
+                }
+                positions.resize(*leds, (-1.0, -1.0));
+                out.extend(positions);
+            },
+            LedStrip::Polyline {
+                points,
+                leds,
+                closed,
+            } => {
+                let points: Vec<(f32, f32)> = points.iter().map(|p| (p.x, p.y)).collect();
+                let mut positions = resample_polyline(&points, *closed, *leds);
+                positions.resize(*leds, (-1.0, -1.0));
+                out.extend(positions);
+            },
+            LedStrip::Points { points } => {
+                out.extend(points.iter().map(|p| (p.x, p.y)));
+            },
+        }
+    }
+}
+
+/// A complete LED mapping: transport settings plus strips in wire order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedMapping {
+    pub protocol: LedProtocol,
+    pub host: String,
+    /// UDP port (defaults to the protocol's standard port)
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// First universe for sACN/Art-Net (ignored by DDP)
+    #[serde(default = "default_universe")]
+    pub universe: u16,
+    /// Gamma exponent applied to sampled colors
+    #[serde(default = "default_gamma")]
+    pub gamma: f32,
+    /// Brightness limit, 0.0 to 1.0
+    #[serde(default = "default_brightness")]
+    pub brightness: f32,
+    /// Radius in pixels of the box averaged around each LED (0 = single pixel)
+    #[serde(default)]
+    pub sample_radius: u32,
+    #[serde(default)]
+    pub color_order: ColorOrder,
+    pub strips: Vec<LedStrip>,
+}
+
+fn default_universe() -> u16 {
+    1
+}
+
+fn default_gamma() -> f32 {
+    2.2
+}
+
+fn default_brightness() -> f32 {
+    1.0
+}
+
+impl LedMapping {
+    /// Load a mapping from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&json).map_err(|e| e.to_string())
+    }
+
+    /// Total number of LEDs across all strips
+    pub fn led_count(&self) -> usize {
+        self.strips.iter().map(LedStrip::led_count).sum()
+    }
+
+    /// Shapes of the regions that region strips follow, in strip order
+    pub fn region_shapes<'a>(
+        &'a self,
+        scene: &'a Scene,
+    ) -> impl Iterator<Item = Option<&'a Shape>> + 'a {
+        self.strips.iter().filter_map(move |strip| match strip {
+            LedStrip::Region { region, .. } => Some(
+                scene
+                    .regions
+                    .iter()
+                    .find(|r| &r.name == region)
+                    .map(Region::get_shape),
+            ),
+            _ => None,
+        })
+    }
+
+    /// Resolve every LED position in wire order
+    pub fn resolve(&self, scene: &Scene) -> Vec<(f32, f32)> {
+        let mut positions = Vec::with_capacity(self.led_count());
+        for strip in &self.strips {
+            strip.resolve_into(scene, &mut positions);
+        }
+        positions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regions::{Polygon, Region};
+
+    fn square_scene() -> Scene {
+        let mut scene = Scene::new("test");
+        scene.add_region(Region::new(
+            "frame",
+            Polygon::from_vertices(vec![
+                Point::new(0.0, 0.0),
+                Point::new(100.0, 0.0),
+                Point::new(100.0, 100.0),
+                Point::new(0.0, 100.0),
+            ]),
+        ));
+        scene
+    }
+
+    fn mapping(strips: Vec<LedStrip>) -> LedMapping {
+        LedMapping {
+            protocol: LedProtocol::Ddp,
+            host: "127.0.0.1".to_string(),
+            port: None,
+            universe: 1,
+            gamma: 1.0,
+            brightness: 1.0,
+            sample_radius: 0,
+            color_order: ColorOrder::Rgb,
+            strips,
+        }
+    }
+
+    #[test]
+    fn test_region_strip_spacing() {
+        let map = mapping(vec![LedStrip::Region {
+            region: "frame".to_string(),
+            leds: 4,
+            reverse: false,
+        }]);
+        let positions = map.resolve(&square_scene());
+        assert_eq!(
+            positions,
+            vec![(0.0, 0.0), (100.0, 0.0), (100.0, 100.0), (0.0, 100.0)]
+        );
+    }
+
+    #[test]
+    fn test_open_polyline_includes_endpoints() {
+        let map = mapping(vec![LedStrip::Polyline {
+            points: vec![Point::new(0.0, 0.0), Point::new(90.0, 0.0)],
+            leds: 4,
+            closed: false,
+        }]);
+        let positions = map.resolve(&Scene::default());
+        assert_eq!(
+            positions,
+            vec![(0.0, 0.0), (30.0, 0.0), (60.0, 0.0), (90.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn test_missing_region_keeps_addresses() {
+        let map = mapping(vec![
+            LedStrip::Region {
+                region: "missing".to_string(),
+                leds: 3,
+                reverse: false,
+            },
+            LedStrip::Points {
+                points: vec![Point::new(5.0, 5.0)],
+            },
+        ]);
+        let positions = map.resolve(&square_scene());
+        assert_eq!(positions.len(), 4);
+        assert_eq!(positions[3], (5.0, 5.0));
+    }
+}
@@ -0,0 +1,307 @@
+//! Pixel-mapped LED output
+//!
+//! Samples the final frame at LED positions from a mapping file, applies
+//! gamma and a brightness limit, and streams the result to an LED controller
+//! over sACN, Art-Net or DDP. Packets are sent from a background thread so a
+//! slow network never stalls rendering.
+
+pub mod ddp;
+mod mapping;
+
+#[allow(unused_imports)]
+pub use mapping::{ColorOrder, LedMapping, LedProtocol, LedStrip};
+
+use crate::display::PixelBuffer;
+use crate::dmx::{artnet, sacn};
+use crate::regions::{Scene, Shape};
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread;
+
+/// RGB LEDs per DMX universe (510 of 512 channels, never splitting an LED)
+const LEDS_PER_UNIVERSE: usize = 170;
+
+const SOURCE_NAME: &str = "wallfacer";
+
+/// LED output stage: mapping, color correction and a sender thread
+pub struct LedOutput {
+    mapping: LedMapping,
+    gamma_lut: [u8; 256],
+    positions: Vec<(f32, f32)>,
+    /// Region shapes `positions` were resolved from; `None` until the first frame
+    resolved_shapes: Option<Vec<Option<Shape>>>,
+    sender: SyncSender<Vec<u8>>,
+    _thread: thread::JoinHandle<()>,
+}
+
+impl LedOutput {
+    /// Load a mapping file and start the sender
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        Self::new(LedMapping::load(path)?)
+    }
+
+    /// Start the sender for a mapping.
+    /// Fails if the controller host can't be resolved or no socket can be bound.
+    pub fn new(mapping: LedMapping) -> Result<Self, String> {
+        let port = mapping
+            .port
+            .unwrap_or_else(|| mapping.protocol.default_port());
+        let target = (mapping.host.as_str(), port)
+            .to_socket_addrs()
+            .map_err(|e| format!("Failed to resolve '{}': {}", mapping.host, e))?
+            .next()
+            .ok_or_else(|| format!("No address for '{}'", mapping.host))?;
+
+        let socket = UdpSocket::bind(("0.0.0.0", 0)).map_err(|e| e.to_string())?;
+        // Allow broadcast addresses for Art-Net setups that use them
+        let _ = socket.set_broadcast(true);
+
+        // Buffer one frame: if the network falls behind, frames are dropped rather than queued
+        let (sender, receiver) = mpsc::sync_channel(1);
+        let transmitter = Transmitter {
+            socket,
+            target,
+            protocol: mapping.protocol,
+            universe: mapping.universe,
+            sequence: 0,
+            cid: source_cid(),
+        };
+        let handle = thread::spawn(move || {
+            transmitter.run(&receiver);
+        });
+
+        Ok(Self {
+            gamma_lut: build_lut(mapping.gamma, mapping.brightness),
+            mapping,
+            positions: Vec::new(),
+            resolved_shapes: None,
+            sender,
+            _thread: handle,
+        })
+    }
+
+    /// Number of mapped LEDs
+    pub fn led_count(&self) -> usize {
+        self.mapping.led_count()
+    }
+
+    /// Mapping this output was created from
+    pub fn mapping(&self) -> &LedMapping {
+        &self.mapping
+    }
+
+    /// Change the brightness limit (0.0 to 1.0)
+    pub fn set_brightness(&mut self, brightness: f32) {
+        self.mapping.brightness = brightness.clamp(0.0, 1.0);
+        self.gamma_lut = build_lut(self.mapping.gamma, self.mapping.brightness);
+    }
+
+    /// Sample the frame at every LED and queue it for sending.
+    /// Positions are re-resolved when a followed region changes, so region strips
+    /// track calibration.
+    pub fn update(&mut self, buffer: &PixelBuffer, scene: &Scene) {
+        self.refresh_positions(scene);
+
+        let order = self.mapping.color_order;
+        let radius = self.mapping.sample_radius as i32;
+        let mut frame = Vec::with_capacity(self.positions.len() * 3);
+        for &(x, y) in &self.positions {
+            let (r, g, b) = sample(buffer, x as i32, y as i32, radius);
+            let corrected = (
+                self.gamma_lut[r as usize],
+                self.gamma_lut[g as usize],
+                self.gamma_lut[b as usize],
+            );
+            frame.extend_from_slice(&order.apply(corrected));
+        }
+
+        match self.sender.try_send(frame) {
+            Ok(()) | Err(TrySendError::Full(_)) => {},
+            Err(TrySendError::Disconnected(_)) => {
+                eprintln!("LED: Sender thread stopped");
+            },
+        }
+    }
+
+    /// Re-resolve LED positions if the regions they follow have changed
+    fn refresh_positions(&mut self, scene: &Scene) {
+        let current = self.resolved_shapes.as_ref().is_some_and(|shapes| {
+            self.mapping
+                .region_shapes(scene)
+                .eq(shapes.iter().map(Option::as_ref))
+        });
+        if !current {
+            self.positions = self.mapping.resolve(scene);
+            self.resolved_shapes = Some(
+                self.mapping
+                    .region_shapes(scene)
+                    .map(Option::<&Shape>::cloned)
+                    .collect(),
+            );
+        }
+    }
+
+    /// Draw LED positions as small markers (for calibration mode)
+    pub fn render_positions(&self, buffer: &mut PixelBuffer) {
+        for (i, &(x, y)) in self.positions.iter().enumerate() {
+            // First LED of the chain in red so wiring direction is visible
+            let color = if i == 0 { (255, 60, 60) } else { (255, 200, 0) };
+            buffer.fill_rect(x as i32 - 1, y as i32 - 1, 3, 3, color.0, color.1, color.2);
+        }
+    }
+}
+
+/// Owns the socket on the sender thread
+struct Transmitter {
+    socket: UdpSocket,
+    target: SocketAddr,
+    protocol: LedProtocol,
+    universe: u16,
+    sequence: u8,
+    cid: [u8; 16],
+}
+
+impl Transmitter {
+    fn run(mut self, receiver: &Receiver<Vec<u8>>) {
+        let mut failures = 0u32;
+        // Exits when the LedOutput is dropped
+        while let Ok(frame) = receiver.recv() {
+            for packet in self.packets(&frame) {
+                if let Err(e) = self.socket.send_to(&packet, self.target) {
+                    failures += 1;
+                    // Log the first failure and then only occasionally to avoid spam
+                    if failures == 1 || failures % 1000 == 0 {
+                        eprintln!(
+                            "LED: Send to {} failed ({} total): {}",
+                            self.target, failures, e
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn packets(&mut self, frame: &[u8]) -> Vec<Vec<u8>> {
+        self.sequence = self.sequence.wrapping_add(1);
+        match self.protocol {
+            LedProtocol::Ddp => {
+                // DDP sequence numbers are 1-15; 0 means "not used"
+                ddp::build_frame(self.sequence % 15 + 1, frame)
+            },
+            LedProtocol::Sacn | LedProtocol::Artnet => frame
+                .chunks(LEDS_PER_UNIVERSE * 3)
+                .enumerate()
+                .map(|(i, chunk)| {
+                    let universe = self.universe.wrapping_add(i as u16);
+                    match self.protocol {
+                        LedProtocol::Artnet => artnet::build(universe, self.sequence, chunk),
+                        _ => sacn::build(
+                            universe,
+                            self.sequence,
+                            sacn::DEFAULT_PRIORITY,
+                            &self.cid,
+                            SOURCE_NAME,
+                            chunk,
+                        ),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Build a combined gamma + brightness lookup table
+fn build_lut(gamma: f32, brightness: f32) -> [u8; 256] {
+    let gamma = gamma.max(0.1);
+    let brightness = brightness.clamp(0.0, 1.0);
+    let mut lut = [0u8; 256];
+    for (i, v) in lut.iter_mut().enumerate() {
+        let linear = (i as f32 / 255.0).powf(gamma);
+        *v = (linear * brightness * 255.0).round() as u8;
+    }
+    lut
+}
+
+/// Average the pixels in a (2r+1)² box; off-screen samples are ignored
+fn sample(buffer: &PixelBuffer, x: i32, y: i32, radius: i32) -> (u8, u8, u8) {
+    if radius <= 0 {
+        return buffer.get_pixel(x, y).unwrap_or((0, 0, 0));
+    }
+
+    let (mut r, mut g, mut b, mut n) = (0u32, 0u32, 0u32, 0u32);
+    for sy in y - radius..=y + radius {
+        for sx in x - radius..=x + radius {
+            if let Some((pr, pg, pb)) = buffer.get_pixel(sx, sy) {
+                r += pr as u32;
+                g += pg as u32;
+                b += pb as u32;
+                n += 1;
+            }
+        }
+    }
+    // All sums are zero when nothing was on-screen
+    let n = n.max(1);
+    ((r / n) as u8, (g / n) as u8, (b / n) as u8)
+}
+
+/// Component identifier for sACN, stable for the lifetime of the process
+fn source_cid() -> [u8; 16] {
+    let mut cid = [0u8; 16];
+    cid[..9].copy_from_slice(SOURCE_NAME.as_bytes());
+    cid[12..].copy_from_slice(&std::process::id().to_be_bytes());
+    cid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lut_limits_brightness() {
+        let lut = build_lut(1.0, 0.5);
+        assert_eq!(lut[0], 0);
+        assert_eq!(lut[255], 128);
+    }
+
+    #[test]
+    fn test_ddp_push_on_last_packet() {
+        let data = vec![7u8; ddp::MAX_PAYLOAD + 30];
+        let packets = ddp::build_frame(1, &data);
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[0][0] & 0x01, 0);
+        assert_eq!(packets[1][0] & 0x01, 1);
+        // Second packet starts at the payload offset
+        assert_eq!(&packets[1][4..8], &(ddp::MAX_PAYLOAD as u32).to_be_bytes());
+        assert_eq!(packets[1].len(), 10 + 30);
+    }
+
+    #[test]
+    fn test_positions_follow_region_changes() {
+        use crate::regions::{Point, Polygon, Region};
+        let square = |size: f32| {
+            let corners = [(0.0, 0.0), (size, 0.0), (size, size), (0.0, size)];
+            Region::new(
+                "frame",
+                Polygon::from_vertices(corners.map(|(x, y)| Point::new(x, y)).to_vec()),
+            )
+        };
+        let mut scene = Scene::new("test");
+        scene.add_region(square(10.0));
+        let mapping: LedMapping = serde_json::from_str(
+            r#"{"protocol": "ddp", "host": "127.0.0.1",
+                "strips": [{"type": "Region", "region": "frame", "leds": 4}]}"#,
+        )
+        .unwrap();
+        let mut led = LedOutput::new(mapping).unwrap();
+        let buffer = PixelBuffer::new();
+
+        led.update(&buffer, &scene);
+        led.update(&buffer, &scene);
+        assert_eq!(led.positions[2], (10.0, 10.0));
+
+        scene.regions[0] = square(20.0);
+        led.update(&buffer, &scene);
+        assert_eq!(led.positions[2], (20.0, 20.0));
+    }
+}
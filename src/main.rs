@@ -2,33 +2,51 @@
 // Remove these as the codebase matures
 #![allow(dead_code)]
 
+mod ansi;
+mod chyron;
+mod config;
 mod control;
 mod display;
+mod dmx;
 mod effects;
+mod feeds;
 mod mqtt;
 mod geometry;
 mod input;
+mod led;
 mod math3d;
 mod noise;
+mod output;
+mod overlay;
 mod particles;
 mod regions;
 mod texture;
 mod util;
 
 use display::{
-    draw_text, ColorEffect, Display, InputEvent, OffsetEffect, PixelBuffer, RenderTarget,
-    ScrollDirection, StyledScroller, DEFAULT_HEIGHT, DEFAULT_WIDTH,
+    draw_text, set_default_font, Display, Font, InputEvent, PixelBuffer, RenderTarget,
+    DEFAULT_HEIGHT, DEFAULT_WIDTH,
 };
-use mqtt::MqttClient;
+use mqtt::{DisplayState, MqttClient, MqttConfig};
 use effects::{
-    Bobs, CopperBars, DotTunnel, Dvd, Earth, Earth2, Effect, EtherealInk, Fire, Glenz,
+    AnsiViewer, Bobs, CopperBars, DotTunnel, Dvd, Earth, Earth2, Effect, EtherealInk, Fire, Glenz,
     GravityBalls, Julia, LavaRegions, Metaballs, Plasma, Raycaster, RegionFire, Ripples,
     Rotozoomer, Rubber, ScrollerDemo, Snowfall, Starfield, TestPattern, TextFxDemo, Tunnel,
-    VectorBalls, Vortex, Worms,
+    VectorBalls, VectorText, Vortex, Worms,
 };
+use chyron::{ChyronManager, ChyronStrip, ChyronStyle, StripLayout, StripRect, StyleSheet};
+use config::Config;
 use control::{Command, Controller};
-use input::CalibrationMode;
-use regions::{Point, Polygon, Region, Scene};
+use dmx::{DmxInput, DmxOptions, FixtureProfile};
+use input::{CalibrationMode, KeystoneEditor, MeshEditor};
+use led::LedOutput;
+use feeds::FeedReader;
+use geometry::Join;
+use output::{
+    CornerPin, Flip, Interpolation, Orientation, OutputConfig, Projector, Rotation, WarpMesh,
+};
+use overlay::Overlay;
+use regions::{BoolOp, ContentLayer, Region, Scene};
 use sdl2::keyboard::Keycode;
 use util::FpsCounter;
 
@@ -44,7 +62,7 @@ fn mask_regions(buffer: &mut PixelBuffer, scene: &Scene, color: (u8, u8, u8)) {
     for region in &scene.regions {
         match region.get_shape() {
             Shape::Polygon(poly) => {
-                buffer.fill_polygon(&poly.as_tuples(), color.0, color.1, color.2);
+                poly.fill(buffer, color.0, color.1, color.2);
             }
             Shape::Circle(circle) => {
                 buffer.fill_circle(
@@ -56,18 +74,44 @@ fn mask_regions(buffer: &mut PixelBuffer, scene: &Scene, color: (u8, u8, u8)) {
                     color.2,
                 );
             }
+            shape => shape.fill(buffer, color.0, color.1, color.2),
+        }
+    }
+}
+
+/// Glow layers around each region's shape, kept until the shape changes
+#[derive(Default)]
+struct GlowCache {
+    regions: std::collections::HashMap<String, (regions::Shape, Vec<Vec<regions::Polygon>>)>,
+}
+
+impl GlowCache {
+    /// The glow layers of region `name`, outermost first
+    fn layers(&mut self, name: &str, shape: &regions::Shape) -> &[Vec<regions::Polygon>] {
+        if self.regions.get(name).map_or(true, |(cached, _)| cached != shape) {
+            let layers = (1..=5)
+                .rev()
+                .map(|layer| shape.offset(layer as f32 * 4.0, Join::Round))
+                .collect();
+            self.regions.insert(name.to_string(), (shape.clone(), layers));
         }
+        &self.regions[name].1
     }
 }
 
 /// Draw glowing effect around user-defined regions (excludes chyron regions)
-fn glow_regions(buffer: &mut PixelBuffer, scene: &Scene, time: f32) {
+fn glow_regions(buffer: &mut PixelBuffer, scene: &Scene, cache: &mut GlowCache, time: f32) {
     use regions::Shape;
 
     // Pulsing glow intensity
     let pulse = (time * 3.0).sin() * 0.3 + 0.7; // 0.4 to 1.0
     let glow_alpha = (200.0 * pulse) as u8;
 
+    // Forget regions that were deleted or renamed
+    cache
+        .regions
+        .retain(|name, _| scene.regions.iter().any(|region| region.name == *name));
+
     for region in &scene.regions {
         // Skip chyron regions
         if region.name.starts_with("chyron") {
@@ -75,21 +119,6 @@ fn glow_regions(buffer: &mut PixelBuffer, scene: &Scene, time: f32) {
         }
 
         match region.get_shape() {
-            Shape::Polygon(poly) => {
-                let verts = poly.as_tuples();
-                // Draw outer glow layers (blended)
-                for layer in (1..=5).rev() {
-                    let alpha = glow_alpha / (layer as u8 + 1);
-                    let expanded = expand_polygon(&verts, layer as f32 * 4.0);
-                    buffer.fill_polygon_blend(&expanded, 80, 180, 255, alpha);
-                }
-                // Solid inner fill (fully opaque, on top of everything)
-                let inner_pulse = ((time * 2.0).sin() * 0.2 + 0.8) as f32;
-                let r = (100.0 * inner_pulse) as u8;
-                let g = (180.0 * inner_pulse) as u8;
-                let b = 255;
-                buffer.fill_polygon(&verts, r, g, b);
-            }
             Shape::Circle(circle) => {
                 let cx = circle.center.x as i32;
                 let cy = circle.center.y as i32;
@@ -106,124 +135,108 @@ fn glow_regions(buffer: &mut PixelBuffer, scene: &Scene, time: f32) {
                 let cg = (180.0 * inner_pulse) as u8;
                 buffer.fill_circle(cx, cy, r, cr, cg, 255);
             }
+            shape => {
+                // Draw outer glow layers (blended)
+                let layers = cache.layers(&region.name, shape);
+                for (layer, pieces) in (1..=5u8).rev().zip(layers) {
+                    let alpha = glow_alpha / (layer + 1);
+                    for piece in pieces {
+                        piece.fill_blend(buffer, 80, 180, 255, alpha);
+                    }
+                }
+                // Solid inner fill (fully opaque, on top of everything)
+                let inner_pulse = ((time * 2.0).sin() * 0.2 + 0.8) as f32;
+                let r = (100.0 * inner_pulse) as u8;
+                let g = (180.0 * inner_pulse) as u8;
+                shape.fill(buffer, r, g, 255);
+            }
         }
     }
 }
 
-/// Expand a polygon outward by a given amount (simple approximation)
-fn expand_polygon(verts: &[(f32, f32)], amount: f32) -> Vec<(f32, f32)> {
-    if verts.len() < 3 {
-        return verts.to_vec();
-    }
-
-    // Calculate centroid
-    let (cx, cy) = verts.iter().fold((0.0, 0.0), |(sx, sy), &(x, y)| (sx + x, sy + y));
-    let n = verts.len() as f32;
-    let (cx, cy) = (cx / n, cy / n);
-
-    // Move each vertex away from centroid
-    verts
-        .iter()
-        .map(|&(x, y)| {
-            let dx = x - cx;
-            let dy = y - cy;
-            let len = (dx * dx + dy * dy).sqrt();
-            if len > 0.001 {
-                (x + dx / len * amount, y + dy / len * amount)
-            } else {
-                (x, y)
-            }
-        })
+/// Normalize an effect name for matching commands: lowercase, alphanumerics only
+fn normalize_effect_name(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
         .collect()
 }
 
 /// Create a scene with virtual chyron regions added for effect bouncing
-/// The chyron regions are horizontal strips at top and bottom of screen
-fn scene_with_chyron_regions(base_scene: &Scene, width: u32, height: u32) -> Scene {
+/// (one per strip that avoids effects, named after the strip)
+fn scene_with_chyron_regions(base_scene: &Scene, chyrons: &[ChyronStrip]) -> Scene {
     let mut scene = base_scene.clone();
-
-    // Calculate chyron strip height (same formula as rendering)
-    let strip_height = (height as f32 * 0.126) as f32;
-    let w = width as f32;
-    let h = height as f32;
-
-    // Top chyron region: rectangle from (0,0) to (width, strip_height)
-    let top_poly = Polygon::from_vertices(vec![
-        Point::new(0.0, 0.0),
-        Point::new(w, 0.0),
-        Point::new(w, strip_height),
-        Point::new(0.0, strip_height),
-    ]);
-    scene.add_region(Region::new("chyron_top", top_poly));
-
-    // Bottom chyron region: rectangle from (0, height-strip_height) to (width, height)
-    let bottom_y = h - strip_height;
-    let bottom_poly = Polygon::from_vertices(vec![
-        Point::new(0.0, bottom_y),
-        Point::new(w, bottom_y),
-        Point::new(w, h),
-        Point::new(0.0, h),
-    ]);
-    scene.add_region(Region::new("chyron_bottom", bottom_poly));
-
+    for chyron in chyrons.iter().filter(|c| c.layout.avoid) {
+        let name = format!("chyron_{}", chyron.layout.name);
+        scene.add_region(Region::new(name, chyron.rect.polygon()));
+    }
     scene
 }
 
-/// Display rotation for portrait/landscape modes
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum Rotation {
-    None,      // 0°
-    Cw90,      // 90° clockwise (portrait)
-    Cw180,     // 180° (upside down)
-    Cw270,     // 270° clockwise / 90° counter-clockwise (portrait)
+/// Drop untouched warps, then give output `name` a corner pin or mesh to
+/// edit if keystone or mesh mode is on
+fn start_warp_edit(scene: &mut Scene, name: &str, keystone_mode: bool, mesh_mode: bool) {
+    scene.drop_identity_outputs();
+    if keystone_mode {
+        scene.output_mut(name).corner_pin.get_or_insert_with(CornerPin::default);
+    }
+    if mesh_mode {
+        scene.output_mut(name).warp_mesh.get_or_insert_with(|| WarpMesh::new(4, 4));
+    }
 }
 
-/// Transform window coordinates to content coordinates based on rotation
-/// This is the inverse of the rotation applied during rendering
-fn transform_mouse(wx: i32, wy: i32, rotation: Rotation, content_w: u32, content_h: u32) -> (i32, i32) {
-    match rotation {
-        Rotation::None => (wx, wy),
-        Rotation::Cw90 => {
-            // Rotation: content(x,y) -> window(h-1-y, x) where h=content_h
-            // Inverse: window(wx,wy) -> content(wy, h-1-wx)
-            (wy, content_h as i32 - 1 - wx)
-        }
-        Rotation::Cw180 => {
-            (content_w as i32 - 1 - wx, content_h as i32 - 1 - wy)
-        }
-        Rotation::Cw270 => {
-            // Rotation: content(x,y) -> window(y, w-1-x) where w=content_w
-            // Inverse: window(wx,wy) -> content(w-1-wy, wx)
-            (content_w as i32 - 1 - wy, wx)
-        }
+/// Mouse position of an event, if it has one
+fn mouse_position(event: &InputEvent) -> Option<(i32, i32)> {
+    match *event {
+        InputEvent::MouseMove { x, y }
+        | InputEvent::MouseDown { x, y, .. }
+        | InputEvent::MouseUp { x, y, .. } => Some((x, y)),
+        _ => None,
     }
 }
 
-/// Transform mouse movement delta from window space to content space
-/// This makes mouse movement feel natural when display is rotated
-fn transform_mouse_delta(dx: i32, dy: i32, rotation: Rotation) -> (i32, i32) {
-    match rotation {
-        Rotation::None => (dx, dy),
-        Rotation::Cw90 => {
-            // Content is rotated 90° CW on display
-            // Physical right (window +X) should move cursor right in content (+X)
-            // But window +X is content -Y after rotation, so we need to transform:
-            // content delta = (dy, -dx)
-            (dy, -dx)
-        }
-        Rotation::Cw180 => {
-            // Content is upside down
-            // Physical right (+X) should move cursor right (+X in content = -X in window)
-            (-dx, -dy)
-        }
-        Rotation::Cw270 => {
-            // Content is rotated 270° CW (90° CCW)
-            // Physical right (window +X) is content +Y, physical down is content -X
-            (-dy, dx)
-        }
+/// Move a mouse event's position through `map`, leaving other events alone
+fn map_mouse_event(event: &InputEvent, map: impl Fn(i32, i32) -> (i32, i32)) -> InputEvent {
+    match *event {
+        InputEvent::MouseMove { x, y } => {
+            let (x, y) = map(x, y);
+            InputEvent::MouseMove { x, y }
+        },
+        InputEvent::MouseDown { x, y, button } => {
+            let (x, y) = map(x, y);
+            InputEvent::MouseDown { x, y, button }
+        },
+        InputEvent::MouseUp { x, y, button } => {
+            let (x, y) = map(x, y);
+            InputEvent::MouseUp { x, y, button }
+        },
+        _ => event.clone(),
     }
 }
 
+/// Map a mouse event from the desktop into an output's frame, undoing its
+/// rotation and flip
+fn output_mouse_event(event: &InputEvent, projector: &Projector) -> InputEvent {
+    let (ox, oy) = projector.config().window().position.unwrap_or((0, 0));
+    let (width, height) = projector.size();
+    let orientation = projector.config().orientation();
+    map_mouse_event(event, |x, y| {
+        orientation.transform_mouse(x - ox, y - oy, width, height)
+    })
+}
+
+/// Map a mouse event from an output's frame to the canvas through its warp
+fn canvas_mouse_event(event: &InputEvent, projector: &Projector) -> InputEvent {
+    let [sx, sy, ..] = projector.config().source;
+    map_mouse_event(event, |x, y| {
+        projector
+            .to_canvas(x as f32 + 0.5, y as f32 + 0.5)
+            .map_or((x + sx as i32, y + sy as i32), |(cx, cy)| {
+                (cx.floor() as i32, cy.floor() as i32)
+            })
+    })
+}
+
 /// Apply mouse acceleration curve
 /// Small movements stay linear, fast movements get amplified
 fn apply_mouse_acceleration(dx: i32, dy: i32) -> (i32, i32) {
@@ -284,10 +297,23 @@ struct AppOptions {
     vsync: bool,
     start_effect: Option<usize>,
     rotation: Rotation,
+    flip: Flip,
     benchmark_seconds: Option<f32>,
     scene_file: Option<String>,
     mqtt_host: String,
     mqtt_topic: String,
+    mqtt_qos: u8,
+    mqtt_ca: Option<String>,
+    mqtt_cert: Option<String>,
+    mqtt_key: Option<String>,
+    dmx: bool,
+    dmx_universe: u16,
+    dmx_address: u16,
+    chyron_presets: Vec<String>,
+    led_map: Option<String>,
+    config_file: Option<String>,
+    fonts: Vec<String>,
+    ansi_dir: Option<String>,
 }
 
 /// Parse command line arguments
@@ -299,10 +325,23 @@ fn parse_args() -> AppOptions {
         vsync: true,
         start_effect: Some(0),
         rotation: Rotation::None,
+        flip: Flip::None,
         benchmark_seconds: None,
         scene_file: None,
         mqtt_host: MqttClient::default_host().to_string(),
         mqtt_topic: MqttClient::default_topic().to_string(),
+        mqtt_qos: 0,
+        mqtt_ca: None,
+        mqtt_cert: None,
+        mqtt_key: None,
+        dmx: false,
+        dmx_universe: 1,
+        dmx_address: 1,
+        chyron_presets: Vec::new(),
+        led_map: None,
+        config_file: None,
+        fonts: Vec::new(),
+        ansi_dir: None,
     };
 
     let mut i = 1;
@@ -357,6 +396,16 @@ fn parse_args() -> AppOptions {
                     i += 1;
                 }
             },
+            "--flip" => {
+                if i + 1 < args.len() {
+                    opts.flip = match args[i + 1].as_str() {
+                        "h" | "horizontal" => Flip::Horizontal,
+                        "v" | "vertical" => Flip::Vertical,
+                        _ => Flip::None,
+                    };
+                    i += 1;
+                }
+            },
             "--benchmark" | "-b" => {
                 // Default to 10 seconds, or parse optional duration
                 let mut duration = 10.0;
@@ -375,7 +424,7 @@ fn parse_args() -> AppOptions {
                     i += 1;
                 }
             },
-            "--mqtt-host" => {
+            "--mqtt-host" | "--mqtt-broker" => {
                 if i + 1 < args.len() {
                     opts.mqtt_host = args[i + 1].clone();
                     i += 1;
@@ -387,6 +436,57 @@ fn parse_args() -> AppOptions {
                     i += 1;
                 }
             },
+            "--mqtt-qos" if i + 1 < args.len() => {
+                if let Ok(q) = args[i + 1].parse::<u8>() {
+                    opts.mqtt_qos = q.min(2);
+                }
+                i += 1;
+            },
+            "--mqtt-ca" if i + 1 < args.len() => {
+                opts.mqtt_ca = Some(args[i + 1].clone());
+                i += 1;
+            },
+            "--mqtt-cert" if i + 1 < args.len() => {
+                opts.mqtt_cert = Some(args[i + 1].clone());
+                i += 1;
+            },
+            "--mqtt-key" if i + 1 < args.len() => {
+                opts.mqtt_key = Some(args[i + 1].clone());
+                i += 1;
+            },
+            "--dmx" => opts.dmx = true,
+            "--dmx-universe" if i + 1 < args.len() => {
+                if let Ok(u) = args[i + 1].parse::<u16>() {
+                    opts.dmx_universe = u;
+                }
+                i += 1;
+            },
+            "--dmx-address" if i + 1 < args.len() => {
+                if let Ok(a) = args[i + 1].parse::<u16>() {
+                    opts.dmx_address = a;
+                }
+                i += 1;
+            },
+            "--chyron-preset" if i + 1 < args.len() => {
+                opts.chyron_presets.push(args[i + 1].clone());
+                i += 1;
+            },
+            "--led-map" if i + 1 < args.len() => {
+                opts.led_map = Some(args[i + 1].clone());
+                i += 1;
+            },
+            "--config" | "-c" if i + 1 < args.len() => {
+                opts.config_file = Some(args[i + 1].clone());
+                i += 1;
+            },
+            "--font" if i + 1 < args.len() => {
+                opts.fonts.push(args[i + 1].clone());
+                i += 1;
+            },
+            "--ansi-dir" if i + 1 < args.len() => {
+                opts.ansi_dir = Some(args[i + 1].clone());
+                i += 1;
+            },
             "--help" => {
                 println!("Usage: wallfacer [OPTIONS]");
                 println!();
@@ -402,17 +502,37 @@ fn parse_args() -> AppOptions {
                 println!("  --resolution WxH, -r WxH  Set resolution (e.g., 1920x1080)");
                 println!("  --effect N, -e N      Start with effect number N (0-indexed)");
                 println!("  --rotate N            Rotate display (0, 90, 180, 270)");
+                println!("  --flip h|v            Mirror display after rotating (rear projection)");
                 println!("  --benchmark [S], -b   Run benchmark for S seconds (default: 10)");
                 println!("  --scene FILE, -s      Load scene/regions from FILE");
                 println!("  --no-vsync            Disable VSync for uncapped framerate");
                 println!(
-                    "  --mqtt-host HOST      MQTT broker address (default: {})",
+                    "  --mqtt-host URL       MQTT broker (default: {})",
                     MqttClient::default_host()
                 );
+                println!("                        HOST[:PORT] or mqtt[s]://[USER:PASS@]HOST[:PORT]");
                 println!(
                     "  --mqtt-topic TOPIC    MQTT topic for chyron (default: {})",
                     MqttClient::default_topic()
                 );
+                println!("  --mqtt-qos N          MQTT QoS for subscriptions and state (0-2, default: 0)");
+                println!("  --mqtt-ca FILE        CA certificate (PEM) for mqtts:// brokers");
+                println!("  --mqtt-cert FILE      Client certificate (PEM) for mutual TLS");
+                println!("  --mqtt-key FILE       Client private key (PEM) for mutual TLS");
+                println!("  --dmx                 Listen for Art-Net/sACN DMX fixture control");
+                println!("  --dmx-universe N      DMX universe for the fixture (default: 1)");
+                println!("  --dmx-address N       DMX start address for the fixture (default: 1)");
+                println!("  --chyron-preset TEXT  Add a chyron preset selectable over DMX (repeatable)");
+                println!("  --led-map FILE        Drive pixel-mapped LEDs described by FILE");
+                println!(
+                    "  --config FILE, -c     Load settings and chyron styles (default: {})",
+                    config::DEFAULT_CONFIG
+                );
+                println!("  --font FILE           Add a BDF/PSF/TTF/OTF font ahead of the built-in one (repeatable)");
+                println!(
+                    "  --ansi-dir DIR        Directory of .ans files for the ANSI art slideshow (default: {})",
+                    effects::DEFAULT_ANSI_DIR
+                );
                 println!("  --help                Show this help message");
                 std::process::exit(0);
             },
@@ -431,21 +551,60 @@ fn main() -> Result<(), String> {
     let vsync = opts.vsync;
     let start_effect = opts.start_effect;
     let rotation = opts.rotation;
+    let orientation = Orientation::new(rotation, opts.flip);
     let benchmark_seconds = opts.benchmark_seconds;
     let scene_file = opts.scene_file;
-    let mqtt_host = opts.mqtt_host;
-    let mqtt_topic = opts.mqtt_topic;
+    let chyron_presets = opts.chyron_presets;
+
+    // Config file: an explicit --config must load, the default is optional
+    let config = match opts.config_file.as_deref() {
+        Some(path) => Config::load(path).map_err(|e| format!("Config '{}': {}", path, e))?,
+        None if std::path::Path::new(config::DEFAULT_CONFIG).exists() => {
+            Config::load(config::DEFAULT_CONFIG).unwrap_or_else(|e| {
+                eprintln!("Warning: Failed to load '{}': {}", config::DEFAULT_CONFIG, e);
+                Config::default()
+            })
+        },
+        None => Config::default(),
+    };
 
-    // For 90/270 rotation, the window dimensions are swapped
-    let (window_w, window_h) = match rotation {
-        Rotation::Cw90 | Rotation::Cw270 => (height, width),
-        _ => (width, height),
+    // Fonts: --font replaces the config list; the built-in font is always the last fallback
+    let fonts: Vec<std::path::PathBuf> = if opts.fonts.is_empty() {
+        config.fonts.clone()
+    } else {
+        opts.fonts.iter().map(Into::into).collect()
     };
+    if !fonts.is_empty() {
+        set_default_font(Font::load_chain(&fonts)?);
+    }
 
-    let (mut display, texture_creator) =
-        Display::with_options("wallfacer", window_w, window_h, vsync)?;
-    let mut target = RenderTarget::with_size(&texture_creator, window_w, window_h)?;
-    // Effects render at original dimensions, then we rotate for display
+    // ANSI art slideshow directory: --ansi-dir, then the config file
+    let ansi_dir = opts
+        .ansi_dir
+        .clone()
+        .map(std::path::PathBuf::from)
+        .or_else(|| config.ansi_dir.clone())
+        .unwrap_or_else(|| effects::DEFAULT_ANSI_DIR.into());
+
+    // One window per output, each showing part of the canvas. Without any in
+    // the config, one window shows all of it, turned by --rotate and --flip
+    // (for 90/270 rotation the window dimensions are swapped).
+    let output_configs = if config.outputs.is_empty() {
+        vec![OutputConfig::whole(width, height, orientation)]
+    } else {
+        config.outputs.clone()
+    };
+    let windows: Vec<_> = output_configs.iter().map(OutputConfig::window).collect();
+    let (window_w, window_h) = (windows[0].width, windows[0].height);
+
+    let (mut display, texture_creators) = Display::with_windows("wallfacer", &windows, vsync)?;
+    let mut targets = texture_creators
+        .iter()
+        .zip(&windows)
+        .map(|(creator, window)| RenderTarget::with_size(creator, window.width, window.height))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut projectors: Vec<Projector> = output_configs.into_iter().map(Projector::new).collect();
+    // Effects render at original dimensions, then each output takes its part
     let mut buffer = PixelBuffer::with_size(width, height);
 
     // FPS counter - use larger window for benchmarks to capture all frames
@@ -453,6 +612,7 @@ fn main() -> Result<(), String> {
     let mut fps_counter = FpsCounter::new(sample_count);
     let mut show_fps = false;
     let mut region_glow = false; // G to enable, H to disable
+    let mut glow_cache = GlowCache::default();
     let mut total_elapsed = 0.0f32;
 
     // Load scene or create new
@@ -496,6 +656,8 @@ fn main() -> Result<(), String> {
         Box::new(GravityBalls::new()), // Gravity Balls
         Box::new(LavaRegions::new()),  // Lava Regions
         Box::new(Vortex::new()),       // Vortex
+        Box::new(AnsiViewer::new(ansi_dir)), // ANSI Art
+        Box::new(VectorText::new()),   // Vector Text
     ];
     // Test pattern shown for any unassigned slot
     let mut test_pattern = TestPattern::new();
@@ -505,6 +667,15 @@ fn main() -> Result<(), String> {
 
     // Calibration mode
     let mut calibration = CalibrationMode::new(scene);
+    // Keystone correction: K in calibration drags the output corners,
+    // M edits the warp mesh, both for the active output (last clicked, or O)
+    let mut active_output = 0;
+    let mut keystone = KeystoneEditor::new();
+    let mut keystone_mode = false;
+    let mut mesh_editor = MeshEditor::new();
+    let mut mesh_mode = false;
+    // Marquees and other content attached to regions in the scene
+    let mut region_content = ContentLayer::new();
     let mut mode = AppMode::Effect;
 
     // Cursor auto-hide after 60 seconds of no mouse movement
@@ -531,61 +702,97 @@ fn main() -> Result<(), String> {
         eprintln!("Control socket: {}", Controller::socket_path());
     }
 
-    // MQTT client for chyron messages (optional - runs without if broker unavailable)
-    let mqtt_client = match MqttClient::new(&mqtt_host, &mqtt_topic) {
+    // MQTT client for chyron messages (connects in the background, retrying
+    // until the broker is reachable; only bad configuration disables it)
+    let mqtt_config =
+        MqttConfig::new(&opts.mqtt_host, &opts.mqtt_topic).map(|config| MqttConfig {
+            qos: opts.mqtt_qos,
+            ca_cert: opts.mqtt_ca.clone().map(Into::into),
+            client_cert: opts.mqtt_cert.clone().map(Into::into),
+            client_key: opts.mqtt_key.clone().map(Into::into),
+            ..config
+        });
+    let mqtt_client = match mqtt_config.and_then(|config| MqttClient::new(&config)) {
         Ok(client) => Some(client),
         Err(e) => {
-            eprintln!("MQTT: Not connected ({}) - running without chyron updates", e);
+            eprintln!("MQTT: Disabled ({}) - running without chyron updates", e);
             None
         }
     };
 
-    // Default chyron text
-    const DEFAULT_CHYRON: &str = "2389 RESEARCH LLC";
+    // DMX fixture input (optional - lighting desk control over Art-Net/sACN)
+    let dmx_input = if opts.dmx {
+        let dmx_options = DmxOptions {
+            profile: FixtureProfile::new(opts.dmx_universe, opts.dmx_address),
+            ..DmxOptions::default()
+        };
+        match DmxInput::new(&dmx_options) {
+            Ok(input) => {
+                eprintln!(
+                    "DMX: Listening on universe {} at address {}",
+                    dmx_options.profile.universe, dmx_options.profile.address
+                );
+                Some(input)
+            },
+            Err(e) => {
+                eprintln!("DMX: Not listening ({})", e);
+                None
+            },
+        }
+    } else {
+        None
+    };
 
-    // Helper to create chyron scrollers with consistent styling
-    let create_chyrons = |text: &str, w: u32, h: u32| -> (StyledScroller, StyledScroller) {
-        // Scale everything relative to buffer size (reference: 640x480, reduced 40%)
-        let scale = (h / 100).max(1);
-        let speed = w as f32 * 0.1;
-        let orbital_radius = h as f32 * 0.02;
-
-        let mut top = StyledScroller::new(text)
-            .direction(ScrollDirection::Leftward)
-            .speed(speed)
-            .scale(scale)
-            .offset(OffsetEffect::Circle {
-                radius: orbital_radius,
-                speed: 3.0,
-            })
-            .color_fx(ColorEffect::Gradient {
-                start: (255, 100, 255),
-                end: (100, 255, 255),
-            });
-        top.set_screen_width(w);
-
-        let mut bottom = StyledScroller::new(text)
-            .direction(ScrollDirection::Rightward)
-            .speed(speed)
-            .scale(scale)
-            .offset(OffsetEffect::Circle {
-                radius: orbital_radius,
-                speed: 3.0,
-            })
-            .color_fx(ColorEffect::Gradient {
-                start: (255, 100, 255),
-                end: (100, 255, 255),
-            });
-        bottom.set_screen_width(w);
+    // Pixel-mapped LED output (optional)
+    let mut led_output = opts.led_map.as_deref().and_then(|path| match LedOutput::load(path) {
+        Ok(output) => {
+            eprintln!("LED: {} LEDs mapped from {}", output.led_count(), path);
+            Some(output)
+        },
+        Err(e) => {
+            eprintln!("LED: Failed to load mapping '{}': {}", path, e);
+            None
+        },
+    });
 
-        (top, bottom)
-    };
+    // Master controls, driven by the DMX fixture or `param` commands
+    let mut master_dimmer = 1.0f32;
+    let mut speed_scale = 1.0f32;
+    let mut hue_shift = 0.0f32;
+    let mut strobe_hz = 0.0f32;
+    // Last values applied from the desk, so keyboard/socket changes aren't overridden every frame
+    let mut dmx_effect: Option<usize> = None;
+    let mut dmx_chyron: Option<usize> = None;
 
-    // Initialize chyrons with default text
-    let (mut chyron_top, mut chyron_bottom) = create_chyrons(DEFAULT_CHYRON, width, height);
+    // Default chyron text
+    const DEFAULT_CHYRON: &str = "2389 RESEARCH LLC";
 
-    // Track override message expiry (None = showing default)
-    let mut chyron_override_expires: Option<f32> = None;
+    // Chyron style presets (built-ins plus any from the config file)
+    let chyron_styles = StyleSheet::new(&config.chyron_styles);
+    let default_style = chyron_styles.resolve(None, &ChyronStyle::default());
+
+    // Chyron strips come from the scene, else the config, else top and bottom.
+    // They're laid out every frame since the scene can change in calibration.
+    let config_strips = config
+        .chyron_strips
+        .unwrap_or_else(StripLayout::defaults);
+    let mut chyrons: Vec<ChyronStrip> = Vec::new();
+
+    // Clock, countdown, QR code and other widgets over the effect
+    let mut overlay = Overlay::new(&config.overlays);
+
+    // Queued messages rotate on the strips; the base text shows when none are active
+    let mut chyron_queue = ChyronManager::new();
+    let feed_reader = FeedReader::start(&config.feeds);
+    let mut base_chyron = DEFAULT_CHYRON.to_string();
+
+    // Announce to Home Assistant; the test pattern is selectable like any effect
+    if let Some(ref client) = mqtt_client {
+        let mut names: Vec<String> = effects.iter().map(|e| e.name().to_string()).collect();
+        names.push(test_pattern.name().to_string());
+        client.publish_discovery(&names);
+    }
+    let mut last_state_publish = f32::NEG_INFINITY;
 
     // Get effect name for benchmark output
     let effect_name = match current_effect {
@@ -602,12 +809,18 @@ fn main() -> Result<(), String> {
     } else {
         println!("=== wallfacer ===");
         println!("Resolution: {}x{}", width, height);
-        if rotation != Rotation::None {
+        if !orientation.is_identity() {
             println!(
-                "Rotation: {:?} (window: {}x{})",
-                rotation, window_w, window_h
+                "Rotation: {:?}, flip: {:?} (window: {}x{})",
+                orientation.rotation, orientation.flip, window_w, window_h
             );
         }
+        if projectors.len() > 1 {
+            for projector in &projectors {
+                let [x, y, w, h] = projector.config().source;
+                println!("Output '{}': {}x{} at {},{}", projector.name(), w, h, x, y);
+            }
+        }
         if vsync {
             println!("VSync: ON (60fps locked). Use --no-vsync for uncapped.");
         } else {
@@ -628,12 +841,24 @@ fn main() -> Result<(), String> {
         println!("  Escape     - Quit");
         println!();
         println!("Calibration mode:");
-        println!("  Left click        - Select region / start drawing polygon");
+        println!("  Left click        - Select region / start drawing with the current tool");
+        println!("  T                 - Next tool: polygon, rectangle, ellipse, rounded");
+        println!("                      rectangle, ring, spline path, bezier path");
         println!("  Shift + drag      - Draw circle (drag to set radius)");
-        println!("  Click + drag      - Move vertices / resize circle");
-        println!("  Close polygon     - Click near first vertex");
+        println!("  Click + drag      - Move vertices / handles / resize circle");
+        println!("  Close polygon     - Click near first vertex (paths too)");
         println!("  Right click       - Cancel / deselect");
         println!("  Delete            - Delete selected region");
+        println!("  C                 - Mark selected region to combine with, then select");
+        println!("                      another and press U (union), I (intersection),");
+        println!("                      D (difference: marked minus selected) or X (xor)");
+        println!("  K                 - Keystone: drag output corners (arrows nudge,");
+        println!("                      Enter next corner, right click/Delete reset)");
+        println!("  O                 - Next output to keystone / mesh warp");
+        println!("  M                 - Mesh warp: drag grid points (shift+click or drag");
+        println!("                      a box to select several, A all, arrows nudge,");
+        println!("                      Enter next point, = subdivide, B bilinear/bicubic,");
+        println!("                      right click/Delete reset)");
     }
 
     'main: loop {
@@ -702,6 +927,11 @@ fn main() -> Result<(), String> {
                         } else {
                             AppMode::Effect
                         };
+                        if mode == AppMode::Effect && (keystone_mode || mesh_mode) {
+                            keystone_mode = false;
+                            mesh_mode = false;
+                            calibration.scene_mut().drop_identity_outputs();
+                        }
                         continue;
                     },
                     Keycode::S => {
@@ -736,12 +966,145 @@ fn main() -> Result<(), String> {
                         eprintln!("Region glow: OFF");
                         continue;
                     },
+                    Keycode::K if mode == AppMode::Calibration => {
+                        keystone_mode = !keystone_mode;
+                        mesh_mode = false;
+                        let name = projectors[active_output].name();
+                        start_warp_edit(calibration.scene_mut(), name, keystone_mode, mesh_mode);
+                        eprintln!("Keystone: {}", if keystone_mode { "ON" } else { "OFF" });
+                        continue;
+                    },
+                    Keycode::M if mode == AppMode::Calibration => {
+                        mesh_mode = !mesh_mode;
+                        keystone_mode = false;
+                        mesh_editor.reset();
+                        let name = projectors[active_output].name();
+                        start_warp_edit(calibration.scene_mut(), name, keystone_mode, mesh_mode);
+                        eprintln!("Mesh warp: {}", if mesh_mode { "ON" } else { "OFF" });
+                        continue;
+                    },
+                    Keycode::T if mode == AppMode::Calibration && !keystone_mode && !mesh_mode => {
+                        eprintln!("Drawing tool: {}", calibration.next_tool().name());
+                        continue;
+                    },
+                    Keycode::C if mode == AppMode::Calibration && !keystone_mode && !mesh_mode => {
+                        match calibration.mark_operand() {
+                            Some(name) => eprintln!("Combine: '{}' marked", name),
+                            None => eprintln!("Combine: select a region first"),
+                        }
+                        continue;
+                    },
+                    Keycode::U | Keycode::I | Keycode::D | Keycode::X
+                        if mode == AppMode::Calibration && !keystone_mode && !mesh_mode =>
+                    {
+                        let op = match *key {
+                            Keycode::U => BoolOp::Union,
+                            Keycode::I => BoolOp::Intersection,
+                            Keycode::D => BoolOp::Difference,
+                            _ => BoolOp::Xor,
+                        };
+                        match calibration.combine_with_operand(op) {
+                            Ok(names) => {
+                                eprintln!("Combine: {} -> {}", op.name(), names.join(", "));
+                            },
+                            Err(e) => eprintln!("Combine: {}", e),
+                        }
+                        continue;
+                    },
+                    Keycode::O if mode == AppMode::Calibration && projectors.len() > 1 => {
+                        active_output = (active_output + 1) % projectors.len();
+                        mesh_editor.reset();
+                        let name = projectors[active_output].name();
+                        start_warp_edit(calibration.scene_mut(), name, keystone_mode, mesh_mode);
+                        eprintln!("Editing output '{}'", name);
+                        continue;
+                    },
+                    Keycode::Equals | Keycode::KpPlus
+                        if mode == AppMode::Calibration && mesh_mode =>
+                    {
+                        let name = projectors[active_output].name();
+                        let warp = calibration.scene_mut().output_mut(name);
+                        if let Some(mesh) = warp.warp_mesh.as_mut() {
+                            if mesh_editor.subdivide(mesh) {
+                                eprintln!("Mesh warp: {}x{} points", mesh.columns, mesh.rows);
+                            }
+                        }
+                        continue;
+                    },
+                    Keycode::B if mode == AppMode::Calibration && mesh_mode => {
+                        let name = projectors[active_output].name();
+                        let warp = calibration.scene_mut().output_mut(name);
+                        if let Some(mesh) = warp.warp_mesh.as_mut() {
+                            mesh.interpolation = match mesh.interpolation {
+                                Interpolation::Bilinear => Interpolation::Bicubic,
+                                Interpolation::Bicubic => Interpolation::Bilinear,
+                            };
+                            eprintln!("Mesh warp: {:?}", mesh.interpolation);
+                        }
+                        continue;
+                    },
+                    Keycode::A if mode == AppMode::Calibration && mesh_mode => {
+                        let warp = calibration.scene().output(projectors[active_output].name());
+                        if let Some(mesh) = warp.and_then(|warp| warp.warp_mesh.as_ref()) {
+                            mesh_editor.select_all(mesh);
+                        }
+                        continue;
+                    },
                     Keycode::Delete | Keycode::Backspace => {
-                        if mode == AppMode::Calibration {
+                        let name = projectors[active_output].name();
+                        if mode == AppMode::Calibration && keystone_mode {
+                            calibration.scene_mut().output_mut(name).corner_pin =
+                                Some(CornerPin::default());
+                        } else if mode == AppMode::Calibration && mesh_mode {
+                            let warp = calibration.scene_mut().output_mut(name);
+                            if let Some(mesh) = warp.warp_mesh.as_mut() {
+                                mesh.points = WarpMesh::new(mesh.columns, mesh.rows).points;
+                            }
+                        } else if mode == AppMode::Calibration {
                             calibration.delete_selected();
                         }
                         continue;
                     },
+                    Keycode::Left | Keycode::Right | Keycode::Up | Keycode::Down
+                        if mode == AppMode::Calibration && (keystone_mode || mesh_mode) =>
+                    {
+                        let step = if shift_held { 10 } else { 1 };
+                        let (dx, dy) = match *key {
+                            Keycode::Left => (-step, 0),
+                            Keycode::Right => (step, 0),
+                            Keycode::Up => (0, -step),
+                            _ => (0, step),
+                        };
+                        // Arrows move the corner the way they point on the wall
+                        let projector = &projectors[active_output];
+                        let orientation = projector.config().orientation();
+                        let (dx, dy) = orientation.transform_mouse_delta(dx, dy);
+                        let (w, h) = projector.size();
+                        let warp = calibration.scene_mut().output_mut(projector.name());
+                        if mesh_mode {
+                            if let Some(mesh) = warp.warp_mesh.as_mut() {
+                                mesh_editor.nudge(mesh, dx, dy, w, h);
+                            }
+                        } else if let Some(pin) = warp.corner_pin.as_mut() {
+                            keystone.nudge(pin, dx, dy, w, h);
+                        }
+                        continue;
+                    },
+                    Keycode::Return | Keycode::KpEnter
+                        if mode == AppMode::Calibration && keystone_mode =>
+                    {
+                        keystone.select_next();
+                        continue;
+                    },
+                    Keycode::Return | Keycode::KpEnter
+                        if mode == AppMode::Calibration && mesh_mode =>
+                    {
+                        let warp = calibration.scene().output(projectors[active_output].name());
+                        if let Some(mesh) = warp.and_then(|warp| warp.warp_mesh.as_ref()) {
+                            mesh_editor.select_next(mesh);
+                        }
+                        continue;
+                    },
                     Keycode::LShift | Keycode::RShift => {
                         shift_held = true;
                         // Forward to calibration so it knows shift is held for circle drawing
@@ -835,9 +1198,45 @@ fn main() -> Result<(), String> {
                     }
                 }
 
-                // Pass mouse events to calibration mode (non-rotated only, rotated uses keyboard)
+                // Pass mouse events to calibration mode (non-rotated only, rotated uses keyboard).
+                // Keystone and mesh handles work on the active output; regions on the
+                // canvas, through whichever output the mouse is over.
                 if mode == AppMode::Calibration && benchmark_seconds.is_none() {
-                    calibration.handle_event(&event);
+                    let over = mouse_position(&event)
+                        .and_then(|(x, y)| projectors.iter().position(|p| p.contains(x, y)));
+                    if keystone_mode || mesh_mode {
+                        // Clicking in another output starts editing that one
+                        if let (InputEvent::MouseDown { .. }, Some(i)) = (&event, over) {
+                            if i != active_output {
+                                active_output = i;
+                                mesh_editor.reset();
+                                let name = projectors[i].name();
+                                let scene = calibration.scene_mut();
+                                start_warp_edit(scene, name, keystone_mode, mesh_mode);
+                            }
+                        }
+                        let projector = &projectors[active_output];
+                        let event = output_mouse_event(&event, projector);
+                        let (w, h) = projector.size();
+                        let warp = calibration.scene_mut().output_mut(projector.name());
+                        if mesh_mode {
+                            if let Some(mesh) = warp.warp_mesh.as_mut() {
+                                mesh_editor.handle_event(&event, mesh, shift_held, w, h);
+                            }
+                        } else if let Some(pin) = warp.corner_pin.as_mut() {
+                            keystone.handle_event(&event, pin, w, h);
+                        }
+                    } else {
+                        let event = over.map_or_else(
+                            || event.clone(),
+                            |i| {
+                                let projector = &projectors[i];
+                                let event = output_mouse_event(&event, projector);
+                                canvas_mouse_event(&event, projector)
+                            },
+                        );
+                        calibration.handle_event(&event);
+                    }
                 }
             }
         }
@@ -850,143 +1249,311 @@ fn main() -> Result<(), String> {
             }
         }
 
-        // Process remote control commands
-        if let Some(ref ctrl) = controller {
-            for cmd in ctrl.poll() {
-                match cmd {
-                    Command::Left => {
-                        current_effect = match current_effect {
-                            Some(0) => None,
-                            Some(idx) => Some(idx - 1),
-                            None => Some(effects.len() - 1),
-                        };
-                    }
-                    Command::Right => {
-                        current_effect = match current_effect {
-                            Some(idx) if idx + 1 >= effects.len() => None,
-                            Some(idx) => Some(idx + 1),
-                            None => Some(0),
-                        };
+        // Process remote control commands (control socket and MQTT command topics)
+        let mut commands = controller.as_ref().map(Controller::poll).unwrap_or_default();
+        if let Some(ref client) = mqtt_client {
+            commands.extend(client.poll_commands());
+        }
+        for cmd in commands {
+            match cmd {
+                Command::Left => {
+                    current_effect = match current_effect {
+                        Some(0) => None,
+                        Some(idx) => Some(idx - 1),
+                        None => Some(effects.len() - 1),
+                    };
+                }
+                Command::Right => {
+                    current_effect = match current_effect {
+                        Some(idx) if idx + 1 >= effects.len() => None,
+                        Some(idx) => Some(idx + 1),
+                        None => Some(0),
+                    };
+                }
+                Command::Tab => {
+                    mode = if mode == AppMode::Effect {
+                        AppMode::Calibration
+                    } else {
+                        AppMode::Effect
+                    };
+                }
+                Command::ToggleFps => {
+                    show_fps = !show_fps;
+                }
+                Command::Save => {
+                    if let Err(e) = calibration.scene().save("scene.json") {
+                        eprintln!("Failed to save: {}", e);
+                    } else {
+                        eprintln!("Scene saved to scene.json");
                     }
-                    Command::Tab => {
-                        mode = if mode == AppMode::Effect {
-                            AppMode::Calibration
-                        } else {
-                            AppMode::Effect
-                        };
+                }
+                Command::Load => {
+                    match Scene::load("scene.json") {
+                        Ok(scene) => {
+                            calibration = CalibrationMode::new(scene);
+                            eprintln!("Scene loaded from scene.json");
+                        }
+                        Err(e) => eprintln!("Failed to load: {}", e),
                     }
-                    Command::ToggleFps => {
-                        show_fps = !show_fps;
+                }
+                Command::Quit => {
+                    break 'main;
+                }
+                Command::Effect(n) => {
+                    if n < effects.len() {
+                        current_effect = Some(n);
                     }
-                    Command::Save => {
-                        if let Err(e) = calibration.scene().save("scene.json") {
-                            eprintln!("Failed to save: {}", e);
-                        } else {
-                            eprintln!("Scene saved to scene.json");
+                }
+                Command::EffectName(name) => {
+                    let wanted = normalize_effect_name(&name);
+                    match effects
+                        .iter()
+                        .position(|e| normalize_effect_name(e.name()) == wanted)
+                    {
+                        Some(idx) => current_effect = Some(idx),
+                        None if wanted == normalize_effect_name(test_pattern.name()) => {
+                            current_effect = None;
                         }
+                        None => eprintln!("Unknown effect: '{}'", name),
                     }
-                    Command::Load => {
-                        match Scene::load("scene.json") {
-                            Ok(scene) => {
-                                calibration = CalibrationMode::new(scene);
-                                eprintln!("Scene loaded from scene.json");
-                            }
-                            Err(e) => eprintln!("Failed to load: {}", e),
-                        }
+                }
+                Command::Param { name, value } => match name.as_str() {
+                    "speed" => speed_scale = value.clamp(0.0, 4.0),
+                    "dimmer" => master_dimmer = value.clamp(0.0, 1.0),
+                    "hue" => hue_shift = value.rem_euclid(360.0),
+                    "strobe" => strobe_hz = value.clamp(0.0, 20.0),
+                    "glow" => region_glow = value >= 0.5,
+                    _ => eprintln!("Unknown parameter: '{}'", name),
+                },
+                Command::LoadScene(path) => match Scene::load(&path) {
+                    Ok(scene) => {
+                        calibration = CalibrationMode::new(scene);
+                        eprintln!("Scene loaded from {}", path);
                     }
-                    Command::Quit => {
-                        break 'main;
+                    Err(e) => eprintln!("Failed to load {}: {}", path, e),
+                },
+                Command::Chyron(msg) => {
+                    eprintln!("Chyron queued: '{}' for {}s", msg.text, msg.ttl);
+                    chyron_queue.push(msg, total_elapsed);
+                }
+                Command::RegionContent { region, content } => {
+                    match calibration.scene_mut().regions.iter_mut().find(|r| r.name == region) {
+                        Some(r) => r.content = content,
+                        None => eprintln!("Unknown region: '{}'", region),
                     }
-                    Command::Effect(n) => {
-                        if n < effects.len() {
-                            current_effect = Some(n);
+                }
+                Command::Overlay { id, widget } => match widget {
+                    Some(widget) => overlay.set(widget),
+                    None => {
+                        if !overlay.remove(&id) {
+                            eprintln!("Unknown overlay: '{}'", id);
                         }
                     }
+                },
+                Command::OverlayEnabled { id, enabled } => {
+                    if !overlay.set_enabled(&id, enabled) {
+                        eprintln!("Unknown overlay: '{}'", id);
+                    }
+                }
+                Command::Color { output, adjust } => {
+                    // A bare `color` command goes to the only output, whatever it's called
+                    let name = match projectors.as_slice() {
+                        [only] if output.is_empty() => only.name().to_string(),
+                        _ => output,
+                    };
+                    if projectors.iter().any(|p| p.name() == name) {
+                        adjust.apply(&mut calibration.scene_mut().output_mut(&name).color);
+                    } else {
+                        eprintln!("Unknown output: '{}'", name);
+                    }
                 }
             }
         }
 
         // Poll MQTT for chyron messages (if connected)
         if let Some(ref client) = mqtt_client {
-            if let Some(msg) = client.poll() {
-                // Set override text with expiry time
-                let (top, bottom) = create_chyrons(&msg.text, width, height);
-                chyron_top = top;
-                chyron_bottom = bottom;
-                chyron_override_expires = Some(total_elapsed + msg.ttl);
-                eprintln!("Chyron override: '{}' for {}s", msg.text, msg.ttl);
+            for msg in client.poll() {
+                chyron_queue.push(msg, total_elapsed);
+            }
+        }
+
+        // Queue items from local feeds (refreshed in the background)
+        if let Some(ref reader) = feed_reader {
+            for msg in reader.poll() {
+                chyron_queue.push(msg, total_elapsed);
+            }
+        }
+
+        // Apply DMX fixture state (if listening)
+        if let Some(state) = dmx_input.as_ref().and_then(DmxInput::poll) {
+            master_dimmer = state.dimmer;
+            speed_scale = state.speed;
+            hue_shift = state.hue_shift;
+            strobe_hz = state.strobe_hz;
+
+            if state.effect != dmx_effect {
+                dmx_effect = state.effect;
+                if let Some(idx) = state.effect {
+                    if idx < effects.len() {
+                        current_effect = Some(idx);
+                    }
+                }
+            }
+
+            if state.chyron_preset != dmx_chyron {
+                dmx_chyron = state.chyron_preset;
+                // Presets replace the base text; queued messages still take precedence
+                base_chyron = state
+                    .chyron_preset
+                    .and_then(|idx| chyron_presets.get(idx))
+                    .map_or(DEFAULT_CHYRON, String::as_str)
+                    .to_string();
+                eprintln!("Chyron preset: '{}'", base_chyron);
+            }
+        }
+
+        // Place the strips, keeping each scroller unless its strip changed
+        let scene = calibration.scene();
+        let layout = scene.chyron_strips.as_ref().unwrap_or(&config_strips);
+        let placed: Vec<(&StripLayout, StripRect)> = layout
+            .iter()
+            .filter_map(|strip| strip.place(width, height, scene).map(|rect| (strip, rect)))
+            .collect();
+        let changed = placed.len() != chyrons.len()
+            || placed
+                .iter()
+                .zip(&chyrons)
+                .any(|((strip, rect), chyron)| **strip != chyron.layout || *rect != chyron.rect);
+        if changed {
+            chyrons = placed
+                .into_iter()
+                .map(|(strip, rect)| {
+                    ChyronStrip::new(strip.clone(), rect, &default_style, &base_chyron)
+                })
+                .collect();
+            let names: Vec<String> = chyrons.iter().map(|c| c.layout.name.clone()).collect();
+            chyron_queue.set_strips(&names);
+        }
+
+        // Show the current queued message on each strip, or the base text.
+        // Scrollers are only rebuilt when what they should show changes.
+        chyron_queue.update(total_elapsed);
+        for (index, chyron) in chyrons.iter_mut().enumerate() {
+            let current = chyron_queue.current(index);
+            let id = current.map(|(id, _)| id);
+            if id != chyron.shown || (id.is_none() && chyron.scroller.text() != base_chyron) {
+                match current {
+                    Some((_, msg)) => chyron.show(
+                        &chyron_styles.resolve(msg.style.as_deref(), &msg.overrides),
+                        &msg.text,
+                    ),
+                    None => chyron.show(&default_style, &base_chyron),
+                }
+                chyron.shown = id;
             }
         }
 
-        // Check if override has expired, revert to default
-        if let Some(expires) = chyron_override_expires {
-            if total_elapsed >= expires {
-                let (top, bottom) = create_chyrons(DEFAULT_CHYRON, width, height);
-                chyron_top = top;
-                chyron_bottom = bottom;
-                chyron_override_expires = None;
-                eprintln!("Chyron reverted to default");
+        // Publish state to MQTT once a second (only changed values are sent)
+        if let Some(ref client) = mqtt_client {
+            if total_elapsed - last_state_publish >= 1.0 {
+                last_state_publish = total_elapsed;
+                let effect = current_effect
+                    .map_or_else(|| test_pattern.name(), |idx| effects[idx].name());
+                client.publish_state(&DisplayState {
+                    effect: effect.to_string(),
+                    mode: match mode {
+                        AppMode::Effect => "effect",
+                        AppMode::Calibration => "calibration",
+                    }
+                    .to_string(),
+                    fps: avg_fps.round() as u32,
+                    chyron: chyrons
+                        .first()
+                        .map_or(base_chyron.as_str(), |c| c.scroller.text())
+                        .to_string(),
+                    dimmer: master_dimmer,
+                });
             }
         }
 
         // Update chyron positions (pause in calibration mode like effects)
+        // Each completed pass moves that strip's rotation along
         if mode == AppMode::Effect {
-            chyron_top.update(dt);
-            chyron_bottom.update(dt);
+            for (index, chyron) in chyrons.iter_mut().enumerate() {
+                let passes = chyron.scroller.passes();
+                chyron.scroller.update(dt);
+                if chyron.scroller.passes() != passes {
+                    chyron_queue.pass_complete(index, total_elapsed);
+                }
+            }
         }
 
         // Create scene with virtual chyron regions so effects bounce off them
-        let effect_scene = scene_with_chyron_regions(calibration.scene(), width, height);
+        let effect_scene = scene_with_chyron_regions(calibration.scene(), &chyrons);
 
         // Update and render current effect (or test pattern for unassigned slots)
         // Pause animation updates when in calibration mode
         // Note: Pass effect_scene so effects bounce off chyron regions
+        // DMX speed channel scales effect animation time
+        let effect_dt = dt * speed_scale;
         let region_color = match current_effect {
             Some(idx) => {
                 if mode == AppMode::Effect {
-                    effects[idx].update(dt, width, height, &effect_scene);
+                    effects[idx].update(effect_dt, width, height, &effect_scene);
                 }
                 effects[idx].render(&mut buffer);
                 effects[idx].region_color()
             },
             None => {
                 if mode == AppMode::Effect {
-                    test_pattern.update(dt, width, height, &effect_scene);
+                    test_pattern.update(effect_dt, width, height, &effect_scene);
                 }
                 test_pattern.render(&mut buffer);
                 test_pattern.region_color()
             },
         };
 
-        // Chyron dimensions scaled to buffer size (reference: 640x480, reduced 40%)
-        let strip_height = (height as f32 * 0.126) as i32;
-        let text_offset = (height as f32 * 0.025) as i32;
+        // DMX hue shift applies to the effect only, so chyron colors stay readable
+        buffer.hue_rotate(hue_shift);
 
-        // Render top chyron (scrolls right to left)
-        // Draw semi-transparent black background (alpha 200)
-        for y in 0..strip_height {
-            buffer.hline_blend(0, buffer.width() as i32 - 1, y, 0, 0, 0, 200);
+        // Chyron strips: translucent background, then the scrolling text
+        for chyron in &chyrons {
+            chyron.render(&mut buffer);
         }
-        // Render text with some vertical offset for orbital motion headroom
-        chyron_top.render(&mut buffer, text_offset);
-
-        // Render bottom chyron (scrolls left to right)
-        // Draw semi-transparent black background at bottom
-        let start_y = buffer.height() as i32 - strip_height;
-        for y in start_y..buffer.height() as i32 {
-            buffer.hline_blend(0, buffer.width() as i32 - 1, y, 0, 0, 0, 200);
-        }
-        // Render text
-        chyron_bottom.render(&mut buffer, start_y + text_offset);
+
+        // Overlay widgets follow the wall clock, so they run in calibration too
+        overlay.update();
+        overlay.render(&mut buffer);
 
         // Mask user-defined regions AFTER chyron render (so they appear on top)
         // If glow mode is enabled (G key), draw glowing effect instead
         if region_glow {
-            glow_regions(&mut buffer, calibration.scene(), total_elapsed);
+            glow_regions(&mut buffer, calibration.scene(), &mut glow_cache, total_elapsed);
         } else {
             mask_regions(&mut buffer, calibration.scene(), region_color);
         }
 
+        // Region content (marquees, text) goes on top of the masked regions.
+        // Animation pauses in calibration, but layouts still follow edits.
+        let content_dt = if mode == AppMode::Effect { dt } else { 0.0 };
+        region_content.update(content_dt, calibration.scene());
+        region_content.render(&mut buffer, calibration.scene());
+
+        // DMX master dimmer and strobe (skipped in calibration so regions stay visible)
+        if mode == AppMode::Effect {
+            let strobe_off = strobe_hz > 0.0 && (total_elapsed * strobe_hz).fract() >= 0.5;
+            if strobe_off {
+                buffer.clear(0, 0, 0);
+            } else if master_dimmer < 1.0 {
+                buffer.fade(master_dimmer);
+            }
+        }
+
+        // Sample the finished frame for the LEDs before any UI overlays
+        if let Some(ref mut led) = led_output {
+            led.update(&buffer, calibration.scene());
+        }
+
         if mode == AppMode::Calibration {
             // Dim the effect a bit more for visibility
             let pixels = buffer.as_bytes_mut();
@@ -997,6 +1564,9 @@ fn main() -> Result<(), String> {
             }
             // Overlay calibration UI
             calibration.render(&mut buffer);
+            if let Some(ref led) = led_output {
+                led.render_positions(&mut buffer);
+            }
         }
 
         // Draw keyboard cursor when in calibration mode with rotation
@@ -1008,9 +1578,13 @@ fn main() -> Result<(), String> {
         if show_fps {
             let (min_fps, max_fps) = fps_counter.min_max_fps();
             let ms = fps_counter.avg_frame_time_ms();
+            let mqtt_status = mqtt_client
+                .as_ref()
+                .map(|client| format!("  MQTT {}", client.connection_state().label()))
+                .unwrap_or_default();
             let fps_text = format!(
-                "FPS {} avg  {} min  {} max  {}ms",
-                avg_fps as u32, min_fps as u32, max_fps as u32, ms as u32
+                "FPS {} avg  {} min  {} max  {}ms{}",
+                avg_fps as u32, min_fps as u32, max_fps as u32, ms as u32, mqtt_status
             );
             // Draw at bottom of screen with shadow for visibility
             let y = buffer.height() as i32 - 12;
@@ -1018,23 +1592,32 @@ fn main() -> Result<(), String> {
             draw_text(&mut buffer, 4, y, &fps_text, 255, 255, 0);
         }
 
-        // Apply rotation and present
-        match rotation {
-            Rotation::None => {
-                display.present(&mut target, &buffer)?;
-            }
-            Rotation::Cw90 => {
-                let rotated = buffer.rotated_90();
-                display.present(&mut target, &rotated)?;
-            }
-            Rotation::Cw180 => {
-                let rotated = buffer.rotated_180();
-                display.present(&mut target, &rotated)?;
-            }
-            Rotation::Cw270 => {
-                let rotated = buffer.rotated_270();
-                display.present(&mut target, &rotated)?;
+        // Each output: its part of the canvas, colour corrected, edge blended,
+        // keystone and mesh warped, then rotated, flipped and presented in its
+        // own window
+        let scene = calibration.scene();
+        for (i, (projector, target)) in projectors.iter_mut().zip(&mut targets).enumerate() {
+            let settings = scene.output(projector.name());
+            let editing = mode == AppMode::Calibration && i == active_output;
+            if let Err(e) = projector.update(settings) {
+                eprintln!("Output '{}': {}", projector.name(), e);
             }
+            let image = projector.render(&mut buffer, |frame| {
+                if !editing {
+                    return;
+                }
+                if let Some(pin) = settings.and_then(|settings| settings.corner_pin.as_ref()) {
+                    if keystone_mode {
+                        keystone.render(frame, pin);
+                    }
+                }
+                if let Some(mesh) = settings.and_then(|settings| settings.warp_mesh.as_ref()) {
+                    if mesh_mode {
+                        mesh_editor.render(frame, mesh);
+                    }
+                }
+            });
+            display.present_to(i, target, image)?;
         }
     }
 
@@ -0,0 +1,719 @@
+//! MQTT client for receiving chyron messages and commands
+//!
+//! Connects to an MQTT broker and subscribes to a topic.
+//! Messages received are forwarded to the main loop for display.
+//!
+//! Commands are accepted under `<topic>/cmd/`, using the same command language
+//! as the control socket. The topic path after `cmd/` supplies the leading
+//! words and the payload the rest, so these are equivalent:
+//!
+//! ```text
+//! wallfacer/cmd/effect        "3"          -> effect 3
+//! wallfacer/cmd/next          ""           -> next
+//! wallfacer/cmd/param         "speed 1.5"  -> param speed 1.5
+//! wallfacer/cmd/param/speed   "1.5"        -> param speed 1.5
+//! wallfacer/cmd/scene/load    "stage.json" -> scene load stage.json
+//! wallfacer/cmd               "fps"        -> fps
+//! ```
+//!
+//! State is published back, retained, under `<topic>/state/` (effect, mode,
+//! fps, chyron, dimmer), with `<topic>/status` set to `online`/`offline` via
+//! the broker's Last Will. Home Assistant discovery configs describe a light
+//! (with the effect list), an effect select, a chyron text entity and sensors,
+//! all wired to the command topics above.
+//!
+//! The connection runs entirely in the background: if the broker is down at
+//! startup or drops later, the client keeps retrying with exponential backoff
+//! and resubscribes and republishes retained state once it is back.
+
+mod broker;
+
+#[allow(unused_imports)]
+pub use broker::{BrokerUrl, MqttConfig};
+
+use crate::chyron::ChyronMessage;
+use crate::control::Command;
+use rumqttc::{Client, Event, Packet, QoS};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+const DEFAULT_HOST: &str = "192.168.23.123";
+const DEFAULT_TOPIC: &str = "wallfacer";
+const DISCOVERY_PREFIX: &str = "homeassistant";
+
+/// Retained messages by topic, replayed whenever the broker session is new
+type RetainedMessages = Arc<Mutex<BTreeMap<String, String>>>;
+
+/// Outgoing request queue size: room for a full replay of retained state
+const REQUEST_CAPACITY: usize = 64;
+
+/// Broker connection state, shared with the background thread
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// Not connected yet since startup
+    Connecting,
+    Connected,
+    /// Lost the broker (or never reached it) and waiting to retry
+    Reconnecting,
+}
+
+impl ConnectionState {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => ConnectionState::Connected,
+            2 => ConnectionState::Reconnecting,
+            _ => ConnectionState::Connecting,
+        }
+    }
+
+    /// Short label for on-screen display
+    pub fn label(self) -> &'static str {
+        match self {
+            ConnectionState::Connecting => "connecting",
+            ConnectionState::Connected => "connected",
+            ConnectionState::Reconnecting => "reconnecting",
+        }
+    }
+}
+
+/// Display state published to `<topic>/state/`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DisplayState {
+    pub effect: String,
+    pub mode: String,
+    pub fps: u32,
+    pub chyron: String,
+    pub dimmer: f32,
+}
+
+/// Translate a message on the command topic tree into a command.
+/// Returns None if the topic is outside `<topic>/cmd` or doesn't parse.
+fn parse_command_topic(topic: &str, base: &str, payload: &str) -> Option<Command> {
+    let rest = topic.strip_prefix(base)?.strip_prefix("/cmd")?;
+    let path = match rest {
+        "" => "",
+        _ => rest.strip_prefix('/')?,
+    };
+    let line = format!("{} {}", path.replace('/', " "), payload.trim());
+    Command::parse(&line)
+}
+
+/// MQTT client that receives messages in a background thread
+pub struct MqttClient {
+    client: Client,
+    topic: String,
+    qos: QoS,
+    retained: RetainedMessages,
+    state: Arc<AtomicU8>,
+    receiver: Receiver<ChyronMessage>,
+    commands: Receiver<Command>,
+    _thread: thread::JoinHandle<()>,
+}
+
+impl MqttClient {
+    /// Create a new MQTT client and start connecting in the background.
+    /// Only fails on configuration errors (such as unreadable certificates);
+    /// an unreachable broker is retried until it comes up.
+    pub fn new(config: &MqttConfig) -> Result<Self, String> {
+        let topic = if config.topic.is_empty() {
+            DEFAULT_TOPIC
+        } else {
+            config.topic.as_str()
+        };
+        let config = MqttConfig {
+            topic: topic.to_string(),
+            ..config.clone()
+        };
+
+        // Use unique client ID to allow multiple instances (local dev + production)
+        let client_id = format!("wallfacer-{}", std::process::id());
+        let options = config.mqtt_options(&client_id)?;
+        let (client, connection) = Client::new(options, REQUEST_CAPACITY);
+
+        let (sender, receiver) = mpsc::channel();
+        let (command_sender, commands) = mpsc::channel();
+        let retained = RetainedMessages::default();
+        let state = Arc::new(AtomicU8::new(ConnectionState::Connecting as u8));
+
+        let session = Session {
+            client: client.clone(),
+            config: config.clone(),
+            retained: Arc::clone(&retained),
+            state: Arc::clone(&state),
+            chyrons: sender,
+            commands: command_sender,
+        };
+        let handle = thread::spawn(move || {
+            session.run(connection);
+        });
+
+        eprintln!("MQTT: Connecting to {}, topic '{}'", config.broker, topic);
+
+        let client = Self {
+            client,
+            topic: topic.to_string(),
+            qos: config.qos(),
+            retained,
+            state,
+            receiver,
+            commands,
+            _thread: handle,
+        };
+        client.publish_retained(&format!("{}/status", topic), "online");
+        Ok(client)
+    }
+
+    /// Current broker connection state
+    pub fn connection_state(&self) -> ConnectionState {
+        ConnectionState::from_u8(self.state.load(Ordering::Relaxed))
+    }
+
+    /// Get any chyron messages that arrived since the last poll (non-blocking)
+    pub fn poll(&self) -> Vec<ChyronMessage> {
+        let mut messages = Vec::new();
+        while let Ok(msg) = self.receiver.try_recv() {
+            messages.push(msg);
+        }
+        messages
+    }
+
+    /// Publish a retained message, skipping it if the payload hasn't changed.
+    /// Never blocks: if the outgoing queue is full the message is dropped and
+    /// sent with the next replay.
+    fn publish_retained(&self, topic: &str, payload: &str) {
+        let Ok(mut retained) = self.retained.lock() else {
+            return;
+        };
+        if retained.get(topic).is_some_and(|p| p == payload) {
+            return;
+        }
+        retained.insert(topic.to_string(), payload.to_string());
+        let _ = self.client.try_publish(topic, self.qos, true, payload);
+    }
+
+    /// Publish display state under `<topic>/state/` (only changed values are sent)
+    pub fn publish_state(&self, state: &DisplayState) {
+        let base = format!("{}/state", self.topic);
+        self.publish_retained(&format!("{}/effect", base), &state.effect);
+        self.publish_retained(&format!("{}/mode", base), &state.mode);
+        self.publish_retained(&format!("{}/fps", base), &state.fps.to_string());
+        self.publish_retained(&format!("{}/chyron", base), &state.chyron);
+        self.publish_retained(&format!("{}/dimmer", base), &format!("{:.3}", state.dimmer));
+    }
+
+    /// Publish Home Assistant discovery configs for this display
+    pub fn publish_discovery(&self, effect_names: &[String]) {
+        for (topic, config) in discovery_configs(&self.topic, effect_names) {
+            self.publish_retained(&topic, &config.to_string());
+        }
+    }
+
+    /// Get any pending commands from the command topics (non-blocking).
+    /// Unlike chyron messages, every command is returned in arrival order.
+    pub fn poll_commands(&self) -> Vec<Command> {
+        let mut commands = Vec::new();
+        while let Ok(cmd) = self.commands.try_recv() {
+            commands.push(cmd);
+        }
+        commands
+    }
+
+    /// Default MQTT host
+    pub fn default_host() -> &'static str {
+        DEFAULT_HOST
+    }
+
+    /// Default MQTT topic
+    pub fn default_topic() -> &'static str {
+        DEFAULT_TOPIC
+    }
+}
+
+/// Connection state owned by the background thread
+struct Session {
+    client: Client,
+    config: MqttConfig,
+    retained: RetainedMessages,
+    state: Arc<AtomicU8>,
+    chyrons: Sender<ChyronMessage>,
+    commands: Sender<Command>,
+}
+
+impl Session {
+    /// Drive the connection until the main thread goes away.
+    /// rumqttc reconnects on the next poll after an error, so backing off is
+    /// just a matter of sleeping before polling again.
+    fn run(self, mut connection: rumqttc::Connection) {
+        let topic = self.config.topic.as_str();
+        let ha_status = format!("{}/status", DISCOVERY_PREFIX);
+
+        let mut backoff = self.config.reconnect_min;
+        let mut total_errors = 0u32;
+        let mut last_error_log: Option<Instant> = None;
+
+        for event in connection.iter() {
+            match event {
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    if publish.topic == topic {
+                        if let Ok(raw) = String::from_utf8(publish.payload.to_vec()) {
+                            let raw = raw.trim();
+                            if !raw.is_empty() {
+                                // Require JSON format: {"text": "message", "ttl": 30}
+                                match ChyronMessage::from_json(raw) {
+                                    Ok(msg) => {
+                                        eprintln!("MQTT: Received '{}' (TTL: {}s)", msg.text, msg.ttl);
+                                        if self.chyrons.send(msg).is_err() {
+                                            // Main thread gone, exit
+                                            break;
+                                        }
+                                    }
+                                    Err(e) => {
+                                        eprintln!("MQTT: Invalid JSON '{}': {}", raw, e);
+                                    }
+                                }
+                            }
+                        }
+                    } else if publish.topic == ha_status {
+                        // Home Assistant restarted: resend discovery and state
+                        if publish.payload.as_ref() == b"online" {
+                            self.replay();
+                        }
+                    } else {
+                        let payload = String::from_utf8_lossy(&publish.payload);
+                        match parse_command_topic(&publish.topic, topic, &payload) {
+                            Some(cmd) => {
+                                eprintln!("MQTT: Command {:?}", cmd);
+                                if self.commands.send(cmd).is_err() {
+                                    break;
+                                }
+                            }
+                            None => {
+                                eprintln!(
+                                    "MQTT: Unknown command on '{}': '{}'",
+                                    publish.topic,
+                                    payload.trim()
+                                );
+                            }
+                        }
+                    }
+                }
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    eprintln!("MQTT: Connected to {}", self.config.broker);
+                    self.state
+                        .store(ConnectionState::Connected as u8, Ordering::Relaxed);
+                    backoff = self.config.reconnect_min;
+                    // Sessions are clean, so subscriptions and retained state
+                    // have to be re-established on every connect
+                    self.subscribe(&ha_status);
+                    self.replay();
+                }
+                Ok(_) => {
+                    // Other events (pings, etc) - just continue
+                }
+                Err(e) => {
+                    total_errors += 1;
+                    let was_connected = self.state.swap(
+                        ConnectionState::Reconnecting as u8,
+                        Ordering::Relaxed,
+                    ) == ConnectionState::Connected as u8;
+                    // Log on disconnect, then only every 30 seconds to avoid spam
+                    let now = Instant::now();
+                    if was_connected
+                        || last_error_log.map_or(true, |t| now.duration_since(t).as_secs() >= 30)
+                    {
+                        eprintln!(
+                            "MQTT: {} unreachable ({} errors, latest: {}), retrying in {:.1}s",
+                            self.config.broker,
+                            total_errors,
+                            e,
+                            backoff.as_secs_f32()
+                        );
+                        last_error_log = Some(now);
+                    }
+                    thread::sleep(backoff);
+                    backoff = (backoff * 2).min(self.config.reconnect_max);
+                }
+            }
+        }
+    }
+
+    /// Subscribe to the chyron topic, the command tree and Home Assistant status
+    fn subscribe(&self, ha_status: &str) {
+        let topic = &self.config.topic;
+        let command_filter = format!("{}/cmd/#", topic);
+        for filter in [topic.as_str(), command_filter.as_str(), ha_status] {
+            if let Err(e) = self.client.try_subscribe(filter, self.config.qos()) {
+                eprintln!("MQTT: Failed to subscribe to '{}': {}", filter, e);
+            }
+        }
+    }
+
+    /// Resend every retained message
+    fn replay(&self) {
+        let Ok(retained) = self.retained.lock() else {
+            return;
+        };
+        for (topic, payload) in retained.iter() {
+            let _ = self
+                .client
+                .try_publish(topic.as_str(), self.config.qos(), true, payload.as_str());
+        }
+    }
+}
+
+/// Build (topic, config) pairs for Home Assistant MQTT discovery
+fn discovery_configs(topic: &str, effect_names: &[String]) -> Vec<(String, serde_json::Value)> {
+    // Node ID must be a valid HA object id, so derive it from the base topic
+    let node: String = topic
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let device = json!({
+        "identifiers": [format!("wallfacer_{}", node)],
+        "name": format!("Wallfacer ({})", topic),
+        "manufacturer": "wallfacer",
+        "model": "Projection display",
+        "sw_version": env!("CARGO_PKG_VERSION"),
+    });
+    let availability = format!("{}/status", topic);
+
+    let entity = |component: &str, object: &str, name: &str, mut config: serde_json::Value| {
+        config["name"] = json!(name);
+        config["unique_id"] = json!(format!("wallfacer_{}_{}", node, object));
+        config["availability_topic"] = json!(availability);
+        config["device"] = device.clone();
+        (
+            format!("{}/{}/{}/{}/config", DISCOVERY_PREFIX, component, node, object),
+            config,
+        )
+    };
+
+    vec![
+        entity(
+            "light",
+            "display",
+            "Display",
+            json!({
+                "command_topic": format!("{}/cmd/param/dimmer", topic),
+                "payload_on": "1",
+                "payload_off": "0",
+                "state_topic": format!("{}/state/dimmer", topic),
+                "state_value_template": "{{ '1' if value | float > 0 else '0' }}",
+                // On and brightness share the dimmer: turning on must send only
+                // the brightness, or HA follows it with "1" and resets to full
+                "on_command_type": "brightness",
+                "brightness_command_topic": format!("{}/cmd/param/dimmer", topic),
+                "brightness_command_template": "{{ (value / 255) | round(3) }}",
+                "brightness_state_topic": format!("{}/state/dimmer", topic),
+                "brightness_value_template": "{{ (value | float * 255) | round(0) | int }}",
+                "effect_command_topic": format!("{}/cmd/effect", topic),
+                "effect_state_topic": format!("{}/state/effect", topic),
+                "effect_list": effect_names,
+            }),
+        ),
+        entity(
+            "select",
+            "effect",
+            "Effect",
+            json!({
+                "command_topic": format!("{}/cmd/effect", topic),
+                "state_topic": format!("{}/state/effect", topic),
+                "options": effect_names,
+            }),
+        ),
+        entity(
+            "text",
+            "chyron",
+            "Chyron",
+            json!({
+                "command_topic": format!("{}/cmd/chyron", topic),
+                "state_topic": format!("{}/state/chyron", topic),
+                "max": 255,
+            }),
+        ),
+        entity(
+            "sensor",
+            "fps",
+            "FPS",
+            json!({
+                "state_topic": format!("{}/state/fps", topic),
+                "unit_of_measurement": "fps",
+                "state_class": "measurement",
+            }),
+        ),
+        entity(
+            "sensor",
+            "mode",
+            "Mode",
+            json!({ "state_topic": format!("{}/state/mode", topic) }),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::ColorAdjust;
+    use crate::regions::RegionContent;
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::time::Duration;
+
+    fn read_packet(stream: &mut TcpStream) -> std::io::Result<(u8, Vec<u8>)> {
+        let mut byte = [0u8; 1];
+        stream.read_exact(&mut byte)?;
+        let header = byte[0];
+        let (mut len, mut shift) = (0usize, 0);
+        loop {
+            stream.read_exact(&mut byte)?;
+            len |= ((byte[0] & 0x7F) as usize) << shift;
+            if byte[0] & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+        }
+        let mut body = vec![0u8; len];
+        stream.read_exact(&mut body)?;
+        Ok((header, body))
+    }
+
+    fn publish_packet(topic: &str, payload: &str) -> Vec<u8> {
+        let mut body = (topic.len() as u16).to_be_bytes().to_vec();
+        body.extend_from_slice(topic.as_bytes());
+        body.extend_from_slice(payload.as_bytes());
+        let mut packet = vec![0x30];
+        let mut len = body.len();
+        loop {
+            let mut b = (len & 0x7F) as u8;
+            len >>= 7;
+            if len > 0 {
+                b |= 0x80;
+            }
+            packet.push(b);
+            if len == 0 {
+                break;
+            }
+        }
+        packet.extend(body);
+        packet
+    }
+
+    /// Minimal MQTT 3.1.1 stand-in for one client: acks everything, forwards
+    /// what the client publishes, and sends `outgoing` once the command tree
+    /// is subscribed
+    fn stand_in_broker(
+        listener: &TcpListener,
+        published: &mpsc::Sender<(String, String)>,
+        outgoing: &[(&str, &str)],
+    ) {
+        let (mut stream, _) = listener.accept().unwrap();
+        while let Ok((header, body)) = read_packet(&mut stream) {
+            let reply = match header >> 4 {
+                // CONNECT -> CONNACK (accepted)
+                1 => vec![0x20, 2, 0, 0],
+                // PUBLISH -> forward, PUBACK if QoS 1
+                3 => {
+                    let len = u16::from_be_bytes([body[0], body[1]]) as usize;
+                    let topic = String::from_utf8_lossy(&body[2..2 + len]).to_string();
+                    let mut offset = 2 + len;
+                    let mut ack = Vec::new();
+                    if (header >> 1) & 3 > 0 {
+                        ack = vec![0x40, 2, body[offset], body[offset + 1]];
+                        offset += 2;
+                    }
+                    let payload = String::from_utf8_lossy(&body[offset..]).to_string();
+                    let _ = published.send((topic, payload));
+                    ack
+                },
+                // SUBSCRIBE -> SUBACK granting QoS 0 to each filter
+                8 => {
+                    let mut filters = Vec::new();
+                    let mut offset = 2;
+                    while offset < body.len() {
+                        let len = u16::from_be_bytes([body[offset], body[offset + 1]]) as usize;
+                        filters.push(String::from_utf8_lossy(&body[offset + 2..offset + 2 + len]).to_string());
+                        offset += 2 + len + 1;
+                    }
+                    let mut reply = vec![0x90, 2 + filters.len() as u8, body[0], body[1]];
+                    reply.resize(reply.len() + filters.len(), 0);
+                    if filters.iter().any(|f| f.ends_with("/cmd/#")) {
+                        for (topic, payload) in outgoing {
+                            reply.extend(publish_packet(topic, payload));
+                        }
+                    }
+                    reply
+                },
+                // PINGREQ -> PINGRESP
+                12 => vec![0xD0, 0],
+                _ => break,
+            };
+            if stream.write_all(&reply).is_err() {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn test_reconnects_to_late_broker() {
+        // Reserve a free port, then leave it closed so the first attempts fail
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut config =
+            MqttConfig::new(&format!("mqtt://127.0.0.1:{}", port), "wallfacer").unwrap();
+        config.reconnect_min = Duration::from_millis(20);
+        config.reconnect_max = Duration::from_millis(100);
+        let client = MqttClient::new(&config).unwrap();
+
+        thread::sleep(Duration::from_millis(200));
+        assert_eq!(client.connection_state(), ConnectionState::Reconnecting);
+
+        let listener = TcpListener::bind(("127.0.0.1", port)).unwrap();
+        let (published_sender, published) = mpsc::channel();
+        thread::spawn(move || {
+            stand_in_broker(
+                &listener,
+                &published_sender,
+                &[("wallfacer/cmd/next", ""), ("wallfacer", r#"{"text": "hello"}"#)],
+            );
+        });
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut commands = Vec::new();
+        while commands.is_empty() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+            commands = client.poll_commands();
+        }
+        assert!(matches!(commands.as_slice(), [Command::Right]));
+        assert_eq!(client.connection_state(), ConnectionState::Connected);
+
+        // The chyron follows the command on the same connection
+        let mut messages = Vec::new();
+        while messages.is_empty() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+            messages = client.poll();
+        }
+        assert_eq!(messages[0].text, "hello");
+
+        // Status published while the broker was down is sent once connected
+        let (topic, payload) = published.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!((topic.as_str(), payload.as_str()), ("wallfacer/status", "online"));
+    }
+
+    #[test]
+    fn test_command_topics() {
+        let parse = |topic, payload| parse_command_topic(topic, "wallfacer", payload);
+        assert!(matches!(parse("wallfacer/cmd/effect", "3"), Some(Command::Effect(3))));
+        assert!(matches!(parse("wallfacer/cmd/next", ""), Some(Command::Right)));
+        assert!(matches!(parse("wallfacer/cmd", "fps"), Some(Command::ToggleFps)));
+        assert!(matches!(
+            parse("wallfacer/cmd/effect", "Plasma"),
+            Some(Command::EffectName(name)) if name == "Plasma"
+        ));
+        assert!(matches!(
+            parse("wallfacer/cmd/param/speed", "1.5"),
+            Some(Command::Param { name, value }) if name == "speed" && value == 1.5
+        ));
+        assert!(matches!(
+            parse("wallfacer/cmd/scene/load", "stage.json"),
+            Some(Command::LoadScene(path)) if path == "stage.json"
+        ));
+    }
+
+    #[test]
+    fn test_rejects_other_topics() {
+        assert!(parse_command_topic("wallfacer/cmdx/next", "wallfacer", "").is_none());
+        assert!(parse_command_topic("other/cmd/next", "wallfacer", "").is_none());
+        assert!(parse_command_topic("wallfacer/cmd/bogus", "wallfacer", "").is_none());
+    }
+
+    #[test]
+    fn test_chyron_command() {
+        let cmd = parse_command_topic("wallfacer/cmd/chyron", "wallfacer", r#"{"text": "hi", "ttl": 5}"#);
+        assert!(matches!(cmd, Some(Command::Chyron(msg)) if msg.text == "hi" && msg.ttl == 5.0));
+    }
+
+    #[test]
+    fn test_region_content_command() {
+        let topic = "wallfacer/cmd/region/frame_1/text";
+        let cmd = parse_command_topic(topic, "wallfacer", "Hi\\nthere");
+        assert!(matches!(
+            cmd,
+            Some(Command::RegionContent { region, content: Some(RegionContent::Text { text, .. }) })
+                if region == "frame_1" && text == "Hi\nthere"
+        ));
+
+        let parse = |payload| parse_command_topic("wallfacer/cmd/region", "wallfacer", payload);
+        let cmd = parse("frame_1 clear");
+        assert!(matches!(cmd, Some(Command::RegionContent { content: None, .. })));
+        let cmd = parse(r#"frame_1 {"type": "Marquee", "text": "NOW SHOWING"}"#);
+        assert!(matches!(
+            cmd,
+            Some(Command::RegionContent {
+                content: Some(RegionContent::Marquee { .. }),
+                ..
+            })
+        ));
+        assert!(parse("frame_1 bogus").is_none());
+    }
+
+    #[test]
+    fn test_color_command() {
+        let cmd = parse_command_topic("wallfacer/cmd/color/left/gain", "wallfacer", "1 0.9 0.8");
+        assert!(matches!(
+            cmd,
+            Some(Command::Color { output, adjust: ColorAdjust::Gain(gain) })
+                if output == "left" && gain == [1.0, 0.9, 0.8]
+        ));
+
+        let parse = |payload| parse_command_topic("wallfacer/cmd/color", "wallfacer", payload);
+        assert!(matches!(
+            parse("gamma 2.2"),
+            Some(Command::Color { output, adjust: ColorAdjust::Gamma(gamma) })
+                if output.is_empty() && gamma == [2.2; 3]
+        ));
+        assert!(matches!(
+            parse(r#"right {"temperature": 5600}"#),
+            Some(Command::Color { adjust: ColorAdjust::Set(color), .. })
+                if color.temperature == 5600.0 && color.contrast == 1.0
+        ));
+        assert!(matches!(
+            parse("lut off"),
+            Some(Command::Color { adjust: ColorAdjust::Lut(None), .. })
+        ));
+        assert!(parse("right gain 1 2").is_none());
+        assert!(parse("right bogus 1").is_none());
+    }
+
+    #[test]
+    fn test_discovery_topics() {
+        let effects = vec!["Plasma".to_string(), "Test Pattern".to_string()];
+        let configs = discovery_configs("lobby/wall", &effects);
+        let (topic, select) = configs
+            .iter()
+            .find(|(topic, _)| topic.starts_with("homeassistant/select/"))
+            .unwrap();
+        assert_eq!(topic, "homeassistant/select/lobby_wall/effect/config");
+        assert_eq!(select["command_topic"], "lobby/wall/cmd/effect");
+        assert_eq!(select["options"][1], "Test Pattern");
+        assert_eq!(select["availability_topic"], "lobby/wall/status");
+        let (_, light) = configs
+            .iter()
+            .find(|(topic, _)| topic.starts_with("homeassistant/light/"))
+            .unwrap();
+        assert_eq!(light["command_topic"], light["brightness_command_topic"]);
+        assert_eq!(light["on_command_type"], "brightness");
+        // Every entity must have a distinct unique_id
+        let mut ids: Vec<_> = configs.iter().map(|(_, c)| c["unique_id"].to_string()).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), configs.len());
+    }
+}
@@ -0,0 +1,484 @@
+//! Per-output colour correction
+//!
+//! Projectors side by side never quite match: one is brighter, one runs
+//! blue, one has a lamp hour count in five figures. Each output can be
+//! evened out with brightness, contrast, per-channel gain and gamma and a
+//! white point, then optionally graded through a `.cube` 3D LUT:
+//!
+//! ```json
+//! "color": { "gain": [1.0, 0.96, 0.9], "temperature": 5600, "lut": "grade.cube" }
+//! ```
+//!
+//! Everything is baked into lookup tables when the settings change, so the
+//! per-frame cost is a few table reads per pixel.
+
+use crate::display::PixelBuffer;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// White point that leaves colours unchanged, in kelvin
+const NEUTRAL_TEMPERATURE: f32 = 6500.0;
+/// Largest `.cube` grid accepted
+const MAX_CUBE_SIZE: usize = 256;
+/// Fixed-point one for LUT cell fractions
+const ONE: i32 = 256;
+
+/// One output's colour correction, in the order it's applied
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColorCorrection {
+    /// Added to every channel: -1 is black, 1 is white
+    pub brightness: f32,
+    /// Scale about mid grey: 0 is flat grey, 1 unchanged
+    pub contrast: f32,
+    /// Per-channel gain, red, green, blue
+    pub gain: [f32; 3],
+    /// White point in kelvin: lower is warmer, 6500 unchanged
+    pub temperature: f32,
+    /// Per-channel gamma, red, green, blue: above 1 lifts the mid tones
+    pub gamma: [f32; 3],
+    /// A `.cube` 3D LUT applied after everything else
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lut: Option<PathBuf>,
+}
+
+impl Default for ColorCorrection {
+    fn default() -> Self {
+        Self {
+            brightness: 0.0,
+            contrast: 1.0,
+            gain: [1.0; 3],
+            temperature: NEUTRAL_TEMPERATURE,
+            gamma: [1.0; 3],
+            lut: None,
+        }
+    }
+}
+
+impl ColorCorrection {
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The corrected value of `value` (0 to 1) on `channel`, before the LUT
+    fn curve(&self, channel: usize, value: f32, white: [f32; 3]) -> f32 {
+        let value = (value - 0.5) * self.contrast + 0.5 + self.brightness;
+        let value = value * self.gain[channel] * white[channel];
+        value
+            .clamp(0.0, 1.0)
+            .powf(1.0 / self.gamma[channel].max(0.01))
+    }
+}
+
+/// A change to an output's colour correction, as sent over the control socket
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColorAdjust {
+    Brightness(f32),
+    Contrast(f32),
+    Gain([f32; 3]),
+    Temperature(f32),
+    Gamma([f32; 3]),
+    /// Load a LUT, or drop it (None)
+    Lut(Option<PathBuf>),
+    /// Replace the whole correction
+    Set(ColorCorrection),
+    /// Back to no correction
+    Reset,
+}
+
+impl ColorAdjust {
+    /// Apply to an output's correction, creating it if needed
+    pub fn apply(self, color: &mut Option<ColorCorrection>) {
+        let correction = match self {
+            Self::Set(correction) => {
+                *color = Some(correction);
+                return;
+            },
+            Self::Reset => {
+                *color = None;
+                return;
+            },
+            _ => color.get_or_insert_with(ColorCorrection::default),
+        };
+        match self {
+            Self::Brightness(value) => correction.brightness = value.clamp(-1.0, 1.0),
+            Self::Contrast(value) => correction.contrast = value.clamp(0.0, 4.0),
+            Self::Gain(gain) => correction.gain = gain.map(|v| v.clamp(0.0, 4.0)),
+            Self::Temperature(kelvin) => correction.temperature = kelvin.clamp(1000.0, 40000.0),
+            Self::Gamma(gamma) => correction.gamma = gamma.map(|v| v.clamp(0.1, 10.0)),
+            Self::Lut(path) => correction.lut = path,
+            Self::Set(_) | Self::Reset => {},
+        }
+    }
+}
+
+/// Channel gains that shift white to a blackbody at `kelvin`, scaled so the
+/// strongest channel is 1 and 6500K leaves white alone
+pub fn white_balance(kelvin: f32) -> [f32; 3] {
+    let [r, g, b] = blackbody(kelvin);
+    let [nr, ng, nb] = blackbody(NEUTRAL_TEMPERATURE);
+    let gains = [r / nr, g / ng, b / nb];
+    let max = gains.iter().copied().fold(f32::MIN, f32::max);
+    gains.map(|gain| gain / max)
+}
+
+/// Approximate colour of a blackbody, 0 to 255 per channel (Tanner Helland's
+/// fit to the CIE tables, good from 1000K to 40000K)
+fn blackbody(kelvin: f32) -> [f32; 3] {
+    let t = kelvin.clamp(1000.0, 40000.0) / 100.0;
+    let r = if t <= 66.0 {
+        255.0
+    } else {
+        329.698_73 * (t - 60.0).powf(-0.133_204_76)
+    };
+    let g = if t <= 66.0 {
+        99.470_8 * t.ln() - 161.119_57
+    } else {
+        288.122_17 * (t - 60.0).powf(-0.075_514_85)
+    };
+    let b = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.517_73 * (t - 10.0).ln() - 305.044_8
+    };
+    [r, g, b].map(|v| v.clamp(1.0, 255.0))
+}
+
+/// A 3D LUT as read from a `.cube` file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cube {
+    /// Grid points along each axis
+    pub size: usize,
+    /// Input values mapped to the first and last grid points
+    pub domain_min: [f32; 3],
+    pub domain_max: [f32; 3],
+    /// Output colours, red varying fastest, then green, then blue
+    pub entries: Vec<[f32; 3]>,
+}
+
+impl Cube {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Parse the Adobe/Resolve `.cube` format
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut size = None;
+        let mut domain_min = [0.0; 3];
+        let mut domain_max = [1.0; 3];
+        let mut entries = Vec::new();
+
+        let numbers = |words: &[&str], line: usize| -> Result<Vec<f32>, String> {
+            words
+                .iter()
+                .map(|word| {
+                    word.parse()
+                        .map_err(|_| format!("line {}: bad number '{}'", line, word))
+                })
+                .collect()
+        };
+        let triple = |words: &[&str], line: usize| -> Result<[f32; 3], String> {
+            match numbers(words, line)?.as_slice() {
+                &[a, b, c] => Ok([a, b, c]),
+                _ => Err(format!("line {}: expected three numbers", line)),
+            }
+        };
+
+        for (i, line) in text.lines().enumerate() {
+            let line_no = i + 1;
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let Some((&keyword, args)) = words.split_first() else {
+                continue;
+            };
+            match keyword {
+                _ if keyword.starts_with('#') => {},
+                "TITLE" => {},
+                "LUT_3D_SIZE" => {
+                    let n = args
+                        .first()
+                        .and_then(|n| n.parse().ok())
+                        .filter(|n| (2..=MAX_CUBE_SIZE).contains(n))
+                        .ok_or_else(|| format!("line {}: bad LUT_3D_SIZE", line_no))?;
+                    size = Some(n);
+                },
+                "LUT_1D_SIZE" => return Err("1D LUTs are not supported".to_string()),
+                "DOMAIN_MIN" => domain_min = triple(args, line_no)?,
+                "DOMAIN_MAX" => domain_max = triple(args, line_no)?,
+                "LUT_3D_INPUT_RANGE" => match numbers(args, line_no)?.as_slice() {
+                    &[min, max] => {
+                        domain_min = [min; 3];
+                        domain_max = [max; 3];
+                    },
+                    _ => return Err(format!("line {}: expected two numbers", line_no)),
+                },
+                _ => entries.push(triple(&words, line_no)?),
+            }
+        }
+
+        let size = size.ok_or("missing LUT_3D_SIZE")?;
+        if entries.len() != size.pow(3) {
+            return Err(format!(
+                "expected {} entries for size {}, found {}",
+                size.pow(3),
+                size,
+                entries.len()
+            ));
+        }
+        if (0..3).any(|c| domain_max[c] <= domain_min[c]) {
+            return Err("DOMAIN_MAX must be above DOMAIN_MIN".to_string());
+        }
+        Ok(Self {
+            size,
+            domain_min,
+            domain_max,
+            entries,
+        })
+    }
+}
+
+/// A LUT ready for integer trilinear lookups
+struct LutTable {
+    size: usize,
+    /// Output colours in 0 to 65535
+    entries: Vec<[i32; 3]>,
+    /// For each channel and input byte: the offset of the grid cell's lower
+    /// corner along that axis, and how far into the cell (0 to `ONE`)
+    cells: [Vec<(usize, i32)>; 3],
+}
+
+impl LutTable {
+    fn sample(&self, rgb: [u8; 3]) -> [u8; 3] {
+        let [(r, fr), (g, fg), (b, fb)] = [0, 1, 2].map(|c| self.cells[c][rgb[c] as usize]);
+        let (n, base) = (self.size, r + g + b);
+        let at = |offset: usize| self.entries[base + offset];
+        let lerp =
+            |a: [i32; 3], b: [i32; 3], f: i32| [0, 1, 2].map(|c| a[c] + (b[c] - a[c]) * f / ONE);
+
+        let near = lerp(lerp(at(0), at(1), fr), lerp(at(n), at(n + 1), fr), fg);
+        let far_base = n * n;
+        let far = lerp(
+            lerp(at(far_base), at(far_base + 1), fr),
+            lerp(at(far_base + n), at(far_base + n + 1), fr),
+            fg,
+        );
+        lerp(near, far, fb).map(|v| ((v.clamp(0, 65535) * 255 + 32767) / 65535) as u8)
+    }
+}
+
+/// A correction baked into lookup tables
+struct ColorTable {
+    curves: [[u8; 256]; 3],
+    lut: Option<LutTable>,
+}
+
+impl ColorTable {
+    fn new(correction: &ColorCorrection, cube: Option<&Cube>) -> Self {
+        let white = white_balance(correction.temperature);
+        let curve = |c: usize, i: usize| correction.curve(c, i as f32 / 255.0, white);
+        let curves = [0, 1, 2].map(|c| {
+            let mut table = [0; 256];
+            for (i, value) in table.iter_mut().enumerate() {
+                *value = (curve(c, i) * 255.0).round() as u8;
+            }
+            table
+        });
+
+        // The curves run in float straight into the LUT's grid coordinates,
+        // so the 8-bit table above doesn't band the LUT's input
+        let lut = cube.map(|cube| {
+            let n = cube.size;
+            let strides = [1, n, n * n];
+            let cells = [0, 1, 2].map(|c| {
+                let (min, max) = (cube.domain_min[c], cube.domain_max[c]);
+                (0..256)
+                    .map(|i| {
+                        let p =
+                            ((curve(c, i) - min) / (max - min)).clamp(0.0, 1.0) * (n - 1) as f32;
+                        let cell = (p as usize).min(n - 2);
+                        let fraction = ((p - cell as f32) * ONE as f32).round() as i32;
+                        (cell * strides[c], fraction)
+                    })
+                    .collect()
+            });
+            LutTable {
+                size: n,
+                entries: cube
+                    .entries
+                    .iter()
+                    .map(|rgb| rgb.map(|v| (v.clamp(0.0, 1.0) * 65535.0).round() as i32))
+                    .collect(),
+                cells,
+            }
+        });
+        Self { curves, lut }
+    }
+
+    fn apply(&self, buffer: &mut PixelBuffer) {
+        for pixel in buffer.as_bytes_mut().chunks_exact_mut(4) {
+            // Byte 0 is alpha, then blue, green, red
+            let rgb = [pixel[3], pixel[2], pixel[1]];
+            let [r, g, b] = self.lut.as_ref().map_or_else(
+                || [0, 1, 2].map(|c| self.curves[c][rgb[c] as usize]),
+                |lut| lut.sample(rgb),
+            );
+            pixel[1] = b;
+            pixel[2] = g;
+            pixel[3] = r;
+        }
+    }
+}
+
+/// The colour correction applied to each frame, rebuilt when its settings change
+pub struct OutputColor {
+    correction: Option<ColorCorrection>,
+    table: Option<ColorTable>,
+}
+
+impl OutputColor {
+    pub fn new() -> Self {
+        Self {
+            correction: None,
+            table: None,
+        }
+    }
+
+    /// Use `correction` (None = leave colours alone). Cheap when nothing
+    /// changed; otherwise rebuilds the tables and reloads the LUT. If the LUT
+    /// won't load the rest of the correction still applies.
+    pub fn update(&mut self, correction: Option<&ColorCorrection>) -> Result<(), String> {
+        if self.correction.as_ref() == correction {
+            return Ok(());
+        }
+        self.correction = correction.cloned();
+        self.table = None;
+        let Some(correction) = correction.filter(|c| !c.is_identity()) else {
+            return Ok(());
+        };
+        let (cube, result) = match correction.lut.as_ref().map(Cube::load).transpose() {
+            Ok(cube) => (cube, Ok(())),
+            Err(e) => (None, Err(e)),
+        };
+        self.table = Some(ColorTable::new(correction, cube.as_ref()));
+        result
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.table.is_some()
+    }
+
+    pub fn apply(&self, buffer: &mut PixelBuffer) {
+        if let Some(ref table) = self.table {
+            table.apply(buffer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A size-2 cube from a function of the corner colour
+    fn cube(f: impl Fn([f32; 3]) -> [f32; 3]) -> String {
+        let mut lines = vec!["# test\nTITLE \"test\"\nLUT_3D_SIZE 2\n".to_string()];
+        for b in 0..2 {
+            for g in 0..2 {
+                for r in 0..2 {
+                    let [r, g, b] = f([r as f32, g as f32, b as f32]);
+                    lines.push(format!("{} {} {}", r, g, b));
+                }
+            }
+        }
+        lines.join("\n")
+    }
+
+    fn correct(table: &ColorTable, r: u8, g: u8, b: u8) -> (u8, u8, u8) {
+        let mut buffer = PixelBuffer::with_size(1, 1);
+        buffer.set_pixel(0, 0, r, g, b);
+        table.apply(&mut buffer);
+        buffer.get_pixel(0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_cube() {
+        let parsed = Cube::parse(&cube(|[r, g, b]| [b, g, r])).unwrap();
+        assert_eq!(parsed.size, 2);
+        assert_eq!(parsed.entries.len(), 8);
+        // Red varies fastest
+        assert_eq!(parsed.entries[1], [0.0, 0.0, 1.0]);
+
+        let ranged = format!("LUT_3D_INPUT_RANGE 0 2\n{}", cube(|rgb| rgb));
+        assert_eq!(Cube::parse(&ranged).unwrap().domain_max, [2.0; 3]);
+        assert!(Cube::parse("LUT_3D_SIZE 2\n0 0 0\n").is_err());
+        assert!(Cube::parse("LUT_1D_SIZE 2\n0 0 0\n1 1 1\n").is_err());
+        assert!(Cube::parse(&cube(|rgb| rgb).replace("1 1 1", "1 one 1")).is_err());
+    }
+
+    #[test]
+    fn test_curves() {
+        let identity = ColorTable::new(&ColorCorrection::default(), None);
+        for v in [0, 1, 127, 200, 255] {
+            assert_eq!(correct(&identity, v, v, v), (v, v, v));
+        }
+        assert_eq!(white_balance(NEUTRAL_TEMPERATURE), [1.0; 3]);
+        let [r, _, b] = white_balance(3200.0);
+        assert!(r == 1.0 && b < 0.8);
+
+        let correction = ColorCorrection {
+            gain: [1.0, 0.5, 1.0],
+            gamma: [1.0, 1.0, 2.0],
+            brightness: 0.1,
+            ..ColorCorrection::default()
+        };
+        let table = ColorTable::new(&correction, None);
+        let (r, g, b) = correct(&table, 102, 102, 102);
+        assert_eq!((r, g), (128, 64));
+        // sqrt(0.5)
+        assert_eq!(b, 180);
+    }
+
+    #[test]
+    fn test_lut_trilinear() {
+        let identity = Cube::parse(&cube(|rgb| rgb)).unwrap();
+        let table = ColorTable::new(&ColorCorrection::default(), Some(&identity));
+        for (r, g, b) in [(0, 0, 0), (255, 255, 255), (10, 128, 240), (77, 3, 199)] {
+            let (cr, cg, cb) = correct(&table, r, g, b);
+            assert!(cr.abs_diff(r) <= 1 && cg.abs_diff(g) <= 1 && cb.abs_diff(b) <= 1);
+        }
+
+        // Swap red and blue, then invert, with the curves feeding the LUT
+        let swap = Cube::parse(&cube(|[r, g, b]| [1.0 - b, 1.0 - g, 1.0 - r])).unwrap();
+        let table = ColorTable::new(&ColorCorrection::default(), Some(&swap));
+        assert_eq!(correct(&table, 255, 64, 0), (255, 191, 0));
+        let correction = ColorCorrection {
+            brightness: 1.0,
+            ..ColorCorrection::default()
+        };
+        let table = ColorTable::new(&correction, Some(&swap));
+        assert_eq!(correct(&table, 255, 64, 0), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_adjust_and_reload() {
+        let mut color = None;
+        ColorAdjust::Gamma([2.2, 2.2, 9999.0]).apply(&mut color);
+        ColorAdjust::Temperature(5000.0).apply(&mut color);
+        let correction = color.clone().unwrap();
+        assert_eq!(correction.gamma, [2.2, 2.2, 10.0]);
+        assert_eq!(correction.temperature, 5000.0);
+
+        let mut output = OutputColor::new();
+        assert!(output.update(color.as_ref()).is_ok() && output.is_active());
+        // A missing LUT is reported once, and the rest still applies
+        ColorAdjust::Lut(Some(PathBuf::from("/nonexistent/grade.cube"))).apply(&mut color);
+        assert!(output.update(color.as_ref()).is_err());
+        assert!(output.update(color.as_ref()).is_ok() && output.is_active());
+
+        ColorAdjust::Reset.apply(&mut color);
+        assert!(output.update(color.as_ref()).is_ok() && !output.is_active());
+    }
+}
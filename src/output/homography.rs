@@ -0,0 +1,161 @@
+//! Planar perspective transforms
+//!
+//! A homography maps one quadrilateral onto another the way a projector
+//! tilted to a wall does. Four point pairs fix the eight unknowns.
+
+/// A 3x3 projective transform, row-major, applied to (x, y, 1)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Homography {
+    m: [f64; 9],
+}
+
+impl Homography {
+    pub fn identity() -> Self {
+        Self {
+            m: [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0],
+        }
+    }
+
+    /// The transform taking each `from` corner to the matching `to` corner.
+    /// None if either quad is degenerate (three corners in a line).
+    pub fn from_points(from: &[(f32, f32); 4], to: &[(f32, f32); 4]) -> Option<Self> {
+        // Two rows per point pair in the unknowns h0..h7 (h8 = 1):
+        // u = (h0 x + h1 y + h2) / (h6 x + h7 y + 1), likewise v with h3..h5
+        let mut a = [[0.0f64; 9]; 8];
+        for (i, (&(x, y), &(u, v))) in from.iter().zip(to).enumerate() {
+            let (x, y, u, v) = (x as f64, y as f64, u as f64, v as f64);
+            a[i * 2] = [x, y, 1.0, 0.0, 0.0, 0.0, -x * u, -y * u, u];
+            a[i * 2 + 1] = [0.0, 0.0, 0.0, x, y, 1.0, -x * v, -y * v, v];
+        }
+        let h = solve(a)?;
+        let mut homography = Self {
+            m: [h[0], h[1], h[2], h[3], h[4], h[5], h[6], h[7], 1.0],
+        };
+
+        // Keep w positive inside the quad so points past the horizon can be told apart
+        let (cx, cy) = from
+            .iter()
+            .fold((0.0, 0.0), |(sx, sy), &(x, y)| (sx + x / 4.0, sy + y / 4.0));
+        if homography.w(cx, cy) < 0.0 {
+            homography.m.iter_mut().for_each(|v| *v = -*v);
+        }
+        Some(homography)
+    }
+
+    fn w(&self, x: f32, y: f32) -> f64 {
+        self.m[6] * x as f64 + self.m[7] * y as f64 + self.m[8]
+    }
+
+    /// Transform a point. None if it lands on or beyond the horizon line.
+    pub fn map(&self, x: f32, y: f32) -> Option<(f32, f32)> {
+        let (x, y) = (x as f64, y as f64);
+        let m = &self.m;
+        let w = m[6] * x + m[7] * y + m[8];
+        if w <= 1e-9 {
+            return None;
+        }
+        let u = (m[0] * x + m[1] * y + m[2]) / w;
+        let v = (m[3] * x + m[4] * y + m[5]) / w;
+        Some((u as f32, v as f32))
+    }
+
+    /// The reverse transform, or None if this one is singular
+    pub fn inverse(&self) -> Option<Self> {
+        let [a, b, c, d, e, f, g, h, i] = self.m;
+        let adjugate = [
+            e * i - f * h,
+            c * h - b * i,
+            b * f - c * e,
+            f * g - d * i,
+            a * i - c * g,
+            c * d - a * f,
+            d * h - e * g,
+            b * g - a * h,
+            a * e - b * d,
+        ];
+        let det = a * adjugate[0] + b * adjugate[3] + c * adjugate[6];
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        Some(Self {
+            m: adjugate.map(|v| v / det),
+        })
+    }
+}
+
+/// Solve an 8x8 system given as an augmented matrix, by Gaussian
+/// elimination with partial pivoting
+fn solve(mut a: [[f64; 9]; 8]) -> Option<[f64; 8]> {
+    for col in 0..8 {
+        let pivot = (col..8).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        let pivot_row = a[col];
+        for (row, values) in a.iter_mut().enumerate() {
+            if row != col {
+                let factor = values[col] / pivot_row[col];
+                for (v, p) in values[col..].iter_mut().zip(&pivot_row[col..]) {
+                    *v -= factor * p;
+                }
+            }
+        }
+    }
+    let mut x = [0.0; 8];
+    for (i, row) in a.iter().enumerate() {
+        x[i] = row[8] / row[i];
+    }
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: (f32, f32), b: (f32, f32)) {
+        assert!(
+            (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3,
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn test_from_points() {
+        let rect = [(0.0, 0.0), (640.0, 0.0), (640.0, 480.0), (0.0, 480.0)];
+        let quad = [(40.0, 20.0), (600.0, 0.0), (620.0, 470.0), (10.0, 440.0)];
+        let h = Homography::from_points(&rect, &quad).unwrap();
+        for (&from, &to) in rect.iter().zip(&quad) {
+            assert_near(h.map(from.0, from.1).unwrap(), to);
+        }
+
+        // The inverse and a solve the other way agree
+        let back = Homography::from_points(&quad, &rect).unwrap();
+        let inverse = h.inverse().unwrap();
+        let p = h.map(200.0, 300.0).unwrap();
+        assert_near(back.map(p.0, p.1).unwrap(), (200.0, 300.0));
+        assert_near(inverse.map(p.0, p.1).unwrap(), (200.0, 300.0));
+
+        // A square onto itself is the identity
+        let same = Homography::from_points(&rect, &rect).unwrap();
+        assert_near(same.map(123.0, 45.0).unwrap(), (123.0, 45.0));
+        assert_near(Homography::identity().map(5.0, 6.0).unwrap(), (5.0, 6.0));
+    }
+
+    #[test]
+    fn test_degenerate_and_horizon() {
+        let rect = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+        let line = [(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (3.0, 0.0)];
+        assert!(Homography::from_points(&rect, &line).is_none());
+
+        // Strong keystone: the sides meet at y = -0.25, so output points above
+        // that have no source
+        let trapezoid = [(0.4, 0.0), (0.6, 0.0), (1.0, 1.0), (0.0, 1.0)];
+        let h = Homography::from_points(&trapezoid, &rect).unwrap();
+        assert!(h.map(0.5, 0.5).is_some());
+        assert!(h.map(0.5, -0.2).is_some());
+        assert!(h.map(0.5, -0.3).is_none());
+    }
+}
@@ -0,0 +1,232 @@
+//! Mesh warps for curved and uneven surfaces
+//!
+//! A grid of control points spread evenly over the frame, each dragged to
+//! where that part of the picture should land on the output. Between the
+//! points the surface is filled in bilinearly (straight cells) or with
+//! Catmull-Rom bicubic patches, which pass through every point and bend
+//! smoothly across cell edges.
+//!
+//! ```json
+//! "warp_mesh": {
+//!   "columns": 3, "rows": 2, "interpolation": "bicubic",
+//!   "points": [[0, 0], [0.5, 0.05], [1, 0], [0, 1], [0.5, 0.95], [1, 1]]
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+/// Largest grid subdivision goes to, per side
+pub const MAX_POINTS: usize = 33;
+
+/// How the surface is filled in between control points
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Interpolation {
+    Bilinear,
+    #[default]
+    Bicubic,
+}
+
+/// A grid of `columns` x `rows` control points, in output fractions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WarpMesh {
+    pub columns: usize,
+    pub rows: usize,
+    #[serde(default)]
+    pub interpolation: Interpolation,
+    /// Row-major, top row first
+    pub points: Vec<(f32, f32)>,
+}
+
+impl WarpMesh {
+    /// An evenly spaced grid that leaves the frame as it is
+    pub fn new(columns: usize, rows: usize) -> Self {
+        let (columns, rows) = (columns.clamp(2, MAX_POINTS), rows.clamp(2, MAX_POINTS));
+        let points = (0..rows)
+            .flat_map(|j| {
+                (0..columns).map(move |i| {
+                    (
+                        i as f32 / (columns - 1) as f32,
+                        j as f32 / (rows - 1) as f32,
+                    )
+                })
+            })
+            .collect();
+        Self {
+            columns,
+            rows,
+            interpolation: Interpolation::default(),
+            points,
+        }
+    }
+
+    /// Whether every point is still where `new` put it
+    pub fn is_identity(&self) -> bool {
+        self.points == Self::new(self.columns, self.rows).points
+    }
+
+    /// Whether the point count matches the grid size
+    pub fn is_valid(&self) -> bool {
+        self.columns >= 2 && self.rows >= 2 && self.points.len() == self.columns * self.rows
+    }
+
+    /// Control point at column `i`, row `j`. Out-of-range indices extend the
+    /// edge cells in a straight line, so edge patches don't curl.
+    fn point(&self, i: isize, j: isize) -> (f32, f32) {
+        let (last_i, last_j) = (self.columns as isize - 1, self.rows as isize - 1);
+        let extend = |k: isize, last: isize| -> (isize, isize, f32) {
+            if k < 0 {
+                (0, 1, -k as f32)
+            } else if k > last {
+                (last, last - 1, (k - last) as f32)
+            } else {
+                (k, k, 0.0)
+            }
+        };
+        let (i0, i1, ti) = extend(i, last_i);
+        let (j0, j1, tj) = extend(j, last_j);
+        let at = |i: isize, j: isize| self.points[j as usize * self.columns + i as usize];
+        // p0 + t * (p0 - p1) along each axis that's out of range
+        let base = at(i0, j0);
+        let across_i = at(i1, j0);
+        let across_j = at(i0, j1);
+        (
+            base.0 + ti * (base.0 - across_i.0) + tj * (base.0 - across_j.0),
+            base.1 + ti * (base.1 - across_i.1) + tj * (base.1 - across_j.1),
+        )
+    }
+
+    /// Where the frame point (s, t) lands, both in fractions of the frame.
+    /// Points outside 0..1 extrapolate from the edge cells.
+    pub fn map(&self, s: f32, t: f32) -> (f32, f32) {
+        let gx = s * (self.columns - 1) as f32;
+        let gy = t * (self.rows - 1) as f32;
+        let i = (gx.floor() as isize).clamp(0, self.columns as isize - 2);
+        let j = (gy.floor() as isize).clamp(0, self.rows as isize - 2);
+        let (fx, fy) = (gx - i as f32, gy - j as f32);
+
+        match self.interpolation {
+            Interpolation::Bilinear => {
+                let lerp = |a: (f32, f32), b: (f32, f32), t: f32| {
+                    (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+                };
+                let top = lerp(self.point(i, j), self.point(i + 1, j), fx);
+                let bottom = lerp(self.point(i, j + 1), self.point(i + 1, j + 1), fx);
+                lerp(top, bottom, fy)
+            },
+            Interpolation::Bicubic => {
+                let wx = catmull_rom_weights(fx);
+                let wy = catmull_rom_weights(fy);
+                let mut out = (0.0, 0.0);
+                for (dj, &wj) in wy.iter().enumerate() {
+                    for (di, &wi) in wx.iter().enumerate() {
+                        let p = self.point(i + di as isize - 1, j + dj as isize - 1);
+                        out.0 += p.0 * wi * wj;
+                        out.1 += p.1 * wi * wj;
+                    }
+                }
+                out
+            },
+        }
+    }
+
+    /// Double the grid resolution (up to `MAX_POINTS` a side), placing the new
+    /// points on the current surface so the picture doesn't move
+    pub fn subdivide(&self) -> Option<Self> {
+        let columns = self.columns * 2 - 1;
+        let rows = self.rows * 2 - 1;
+        if columns > MAX_POINTS || rows > MAX_POINTS {
+            return None;
+        }
+        let points = (0..rows)
+            .flat_map(|j| {
+                (0..columns).map(move |i| {
+                    self.map(
+                        i as f32 / (columns - 1) as f32,
+                        j as f32 / (rows - 1) as f32,
+                    )
+                })
+            })
+            .collect();
+        Some(Self {
+            columns,
+            rows,
+            interpolation: self.interpolation,
+            points,
+        })
+    }
+}
+
+/// Weights of the four points around a span for Catmull-Rom at `t` in 0..1
+fn catmull_rom_weights(t: f32) -> [f32; 4] {
+    let (t2, t3) = (t * t, t * t * t);
+    [
+        0.5 * (-t3 + 2.0 * t2 - t),
+        0.5 * (3.0 * t3 - 5.0 * t2 + 2.0),
+        0.5 * (-3.0 * t3 + 4.0 * t2 + t),
+        0.5 * (t3 - t2),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: (f32, f32), b: (f32, f32)) {
+        assert!(
+            (a.0 - b.0).abs() < 1e-4 && (a.1 - b.1).abs() < 1e-4,
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn test_identity_mesh() {
+        for interpolation in [Interpolation::Bilinear, Interpolation::Bicubic] {
+            let mesh = WarpMesh {
+                interpolation,
+                ..WarpMesh::new(4, 3)
+            };
+            assert!(mesh.is_valid() && mesh.is_identity());
+            for (s, t) in [(0.0, 0.0), (0.3, 0.7), (1.0, 1.0), (0.5, 0.25)] {
+                assert_near(mesh.map(s, t), (s, t));
+            }
+            // Outside the frame the grid carries straight on
+            assert_near(mesh.map(-0.2, 1.1), (-0.2, 1.1));
+        }
+    }
+
+    #[test]
+    fn test_bent_mesh() {
+        // Push the middle column right: points stay put, bicubic curves smoothly
+        let mut mesh = WarpMesh::new(3, 2);
+        mesh.points[1].0 = 0.6;
+        mesh.points[4].0 = 0.6;
+        assert!(!mesh.is_identity());
+        assert_near(mesh.map(0.5, 0.5), (0.6, 0.5));
+        let bicubic = mesh.map(0.25, 0.0).0;
+        mesh.interpolation = Interpolation::Bilinear;
+        assert_near(mesh.map(0.25, 0.0), (0.3, 0.0));
+        assert!(bicubic > 0.3);
+
+        let json = serde_json::to_string(&mesh).unwrap();
+        assert!(json.contains(r#""interpolation":"bilinear""#));
+        assert_eq!(serde_json::from_str::<WarpMesh>(&json).unwrap(), mesh);
+    }
+
+    #[test]
+    fn test_subdivide() {
+        let mut mesh = WarpMesh::new(3, 3);
+        mesh.points[4] = (0.55, 0.45);
+        let finer = mesh.subdivide().unwrap();
+        assert_eq!((finer.columns, finer.rows, finer.points.len()), (5, 5, 25));
+        // Old points keep their places and the surface is unchanged
+        assert_near(finer.points[12], (0.55, 0.45));
+        for (s, t) in [(0.1, 0.2), (0.5, 0.5), (0.8, 0.35)] {
+            let (a, b) = (mesh.map(s, t), finer.map(s, t));
+            assert!((a.0 - b.0).abs() < 0.01 && (a.1 - b.1).abs() < 0.01);
+        }
+        assert!(WarpMesh::new(20, 3).subdivide().is_none());
+    }
+}
@@ -0,0 +1,322 @@
+//! Output stage: everything between the finished frame and the projector
+//!
+//! Effects, regions and overlays all draw into an undistorted content
+//! buffer. Here it's warped to suit the surface (a corner pin for keystone
+//! correction, then a mesh for curved surfaces), then rotated or mirrored
+//! (see `orientation`), colour corrected (see `color`) and presented. The
+//! warp lives in the scene file next to the regions it was calibrated with:
+//!
+//! ```json
+//! "corner_pin": { "corners": [[0.03, 0.0], [0.98, 0.04], [1.0, 1.0], [0.0, 0.97]] }
+//! ```
+//!
+//! With several outputs (see `projector`) each has its own warp and colour,
+//! saved under the output's name in the scene's `outputs`.
+
+mod color;
+mod homography;
+mod mesh;
+mod orientation;
+mod projector;
+mod warp;
+
+#[allow(unused_imports)]
+pub use color::{ColorAdjust, ColorCorrection, Cube, OutputColor};
+#[allow(unused_imports)]
+pub use homography::Homography;
+#[allow(unused_imports)]
+pub use mesh::{Interpolation, WarpMesh};
+#[allow(unused_imports)]
+pub use orientation::{Flip, Orientation, Rotation};
+#[allow(unused_imports)]
+pub use projector::{EdgeBlend, OutputConfig, Projector};
+#[allow(unused_imports)]
+pub use warp::{Vertex, WarpMap};
+
+use crate::display::PixelBuffer;
+use serde::{Deserialize, Serialize};
+
+/// Spacing of the content grid a mesh warp is traced with, in pixels
+const MESH_STEP: u32 = 8;
+
+/// One output's corrections as saved in the scene: corner pin, then mesh,
+/// then colour
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OutputSettings {
+    /// Keystone correction for the projector this scene was calibrated on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub corner_pin: Option<CornerPin>,
+    /// Mesh warp for curved or uneven surfaces, applied after the corner pin
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warp_mesh: Option<WarpMesh>,
+    /// Colour correction to match this projector to its neighbours
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<ColorCorrection>,
+}
+
+impl OutputSettings {
+    pub fn is_empty(&self) -> bool {
+        self.corner_pin.is_none() && self.warp_mesh.is_none() && self.color.is_none()
+    }
+
+    /// Drop corrections left as they started, so saved scenes only carry real ones
+    pub fn drop_identity(&mut self) {
+        if self.corner_pin.is_some_and(|pin| pin.is_identity()) {
+            self.corner_pin = None;
+        }
+        if self.warp_mesh.as_ref().is_some_and(WarpMesh::is_identity) {
+            self.warp_mesh = None;
+        }
+        if self
+            .color
+            .as_ref()
+            .is_some_and(ColorCorrection::is_identity)
+        {
+            self.color = None;
+        }
+    }
+}
+
+/// Where the content's corners land on the output, as fractions of its size:
+/// top left, top right, bottom right, bottom left
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CornerPin {
+    pub corners: [(f32, f32); 4],
+}
+
+impl Default for CornerPin {
+    fn default() -> Self {
+        Self {
+            corners: [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)],
+        }
+    }
+}
+
+impl CornerPin {
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Corners in pixels on a `width` x `height` output
+    pub fn corners_px(&self, width: u32, height: u32) -> [(f32, f32); 4] {
+        self.corners
+            .map(|(x, y)| (x * width as f32, y * height as f32))
+    }
+
+    /// Whether the corners make a convex quad in order, so the warp doesn't fold
+    pub fn is_convex(&self) -> bool {
+        let c = &self.corners;
+        let turns: Vec<f32> = (0..4)
+            .map(|i| {
+                let (a, b, p) = (c[i], c[(i + 1) % 4], c[(i + 2) % 4]);
+                (b.0 - a.0) * (p.1 - b.1) - (b.1 - a.1) * (p.0 - b.0)
+            })
+            .collect();
+        turns.iter().all(|&t| t > 0.0) || turns.iter().all(|&t| t < 0.0)
+    }
+
+    /// Content-to-output transform for a `width` x `height` frame
+    pub fn homography(&self, width: u32, height: u32) -> Option<Homography> {
+        let (w, h) = (width as f32, height as f32);
+        let content = [(0.0, 0.0), (w, 0.0), (w, h), (0.0, h)];
+        Homography::from_points(&content, &self.corners_px(width, height))
+    }
+}
+
+/// The warp applied to each frame, rebuilt when its settings change
+pub struct OutputWarp {
+    pin: Option<CornerPin>,
+    mesh: Option<WarpMesh>,
+    size: (u32, u32),
+    /// Output to content, for mapping mouse positions back (pin only;
+    /// with a mesh the map itself is looked up)
+    to_content: Option<Homography>,
+    map: Option<WarpMap>,
+    frame: PixelBuffer,
+}
+
+impl OutputWarp {
+    pub fn new() -> Self {
+        Self {
+            pin: None,
+            mesh: None,
+            size: (0, 0),
+            to_content: None,
+            map: None,
+            frame: PixelBuffer::with_size(0, 0),
+        }
+    }
+
+    /// Use `settings` for a `width` x `height` frame (None = no warp)
+    pub fn update_from(&mut self, settings: Option<&OutputSettings>, width: u32, height: u32) {
+        let pin = settings.and_then(|settings| settings.corner_pin.as_ref());
+        let mesh = settings.and_then(|settings| settings.warp_mesh.as_ref());
+        self.update(pin, mesh, width, height);
+    }
+
+    /// Use `pin` and `mesh` for a `width` x `height` frame (None = no warp).
+    /// Cheap when nothing changed; otherwise rebuilds the lookup map.
+    pub fn update(
+        &mut self,
+        pin: Option<&CornerPin>,
+        mesh: Option<&WarpMesh>,
+        width: u32,
+        height: u32,
+    ) {
+        let mesh = mesh.filter(|mesh| mesh.is_valid());
+        if self.pin.as_ref() == pin && self.mesh.as_ref() == mesh && self.size == (width, height) {
+            return;
+        }
+        self.pin = pin.copied();
+        self.mesh = mesh.cloned();
+        self.size = (width, height);
+        let to_output = pin.and_then(|pin| pin.homography(width, height));
+        self.to_content = to_output.and_then(|h| h.inverse());
+        self.map = match mesh {
+            Some(mesh) => Some(Self::mesh_map(to_output.as_ref(), mesh, width, height)),
+            None => self.to_content.map(|to_content| {
+                WarpMap::from_fn(width, height, width, height, |x, y| to_content.map(x, y))
+            }),
+        };
+        if self.map.is_some() && (self.frame.width(), self.frame.height()) != (width, height) {
+            self.frame = PixelBuffer::with_size(width, height);
+        }
+    }
+
+    /// Trace a grid over the content through the pin and then the mesh, and
+    /// fill in the output between the traced points
+    fn mesh_map(pin: Option<&Homography>, mesh: &WarpMesh, width: u32, height: u32) -> WarpMap {
+        let (w, h) = (width as f32, height as f32);
+        let columns = width.div_ceil(MESH_STEP).max(1) as usize + 1;
+        let rows = height.div_ceil(MESH_STEP).max(1) as usize + 1;
+        let grid: Vec<Option<Vertex>> = (0..rows)
+            .flat_map(|j| (0..columns).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                let u = w * i as f32 / (columns - 1) as f32;
+                let v = h * j as f32 / (rows - 1) as f32;
+                let (px, py) = pin.map_or(Some((u, v)), |pin| pin.map(u, v))?;
+                let (x, y) = mesh.map(px / w, py / h);
+                Some(Vertex {
+                    x: x * w,
+                    y: y * h,
+                    u,
+                    v,
+                })
+            })
+            .collect();
+        let triangles = (0..rows - 1)
+            .flat_map(|j| (0..columns - 1).map(move |i| (i, j)))
+            .filter_map(|(i, j)| {
+                let at = |i: usize, j: usize| grid[j * columns + i];
+                Some((at(i, j)?, at(i + 1, j)?, at(i + 1, j + 1)?, at(i, j + 1)?))
+            })
+            .flat_map(|(a, b, c, d)| [[a, b, c], [a, c, d]]);
+        WarpMap::from_triangles(width, height, width, height, triangles)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.map.is_some()
+    }
+
+    /// Warp a finished frame. None if there's no warp, so the frame can be
+    /// presented as it is.
+    pub fn apply(&mut self, content: &PixelBuffer) -> Option<&mut PixelBuffer> {
+        let map = self.map.as_ref()?;
+        map.apply(content, &mut self.frame);
+        Some(&mut self.frame)
+    }
+
+    /// The content point shown at output point (x, y), for mouse input.
+    /// Unchanged without a warp; None past the horizon of a steep one.
+    pub fn to_content(&self, x: f32, y: f32) -> Option<(f32, f32)> {
+        if self.mesh.is_some() {
+            let map = self.map.as_ref()?;
+            return map.source_at(x.floor() as i32, y.floor() as i32);
+        }
+        self.to_content.map_or(Some((x, y)), |h| h.map(x, y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corner_pin() {
+        let pin = CornerPin::default();
+        assert!(pin.is_identity() && pin.is_convex());
+        let skewed = CornerPin {
+            corners: [(0.1, 0.0), (0.9, 0.1), (1.0, 1.0), (0.0, 0.9)],
+        };
+        assert!(skewed.is_convex());
+        let twisted = CornerPin {
+            corners: [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)],
+        };
+        assert!(!twisted.is_convex());
+
+        let pin: CornerPin =
+            serde_json::from_str(r#"{"corners": [[0.1, 0], [0.9, 0.1], [1, 1], [0, 0.9]]}"#)
+                .unwrap();
+        assert_eq!(pin, skewed);
+        let h = pin.homography(200, 100).unwrap();
+        let (x, y) = h.map(200.0, 0.0).unwrap();
+        assert!((x - 180.0).abs() < 1e-3 && (y - 10.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_output_warp() {
+        let mut warp = OutputWarp::new();
+        let content = PixelBuffer::with_size(8, 8);
+        warp.update(None, None, 8, 8);
+        assert!(warp.apply(&content).is_none());
+        assert_eq!(warp.to_content(3.0, 4.0), Some((3.0, 4.0)));
+
+        // Squeeze the content into the right half: the left half goes black
+        let pin = CornerPin {
+            corners: [(0.5, 0.0), (1.0, 0.0), (1.0, 1.0), (0.5, 1.0)],
+        };
+        let mut content = PixelBuffer::with_size(8, 8);
+        content.clear(255, 255, 255);
+        warp.update(Some(&pin), None, 8, 8);
+        let frame = warp.apply(&content).unwrap();
+        assert_eq!(frame.get_pixel(1, 4), Some((0, 0, 0)));
+        assert_eq!(frame.get_pixel(6, 4), Some((255, 255, 255)));
+        let (x, y) = warp.to_content(6.0, 4.0).unwrap();
+        assert!((x - 4.0).abs() < 1e-3 && (y - 4.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_mesh_warp() {
+        // Squeeze the middle column of a mesh to the right: the left edge still
+        // shows the content's left edge, and a grid point lands where it was put
+        let mut mesh = WarpMesh::new(3, 3);
+        mesh.interpolation = Interpolation::Bilinear;
+        for j in 0..3 {
+            mesh.points[j * 3 + 1].0 = 0.75;
+        }
+        let mut content = PixelBuffer::with_size(16, 16);
+        content.clear(255, 255, 255);
+        let mut warp = OutputWarp::new();
+        warp.update(None, Some(&mesh), 16, 16);
+        assert!(warp.is_active());
+        let frame = warp.apply(&content).unwrap();
+        assert_eq!(frame.get_pixel(0, 8), Some((255, 255, 255)));
+        // Output x 12..16 shows content 8..16, so pixel 12's center is content 9
+        let (x, y) = warp.to_content(12.0, 8.0).unwrap();
+        assert!((x - 9.0).abs() < 0.01 && (y - 8.5).abs() < 0.01);
+
+        // A mesh pulled in from the edges leaves a black border
+        let mesh = WarpMesh {
+            points: WarpMesh::new(2, 2)
+                .points
+                .iter()
+                .map(|&(x, y)| (x * 0.5, y))
+                .collect(),
+            ..WarpMesh::new(2, 2)
+        };
+        warp.update(None, Some(&mesh), 16, 16);
+        assert_eq!(warp.to_content(12.0, 8.0), None);
+        let frame = warp.apply(&content).unwrap();
+        assert_eq!(frame.get_pixel(12, 8), Some((0, 0, 0)));
+    }
+}
@@ -0,0 +1,242 @@
+//! How a finished frame is laid onto its window
+//!
+//! A rotation followed by an optional mirror covers all eight ways a
+//! rectangle can be turned over: rear-projection screens want a horizontal
+//! flip, ceiling mounts a vertical one or a 180° turn, portrait setups 90°.
+
+use crate::display::PixelBuffer;
+use serde::{Deserialize, Serialize};
+
+/// Display rotation for portrait/landscape modes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rotation {
+    #[default]
+    None, // 0°
+    Cw90,  // 90° clockwise (portrait)
+    Cw180, // 180° (upside down)
+    Cw270, // 270° clockwise / 90° counter-clockwise (portrait)
+}
+
+/// Mirroring applied after rotation, in window space
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Flip {
+    #[default]
+    None,
+    /// Left and right swapped (rear projection)
+    Horizontal,
+    /// Top and bottom swapped
+    Vertical,
+}
+
+/// A rotation, then a flip
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Orientation {
+    pub rotation: Rotation,
+    pub flip: Flip,
+}
+
+/// Where frame pixel (0, 0) lands in the window, and the window steps for
+/// one frame pixel right and one down
+struct Axes {
+    origin: (i32, i32),
+    x: (i32, i32),
+    y: (i32, i32),
+}
+
+impl Orientation {
+    pub const fn new(rotation: Rotation, flip: Flip) -> Self {
+        Self { rotation, flip }
+    }
+
+    pub fn is_identity(self) -> bool {
+        self == Self::default()
+    }
+
+    /// Whether the window is as wide as the frame is tall
+    pub fn swaps_axes(self) -> bool {
+        matches!(self.rotation, Rotation::Cw90 | Rotation::Cw270)
+    }
+
+    /// Window size for a `width` x `height` frame
+    pub fn window_size(self, width: u32, height: u32) -> (u32, u32) {
+        if self.swaps_axes() {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    fn axes(self, width: u32, height: u32) -> Axes {
+        let (w, h) = (width as i32, height as i32);
+        let mut axes = match self.rotation {
+            // content(x, y) -> window(x, y)
+            Rotation::None => Axes {
+                origin: (0, 0),
+                x: (1, 0),
+                y: (0, 1),
+            },
+            // content(x, y) -> window(h-1-y, x)
+            Rotation::Cw90 => Axes {
+                origin: (h - 1, 0),
+                x: (0, 1),
+                y: (-1, 0),
+            },
+            // content(x, y) -> window(w-1-x, h-1-y)
+            Rotation::Cw180 => Axes {
+                origin: (w - 1, h - 1),
+                x: (-1, 0),
+                y: (0, -1),
+            },
+            // content(x, y) -> window(y, w-1-x)
+            Rotation::Cw270 => Axes {
+                origin: (0, w - 1),
+                x: (0, -1),
+                y: (1, 0),
+            },
+        };
+        let (window_w, window_h) = self.window_size(width, height);
+        match self.flip {
+            Flip::None => {},
+            Flip::Horizontal => {
+                axes.origin.0 = window_w as i32 - 1 - axes.origin.0;
+                axes.x.0 = -axes.x.0;
+                axes.y.0 = -axes.y.0;
+            },
+            Flip::Vertical => {
+                axes.origin.1 = window_h as i32 - 1 - axes.origin.1;
+                axes.x.1 = -axes.x.1;
+                axes.y.1 = -axes.y.1;
+            },
+        }
+        axes
+    }
+
+    /// Window pixel showing pixel (x, y) of a `width` x `height` frame
+    pub fn to_window(self, x: i32, y: i32, width: u32, height: u32) -> (i32, i32) {
+        let Axes {
+            origin,
+            x: ax,
+            y: ay,
+        } = self.axes(width, height);
+        (
+            origin.0 + x * ax.0 + y * ay.0,
+            origin.1 + x * ax.1 + y * ay.1,
+        )
+    }
+
+    /// Transform window coordinates to content coordinates: the frame pixel
+    /// shown at window pixel (wx, wy). The inverse of `to_window`.
+    pub fn transform_mouse(self, wx: i32, wy: i32, width: u32, height: u32) -> (i32, i32) {
+        let Axes {
+            origin,
+            x: ax,
+            y: ay,
+        } = self.axes(width, height);
+        // The axes are orthonormal, so the inverse is the transpose
+        let (dx, dy) = (wx - origin.0, wy - origin.1);
+        (dx * ax.0 + dy * ax.1, dx * ay.0 + dy * ay.1)
+    }
+
+    /// Transform a movement from window space to content space, so keys and
+    /// the mouse move things the way they point on the wall
+    pub fn transform_mouse_delta(self, dx: i32, dy: i32) -> (i32, i32) {
+        let Axes { x: ax, y: ay, .. } = self.axes(1, 1);
+        (dx * ax.0 + dy * ax.1, dx * ay.0 + dy * ay.1)
+    }
+
+    /// Lay `frame` out in `window` in a single pass, resizing `window` to fit
+    pub fn apply(self, frame: &PixelBuffer, window: &mut PixelBuffer) {
+        let (width, height) = (frame.width(), frame.height());
+        let (window_w, window_h) = self.window_size(width, height);
+        if (window.width(), window.height()) != (window_w, window_h) {
+            *window = PixelBuffer::with_size(window_w, window_h);
+        }
+        let Axes {
+            origin,
+            x: ax,
+            y: ay,
+        } = self.axes(width, height);
+        // Byte offsets in the window for one frame pixel right and one down
+        let stride = window_w as isize;
+        let step_x = (ax.1 as isize * stride + ax.0 as isize) * 4;
+        let step_y = (ay.1 as isize * stride + ay.0 as isize) * 4;
+        let mut row_start = (origin.1 as isize * stride + origin.0 as isize) * 4;
+
+        let dst = window.as_bytes_mut();
+        for row in frame.as_bytes().chunks_exact(width as usize * 4) {
+            let mut at = row_start;
+            for pixel in row.chunks_exact(4) {
+                dst[at as usize..at as usize + 4].copy_from_slice(pixel);
+                at += step_x;
+            }
+            row_start += step_y;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Orientation; 8] = [
+        Orientation::new(Rotation::None, Flip::None),
+        Orientation::new(Rotation::Cw90, Flip::None),
+        Orientation::new(Rotation::Cw180, Flip::None),
+        Orientation::new(Rotation::Cw270, Flip::None),
+        Orientation::new(Rotation::None, Flip::Horizontal),
+        Orientation::new(Rotation::Cw90, Flip::Horizontal),
+        Orientation::new(Rotation::None, Flip::Vertical),
+        Orientation::new(Rotation::Cw90, Flip::Vertical),
+    ];
+
+    #[test]
+    fn test_matches_rotated_buffers() {
+        let mut frame = PixelBuffer::with_size(3, 2);
+        for (i, (x, y)) in [(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]
+            .into_iter()
+            .enumerate()
+        {
+            frame.set_pixel(x, y, i as u8 * 40, 0, 0);
+        }
+        let mut window = PixelBuffer::with_size(0, 0);
+        let rotations = [
+            (Rotation::Cw90, frame.rotated_90()),
+            (Rotation::Cw180, frame.rotated_180()),
+            (Rotation::Cw270, frame.rotated_270()),
+        ];
+        for (rotation, expected) in rotations {
+            Orientation::new(rotation, Flip::None).apply(&frame, &mut window);
+            assert_eq!(window.as_bytes(), expected.as_bytes());
+        }
+
+        // Mirrored: the top-left pixel is now top-right
+        Orientation::new(Rotation::None, Flip::Horizontal).apply(&frame, &mut window);
+        assert_eq!(window.get_pixel(2, 0), Some((0, 0, 0)));
+        assert_eq!(window.get_pixel(0, 0), Some((80, 0, 0)));
+        assert_eq!(window.get_pixel(0, 1), Some((200, 0, 0)));
+    }
+
+    #[test]
+    fn test_mouse_round_trip() {
+        for orientation in ALL {
+            for (x, y) in [(0, 0), (5, 2), (9, 3)] {
+                let (wx, wy) = orientation.to_window(x, y, 10, 4);
+                let (ww, wh) = orientation.window_size(10, 4);
+                assert!(wx >= 0 && wy >= 0 && wx < ww as i32 && wy < wh as i32);
+                assert_eq!(orientation.transform_mouse(wx, wy, 10, 4), (x, y));
+            }
+            // A window step maps to the frame step that lands there
+            let (dx, dy) = orientation.transform_mouse_delta(1, 0);
+            let (ox, oy) = orientation.to_window(5, 2, 10, 4);
+            assert_eq!(orientation.to_window(5 + dx, 2 + dy, 10, 4), (ox + 1, oy));
+        }
+        // The old rotation-only mappings
+        let cw90 = Orientation::new(Rotation::Cw90, Flip::None);
+        assert_eq!(cw90.transform_mouse(1, 7, 10, 4), (7, 2));
+        assert_eq!(cw90.transform_mouse_delta(3, 5), (5, -3));
+        let cw270 = Orientation::new(Rotation::Cw270, Flip::None);
+        assert_eq!(cw270.transform_mouse_delta(3, 5), (-5, 3));
+    }
+}
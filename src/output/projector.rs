@@ -0,0 +1,356 @@
+//! Several outputs sharing one canvas
+//!
+//! Effects render one canvas (`--width` x `--height`). Each output shows a
+//! rectangle of it in its own borderless window, placed on that projector's
+//! part of the desktop, with its own warp, colour, rotation and flip. Where neighbouring
+//! projectors overlap, both fade their shared edge so the overlap is no
+//! brighter than the rest. A 3520x1080 canvas over two 1920x1080 projectors:
+//!
+//! ```json
+//! "outputs": [
+//!   { "name": "left", "source": [0, 0, 1920, 1080], "position": [0, 0],
+//!     "blend": { "right": 320 } },
+//!   { "name": "right", "source": [1600, 0, 1920, 1080], "position": [1920, 0],
+//!     "blend": { "left": 320 } }
+//! ]
+//! ```
+
+use super::{Flip, Orientation, OutputColor, OutputSettings, OutputWarp, Rotation};
+use crate::display::{PixelBuffer, WindowSpec};
+use serde::{Deserialize, Serialize};
+
+/// Fixed-point one for blend factors
+const ONE: u32 = 256;
+
+/// Overlap ramps at an output's edges
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EdgeBlend {
+    /// Overlap width in canvas pixels at each edge (0 = no blending)
+    pub left: u32,
+    pub right: u32,
+    pub top: u32,
+    pub bottom: u32,
+    /// Projector gamma: ramps are shaped in light, not pixel values, so two
+    /// overlapping projectors add up to full brightness
+    pub gamma: f32,
+    /// Ramp shape: 1 is a straight fade, higher values ease in and out
+    pub power: f32,
+}
+
+impl Default for EdgeBlend {
+    fn default() -> Self {
+        Self {
+            left: 0,
+            right: 0,
+            top: 0,
+            bottom: 0,
+            gamma: 2.2,
+            power: 2.0,
+        }
+    }
+}
+
+impl EdgeBlend {
+    pub fn is_none(&self) -> bool {
+        self.left == 0 && self.right == 0 && self.top == 0 && self.bottom == 0
+    }
+
+    /// Light let through `t` of the way across an overlap (0 = outer edge).
+    /// Symmetric, so `ramp(t) + ramp(1 - t) == 1` for the two projectors.
+    pub fn ramp(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        if t < 0.5 {
+            0.5 * (2.0 * t).powf(self.power)
+        } else {
+            1.0 - 0.5 * (2.0 * (1.0 - t)).powf(self.power)
+        }
+    }
+
+    /// Pixel-value factor for `t` of the way across an overlap
+    fn factor(&self, t: f32) -> f32 {
+        self.ramp(t).powf(1.0 / self.gamma.max(0.1))
+    }
+
+    /// Factors along one axis of `size` pixels, with overlaps `start` and `end`
+    fn axis(&self, size: u32, start: u32, end: u32) -> Vec<u32> {
+        (0..size)
+            .map(|i| {
+                let mut factor = 1.0;
+                if i < start {
+                    factor *= self.factor((i as f32 + 0.5) / start as f32);
+                }
+                if size - i <= end {
+                    factor *= self.factor(((size - i) as f32 - 0.5) / end as f32);
+                }
+                (factor * ONE as f32).round() as u32
+            })
+            .collect()
+    }
+}
+
+/// Precomputed blend factors: each pixel is scaled by its column's times its row's
+struct BlendMask {
+    columns: Vec<u32>,
+    rows: Vec<u32>,
+}
+
+impl BlendMask {
+    fn new(blend: &EdgeBlend, width: u32, height: u32) -> Option<Self> {
+        (!blend.is_none()).then(|| Self {
+            columns: blend.axis(width, blend.left, blend.right),
+            rows: blend.axis(height, blend.top, blend.bottom),
+        })
+    }
+
+    fn apply(&self, buffer: &mut PixelBuffer) {
+        if buffer.width() as usize != self.columns.len()
+            || buffer.height() as usize != self.rows.len()
+        {
+            return;
+        }
+        let stride = self.columns.len() * 4;
+        for (row, &fy) in buffer
+            .as_bytes_mut()
+            .chunks_exact_mut(stride)
+            .zip(&self.rows)
+        {
+            for (pixel, &fx) in row.chunks_exact_mut(4).zip(&self.columns) {
+                let factor = fx * fy;
+                if factor == ONE * ONE {
+                    continue;
+                }
+                // Byte 0 is alpha
+                for channel in &mut pixel[1..] {
+                    *channel = ((*channel as u32 * factor) >> 16) as u8;
+                }
+            }
+        }
+    }
+}
+
+/// One output window's share of the canvas
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputConfig {
+    /// Key for this output's settings in the scene ("" uses the scene's own)
+    #[serde(default)]
+    pub name: String,
+    /// Canvas rectangle shown: x, y, width, height in pixels
+    pub source: [u32; 4],
+    /// Desktop position of the borderless window (default: one centered window)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<(i32, i32)>,
+    #[serde(default)]
+    pub rotation: Rotation,
+    #[serde(default)]
+    pub flip: Flip,
+    #[serde(default)]
+    pub blend: EdgeBlend,
+}
+
+impl OutputConfig {
+    /// The whole canvas in one centered window
+    pub fn whole(width: u32, height: u32, orientation: Orientation) -> Self {
+        Self {
+            name: String::new(),
+            source: [0, 0, width, height],
+            position: None,
+            rotation: orientation.rotation,
+            flip: orientation.flip,
+            blend: EdgeBlend::default(),
+        }
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        Orientation::new(self.rotation, self.flip)
+    }
+
+    /// Window for this output: the source size, turned by the orientation
+    pub fn window(&self) -> WindowSpec {
+        let [_, _, width, height] = self.source;
+        let (width, height) = self.orientation().window_size(width, height);
+        WindowSpec {
+            position: self.position,
+            width,
+            height,
+        }
+    }
+}
+
+/// An output at run time: its config, warp, colour and scratch buffers
+pub struct Projector {
+    config: OutputConfig,
+    warp: OutputWarp,
+    color: OutputColor,
+    blend: Option<BlendMask>,
+    region: PixelBuffer,
+    /// The frame turned to suit the window
+    window: PixelBuffer,
+}
+
+impl Projector {
+    pub fn new(config: OutputConfig) -> Self {
+        let [_, _, width, height] = config.source;
+        Self {
+            blend: BlendMask::new(&config.blend, width, height),
+            region: PixelBuffer::with_size(0, 0),
+            window: PixelBuffer::with_size(0, 0),
+            warp: OutputWarp::new(),
+            color: OutputColor::new(),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &OutputConfig {
+        &self.config
+    }
+
+    pub fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    /// Frame size before orientation
+    pub fn size(&self) -> (u32, u32) {
+        (self.config.source[2], self.config.source[3])
+    }
+
+    /// Whether desktop point (x, y) falls in this output's window
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        let window = self.config.window();
+        let (wx, wy) = window.position.unwrap_or((0, 0));
+        x >= wx && y >= wy && x < wx + window.width as i32 && y < wy + window.height as i32
+    }
+
+    /// Follow the scene's warp and colour for this output. Cheap when nothing
+    /// changed; errors come from loading a LUT.
+    pub fn update(&mut self, settings: Option<&OutputSettings>) -> Result<(), String> {
+        let (width, height) = self.size();
+        self.warp.update_from(settings, width, height);
+        self.color
+            .update(settings.and_then(|settings| settings.color.as_ref()))
+    }
+
+    /// This output's window image: its part of the canvas colour corrected
+    /// with blended edges, warped, passed to `draw` (for calibration handles, which belong
+    /// on the warped frame), then rotated and flipped. Uses the canvas itself
+    /// when there's nothing to do.
+    pub fn render<'a>(
+        &'a mut self,
+        canvas: &'a mut PixelBuffer,
+        draw: impl FnOnce(&mut PixelBuffer),
+    ) -> &'a PixelBuffer {
+        let [x, y, width, height] = self.config.source;
+        let whole = (x, y, width, height) == (0, 0, canvas.width(), canvas.height());
+        let region = if whole && self.blend.is_none() && !self.color.is_active() {
+            canvas
+        } else {
+            if (self.region.width(), self.region.height()) != (width, height) {
+                self.region = PixelBuffer::with_size(width, height);
+            }
+            self.region.copy_rect_from(canvas, x as i32, y as i32);
+            // Blend ramps are shaped for the projector's light, so they go
+            // on after it's been matched to its neighbours
+            self.color.apply(&mut self.region);
+            if let Some(ref blend) = self.blend {
+                blend.apply(&mut self.region);
+            }
+            &mut self.region
+        };
+        let frame = self.warp.apply(region).unwrap_or(region);
+        draw(frame);
+        let orientation = self.config.orientation();
+        if orientation.is_identity() {
+            return frame;
+        }
+        orientation.apply(frame, &mut self.window);
+        &self.window
+    }
+
+    /// The canvas point shown at frame point (x, y), for mouse input
+    pub fn to_canvas(&self, x: f32, y: f32) -> Option<(f32, f32)> {
+        let (cx, cy) = self.warp.to_content(x, y)?;
+        Some((
+            cx + self.config.source[0] as f32,
+            cy + self.config.source[1] as f32,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::ColorCorrection;
+
+    #[test]
+    fn test_blend_ramp() {
+        let blend = EdgeBlend::default();
+        for t in [0.0, 0.1, 0.3, 0.5, 0.8] {
+            assert!((blend.ramp(t) + blend.ramp(1.0 - t) - 1.0).abs() < 1e-6);
+        }
+        assert_eq!((blend.ramp(0.0), blend.ramp(1.0)), (0.0, 1.0));
+
+        // Overlapping pixels are dimmed in pixel values by the gamma-corrected ramp
+        let blend = EdgeBlend {
+            right: 4,
+            power: 1.0,
+            ..EdgeBlend::default()
+        };
+        let mask = BlendMask::new(&blend, 8, 1).unwrap();
+        let mut buffer = PixelBuffer::with_size(8, 1);
+        buffer.clear(255, 255, 255);
+        mask.apply(&mut buffer);
+        assert_eq!(buffer.get_pixel(3, 0), Some((255, 255, 255)));
+        let (inner, _, _) = buffer.get_pixel(4, 0).unwrap();
+        let (outer, _, _) = buffer.get_pixel(7, 0).unwrap();
+        // Light 7/8 and 1/8 of full
+        assert!((inner as f32 / 255.0).powf(2.2) > 0.85);
+        assert!(((outer as f32 / 255.0).powf(2.2) - 0.125).abs() < 0.01);
+        assert!(BlendMask::new(&EdgeBlend::default(), 8, 1).is_none());
+    }
+
+    #[test]
+    fn test_projector() {
+        let config: OutputConfig = serde_json::from_str(
+            r#"{"name": "right", "source": [4, 0, 4, 2], "position": [640, 0],
+                "rotation": "cw90", "flip": "vertical"}"#,
+        )
+        .unwrap();
+        let window = config.window();
+        assert_eq!((window.width, window.height), (2, 4));
+        let mut projector = Projector::new(config);
+        assert!(projector.contains(641, 3) && !projector.contains(639, 0));
+
+        let mut canvas = PixelBuffer::with_size(8, 2);
+        canvas.set_pixel(5, 1, 200, 0, 0);
+        projector.update(None).unwrap();
+        let image = projector.render(&mut canvas, |frame| {
+            assert_eq!((frame.width(), frame.height()), (4, 2));
+            assert_eq!(frame.get_pixel(1, 1), Some((200, 0, 0)));
+        });
+        // Turned clockwise to (0, 1), then flipped top to bottom
+        assert_eq!((image.width(), image.height()), (2, 4));
+        assert_eq!(image.get_pixel(0, 2), Some((200, 0, 0)));
+        assert_eq!(projector.to_canvas(1.5, 1.5), Some((5.5, 1.5)));
+
+        // The whole canvas without blending is shown as it is
+        let mut whole = Projector::new(OutputConfig::whole(8, 2, Orientation::default()));
+        whole.update(None).unwrap();
+        let image = whole.render(&mut canvas, |_| {}) as *const PixelBuffer;
+        assert!(std::ptr::eq(image, &canvas));
+
+        // Unless it's colour corrected: the shared canvas is left alone
+        let settings = OutputSettings {
+            color: Some(ColorCorrection {
+                gain: [0.5, 1.0, 1.0],
+                ..ColorCorrection::default()
+            }),
+            ..OutputSettings::default()
+        };
+        whole.update(Some(&settings)).unwrap();
+        assert_eq!(
+            whole.render(&mut canvas, |_| {}).get_pixel(5, 1),
+            Some((100, 0, 0))
+        );
+        assert_eq!(canvas.get_pixel(5, 1), Some((200, 0, 0)));
+    }
+}
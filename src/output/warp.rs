@@ -0,0 +1,213 @@
+//! Precomputed output warps
+//!
+//! Any distortion of the finished frame is baked into a per-pixel lookup of
+//! where in the source each output pixel comes from, so applying it costs a
+//! bilinear sample per pixel however the map was made.
+
+use crate::display::PixelBuffer;
+
+/// Fixed-point fraction bits in the stored source positions
+const FRACTION_BITS: u32 = 8;
+const ONE: i32 = 1 << FRACTION_BITS;
+/// Marks an output pixel with no source (drawn black)
+const OUTSIDE: (i32, i32) = (i32::MIN, i32::MIN);
+
+/// A triangle corner for `WarpMap::from_triangles`: output point (x, y)
+/// shows source point (u, v)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vertex {
+    pub x: f32,
+    pub y: f32,
+    pub u: f32,
+    pub v: f32,
+}
+
+/// Source position for every pixel of a `width` x `height` output
+pub struct WarpMap {
+    width: u32,
+    height: u32,
+    src_width: u32,
+    src_height: u32,
+    /// Top-left sample position in fixed point, row-major
+    taps: Vec<(i32, i32)>,
+}
+
+impl WarpMap {
+    /// Build a map from a function giving the source point (in pixels) seen at
+    /// each output point, or None where there's nothing to show. Both are
+    /// continuous coordinates: pixel (0, 0) covers 0..1, its center is 0.5.
+    pub fn from_fn(
+        width: u32,
+        height: u32,
+        src_width: u32,
+        src_height: u32,
+        source: impl Fn(f32, f32) -> Option<(f32, f32)>,
+    ) -> Self {
+        let (sw, sh) = (src_width as f32, src_height as f32);
+        let mut taps = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let tap = source(x as f32 + 0.5, y as f32 + 0.5)
+                    .filter(|&(u, v)| u >= 0.0 && u <= sw && v >= 0.0 && v <= sh)
+                    .map_or(OUTSIDE, |(u, v)| {
+                        (
+                            ((u - 0.5) * ONE as f32).round() as i32,
+                            ((v - 0.5) * ONE as f32).round() as i32,
+                        )
+                    });
+                taps.push(tap);
+            }
+        }
+        Self {
+            width,
+            height,
+            src_width,
+            src_height,
+            taps,
+        }
+    }
+
+    /// Build a map by drawing triangles onto the output. Each corner is an
+    /// output point and the source point that should show there, in pixels;
+    /// sources are interpolated across each triangle. Where triangles overlap
+    /// the last one wins, and pixels no triangle covers are black.
+    pub fn from_triangles(
+        width: u32,
+        height: u32,
+        src_width: u32,
+        src_height: u32,
+        triangles: impl IntoIterator<Item = [Vertex; 3]>,
+    ) -> Self {
+        let mut taps = vec![OUTSIDE; (width * height) as usize];
+        for [a, b, c] in triangles {
+            let area = (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
+            if area.abs() < 1e-6 {
+                continue;
+            }
+            // Pixel centers inside the triangle's bounding box
+            let x0 = (a.x.min(b.x).min(c.x) - 0.5).ceil().max(0.0) as u32;
+            let y0 = (a.y.min(b.y).min(c.y) - 0.5).ceil().max(0.0) as u32;
+            let x1 = ((a.x.max(b.x).max(c.x) - 0.5).floor() as i64).min(width as i64 - 1);
+            let y1 = ((a.y.max(b.y).max(c.y) - 0.5).floor() as i64).min(height as i64 - 1);
+            for y in y0 as i64..=y1 {
+                for x in x0 as i64..=x1 {
+                    let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                    // Barycentric weights; all non-negative inside
+                    let wa = ((b.x - px) * (c.y - py) - (b.y - py) * (c.x - px)) / area;
+                    let wb = ((c.x - px) * (a.y - py) - (c.y - py) * (a.x - px)) / area;
+                    let wc = 1.0 - wa - wb;
+                    if wa < -1e-4 || wb < -1e-4 || wc < -1e-4 {
+                        continue;
+                    }
+                    let u = a.u * wa + b.u * wb + c.u * wc;
+                    let v = a.v * wa + b.v * wb + c.v * wc;
+                    taps[(y as u32 * width + x as u32) as usize] = (
+                        ((u - 0.5) * ONE as f32).round() as i32,
+                        ((v - 0.5) * ONE as f32).round() as i32,
+                    );
+                }
+            }
+        }
+        Self {
+            width,
+            height,
+            src_width,
+            src_height,
+            taps,
+        }
+    }
+
+    /// Source point (in pixels) shown at output pixel (x, y), if any
+    pub fn source_at(&self, x: i32, y: i32) -> Option<(f32, f32)> {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return None;
+        }
+        let (tx, ty) = self.taps[(y as u32 * self.width + x as u32) as usize];
+        ((tx, ty) != OUTSIDE).then(|| (tx as f32 / ONE as f32 + 0.5, ty as f32 / ONE as f32 + 0.5))
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Warp `src` into `dst`. Both must match the sizes the map was built for.
+    pub fn apply(&self, src: &PixelBuffer, dst: &mut PixelBuffer) {
+        if src.width() != self.src_width
+            || src.height() != self.src_height
+            || dst.width() != self.width
+            || dst.height() != self.height
+        {
+            return;
+        }
+        let (max_x, max_y) = (self.src_width as i32 - 1, self.src_height as i32 - 1);
+        let stride = self.src_width as usize * 4;
+        let pixels = src.as_bytes();
+        for (out, &(tx, ty)) in dst.as_bytes_mut().chunks_exact_mut(4).zip(&self.taps) {
+            if (tx, ty) == OUTSIDE {
+                out.copy_from_slice(&[255, 0, 0, 0]);
+                continue;
+            }
+            // Clamp the 2x2 neighbourhood to the edges
+            let (x0, y0) = (tx >> FRACTION_BITS, ty >> FRACTION_BITS);
+            let (fx, fy) = (tx & (ONE - 1), ty & (ONE - 1));
+            let (x1, y1) = ((x0 + 1).clamp(0, max_x), (y0 + 1).clamp(0, max_y));
+            let (x0, y0) = (x0.clamp(0, max_x), y0.clamp(0, max_y));
+            let row0 = y0 as usize * stride;
+            let row1 = y1 as usize * stride;
+            let (c0, c1) = (x0 as usize * 4, x1 as usize * 4);
+            for (i, channel) in out.iter_mut().enumerate() {
+                let top =
+                    pixels[row0 + c0 + i] as i32 * (ONE - fx) + pixels[row0 + c1 + i] as i32 * fx;
+                let bottom =
+                    pixels[row1 + c0 + i] as i32 * (ONE - fx) + pixels[row1 + c1 + i] as i32 * fx;
+                let value = top * (ONE - fy) + bottom * fy;
+                *channel = ((value + (1 << (FRACTION_BITS * 2 - 1))) >> (FRACTION_BITS * 2)) as u8;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identity_and_shift() {
+        let mut src = PixelBuffer::with_size(4, 2);
+        for x in 0..4 {
+            src.set_pixel(x, 0, (x * 80) as u8, 0, 0);
+            src.set_pixel(x, 1, 0, (x * 80) as u8, 0);
+        }
+        let mut dst = PixelBuffer::with_size(4, 2);
+
+        WarpMap::from_fn(4, 2, 4, 2, |x, y| Some((x, y))).apply(&src, &mut dst);
+        assert_eq!(dst.as_bytes(), src.as_bytes());
+
+        // Half a pixel to the right blends neighbours; off the edge is black
+        WarpMap::from_fn(4, 2, 4, 2, |x, y| Some((x + 0.5, y))).apply(&src, &mut dst);
+        assert_eq!(dst.get_pixel(0, 0), Some((40, 0, 0)));
+        assert_eq!(dst.get_pixel(3, 1), Some((0, 240, 0)));
+        WarpMap::from_fn(4, 2, 4, 2, |x, y| (x < 2.0).then_some((x + 3.0, y)))
+            .apply(&src, &mut dst);
+        assert_eq!(dst.get_pixel(1, 0), Some((0, 0, 0)));
+        assert_eq!(dst.get_pixel(2, 0), Some((0, 0, 0)));
+        assert_eq!(dst.get_pixel(0, 1), Some((0, 240, 0)));
+    }
+
+    #[test]
+    fn test_triangles() {
+        // Two triangles covering the left half of a 4x2 output show the whole source
+        let vertex = |x, y, u, v| Vertex { x, y, u, v };
+        let (tl, tr) = (vertex(0.0, 0.0, 0.0, 0.0), vertex(2.0, 0.0, 4.0, 0.0));
+        let (br, bl) = (vertex(2.0, 2.0, 4.0, 2.0), vertex(0.0, 2.0, 0.0, 2.0));
+        let map = WarpMap::from_triangles(4, 2, 4, 2, [[tl, tr, br], [tl, br, bl]]);
+        assert_eq!(map.source_at(0, 0), Some((1.0, 0.5)));
+        assert_eq!(map.source_at(1, 1), Some((3.0, 1.5)));
+        assert_eq!(map.source_at(2, 0), None);
+        assert_eq!(map.source_at(-1, 0), None);
+    }
+}
@@ -0,0 +1,561 @@
+//! Wall-clock time for overlay widgets
+//!
+//! Converts Unix time to a local calendar date and time without any
+//! dependencies. The local UTC offset comes from `TZ` (a zone name, `:path`
+//! or POSIX rule) or else `/etc/localtime` (TZif), including the POSIX rule
+//! at the end of TZif files for dates past their transition table, and falls
+//! back to UTC. Formatting supports a small `strftime` subset.
+
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DAY: i64 = 86_400;
+
+/// Where zone names in `TZ` are looked up
+const ZONEINFO: &str = "/usr/share/zoneinfo";
+
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// Current Unix time in seconds
+pub fn now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0.0, |d| d.as_secs_f64())
+}
+
+/// Calendar date and time of day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: i64,
+    /// 1-12
+    pub month: u32,
+    /// 1-31
+    pub day: u32,
+    /// 0 = Sunday
+    pub weekday: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+}
+
+impl DateTime {
+    /// Date and time `offset` seconds ahead of UTC
+    pub fn from_unix(unix: i64, offset: i64) -> Self {
+        let local = unix + offset;
+        let days = local.div_euclid(DAY);
+        let secs = local.rem_euclid(DAY) as u32;
+        let (year, month, day) = civil_from_days(days);
+        Self {
+            year,
+            month,
+            day,
+            weekday: weekday(days),
+            hour: secs / 3600,
+            minute: secs / 60 % 60,
+            second: secs % 60,
+        }
+    }
+
+    /// Local date and time, using the system time zone
+    pub fn local(unix: i64) -> Self {
+        Self::from_unix(unix, local_offset(unix))
+    }
+
+    /// Seconds since local midnight
+    pub fn seconds_of_day(&self) -> u32 {
+        self.hour * 3600 + self.minute * 60 + self.second
+    }
+
+    /// Format with `strftime`-style fields: `%H %I %M %S %p %Y %y %m %d %e
+    /// %A %a %B %b %%`. Anything else is copied as is.
+    pub fn format(&self, pattern: &str) -> String {
+        let mut out = String::new();
+        let mut chars = pattern.chars();
+        while let Some(ch) = chars.next() {
+            if ch != '%' {
+                out.push(ch);
+                continue;
+            }
+            let weekday = WEEKDAYS[self.weekday as usize];
+            let month = MONTHS[self.month as usize - 1];
+            match chars.next() {
+                Some('H') => write!(out, "{:02}", self.hour).unwrap_or_default(),
+                Some('I') => write!(out, "{:02}", (self.hour + 11) % 12 + 1).unwrap_or_default(),
+                Some('M') => write!(out, "{:02}", self.minute).unwrap_or_default(),
+                Some('S') => write!(out, "{:02}", self.second).unwrap_or_default(),
+                Some('p') => out += if self.hour < 12 { "AM" } else { "PM" },
+                Some('Y') => write!(out, "{}", self.year).unwrap_or_default(),
+                Some('y') => write!(out, "{:02}", self.year.rem_euclid(100)).unwrap_or_default(),
+                Some('m') => write!(out, "{:02}", self.month).unwrap_or_default(),
+                Some('d') => write!(out, "{:02}", self.day).unwrap_or_default(),
+                Some('e') => write!(out, "{}", self.day).unwrap_or_default(),
+                Some('A') => out += weekday,
+                Some('a') => out += &weekday[..3],
+                Some('B') => out += month,
+                Some('b') => out += &month[..3],
+                Some('%') => out.push('%'),
+                Some(other) => {
+                    out.push('%');
+                    out.push(other);
+                },
+                None => out.push('%'),
+            }
+        }
+        out
+    }
+}
+
+/// Days since 1970-01-01 for a date (proleptic Gregorian)
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let month = i64::from(month);
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Date for a count of days since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Day of the week for a count of days since 1970-01-01 (a Thursday)
+fn weekday(days: i64) -> u32 {
+    (days + 4).rem_euclid(7) as u32
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    let next = if month == 12 {
+        days_from_civil(year + 1, 1, 1)
+    } else {
+        days_from_civil(year, month + 1, 1)
+    };
+    (next - days_from_civil(year, month, 1)) as u32
+}
+
+/// Seconds ahead of UTC for the system time zone at a moment
+pub fn local_offset(unix: i64) -> i64 {
+    static ZONE: OnceLock<Option<TimeZone>> = OnceLock::new();
+    ZONE.get_or_init(|| TimeZone::from_tz(env::var("TZ").ok().as_deref()))
+        .as_ref()
+        .map_or(0, |zone| zone.offset_at(unix))
+}
+
+/// A time zone read from a TZif file or a POSIX rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeZone {
+    /// Transition times and the offset that starts at each
+    transitions: Vec<(i64, i64)>,
+    /// Offset before the first transition
+    initial: i64,
+    /// Rule for times after the last transition
+    rule: Option<PosixRule>,
+}
+
+impl TimeZone {
+    /// The zone a `TZ` value names, read the way glibc does: unset is
+    /// `/etc/localtime` and empty is UTC (`None`). `:path` or a name under
+    /// /usr/share/zoneinfo is a TZif file; otherwise it's a POSIX rule.
+    pub fn from_tz(tz: Option<&str>) -> Option<Self> {
+        let Some(tz) = tz else {
+            return Self::read(Path::new("/etc/localtime"));
+        };
+        if let Some(file) = tz.strip_prefix(':') {
+            return Self::read(&Path::new(ZONEINFO).join(file));
+        }
+        if tz.is_empty() {
+            return None;
+        }
+        Self::read(&Path::new(ZONEINFO).join(tz)).or_else(|| {
+            let rule = PosixRule::parse(tz)?;
+            Some(Self {
+                transitions: Vec::new(),
+                initial: rule.std_offset,
+                rule: Some(rule),
+            })
+        })
+    }
+
+    /// Read a TZif file
+    fn read(path: &Path) -> Option<Self> {
+        fs::read(path).ok().and_then(|data| Self::parse(&data))
+    }
+
+    /// Parse TZif data (version 1, or the 64-bit block of version 2+)
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let header = Header::read(data)?;
+        let (header, body, time_size) = if header.version >= b'2' {
+            let rest = data.get(44 + header.block_len(4)..)?;
+            (Header::read(rest)?, rest.get(44..)?, 8)
+        } else {
+            (header, data.get(44..)?, 4)
+        };
+
+        let read_time = |at: usize| -> Option<i64> {
+            let bytes = body.get(at..at + time_size)?;
+            Some(if time_size == 8 {
+                i64::from_be_bytes(bytes.try_into().ok()?)
+            } else {
+                i64::from(i32::from_be_bytes(bytes.try_into().ok()?))
+            })
+        };
+        let index_start = header.time_count * time_size;
+        let type_start = index_start + header.time_count;
+        let type_offset = |index: usize| -> Option<i64> {
+            let at = type_start + index * 6;
+            let bytes = body.get(at..at + 4)?;
+            Some(i64::from(i32::from_be_bytes(bytes.try_into().ok()?)))
+        };
+
+        let transitions = (0..header.time_count)
+            .map(|i| {
+                let index = *body.get(index_start + i)?;
+                Some((read_time(i * time_size)?, type_offset(usize::from(index))?))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let initial = type_offset(0)?;
+
+        // Version 2+ files end with a POSIX TZ string between newlines
+        let rule = if time_size == 8 {
+            let footer = body.get(header.block_len(8)..)?;
+            let footer = std::str::from_utf8(footer).ok()?;
+            footer
+                .trim_matches('\n')
+                .lines()
+                .next()
+                .and_then(PosixRule::parse)
+        } else {
+            None
+        };
+
+        Some(Self {
+            transitions,
+            initial,
+            rule,
+        })
+    }
+
+    /// Seconds ahead of UTC at a moment
+    pub fn offset_at(&self, unix: i64) -> i64 {
+        match self.transitions.last() {
+            Some(&(last, _)) if unix >= last && self.rule.is_some() => {
+                self.rule.as_ref().map_or(0, |rule| rule.offset_at(unix))
+            },
+            None => self
+                .rule
+                .as_ref()
+                .map_or(self.initial, |rule| rule.offset_at(unix)),
+            Some(_) => {
+                let after = self.transitions.partition_point(|&(at, _)| at <= unix);
+                after
+                    .checked_sub(1)
+                    .map_or(self.initial, |i| self.transitions[i].1)
+            },
+        }
+    }
+}
+
+/// Counts from a TZif header
+struct Header {
+    version: u8,
+    is_ut_count: usize,
+    is_std_count: usize,
+    leap_count: usize,
+    time_count: usize,
+    type_count: usize,
+    char_count: usize,
+}
+
+impl Header {
+    fn read(data: &[u8]) -> Option<Self> {
+        if data.get(..4)? != b"TZif" {
+            return None;
+        }
+        let count = |i: usize| -> Option<usize> {
+            let at = 20 + i * 4;
+            Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?) as usize)
+        };
+        Some(Self {
+            version: *data.get(4)?,
+            is_ut_count: count(0)?,
+            is_std_count: count(1)?,
+            leap_count: count(2)?,
+            time_count: count(3)?,
+            type_count: count(4)?,
+            char_count: count(5)?,
+        })
+    }
+
+    /// Length of the data block after the header, with `time_size`-byte times
+    fn block_len(&self, time_size: usize) -> usize {
+        self.time_count * (time_size + 1)
+            + self.type_count * 6
+            + self.char_count
+            + self.leap_count * (time_size + 4)
+            + self.is_std_count
+            + self.is_ut_count
+    }
+}
+
+/// A POSIX TZ rule like `EST5EDT,M3.2.0,M11.1.0` (only `M` date rules)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PosixRule {
+    /// Seconds ahead of UTC outside daylight saving time
+    std_offset: i64,
+    /// Daylight saving offset and the rules for its start and end
+    dst: Option<(i64, DateRule, DateRule)>,
+}
+
+/// Month, week (5 = last) and weekday of a change, and the local time it happens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DateRule {
+    month: u32,
+    week: u32,
+    weekday: u32,
+    time: i64,
+}
+
+impl PosixRule {
+    pub fn parse(tz: &str) -> Option<Self> {
+        let mut rest = tz;
+        skip_name(&mut rest)?;
+        let std_offset = -parse_offset(&mut rest)?;
+        if rest.is_empty() {
+            return Some(Self {
+                std_offset,
+                dst: None,
+            });
+        }
+        skip_name(&mut rest)?;
+        let dst_offset = if rest.starts_with(',') {
+            std_offset + 3600
+        } else {
+            -parse_offset(&mut rest)?
+        };
+        let mut rules = rest.strip_prefix(',')?.split(',');
+        let start = DateRule::parse(rules.next()?)?;
+        let end = DateRule::parse(rules.next()?)?;
+        Some(Self {
+            std_offset,
+            dst: Some((dst_offset, start, end)),
+        })
+    }
+
+    pub fn offset_at(&self, unix: i64) -> i64 {
+        let Some((dst_offset, start, end)) = self.dst else {
+            return self.std_offset;
+        };
+        let year = DateTime::from_unix(unix, self.std_offset).year;
+        // Changes happen at local time: the start in standard time, the end in DST
+        let start = start.local_time(year) - self.std_offset;
+        let end = end.local_time(year) - dst_offset;
+        let in_dst = if start < end {
+            unix >= start && unix < end
+        } else {
+            // Southern hemisphere: DST spans the new year
+            unix >= start || unix < end
+        };
+        if in_dst {
+            dst_offset
+        } else {
+            self.std_offset
+        }
+    }
+}
+
+impl DateRule {
+    /// `Mm.w.d[/time]`
+    fn parse(rule: &str) -> Option<Self> {
+        let (date, time) = rule.split_once('/').unwrap_or((rule, "2"));
+        let mut fields = date.strip_prefix('M')?.split('.');
+        let mut field = || fields.next()?.parse::<u32>().ok();
+        let (month, week, weekday) = (field()?, field()?, field()?);
+        if !(1..=12).contains(&month) || !(1..=5).contains(&week) || weekday > 6 {
+            return None;
+        }
+        let mut time = time;
+        Some(Self {
+            month,
+            week,
+            weekday,
+            time: parse_offset(&mut time)?,
+        })
+    }
+
+    /// Seconds since the epoch, in local time, of the change in `year`
+    fn local_time(self, year: i64) -> i64 {
+        let first = days_from_civil(year, self.month, 1);
+        let mut day = (self.weekday + 7 - weekday(first)) % 7 + (self.week - 1) * 7;
+        while day >= days_in_month(year, self.month) {
+            day -= 7;
+        }
+        (first + i64::from(day)) * DAY + self.time
+    }
+}
+
+/// Skip a zone abbreviation: letters, or anything in angle brackets
+fn skip_name(rest: &mut &str) -> Option<()> {
+    let len = rest.strip_prefix('<').map_or_else(
+        || {
+            Some(
+                rest.find(|c: char| !c.is_ascii_alphabetic())
+                    .unwrap_or(rest.len()),
+            )
+        },
+        |quoted| Some(quoted.find('>')? + 2),
+    )?;
+    if len < 3 {
+        return None;
+    }
+    *rest = &rest[len..];
+    Some(())
+}
+
+/// `[+-]hh[:mm[:ss]]` in seconds, consumed from the front of `rest`
+fn parse_offset(rest: &mut &str) -> Option<i64> {
+    let end = rest
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, ':' | '+' | '-')))
+        .unwrap_or(rest.len());
+    let (text, tail) = rest.split_at(end);
+    *rest = tail;
+    let (sign, text) = text.strip_prefix('-').map_or_else(
+        || (1, text.strip_prefix('+').unwrap_or(text)),
+        |text| (-1, text),
+    );
+    let mut seconds = 0;
+    for (i, part) in text.split(':').enumerate() {
+        if i > 2 {
+            return None;
+        }
+        seconds += part.parse::<i64>().ok()? * [3600, 60, 1][i];
+    }
+    Some(sign * seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_civil_dates() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        let time = DateTime::from_unix(1_782_990_245, 0);
+        assert_eq!(
+            time.format("%a %e %b %Y %H:%M:%S %I%p"),
+            "Thu 2 Jul 2026 11:04:05 11AM"
+        );
+        assert_eq!(
+            DateTime::from_unix(-1, 0).format("%Y-%m-%d %H"),
+            "1969-12-31 23"
+        );
+        assert_eq!(days_in_month(2024, 2), 29);
+    }
+
+    #[test]
+    fn test_posix_rule() {
+        let rule = PosixRule::parse("EST5EDT,M3.2.0,M11.1.0").unwrap();
+        // DST in 2026 runs from 8 March 07:00 UTC to 1 November 06:00 UTC
+        let start = days_from_civil(2026, 3, 8) * DAY + 7 * 3600;
+        let end = days_from_civil(2026, 11, 1) * DAY + 6 * 3600;
+        assert_eq!(rule.offset_at(start - 1), -5 * 3600);
+        assert_eq!(rule.offset_at(start), -4 * 3600);
+        assert_eq!(rule.offset_at(end - 1), -4 * 3600);
+        assert_eq!(rule.offset_at(end), -5 * 3600);
+
+        // Southern hemisphere, with a quoted name and an explicit change time
+        let rule = PosixRule::parse("<+1030>-10:30<+11>-11,M10.1.0,M4.1.0").unwrap();
+        let january = days_from_civil(2026, 1, 15) * DAY;
+        let july = days_from_civil(2026, 7, 15) * DAY;
+        assert_eq!(rule.offset_at(january), 11 * 3600);
+        assert_eq!(rule.offset_at(july), 10 * 3600 + 1800);
+        assert_eq!(PosixRule::parse("UTC0").unwrap().offset_at(july), 0);
+    }
+
+    #[test]
+    fn test_tzif() {
+        // Version 2 file with one transition and a rule for later dates
+        fn build(time_size: usize, times: &[i64], footer: &str) -> Vec<u8> {
+            let mut out = b"TZif2".to_vec();
+            out.extend([0; 15]);
+            for count in [0, 0, 0, times.len() as u32, 2, 4] {
+                out.extend(count.to_be_bytes());
+            }
+            for &t in times {
+                if time_size == 8 {
+                    out.extend(t.to_be_bytes());
+                } else {
+                    out.extend((t as i32).to_be_bytes());
+                }
+            }
+            out.extend(times.iter().map(|_| 1u8));
+            out.extend((3600i32).to_be_bytes());
+            out.extend([0, 0]);
+            out.extend((7200i32).to_be_bytes());
+            out.extend([1, 0]);
+            out.extend(b"AB\0\0");
+            out.extend(footer.as_bytes());
+            out
+        }
+        let mut data = build(4, &[1000], "");
+        data.extend(build(8, &[1000], "\nCET-1CEST,M3.5.0,M10.5.0/3\n"));
+        let zone = TimeZone::parse(&data).unwrap();
+        assert_eq!(zone.offset_at(0), 3600);
+        let july = days_from_civil(2026, 7, 1) * DAY;
+        assert_eq!(zone.offset_at(july), 7200);
+        let december = days_from_civil(2026, 12, 1) * DAY;
+        assert_eq!(zone.offset_at(december), 3600);
+    }
+
+    #[test]
+    fn test_tz_variable() {
+        // A POSIX rule that isn't a zone file name
+        let zone = TimeZone::from_tz(Some("<+0530>-5:30")).unwrap();
+        assert_eq!(zone.offset_at(0), 5 * 3600 + 1800);
+        let zone = TimeZone::from_tz(Some("EST5EDT,M3.2.0,M11.1.0")).unwrap();
+        let july = days_from_civil(2026, 7, 1) * DAY;
+        assert_eq!(zone.offset_at(july), -4 * 3600);
+
+        // Empty is UTC, and a missing file isn't read as a rule
+        assert!(TimeZone::from_tz(Some("")).is_none());
+        assert!(TimeZone::from_tz(Some(":/nonexistent/UTC0")).is_none());
+    }
+}
@@ -0,0 +1,270 @@
+//! Overlay widgets: clocks, countdowns, text, images and QR codes
+//!
+//! Widgets come from the config file's `overlays` list and can be added,
+//! replaced, shown, hidden and removed by ID over the control socket. They
+//! are drawn over the effect and chyrons, under the region masks, each in a
+//! box placed by an anchor point on the screen. See `widget` for the format.
+
+mod clock;
+mod qr;
+mod widget;
+
+#[allow(unused_imports)]
+pub use qr::{EcLevel, QrCode};
+#[allow(unused_imports)]
+pub use widget::{Anchor, Schedule, Widget, WidgetKind, WidgetStyle};
+
+use crate::display::{Font, PixelBuffer};
+use crate::texture::Texture;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::surface::Surface;
+use std::path::Path;
+
+/// Quiet zone around a QR code, in modules
+const QR_QUIET_ZONE: usize = 4;
+
+/// What a widget draws this frame
+enum Content {
+    Text(String),
+    Image(Option<Texture>),
+    Qr(Option<QrCode>),
+}
+
+impl Content {
+    /// Load or encode what a widget needs up front (text is set each update)
+    fn new(widget: &Widget) -> Self {
+        match &widget.kind {
+            WidgetKind::Image { path } => Content::Image(
+                load_bmp(path)
+                    .map_err(|e| eprintln!("Overlay '{}': {}: {}", widget.id, path.display(), e))
+                    .ok(),
+            ),
+            WidgetKind::Qr { data, ec_level, .. } => Content::Qr(
+                QrCode::encode(data, *ec_level)
+                    .map_err(|e| eprintln!("Overlay '{}': {}", widget.id, e))
+                    .ok(),
+            ),
+            _ => Content::Text(String::new()),
+        }
+    }
+}
+
+struct Live {
+    widget: Widget,
+    content: Content,
+    visible: bool,
+}
+
+/// Every overlay widget and what it's showing
+pub struct Overlay {
+    widgets: Vec<Live>,
+    font: Font,
+}
+
+impl Overlay {
+    pub fn new(widgets: &[Widget]) -> Self {
+        let mut overlay = Self {
+            widgets: Vec::new(),
+            font: Font::default(),
+        };
+        for widget in widgets {
+            overlay.set(widget.clone());
+        }
+        overlay
+    }
+
+    /// Add a widget, replacing any with the same ID in place
+    pub fn set(&mut self, widget: Widget) {
+        let live = Live {
+            content: Content::new(&widget),
+            widget,
+            visible: false,
+        };
+        match self
+            .widgets
+            .iter_mut()
+            .find(|l| l.widget.id == live.widget.id)
+        {
+            Some(existing) => *existing = live,
+            None => self.widgets.push(live),
+        }
+    }
+
+    /// Remove a widget by ID. Returns false if there was none.
+    pub fn remove(&mut self, id: &str) -> bool {
+        let count = self.widgets.len();
+        self.widgets.retain(|l| l.widget.id != id);
+        self.widgets.len() != count
+    }
+
+    /// Show or hide a widget by ID. Returns false if there was none.
+    pub fn set_enabled(&mut self, id: &str, enabled: bool) -> bool {
+        self.widgets
+            .iter_mut()
+            .find(|l| l.widget.id == id)
+            .map(|l| l.widget.enabled = enabled)
+            .is_some()
+    }
+
+    /// Refresh each widget's visibility and text for the current time
+    pub fn update(&mut self) {
+        self.update_at(clock::now());
+    }
+
+    fn update_at(&mut self, now: f64) {
+        for live in &mut self.widgets {
+            live.visible = live.widget.enabled && live.widget.schedule.is_active(now);
+            if let Content::Text(ref mut text) = live.content {
+                match live.widget.text_at(now) {
+                    Some(current) => *text = current,
+                    None => live.visible = false,
+                }
+            }
+        }
+    }
+
+    pub fn render(&self, buffer: &mut PixelBuffer) {
+        let screen_height = buffer.height() as f32;
+        for live in self.widgets.iter().filter(|l| l.visible) {
+            let widget = &live.widget;
+            let size = (widget.style.size * screen_height).max(1.0);
+            let padding = (widget.style.padding * size).round() as i32;
+
+            // Content size in pixels
+            let (width, height) = match &live.content {
+                Content::Text(text) => {
+                    let scale = size / self.font.height(1.0) as f32;
+                    (self.font.text_width(text, scale), self.font.height(scale))
+                },
+                Content::Image(Some(image)) => {
+                    let height = size.round() as u32;
+                    let width = image.width() * height / image.height().max(1);
+                    (width, height)
+                },
+                Content::Qr(Some(qr)) => {
+                    let side = qr_module_size(qr, size) * (qr.size() + QR_QUIET_ZONE * 2) as u32;
+                    (side, side)
+                },
+                Content::Image(None) | Content::Qr(None) => continue,
+            };
+
+            // Box placed by its anchor
+            let (fx, fy) = widget.anchor.fractions();
+            let box_width = width as i32 + padding * 2;
+            let box_height = height as i32 + padding * 2;
+            let left = (widget.x * buffer.width() as f32 - fx * box_width as f32).round() as i32;
+            let top = (widget.y * screen_height - fy * box_height as f32).round() as i32;
+            if let Some((r, g, b, a)) = widget.style.background {
+                for y in top..top + box_height {
+                    buffer.hline_blend(left, left + box_width - 1, y, r, g, b, a);
+                }
+            }
+            let (x, y) = (left + padding, top + padding);
+
+            match &live.content {
+                Content::Text(text) => {
+                    let (r, g, b) = widget.style.color;
+                    let scale = size / self.font.height(1.0) as f32;
+                    self.font.draw_text(buffer, x, y, text, r, g, b, scale);
+                },
+                Content::Image(Some(image)) => draw_image(buffer, image, x, y, width, height),
+                Content::Qr(Some(qr)) => {
+                    let WidgetKind::Qr { dark, light, .. } = widget.kind else {
+                        continue;
+                    };
+                    draw_qr(buffer, qr, x, y, qr_module_size(qr, size), dark, light);
+                },
+                Content::Image(None) | Content::Qr(None) => {},
+            }
+        }
+    }
+}
+
+/// Whole pixels per module so a QR code and its quiet zone fit in `size`
+fn qr_module_size(qr: &QrCode, size: f32) -> u32 {
+    ((size / (qr.size() + QR_QUIET_ZONE * 2) as f32) as u32).max(1)
+}
+
+fn draw_qr(
+    buffer: &mut PixelBuffer,
+    qr: &QrCode,
+    x: i32,
+    y: i32,
+    module: u32,
+    dark: (u8, u8, u8),
+    light: (u8, u8, u8),
+) {
+    let side = module * (qr.size() + QR_QUIET_ZONE * 2) as u32;
+    buffer.fill_rect(x, y, side, side, light.0, light.1, light.2);
+    let origin = (QR_QUIET_ZONE as u32 * module) as i32;
+    for my in 0..qr.size() {
+        for mx in 0..qr.size() {
+            if qr.is_dark(mx, my) {
+                buffer.fill_rect(
+                    x + origin + (mx as u32 * module) as i32,
+                    y + origin + (my as u32 * module) as i32,
+                    module,
+                    module,
+                    dark.0,
+                    dark.1,
+                    dark.2,
+                );
+            }
+        }
+    }
+}
+
+/// Draw an image scaled to `width` x `height`, blending by its alpha
+fn draw_image(buffer: &mut PixelBuffer, image: &Texture, x: i32, y: i32, width: u32, height: u32) {
+    for dy in 0..height {
+        let v = (dy as f32 + 0.5) / height as f32;
+        for dx in 0..width {
+            let u = (dx as f32 + 0.5) / width as f32;
+            let (r, g, b, a) = image.sample_rgba(u, v);
+            buffer.blend_pixel(x + dx as i32, y + dy as i32, r, g, b, a);
+        }
+    }
+}
+
+/// Load a BMP file through SDL
+fn load_bmp(path: &Path) -> Result<Texture, String> {
+    let surface = Surface::load_bmp(path)?.convert_format(PixelFormatEnum::RGBA32)?;
+    let (width, height) = (surface.width(), surface.height());
+    let pitch = surface.pitch() as usize;
+    let row = width as usize * 4;
+    let data = surface.with_lock(|pixels| {
+        pixels
+            .chunks(pitch)
+            .take(height as usize)
+            .flat_map(|line| &line[..row])
+            .copied()
+            .collect::<Vec<u8>>()
+    });
+    Texture::from_rgba(width, height, data).ok_or_else(|| "Bad image size".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_replace_and_toggle() {
+        let text = |id: &str, text: &str| {
+            Widget::new(
+                id,
+                WidgetKind::Text {
+                    text: text.to_string(),
+                },
+            )
+        };
+        let mut overlay = Overlay::new(&[text("a", "one"), text("b", "two")]);
+        overlay.set(text("a", "uno"));
+        assert_eq!(overlay.widgets.len(), 2);
+        assert!(overlay.set_enabled("b", false));
+        assert!(!overlay.set_enabled("missing", false));
+        overlay.update_at(0.0);
+        assert!(matches!(&overlay.widgets[0].content, Content::Text(t) if t == "uno"));
+        assert!(overlay.widgets[0].visible && !overlay.widgets[1].visible);
+        assert!(overlay.remove("a") && !overlay.remove("a"));
+    }
+}
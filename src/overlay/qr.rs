@@ -0,0 +1,618 @@
+//! QR code encoder
+//!
+//! Encodes text as a QR code (ISO/IEC 18004) in byte mode, choosing the
+//! smallest version from 1 to 40 that fits and the mask with the lowest
+//! penalty. Error correction is raised above the requested level when it
+//! costs no extra size.
+
+use serde::{Deserialize, Serialize};
+
+/// How much damage a code can survive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+pub enum EcLevel {
+    /// About 7% of codewords recoverable
+    L,
+    /// About 15%
+    #[default]
+    M,
+    /// About 25%
+    Q,
+    /// About 30%
+    H,
+}
+
+impl EcLevel {
+    const ALL: [EcLevel; 4] = [EcLevel::L, EcLevel::M, EcLevel::Q, EcLevel::H];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Value in the format information
+    fn format_bits(self) -> u32 {
+        match self {
+            EcLevel::L => 1,
+            EcLevel::M => 0,
+            EcLevel::Q => 3,
+            EcLevel::H => 2,
+        }
+    }
+}
+
+#[rustfmt::skip]
+const ECC_CODEWORDS_PER_BLOCK: [[u8; 41]; 4] = [
+    [0, 7, 10, 15, 20, 26, 18, 20, 24, 30, 18, 20, 24, 26, 30, 22, 24, 28, 30, 28, 28, 28, 28, 30, 30, 26, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+    [0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28],
+    [0, 13, 22, 18, 26, 18, 24, 18, 22, 20, 24, 28, 26, 24, 20, 30, 24, 28, 28, 26, 30, 28, 30, 30, 30, 30, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+    [0, 17, 28, 22, 16, 22, 28, 26, 26, 24, 28, 24, 28, 22, 24, 24, 30, 28, 28, 26, 28, 30, 24, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+];
+
+#[rustfmt::skip]
+const ERROR_CORRECTION_BLOCKS: [[u8; 41]; 4] = [
+    [0, 1, 1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 4, 4, 4, 6, 6, 6, 6, 7, 8, 8, 9, 9, 10, 12, 12, 12, 13, 14, 15, 16, 17, 18, 19, 19, 20, 21, 22, 24, 25],
+    [0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21, 23, 25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49],
+    [0, 1, 1, 2, 2, 4, 4, 6, 6, 8, 8, 8, 10, 12, 16, 12, 17, 16, 18, 21, 20, 23, 23, 25, 27, 29, 34, 34, 35, 38, 40, 43, 45, 48, 51, 53, 56, 59, 62, 65, 68],
+    [0, 1, 1, 2, 4, 4, 4, 5, 6, 8, 8, 11, 11, 16, 16, 18, 16, 19, 21, 25, 25, 25, 34, 30, 32, 35, 37, 40, 42, 45, 48, 51, 54, 57, 60, 63, 66, 70, 74, 77, 81],
+];
+
+/// Penalty weights for mask selection
+const PENALTY_RUN: usize = 3;
+const PENALTY_BLOCK: usize = 3;
+const PENALTY_FINDER: usize = 40;
+const PENALTY_BALANCE: usize = 10;
+
+/// An encoded QR code: a square of dark and light modules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QrCode {
+    version: u8,
+    ec_level: EcLevel,
+    mask: u8,
+    size: usize,
+    modules: Vec<bool>,
+    /// Finder, timing, alignment, format and version modules
+    function: Vec<bool>,
+}
+
+impl QrCode {
+    /// Encode text in byte mode at `min_level` or better.
+    /// Fails if it's too long for version 40.
+    pub fn encode(text: &str, min_level: EcLevel) -> Result<Self, String> {
+        let data = text.as_bytes();
+        let bits_needed = |version: u8| 4 + count_bits(version) + data.len() * 8;
+        let version = (1..=40)
+            .find(|&v| bits_needed(v) <= data_codewords(v, min_level) * 8)
+            .ok_or_else(|| format!("{} bytes is too long for a QR code", data.len()))?;
+        let ec_level = EcLevel::ALL
+            .into_iter()
+            .filter(|&level| level >= min_level)
+            .filter(|&level| bits_needed(version) <= data_codewords(version, level) * 8)
+            .max()
+            .unwrap_or(min_level);
+
+        // Mode, length, data, terminator and padding
+        let capacity = data_codewords(version, ec_level) * 8;
+        let mut bits = BitBuffer::default();
+        bits.push(0b0100, 4);
+        bits.push(data.len() as u32, count_bits(version));
+        for &byte in data {
+            bits.push(u32::from(byte), 8);
+        }
+        bits.push(0, (capacity - bits.len()).min(4));
+        bits.push(0, (8 - bits.len() % 8) % 8);
+        let mut codewords = bits.into_bytes();
+        for pad in [0xEC, 0x11].into_iter().cycle() {
+            if codewords.len() * 8 >= capacity {
+                break;
+            }
+            codewords.push(pad);
+        }
+
+        let size = usize::from(version) * 4 + 17;
+        let mut qr = Self {
+            version,
+            ec_level,
+            mask: 0,
+            size,
+            modules: vec![false; size * size],
+            function: vec![false; size * size],
+        };
+        qr.draw_function_patterns();
+        qr.draw_codewords(&add_ecc_and_interleave(&codewords, version, ec_level));
+
+        // Keep the mask with the lowest penalty
+        let mask = (0..8)
+            .min_by_key(|&mask| {
+                qr.apply_mask(mask);
+                qr.draw_format_bits(mask);
+                let penalty = qr.penalty();
+                qr.apply_mask(mask);
+                penalty
+            })
+            .unwrap_or(0);
+        qr.apply_mask(mask);
+        qr.draw_format_bits(mask);
+        qr.mask = mask;
+        Ok(qr)
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn ec_level(&self) -> EcLevel {
+        self.ec_level
+    }
+
+    pub fn mask(&self) -> u8 {
+        self.mask
+    }
+
+    /// Modules per side, without the quiet zone
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Whether a module is dark (false outside the code)
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        x < self.size && y < self.size && self.modules[y * self.size + x]
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+        self.function[y * self.size + x] = true;
+    }
+
+    fn draw_function_patterns(&mut self) {
+        let size = self.size;
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+
+        for (cx, cy) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            for dy in -4i32..=4 {
+                for dx in -4i32..=4 {
+                    let (x, y) = (cx as i32 + dx, cy as i32 + dy);
+                    if (0..size as i32).contains(&x) && (0..size as i32).contains(&y) {
+                        let dist = dx.abs().max(dy.abs());
+                        self.set_function(x as usize, y as usize, dist != 2 && dist != 4);
+                    }
+                }
+            }
+        }
+
+        // Alignment patterns everywhere except over the finders
+        let positions = alignment_positions(self.version);
+        let last = positions.len().saturating_sub(1);
+        for (i, &cx) in positions.iter().enumerate() {
+            for (j, &cy) in positions.iter().enumerate() {
+                if (i, j) == (0, 0) || (i, j) == (0, last) || (i, j) == (last, 0) {
+                    continue;
+                }
+                for dy in -2i32..=2 {
+                    for dx in -2i32..=2 {
+                        let dist = dx.abs().max(dy.abs());
+                        let (x, y) = ((cx as i32 + dx) as usize, (cy as i32 + dy) as usize);
+                        self.set_function(x, y, dist != 1);
+                    }
+                }
+            }
+        }
+
+        // Reserve the format areas; the real bits go in once the mask is chosen
+        self.draw_format_bits(0);
+
+        if self.version >= 7 {
+            let bits = version_bits(self.version);
+            for i in 0..18 {
+                let dark = (bits >> i) & 1 != 0;
+                let (a, b) = (size - 11 + i % 3, i / 3);
+                self.set_function(a, b, dark);
+                self.set_function(b, a, dark);
+            }
+        }
+    }
+
+    fn draw_format_bits(&mut self, mask: u8) {
+        let bits = format_bits(self.ec_level, mask);
+        let bit = |i: usize| (bits >> i) & 1 != 0;
+        let size = self.size;
+
+        // Around the top-left finder
+        for i in 0..=5 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        // Split between the other two finders
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    /// Place data bits in the zigzag order, two columns at a time from the right
+    fn draw_codewords(&mut self, data: &[u8]) {
+        let size = self.size;
+        let mut i = 0;
+        let mut right = size - 1;
+        loop {
+            if right == 6 {
+                right = 5;
+            }
+            for vert in 0..size {
+                for j in 0..2 {
+                    let x = right - j;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward { size - 1 - vert } else { vert };
+                    if !self.function[y * size + x] && i < data.len() * 8 {
+                        self.modules[y * size + x] = (data[i / 8] >> (7 - i % 8)) & 1 != 0;
+                        i += 1;
+                    }
+                }
+            }
+            if right < 2 {
+                break;
+            }
+            right -= 2;
+        }
+    }
+
+    /// XOR a mask pattern over the data modules (applying twice undoes it)
+    fn apply_mask(&mut self, mask: u8) {
+        let size = self.size;
+        for y in 0..size {
+            for x in 0..size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                let index = y * size + x;
+                self.modules[index] ^= invert && !self.function[index];
+            }
+        }
+    }
+
+    /// How hard the code is to scan: long runs, solid blocks, finder
+    /// look-alikes and an uneven dark/light balance all count against it
+    fn penalty(&self) -> usize {
+        let size = self.size;
+        let rows = (0..size).map(|y| (0..size).map(|x| self.is_dark(x, y)).collect::<Vec<_>>());
+        let columns = (0..size).map(|x| (0..size).map(|y| self.is_dark(x, y)).collect::<Vec<_>>());
+        let finder = [
+            true, false, true, true, true, false, true, false, false, false, false,
+        ];
+        let mut finder_reversed = finder;
+        finder_reversed.reverse();
+
+        let mut penalty = 0;
+        for line in rows.chain(columns) {
+            let mut run = 1;
+            for i in 1..=line.len() {
+                if i < line.len() && line[i] == line[i - 1] {
+                    run += 1;
+                    continue;
+                }
+                if run >= 5 {
+                    penalty += PENALTY_RUN + run - 5;
+                }
+                run = 1;
+            }
+            penalty += line
+                .windows(finder.len())
+                .filter(|w| *w == finder || *w == finder_reversed)
+                .count()
+                * PENALTY_FINDER;
+        }
+
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let dark = self.is_dark(x, y);
+                if self.is_dark(x + 1, y) == dark
+                    && self.is_dark(x, y + 1) == dark
+                    && self.is_dark(x + 1, y + 1) == dark
+                {
+                    penalty += PENALTY_BLOCK;
+                }
+            }
+        }
+
+        let total = size * size;
+        let dark = self.modules.iter().filter(|&&m| m).count();
+        let k = (dark * 20)
+            .abs_diff(total * 10)
+            .div_ceil(total)
+            .saturating_sub(1);
+        penalty + k * PENALTY_BALANCE
+    }
+}
+
+/// Bits in the character count for byte mode
+fn count_bits(version: u8) -> usize {
+    if version < 10 {
+        8
+    } else {
+        16
+    }
+}
+
+/// Modules available for data and error correction
+fn raw_data_modules(version: u8) -> usize {
+    let v = usize::from(version);
+    let mut result = (16 * v + 128) * v + 64;
+    if v >= 2 {
+        let align = v / 7 + 2;
+        result -= (25 * align - 10) * align - 55;
+        if v >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+/// Data codewords (excluding error correction) for a version and level
+fn data_codewords(version: u8, level: EcLevel) -> usize {
+    let v = usize::from(version);
+    raw_data_modules(version) / 8
+        - usize::from(ECC_CODEWORDS_PER_BLOCK[level.index()][v])
+            * usize::from(ERROR_CORRECTION_BLOCKS[level.index()][v])
+}
+
+/// Centres of the alignment patterns along each axis
+fn alignment_positions(version: u8) -> Vec<usize> {
+    if version == 1 {
+        return Vec::new();
+    }
+    let v = usize::from(version);
+    let count = v / 7 + 2;
+    let step = if v == 32 {
+        26
+    } else {
+        (v * 4 + count * 2 + 1) / (count * 2 - 2) * 2
+    };
+    let size = v * 4 + 17;
+    let mut positions: Vec<usize> = (0..count - 1).map(|i| size - 7 - i * step).collect();
+    positions.push(6);
+    positions.reverse();
+    positions
+}
+
+/// 15-bit format information: level and mask with BCH error correction
+fn format_bits(level: EcLevel, mask: u8) -> u32 {
+    let data = level.format_bits() << 3 | u32::from(mask);
+    let mut rem = data;
+    for _ in 0..10 {
+        rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+    }
+    (data << 10 | rem) ^ 0x5412
+}
+
+/// 18-bit version information for versions 7 and up
+fn version_bits(version: u8) -> usize {
+    let version = usize::from(version);
+    let mut rem = version;
+    for _ in 0..12 {
+        rem = (rem << 1) ^ ((rem >> 11) * 0x1F25);
+    }
+    version << 12 | rem
+}
+
+/// Split data into blocks, add Reed-Solomon error correction to each and
+/// interleave them
+fn add_ecc_and_interleave(data: &[u8], version: u8, level: EcLevel) -> Vec<u8> {
+    let v = usize::from(version);
+    let blocks = usize::from(ERROR_CORRECTION_BLOCKS[level.index()][v]);
+    let ecc_len = usize::from(ECC_CODEWORDS_PER_BLOCK[level.index()][v]);
+    let raw = raw_data_modules(version) / 8;
+    let short_blocks = blocks - raw % blocks;
+    let short_len = raw / blocks;
+
+    let divisor = reed_solomon_divisor(ecc_len);
+    let mut start = 0;
+    let encoded: Vec<Vec<u8>> = (0..blocks)
+        .map(|i| {
+            let len = short_len - ecc_len + usize::from(i >= short_blocks);
+            let chunk = &data[start..start + len];
+            start += len;
+            let mut block = chunk.to_vec();
+            // Short blocks get a placeholder so every block lines up
+            if i < short_blocks {
+                block.push(0);
+            }
+            block.extend(reed_solomon_remainder(chunk, &divisor));
+            block
+        })
+        .collect();
+
+    let mut result = Vec::with_capacity(raw);
+    for i in 0..encoded[0].len() {
+        for (j, block) in encoded.iter().enumerate() {
+            if i != short_len - ecc_len || j >= short_blocks {
+                result.push(block[i]);
+            }
+        }
+    }
+    result
+}
+
+/// Generator polynomial for `degree` error correction codewords
+fn reed_solomon_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0; degree];
+    result[degree - 1] = 1;
+    let mut root = 1;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = gf_multiply(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = gf_multiply(root, 0x02);
+    }
+    result
+}
+
+fn reed_solomon_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0; divisor.len()];
+    for &byte in data {
+        let factor = byte ^ result.remove(0);
+        result.push(0);
+        for (r, &d) in result.iter_mut().zip(divisor) {
+            *r ^= gf_multiply(d, factor);
+        }
+    }
+    result
+}
+
+/// Multiply in GF(2^8) modulo x^8 + x^4 + x^3 + x^2 + 1
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut z: u32 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x11D);
+        z ^= u32::from((y >> i) & 1) * u32::from(x);
+    }
+    z as u8
+}
+
+/// Bits appended most significant first
+#[derive(Default)]
+struct BitBuffer {
+    bits: Vec<bool>,
+}
+
+impl BitBuffer {
+    fn push(&mut self, value: u32, count: usize) {
+        self.bits
+            .extend((0..count).rev().map(|i| (value >> i) & 1 != 0));
+    }
+
+    fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.bits
+            .chunks(8)
+            .map(|byte| byte.iter().fold(0, |acc, &bit| acc << 1 | u8::from(bit)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Read the codewords back out of a finished code
+    fn read_codewords(qr: &QrCode) -> Vec<u8> {
+        let mut unmasked = qr.clone();
+        unmasked.apply_mask(qr.mask);
+        let size = qr.size;
+        let mut bits = BitBuffer::default();
+        let mut right = size - 1;
+        loop {
+            if right == 6 {
+                right = 5;
+            }
+            for vert in 0..size {
+                for j in 0..2 {
+                    let x = right - j;
+                    let y = if (right + 1) & 2 == 0 {
+                        size - 1 - vert
+                    } else {
+                        vert
+                    };
+                    if !unmasked.function[y * size + x] {
+                        bits.push(u32::from(unmasked.is_dark(x, y)), 1);
+                    }
+                }
+            }
+            if right < 2 {
+                break;
+            }
+            right -= 2;
+        }
+        let mut bytes = bits.into_bytes();
+        bytes.truncate(raw_data_modules(qr.version) / 8);
+        bytes
+    }
+
+    #[test]
+    fn test_tables() {
+        // Known capacities in bytes for byte mode
+        assert_eq!(data_codewords(1, EcLevel::L), 19);
+        assert_eq!(data_codewords(1, EcLevel::H), 9);
+        assert_eq!(data_codewords(40, EcLevel::L), 2956);
+        assert_eq!(data_codewords(40, EcLevel::H), 1276);
+        assert_eq!(alignment_positions(7), vec![6, 22, 38]);
+        assert_eq!(alignment_positions(32), vec![6, 34, 60, 86, 112, 138]);
+        assert_eq!(format_bits(EcLevel::L, 0), 0b111011111000100);
+        assert_eq!(format_bits(EcLevel::M, 0), 0b101010000010010);
+        assert_eq!(version_bits(7), 0b000111110010010100);
+    }
+
+    #[test]
+    fn test_reed_solomon() {
+        // "HELLO WORLD" 1-M example from the standard's annex
+        let data = [
+            0x20, 0x5B, 0x0B, 0x78, 0xD1, 0x72, 0xDC, 0x4D, 0x43, 0x40, 0xEC, 0x11, 0xEC, 0x11,
+            0xEC, 0x11,
+        ];
+        let ecc = reed_solomon_remainder(&data, &reed_solomon_divisor(10));
+        assert_eq!(
+            ecc,
+            [0xC4, 0x23, 0x27, 0x77, 0xEB, 0xD7, 0xE7, 0xE2, 0x5D, 0x17]
+        );
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let text = "https://example.com/events/2389?ref=wall";
+        let qr = QrCode::encode(text, EcLevel::M).unwrap();
+        assert_eq!(qr.version(), 3);
+        assert_eq!(qr.size(), 29);
+
+        // Finder corners and the dark module
+        assert!(qr.is_dark(0, 0) && qr.is_dark(28, 0) && qr.is_dark(0, 28));
+        assert!(!qr.is_dark(7, 7) && qr.is_dark(8, 21));
+
+        // Both copies of the format information agree
+        let bits = format_bits(qr.ec_level(), qr.mask());
+        for i in 0..6 {
+            let bit = (bits >> i) & 1 != 0;
+            assert_eq!(qr.is_dark(8, i), bit);
+            assert_eq!(qr.is_dark(28 - i, 8), bit);
+        }
+
+        // One block at version 3-M: data then its error correction
+        let codewords = read_codewords(&qr);
+        let data_len = data_codewords(qr.version(), qr.ec_level());
+        let (data, ecc) = codewords.split_at(data_len);
+        let divisor = reed_solomon_divisor(ecc.len());
+        assert_eq!(reed_solomon_remainder(data, &divisor), ecc);
+        assert_eq!(data[0] >> 4, 0b0100);
+        let len = usize::from(data[0] << 4 | data[1] >> 4);
+        assert_eq!(len, text.len());
+        let decoded: Vec<u8> = (0..len)
+            .map(|i| data[i + 1] << 4 | data[i + 2] >> 4)
+            .collect();
+        assert_eq!(decoded, text.as_bytes());
+
+        assert!(QrCode::encode(&"x".repeat(3000), EcLevel::L).is_err());
+        let big = QrCode::encode(&"x".repeat(2900), EcLevel::L).unwrap();
+        assert_eq!(big.version(), 40);
+    }
+}
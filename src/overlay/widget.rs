@@ -0,0 +1,357 @@
+//! Overlay widget format
+//!
+//! Only `id` and `type` are required; placement, style and schedule fields
+//! sit alongside the type's own:
+//!
+//! ```json
+//! {
+//!   "id": "showtime",
+//!   "type": "Countdown",
+//!   "at": 1793563200,
+//!   "text": "Doors in {}",
+//!   "done": "Doors open!",
+//!   "x": 0.5, "y": 0.9, "anchor": "bottom",
+//!   "size": 0.08,
+//!   "color": [255, 220, 0],
+//!   "background": [0, 0, 0, 160],
+//!   "daily": ["18:00", "02:00"]
+//! }
+//! ```
+//!
+//! Positions and sizes are fractions of the screen height (x of the width),
+//! so a layout works at any resolution. Times are Unix timestamps; daily
+//! windows are local time and may wrap past midnight.
+
+use super::clock::DateTime;
+use super::qr::EcLevel;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// What a widget shows
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum WidgetKind {
+    /// Time of day, formatted with `%H %M %S %I %p` and friends
+    Clock {
+        #[serde(default = "default_clock_format")]
+        format: String,
+        /// Hours ahead of UTC (default: the system time zone)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        utc_offset: Option<f32>,
+    },
+    /// Today's date, formatted with `%A %e %B %Y` and friends
+    Date {
+        #[serde(default = "default_date_format")]
+        format: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        utc_offset: Option<f32>,
+    },
+    /// Time left until a moment, like `2d 03:04:05` or `03:04:05`
+    Countdown {
+        /// Unix timestamp to count down to
+        at: f64,
+        /// `{}` is replaced with the time left
+        #[serde(default = "default_countdown_text")]
+        text: String,
+        /// Shown once the time is up (hidden if empty)
+        #[serde(default)]
+        done: String,
+    },
+    Text {
+        text: String,
+    },
+    /// A BMP file, scaled to `size`
+    Image {
+        path: PathBuf,
+    },
+    /// A QR code for `data`, scaled to `size` including its quiet zone
+    Qr {
+        data: String,
+        /// Minimum error correction (raised when it doesn't cost size)
+        #[serde(default)]
+        ec_level: EcLevel,
+        #[serde(default = "default_qr_dark")]
+        dark: (u8, u8, u8),
+        #[serde(default = "default_qr_light")]
+        light: (u8, u8, u8),
+    },
+}
+
+fn default_clock_format() -> String {
+    "%H:%M".to_string()
+}
+
+fn default_date_format() -> String {
+    "%A %e %B".to_string()
+}
+
+fn default_countdown_text() -> String {
+    "{}".to_string()
+}
+
+fn default_qr_dark() -> (u8, u8, u8) {
+    (0, 0, 0)
+}
+
+fn default_qr_light() -> (u8, u8, u8) {
+    (255, 255, 255)
+}
+
+/// Which point of the widget sits at its position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    #[default]
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Fraction of the widget's width and height to the left of and above the anchor
+    pub fn fractions(self) -> (f32, f32) {
+        let x = match self {
+            Anchor::TopLeft | Anchor::Left | Anchor::BottomLeft => 0.0,
+            Anchor::Top | Anchor::Center | Anchor::Bottom => 0.5,
+            Anchor::TopRight | Anchor::Right | Anchor::BottomRight => 1.0,
+        };
+        let y = match self {
+            Anchor::TopLeft | Anchor::Top | Anchor::TopRight => 0.0,
+            Anchor::Left | Anchor::Center | Anchor::Right => 0.5,
+            Anchor::BottomLeft | Anchor::Bottom | Anchor::BottomRight => 1.0,
+        };
+        (x, y)
+    }
+}
+
+/// Size and colors
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WidgetStyle {
+    /// Content height as a fraction of the screen height
+    #[serde(default = "default_size")]
+    pub size: f32,
+    /// Text color
+    #[serde(default = "default_color")]
+    pub color: (u8, u8, u8),
+    /// Box behind the widget, RGBA (none if unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background: Option<(u8, u8, u8, u8)>,
+    /// Space between the content and the box edge, as a fraction of `size`
+    #[serde(default = "default_padding")]
+    pub padding: f32,
+}
+
+fn default_size() -> f32 {
+    0.06
+}
+
+fn default_color() -> (u8, u8, u8) {
+    (255, 255, 255)
+}
+
+fn default_padding() -> f32 {
+    0.25
+}
+
+impl Default for WidgetStyle {
+    fn default() -> Self {
+        Self {
+            size: default_size(),
+            color: default_color(),
+            background: None,
+            padding: default_padding(),
+        }
+    }
+}
+
+/// When a widget is shown; every condition that's set must hold
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Schedule {
+    /// Unix timestamp to appear at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<f64>,
+    /// Unix timestamp to disappear at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<f64>,
+    /// Local start and end times each day, as "HH:MM"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily: Option<(String, String)>,
+}
+
+impl Schedule {
+    /// Whether the widget should be showing at Unix time `now`
+    pub fn is_active(&self, now: f64) -> bool {
+        if self.from.is_some_and(|from| now < from) || self.until.is_some_and(|until| now >= until)
+        {
+            return false;
+        }
+        let Some((start, end)) = &self.daily else {
+            return true;
+        };
+        let (Some(start), Some(end)) = (parse_time_of_day(start), parse_time_of_day(end)) else {
+            return true;
+        };
+        let time = DateTime::local(now as i64).seconds_of_day();
+        if start <= end {
+            time >= start && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+}
+
+/// "HH:MM" or "HH:MM:SS" in seconds since midnight
+fn parse_time_of_day(text: &str) -> Option<u32> {
+    let mut seconds = 0;
+    for (i, part) in text.trim().split(':').enumerate() {
+        let value: u32 = part.parse().ok()?;
+        let (limit, unit) = [(24, 3600), (60, 60), (60, 1)].get(i).copied()?;
+        if value >= limit {
+            return None;
+        }
+        seconds += value * unit;
+    }
+    Some(seconds)
+}
+
+/// A configured overlay widget
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Widget {
+    /// Widgets are replaced, shown and hidden by ID
+    pub id: String,
+    #[serde(flatten)]
+    pub kind: WidgetKind,
+    /// Anchor position as fractions of the screen width and height
+    #[serde(default = "default_position")]
+    pub x: f32,
+    #[serde(default = "default_position")]
+    pub y: f32,
+    #[serde(default)]
+    pub anchor: Anchor,
+    #[serde(flatten)]
+    pub style: WidgetStyle,
+    #[serde(flatten)]
+    pub schedule: Schedule,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_position() -> f32 {
+    0.5
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Widget {
+    /// A widget in the middle of the screen with the default style
+    pub fn new(id: impl Into<String>, kind: WidgetKind) -> Self {
+        Self {
+            id: id.into(),
+            kind,
+            x: default_position(),
+            y: default_position(),
+            anchor: Anchor::default(),
+            style: WidgetStyle::default(),
+            schedule: Schedule::default(),
+            enabled: true,
+        }
+    }
+
+    /// Parse the JSON format: {"id": "clock", "type": "Clock", ...}
+    pub fn from_json(raw: &str) -> Result<Self, String> {
+        serde_json::from_str(raw).map_err(|e| e.to_string())
+    }
+
+    /// Text to draw at Unix time `now`, for text-like widgets.
+    /// None for images and QR codes, and for finished countdowns with no `done` text.
+    pub fn text_at(&self, now: f64) -> Option<String> {
+        let local = |offset: Option<f32>| {
+            let unix = now.floor() as i64;
+            offset.map_or_else(
+                || DateTime::local(unix),
+                |hours| DateTime::from_unix(unix, (hours * 3600.0) as i64),
+            )
+        };
+        match &self.kind {
+            WidgetKind::Clock { format, utc_offset } | WidgetKind::Date { format, utc_offset } => {
+                Some(local(*utc_offset).format(format))
+            },
+            WidgetKind::Countdown { at, text, done } => {
+                let left = (at - now).ceil();
+                if left <= 0.0 {
+                    return (!done.is_empty()).then(|| done.clone());
+                }
+                Some(text.replace("{}", &format_duration(left as u64)))
+            },
+            WidgetKind::Text { text } => Some(text.clone()),
+            WidgetKind::Image { .. } | WidgetKind::Qr { .. } => None,
+        }
+    }
+}
+
+/// `2d 03:04:05`, `03:04:05` or `04:05`
+fn format_duration(seconds: u64) -> String {
+    let (days, hours) = (seconds / 86_400, seconds / 3600 % 24);
+    let (minutes, seconds) = (seconds / 60 % 60, seconds % 60);
+    if days > 0 {
+        format!("{}d {:02}:{:02}:{:02}", days, hours, minutes, seconds)
+    } else if hours > 0 {
+        format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}", minutes, seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_widget_json() {
+        let widget = Widget::from_json(
+            r#"{"id": "doors", "type": "Countdown", "at": 1000, "text": "Doors in {}",
+                "anchor": "bottom", "y": 0.9, "background": [0, 0, 0, 160], "until": 2000}"#,
+        )
+        .unwrap();
+        assert_eq!(widget.anchor, Anchor::Bottom);
+        assert_eq!((widget.x, widget.y), (0.5, 0.9));
+        assert_eq!(widget.style.background, Some((0, 0, 0, 160)));
+        assert_eq!(widget.style.size, default_size());
+        assert_eq!(widget.schedule.until, Some(2000.0));
+        assert_eq!(widget.text_at(0.0).as_deref(), Some("Doors in 16:40"));
+        assert_eq!(widget.text_at(999.5).as_deref(), Some("Doors in 00:01"));
+        assert_eq!(widget.text_at(1000.0), None);
+        assert_eq!(format_duration(2 * 86_400 + 3723), "2d 01:02:03");
+
+        let clock = Widget::new(
+            "clock",
+            WidgetKind::Clock {
+                format: "%H:%M:%S".to_string(),
+                utc_offset: Some(-5.0),
+            },
+        );
+        assert_eq!(clock.text_at(3661.9).as_deref(), Some("20:01:01"));
+    }
+
+    #[test]
+    fn test_schedule() {
+        let schedule = Schedule {
+            from: Some(100.0),
+            until: Some(200.0),
+            daily: None,
+        };
+        assert!(!schedule.is_active(99.0));
+        assert!(schedule.is_active(100.0));
+        assert!(!schedule.is_active(200.0));
+        assert_eq!(parse_time_of_day("18:30"), Some(66_600));
+        assert_eq!(parse_time_of_day("24:00"), None);
+    }
+}
@@ -0,0 +1,166 @@
+//! Boolean operations between region shapes
+//!
+//! Both shapes are flattened to polygons and the result is traced along
+//! their edges by `geometry::boundary_contours`.
+
+use super::{Polygon, Shape};
+use crate::geometry::boundary_contours;
+use serde::{Deserialize, Serialize};
+
+/// How two shapes are combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoolOp {
+    /// Inside either
+    Union,
+    /// Inside both
+    Intersection,
+    /// Inside the first but not the second
+    Difference,
+    /// Inside exactly one
+    Xor,
+}
+
+impl BoolOp {
+    pub fn name(self) -> &'static str {
+        match self {
+            BoolOp::Union => "union",
+            BoolOp::Intersection => "intersection",
+            BoolOp::Difference => "difference",
+            BoolOp::Xor => "xor",
+        }
+    }
+
+    fn keeps(self, in_a: bool, in_b: bool) -> bool {
+        match self {
+            BoolOp::Union => in_a || in_b,
+            BoolOp::Intersection => in_a && in_b,
+            BoolOp::Difference => in_a && !in_b,
+            BoolOp::Xor => in_a != in_b,
+        }
+    }
+}
+
+/// Combine two shapes. Each polygon returned is one separate piece of the
+/// result with its holes, largest first; none at all when nothing is left.
+pub fn combine(a: &Shape, b: &Shape, op: BoolOp) -> Vec<Polygon> {
+    combine_polygons(&a.to_polygon(), &b.to_polygon(), op)
+}
+
+/// Combine a list of shapes left to right: `((s0 op s1) op s2) ...`
+pub fn combine_all(shapes: &[Shape], op: BoolOp) -> Vec<Polygon> {
+    let Some((first, rest)) = shapes.split_first() else {
+        return Vec::new();
+    };
+    let mut pieces = vec![first.to_polygon()];
+    for shape in rest {
+        pieces = combine_polygons(&merge(pieces), &shape.to_polygon(), op);
+    }
+    pieces
+}
+
+/// Several pieces as one even-odd polygon, to feed into another operation
+fn merge(pieces: Vec<Polygon>) -> Polygon {
+    let mut contours = pieces.into_iter().flat_map(|piece| {
+        let Polygon {
+            vertices, holes, ..
+        } = piece;
+        std::iter::once(vertices).chain(holes)
+    });
+    let vertices = contours.next().unwrap_or_default();
+    Polygon::from_vertices(vertices).with_holes(contours.collect())
+}
+
+fn combine_polygons(a: &Polygon, b: &Polygon, op: BoolOp) -> Vec<Polygon> {
+    let mut contours = a.contour_tuples();
+    contours.extend(b.contour_tuples());
+    let traced = boundary_contours(&contours, |x, y| {
+        op.keeps(a.contains(x, y), b.contains(x, y))
+    });
+    Polygon::from_contours(traced)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regions::{Circle, Point, Rect, Ring};
+
+    fn square(x: f32, y: f32, size: f32) -> Shape {
+        let half = size / 2.0;
+        Shape::Rect(Rect::new(Point::new(x + half, y + half), size, size))
+    }
+
+    #[test]
+    fn test_overlapping_squares() {
+        let (a, b) = (square(0.0, 0.0, 10.0), square(5.0, 5.0, 10.0));
+        let area = |pieces: &[Polygon]| pieces.iter().map(Polygon::area).sum::<f32>();
+
+        let union = combine(&a, &b, BoolOp::Union);
+        assert_eq!(union.len(), 1);
+        assert!((area(&union) - 175.0).abs() < 0.01);
+        assert_eq!(union[0].vertices.len(), 8);
+
+        let both = combine(&a, &b, BoolOp::Intersection);
+        assert!((area(&both) - 25.0).abs() < 0.01);
+        assert!(both[0].contains(7.0, 7.0) && !both[0].contains(2.0, 2.0));
+
+        let difference = combine(&a, &b, BoolOp::Difference);
+        assert!((area(&difference) - 75.0).abs() < 0.01);
+        assert!(difference[0].contains(2.0, 2.0) && !difference[0].contains(7.0, 7.0));
+
+        // Two L shapes meeting at the corners of the overlap
+        let xor = combine(&a, &b, BoolOp::Xor);
+        assert!((area(&xor) - 150.0).abs() < 0.01);
+        let inside = |x, y| xor.iter().any(|piece| piece.contains(x, y));
+        assert!(inside(2.0, 2.0) && inside(12.0, 12.0) && !inside(7.0, 7.0));
+    }
+
+    #[test]
+    fn test_holes_islands_and_shared_edges() {
+        // A window cut out of a frame
+        let frame = combine(
+            &square(0.0, 0.0, 100.0),
+            &square(25.0, 25.0, 50.0),
+            BoolOp::Difference,
+        );
+        assert_eq!(frame.len(), 1);
+        assert_eq!(frame[0].holes.len(), 1);
+        assert!(frame[0].contains(10.0, 10.0) && !frame[0].contains(50.0, 50.0));
+
+        // Separate shapes stay separate pieces, largest first
+        let apart = combine(
+            &square(0.0, 0.0, 10.0),
+            &square(20.0, 0.0, 20.0),
+            BoolOp::Union,
+        );
+        assert_eq!(apart.len(), 2);
+        assert!(apart[0].area() > apart[1].area());
+
+        // Squares side by side merge along the shared edge
+        let merged = combine(
+            &square(0.0, 0.0, 10.0),
+            &square(10.0, 0.0, 10.0),
+            BoolOp::Union,
+        );
+        assert_eq!(merged.len(), 1);
+        assert!((merged[0].area() - 200.0).abs() < 0.01);
+        assert!(merged[0].contains(10.0, 5.0));
+
+        // ...and have nothing in common
+        let none = combine(
+            &square(0.0, 0.0, 10.0),
+            &square(10.0, 0.0, 10.0),
+            BoolOp::Intersection,
+        );
+        assert!(none.is_empty());
+
+        // Round shapes go through their outlines; a ring keeps its hole
+        let ring = Shape::Ring(Ring::new(Point::new(50.0, 50.0), 20.0, 40.0));
+        let dot = Shape::Circle(Circle::new(Point::new(50.0, 50.0), 5.0));
+        let filled = combine_all(&[ring, dot, square(45.0, 5.0, 10.0)], BoolOp::Union);
+        assert_eq!(filled.len(), 2);
+        assert_eq!(filled[0].holes.len(), 1);
+        assert!(filled[0].contains(50.0, 5.0) && !filled[0].contains(50.0, 35.0));
+        assert!(filled[1].contains(50.0, 50.0));
+    }
+}
@@ -0,0 +1,377 @@
+//! Region content: text and graphics drawn in or around a region
+//!
+//! Content is stored with the region in `scene.json`, so it follows the
+//! region through calibration:
+//!
+//! ```json
+//! {
+//!   "name": "frame_1",
+//!   "shape": { "type": "Polygon", "vertices": [...] },
+//!   "content": { "type": "Marquee", "text": "NOW SHOWING", "speed": 40, "color": [255, 200, 0] }
+//! }
+//! ```
+//!
+//! or, to fill the region with wrapped text:
+//!
+//! ```json
+//! "content": { "type": "Text", "text": "Welcome!\nBar open until 2am", "align": "center" }
+//! ```
+
+use super::{Point, Scene, Shape};
+use crate::display::{
+    fit_text, Align, Font, LayoutOptions, PathScroller, PixelBuffer, TextBlock, TextPath, VAlign,
+};
+use crate::geometry::signed_area;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f32::consts::FRAC_PI_2;
+
+/// What to draw for a region, on top of the effect
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum RegionContent {
+    /// Text running around the region's outline, or along a spline of its own
+    Marquee {
+        text: String,
+        /// Pixels per second along the path (negative runs backwards)
+        #[serde(default = "default_marquee_speed")]
+        speed: f32,
+        /// Text size (1 = 8px line)
+        #[serde(default = "default_marquee_scale")]
+        scale: f32,
+        #[serde(default = "default_color")]
+        color: (u8, u8, u8),
+        /// Run the text inside the outline instead of outside it
+        #[serde(default)]
+        inside: bool,
+        /// Gap between the outline and the text baseline, in pixels
+        #[serde(default)]
+        margin: f32,
+        /// Minimum space between repeats around the outline, in pixels
+        #[serde(default = "default_marquee_gap")]
+        gap: f32,
+        /// Follow a smooth curve through these points instead of the outline
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        spline: Vec<Point>,
+        /// Join the spline's last point back to its first
+        #[serde(default)]
+        closed: bool,
+    },
+    /// Text word-wrapped inside the region, sized to fill it.
+    /// Newlines separate paragraphs.
+    Text {
+        text: String,
+        #[serde(default = "default_color")]
+        color: (u8, u8, u8),
+        #[serde(default)]
+        align: Align,
+        #[serde(default)]
+        valign: VAlign,
+        /// Smallest text size (1 = 8px line); text that doesn't fit at this size is cut off
+        #[serde(default = "default_min_scale")]
+        min_scale: f32,
+        /// Largest text size
+        #[serde(default = "default_max_scale")]
+        max_scale: f32,
+        /// Line pitch as a multiple of the line height
+        #[serde(default = "default_line_spacing")]
+        line_spacing: f32,
+        /// Space kept clear inside the region's edges, in pixels
+        #[serde(default = "default_padding")]
+        padding: f32,
+    },
+}
+
+fn default_marquee_speed() -> f32 {
+    40.0
+}
+
+fn default_marquee_scale() -> f32 {
+    2.0
+}
+
+fn default_marquee_gap() -> f32 {
+    32.0
+}
+
+fn default_min_scale() -> f32 {
+    LayoutOptions::default().min_scale
+}
+
+fn default_max_scale() -> f32 {
+    LayoutOptions::default().max_scale
+}
+
+fn default_line_spacing() -> f32 {
+    LayoutOptions::default().line_spacing
+}
+
+fn default_padding() -> f32 {
+    LayoutOptions::default().padding
+}
+
+fn default_color() -> (u8, u8, u8) {
+    (255, 255, 255)
+}
+
+impl RegionContent {
+    /// A marquee with default settings
+    pub fn marquee(text: impl Into<String>) -> Self {
+        RegionContent::Marquee {
+            text: text.into(),
+            speed: default_marquee_speed(),
+            scale: default_marquee_scale(),
+            color: default_color(),
+            inside: false,
+            margin: 0.0,
+            gap: default_marquee_gap(),
+            spline: Vec::new(),
+            closed: false,
+        }
+    }
+
+    /// Fitted text with default settings
+    pub fn text(text: impl Into<String>) -> Self {
+        let options = LayoutOptions::default();
+        RegionContent::Text {
+            text: text.into(),
+            color: default_color(),
+            align: options.align,
+            valign: options.valign,
+            min_scale: options.min_scale,
+            max_scale: options.max_scale,
+            line_spacing: options.line_spacing,
+            padding: options.padding,
+        }
+    }
+}
+
+/// Path a marquee follows for a region
+fn marquee_path(shape: &Shape, spline: &[Point], closed: bool, inside: bool) -> TextPath {
+    if !spline.is_empty() {
+        let points: Vec<(f32, f32)> = spline.iter().map(|p| (p.x, p.y)).collect();
+        return TextPath::spline(&points, closed);
+    }
+    // Outlines are walked clockwise on screen, which puts text outside
+    let path = match shape {
+        Shape::Circle(c) => TextPath::circle((c.center.x, c.center.y), c.radius, -FRAC_PI_2),
+        _ => {
+            let points = shape.outline_tuples();
+            let path = TextPath::polyline(&points, true);
+            if signed_area(&points) < 0.0 {
+                path.reversed()
+            } else {
+                path
+            }
+        },
+    };
+    if inside {
+        path.reversed()
+    } else {
+        path
+    }
+}
+
+/// Runtime state behind a region's content
+enum Live {
+    Marquee(PathScroller),
+    /// Laid-out text, redone when the region's outline moves
+    Text {
+        outline: Vec<Point>,
+        font: Font,
+        block: TextBlock,
+    },
+}
+
+impl Live {
+    fn new(content: &RegionContent, shape: &Shape) -> Self {
+        match content {
+            RegionContent::Marquee {
+                text,
+                speed,
+                scale,
+                color,
+                margin,
+                gap,
+                ..
+            } => Live::Marquee(
+                PathScroller::new(text.as_str())
+                    .speed(*speed)
+                    .scale(*scale)
+                    .color(color.0, color.1, color.2)
+                    .gap(*gap)
+                    .margin(*margin),
+            ),
+            RegionContent::Text { .. } => {
+                let font = Font::default();
+                let block = layout_text(content, shape, &font);
+                Live::Text {
+                    outline: shape.outline(),
+                    font,
+                    block,
+                }
+            },
+        }
+    }
+}
+
+/// Wrap a text content's message into a shape
+fn layout_text(content: &RegionContent, shape: &Shape, font: &Font) -> TextBlock {
+    let RegionContent::Text {
+        text,
+        align,
+        valign,
+        min_scale,
+        max_scale,
+        line_spacing,
+        padding,
+        ..
+    } = content
+    else {
+        return TextBlock::default();
+    };
+    let options = LayoutOptions {
+        align: *align,
+        valign: *valign,
+        min_scale: *min_scale,
+        max_scale: *max_scale,
+        line_spacing: *line_spacing,
+        padding: *padding,
+    };
+    let (_, top, _, bottom) = shape.bounds().unwrap_or_default();
+    fit_text(font, text, top, bottom, |y| shape.spans_at(y), &options)
+}
+
+/// Live state for the content of every region in a scene
+///
+/// Regions are matched by name; state is rebuilt when a region's content
+/// changes and dropped when the region goes away.
+#[derive(Default)]
+pub struct ContentLayer {
+    live: HashMap<String, (RegionContent, Live)>,
+}
+
+impl ContentLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sync with the scene's regions and advance animations by `dt`
+    /// (call with 0 while paused so edits still show)
+    pub fn update(&mut self, dt: f32, scene: &Scene) {
+        self.live.retain(|name, (content, _)| {
+            let region = scene.regions.iter().find(|r| &r.name == name);
+            region.and_then(|r| r.content.as_ref()) == Some(content)
+        });
+
+        for region in &scene.regions {
+            let Some(content) = region.content.as_ref() else {
+                continue;
+            };
+            let shape = region.get_shape();
+            let (_, live) = self
+                .live
+                .entry(region.name.clone())
+                .or_insert_with(|| (content.clone(), Live::new(content, shape)));
+
+            match (content, live) {
+                (
+                    RegionContent::Marquee {
+                        inside,
+                        spline,
+                        closed,
+                        ..
+                    },
+                    Live::Marquee(scroller),
+                ) => {
+                    scroller.update(dt, &marquee_path(shape, spline, *closed, *inside));
+                },
+                (
+                    RegionContent::Text { .. },
+                    Live::Text {
+                        outline,
+                        font,
+                        block,
+                    },
+                ) => {
+                    let current = shape.outline();
+                    if *outline != current {
+                        *block = layout_text(content, shape, font);
+                        *outline = current;
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+
+    /// Draw every region's content
+    pub fn render(&self, buffer: &mut PixelBuffer, scene: &Scene) {
+        for region in &scene.regions {
+            let Some(content) = region.content.as_ref() else {
+                continue;
+            };
+            let Some((_, live)) = self.live.get(&region.name) else {
+                continue;
+            };
+            match (content, live) {
+                (
+                    RegionContent::Marquee {
+                        inside,
+                        spline,
+                        closed,
+                        ..
+                    },
+                    Live::Marquee(scroller),
+                ) => {
+                    let path = marquee_path(region.get_shape(), spline, *closed, *inside);
+                    scroller.render(buffer, &path);
+                },
+                (RegionContent::Text { color, .. }, Live::Text { font, block, .. }) => {
+                    block.render(buffer, font, *color);
+                },
+                _ => {},
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regions::{Circle, Polygon};
+
+    #[test]
+    fn test_marquee_runs_clockwise_outside() {
+        // Counter-clockwise on screen: top-left, bottom-left, bottom-right, top-right
+        let square = Polygon::from_vertices(vec![
+            Point::new(0.0, 0.0),
+            Point::new(0.0, 10.0),
+            Point::new(10.0, 10.0),
+            Point::new(10.0, 0.0),
+        ]);
+        let shape = Shape::Polygon(square);
+        let outside = marquee_path(&shape, &[], false, false);
+        // Heading right along the top edge
+        assert_eq!(outside.point_at(5.0), Some((5.0, 0.0)));
+        let inside = marquee_path(&shape, &[], false, true);
+        assert_eq!(inside.point_at(5.0), Some((0.0, 5.0)));
+
+        let circle = Shape::Circle(Circle::new(Point::new(0.0, 0.0), 10.0));
+        let (x, y) = marquee_path(&circle, &[], false, false)
+            .point_at(0.0)
+            .unwrap();
+        assert!(x.abs() < 1e-3 && (y + 10.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_content_serde() {
+        let json = r#"{"type": "Marquee", "text": "NOW SHOWING", "color": [255, 200, 0]}"#;
+        let content: RegionContent = serde_json::from_str(json).unwrap();
+        let mut expected = RegionContent::marquee("NOW SHOWING");
+        if let RegionContent::Marquee { ref mut color, .. } = expected {
+            *color = (255, 200, 0);
+        }
+        assert_eq!(content, expected);
+    }
+}
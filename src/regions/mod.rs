@@ -1,9 +1,24 @@
+mod boolean;
+mod content;
 mod polygon;
 mod scene;
+mod shapes;
 
-pub use polygon::Polygon;
+#[allow(unused_imports)]
+pub use boolean::{combine, combine_all, BoolOp};
+#[allow(unused_imports)]
+pub use content::{ContentLayer, RegionContent};
+#[allow(unused_imports)]
+pub use polygon::{FillRule, Polygon};
 pub use scene::Scene;
+#[allow(unused_imports)]
+pub use shapes::{Curve, CurvePath, Ellipse, Rect, Ring, RoundedRect};
 
+use crate::display::PixelBuffer;
+use crate::geometry::{
+    boundary_contours, circle_circle_collision, circle_edge_collision, circle_polygon_collision,
+    circle_ring_collision, offset_contours, rect_polygon_collision, Join,
+};
 use serde::{Deserialize, Serialize};
 
 /// A point in 2D space
@@ -26,7 +41,7 @@ impl Point {
 }
 
 /// A circle defined by center and radius
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Circle {
     pub center: Point,
     pub radius: f32,
@@ -55,14 +70,30 @@ impl Circle {
     pub fn centroid(&self) -> Point {
         self.center
     }
+
+    /// The run inside the circle along the line `y`, if it crosses
+    pub fn spans_at(&self, y: f32) -> Vec<(f32, f32)> {
+        let dy = y - self.center.y;
+        let half = (self.radius * self.radius - dy * dy).sqrt();
+        if half > 0.0 {
+            vec![(self.center.x - half, self.center.x + half)]
+        } else {
+            Vec::new()
+        }
+    }
 }
 
-/// A shape that can be either a polygon or a circle
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The outline of a region
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum Shape {
     Polygon(Polygon),
     Circle(Circle),
+    Rect(Rect),
+    Ellipse(Ellipse),
+    RoundedRect(RoundedRect),
+    Ring(Ring),
+    Path(CurvePath),
 }
 
 impl Shape {
@@ -70,6 +101,11 @@ impl Shape {
         match self {
             Shape::Polygon(p) => p.contains(x, y),
             Shape::Circle(c) => c.contains(x, y),
+            Shape::Rect(r) => r.contains(x, y),
+            Shape::Ellipse(e) => e.contains(x, y),
+            Shape::RoundedRect(r) => r.contains(x, y),
+            Shape::Ring(r) => r.contains(x, y),
+            Shape::Path(p) => p.polygon().contains(x, y),
         }
     }
 
@@ -77,6 +113,11 @@ impl Shape {
         match self {
             Shape::Polygon(p) => p.bounds(),
             Shape::Circle(c) => Some(c.bounds()),
+            Shape::Rect(r) => Some(r.bounds()),
+            Shape::Ellipse(e) => Some(e.bounds()),
+            Shape::RoundedRect(r) => Some(r.bounds()),
+            Shape::Ring(r) => Some(r.bounds()),
+            Shape::Path(p) => p.polygon().bounds(),
         }
     }
 
@@ -84,6 +125,142 @@ impl Shape {
         match self {
             Shape::Polygon(p) => p.centroid(),
             Shape::Circle(c) => Some(c.centroid()),
+            Shape::Rect(r) => Some(r.centroid()),
+            Shape::Ellipse(e) => Some(e.centroid()),
+            Shape::RoundedRect(r) => Some(r.centroid()),
+            Shape::Ring(r) => Some(r.centroid()),
+            Shape::Path(p) => p.polygon().centroid(),
+        }
+    }
+
+    /// Horizontal runs inside the shape along the line `y`, sorted left to right
+    pub fn spans_at(&self, y: f32) -> Vec<(f32, f32)> {
+        match self {
+            Shape::Polygon(p) => p.spans_at(y),
+            Shape::Circle(c) => c.spans_at(y),
+            Shape::Ellipse(e) => e.spans_at(y),
+            Shape::Ring(r) => r.spans_at(y),
+            Shape::Path(p) => p.polygon().spans_at(y),
+            Shape::Rect(_) | Shape::RoundedRect(_) => {
+                Polygon::from_vertices(self.outline()).spans_at(y)
+            },
+        }
+    }
+
+    /// Closed outline as a list of points (curves are approximated with
+    /// segments; a ring's is its outer edge)
+    pub fn outline(&self) -> Vec<Point> {
+        match self {
+            Shape::Polygon(p) => p.vertices.clone(),
+            Shape::Circle(c) => {
+                let segments = ((c.radius * 0.5) as usize).clamp(16, 256);
+                (0..segments)
+                    .map(|i| {
+                        let angle = i as f32 / segments as f32 * std::f32::consts::TAU;
+                        Point::new(
+                            c.center.x + angle.cos() * c.radius,
+                            c.center.y + angle.sin() * c.radius,
+                        )
+                    })
+                    .collect()
+            },
+            Shape::Rect(r) => r.corners(),
+            Shape::Ellipse(e) => e.outline(),
+            Shape::RoundedRect(r) => r.outline(),
+            Shape::Ring(r) => Shape::Circle(Circle::new(r.center, r.outer_radius)).outline(),
+            Shape::Path(p) => p.polygon().vertices.clone(),
+        }
+    }
+
+    /// The shape as a polygon, curves approximated with segments (a ring's
+    /// inner edge becomes a hole)
+    pub fn to_polygon(&self) -> Polygon {
+        match self {
+            Shape::Polygon(p) => p.clone(),
+            Shape::Ring(r) => {
+                Polygon::from_vertices(self.outline()).with_holes(vec![Shape::Circle(Circle::new(
+                    r.center,
+                    r.inner_radius,
+                ))
+                .outline()])
+            },
+            _ => Polygon::from_vertices(self.outline()),
+        }
+    }
+
+    /// The shape grown by `distance` all round (shrunk if negative), for
+    /// glows, outlines and keep-out margins. Usually one polygon, but a
+    /// shrunk shape may come apart into several or vanish altogether.
+    pub fn offset(&self, distance: f32, join: Join) -> Vec<Polygon> {
+        let polygon = self.to_polygon();
+        let contours = if polygon.fill_rule == FillRule::NonZero {
+            // Trace the area first, the offset goes by the even-odd rule
+            boundary_contours(&polygon.contour_tuples(), |x, y| polygon.contains(x, y))
+        } else {
+            polygon.contour_tuples()
+        };
+        Polygon::from_contours(offset_contours(&contours, distance, join))
+    }
+
+    /// Outline in the tuple format the `geometry` functions take
+    pub fn outline_tuples(&self) -> Vec<(f32, f32)> {
+        self.outline().iter().map(|p| (p.x, p.y)).collect()
+    }
+
+    /// Fill the shape, rasterized from its spans
+    pub fn fill(&self, buffer: &mut PixelBuffer, r: u8, g: u8, b: u8) {
+        if let Some((_, min_y, _, max_y)) = self.bounds() {
+            buffer.fill_spans(min_y, max_y, |y| self.spans_at(y), r, g, b);
+        }
+    }
+
+    /// Fill the shape with alpha blending
+    pub fn fill_blend(&self, buffer: &mut PixelBuffer, r: u8, g: u8, b: u8, a: u8) {
+        if let Some((_, min_y, _, max_y)) = self.bounds() {
+            buffer.fill_spans_blend(min_y, max_y, |y| self.spans_at(y), r, g, b, a);
+        }
+    }
+
+    /// Collision with a moving circle: (normal_x, normal_y, penetration_depth)
+    /// pushing the circle clear of the shape, as `geometry` returns them
+    pub fn circle_collision(&self, cx: f32, cy: f32, radius: f32) -> Option<(f32, f32, f32)> {
+        match self {
+            Shape::Circle(c) => {
+                circle_circle_collision(cx, cy, radius, c.center.x, c.center.y, c.radius)
+            },
+            Shape::Ring(r) => circle_ring_collision(
+                cx,
+                cy,
+                radius,
+                r.center.x,
+                r.center.y,
+                r.inner_radius,
+                r.outer_radius,
+            ),
+            // Inside a hole only its edges are in the way, pushing back into it
+            Shape::Polygon(p) if !p.holes.is_empty() => p.hole_at(cx, cy).map_or_else(
+                || circle_polygon_collision(cx, cy, radius, &self.outline_tuples()),
+                |hole| circle_edge_collision(cx, cy, radius, &hole),
+            ),
+            _ => circle_polygon_collision(cx, cy, radius, &self.outline_tuples()),
+        }
+    }
+
+    /// Collision with a moving rectangle (top left `x`, `y`). Round shapes and
+    /// polygon holes treat the rectangle as a circle as wide as its longer side.
+    pub fn rect_collision(&self, x: f32, y: f32, w: f32, h: f32) -> Option<(f32, f32, f32)> {
+        match self {
+            Shape::Circle(_) | Shape::Ring(_) => {
+                self.circle_collision(x + w / 2.0, y + h / 2.0, w.max(h) / 2.0)
+            },
+            Shape::Polygon(p) if !p.holes.is_empty() => {
+                let (cx, cy) = (x + w / 2.0, y + h / 2.0);
+                p.hole_at(cx, cy).map_or_else(
+                    || rect_polygon_collision(x, y, w, h, &self.outline_tuples()),
+                    |hole| circle_edge_collision(cx, cy, w.max(h) / 2.0, &hole),
+                )
+            },
+            _ => rect_polygon_collision(x, y, w, h, &self.outline_tuples()),
         }
     }
 
@@ -91,7 +268,7 @@ impl Shape {
     pub fn as_polygon(&self) -> Option<&Polygon> {
         match self {
             Shape::Polygon(p) => Some(p),
-            Shape::Circle(_) => None,
+            _ => None,
         }
     }
 
@@ -99,7 +276,7 @@ impl Shape {
     pub fn as_circle(&self) -> Option<&Circle> {
         match self {
             Shape::Circle(c) => Some(c),
-            Shape::Polygon(_) => None,
+            _ => None,
         }
     }
 }
@@ -116,6 +293,9 @@ pub struct Region {
     pub shape: Option<Shape>,
     #[serde(default)]
     pub tags: Vec<String>,
+    /// Text or graphics drawn in or around the region
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<RegionContent>,
 }
 
 impl Region {
@@ -125,6 +305,7 @@ impl Region {
             polygon: None,
             shape: Some(Shape::Polygon(polygon)),
             tags: Vec::new(),
+            content: None,
         }
     }
 
@@ -134,6 +315,17 @@ impl Region {
             polygon: None,
             shape: Some(Shape::Circle(circle)),
             tags: Vec::new(),
+            content: None,
+        }
+    }
+
+    pub fn new_shape(name: impl Into<String>, shape: Shape) -> Self {
+        Self {
+            name: name.into(),
+            polygon: None,
+            shape: Some(shape),
+            tags: Vec::new(),
+            content: None,
         }
     }
 
@@ -142,6 +334,11 @@ impl Region {
         self
     }
 
+    pub fn with_content(mut self, content: RegionContent) -> Self {
+        self.content = Some(content);
+        self
+    }
+
     pub fn contains(&self, x: f32, y: f32) -> bool {
         self.get_shape().contains(x, y)
     }
@@ -206,7 +403,7 @@ impl Region {
     pub fn polygon(&self) -> &Polygon {
         match &self.shape {
             Some(Shape::Polygon(p)) => p,
-            Some(Shape::Circle(_)) => panic!("Called polygon() on a circle region"),
+            Some(_) => panic!("Called polygon() on a non-polygon region"),
             None => self.polygon.as_ref().expect("No shape or polygon"),
         }
     }
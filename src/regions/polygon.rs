@@ -1,21 +1,64 @@
 use super::Point;
+use crate::display::PixelBuffer;
+use crate::geometry::{point_in_polygon, signed_area};
 use serde::{Deserialize, Serialize};
 
-/// A simple polygon defined by vertices
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Which points a polygon's contours enclose
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FillRule {
+    /// Inside when a ray crosses the contours an odd number of times, so
+    /// any contour inside another cuts a hole
+    #[default]
+    EvenOdd,
+    /// Inside when the contours wind round the point, so a hole must run the
+    /// other way to the outline around it
+    NonZero,
+}
+
+impl FillRule {
+    fn is_even_odd(&self) -> bool {
+        *self == FillRule::EvenOdd
+    }
+}
+
+/// A polygon defined by vertices, optionally with holes cut out of it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Polygon {
     pub vertices: Vec<Point>,
+    /// Contours cut out of the polygon
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub holes: Vec<Vec<Point>>,
+    #[serde(default, skip_serializing_if = "FillRule::is_even_odd")]
+    pub fill_rule: FillRule,
 }
 
 impl Polygon {
     pub fn new() -> Self {
+        Self::from_vertices(Vec::new())
+    }
+
+    pub fn from_vertices(vertices: Vec<Point>) -> Self {
         Self {
-            vertices: Vec::new(),
+            vertices,
+            holes: Vec::new(),
+            fill_rule: FillRule::EvenOdd,
         }
     }
 
-    pub fn from_vertices(vertices: Vec<Point>) -> Self {
-        Self { vertices }
+    pub fn with_holes(mut self, holes: Vec<Vec<Point>>) -> Self {
+        self.holes = holes;
+        self
+    }
+
+    pub fn with_fill_rule(mut self, fill_rule: FillRule) -> Self {
+        self.fill_rule = fill_rule;
+        self
+    }
+
+    /// The outline followed by the holes
+    pub fn contours(&self) -> impl Iterator<Item = &[Point]> {
+        std::iter::once(self.vertices.as_slice()).chain(self.holes.iter().map(Vec::as_slice))
     }
 
     pub fn add_vertex(&mut self, x: f32, y: f32) {
@@ -32,22 +75,21 @@ impl Polygon {
             return false;
         }
 
-        let mut inside = false;
-        let n = self.vertices.len();
-
-        let mut j = n - 1;
-        for i in 0..n {
-            let vi = &self.vertices[i];
-            let vj = &self.vertices[j];
-
+        // Count the edges a ray to the right crosses, by direction
+        let mut winding = 0;
+        let mut crossings = 0;
+        for (vi, vj) in self.all_edges() {
             if ((vi.y > y) != (vj.y > y)) && (x < (vj.x - vi.x) * (y - vi.y) / (vj.y - vi.y) + vi.x)
             {
-                inside = !inside;
+                crossings += 1;
+                winding += if vj.y > vi.y { 1 } else { -1 };
             }
-            j = i;
         }
 
-        inside
+        match self.fill_rule {
+            FillRule::EvenOdd => crossings % 2 == 1,
+            FillRule::NonZero => winding != 0,
+        }
     }
 
     /// Get the bounding box (min_x, min_y, max_x, max_y)
@@ -71,17 +113,133 @@ impl Polygon {
         Some((min_x, min_y, max_x, max_y))
     }
 
-    /// Get edges as line segments
+    /// Horizontal runs inside the polygon along the line `y`, as sorted (x_start, x_end) pairs
+    pub fn spans_at(&self, y: f32) -> Vec<(f32, f32)> {
+        // Where each edge crosses, and which way it winds
+        let mut crossings: Vec<(f32, i32)> = self
+            .all_edges()
+            .filter(|(a, b)| (a.y > y) != (b.y > y))
+            .map(|(a, b)| {
+                let x = a.x + (y - a.y) / (b.y - a.y) * (b.x - a.x);
+                (x, if b.y > a.y { 1 } else { -1 })
+            })
+            .collect();
+        crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        match self.fill_rule {
+            FillRule::EvenOdd => crossings
+                .chunks_exact(2)
+                .map(|c| (c[0].0, c[1].0))
+                .collect(),
+            FillRule::NonZero => {
+                let mut spans = Vec::new();
+                let mut winding = 0;
+                let mut start = 0.0;
+                for (x, direction) in crossings {
+                    if winding == 0 {
+                        start = x;
+                    }
+                    winding += direction;
+                    if winding == 0 {
+                        spans.push((start, x));
+                    }
+                }
+                spans
+            },
+        }
+    }
+
+    /// Get edges of the outline as line segments
     pub fn edges(&self) -> impl Iterator<Item = (&Point, &Point)> {
         let n = self.vertices.len();
         (0..n).map(move |i| (&self.vertices[i], &self.vertices[(i + 1) % n]))
     }
 
+    /// Edges of the outline and every hole
+    pub fn all_edges(&self) -> impl Iterator<Item = (&Point, &Point)> {
+        self.contours().flat_map(|contour| {
+            let n = contour.len();
+            (0..n).map(move |i| (&contour[i], &contour[(i + 1) % n]))
+        })
+    }
+
     /// Convert vertices to tuple format for fill_polygon API
     pub fn as_tuples(&self) -> Vec<(f32, f32)> {
         self.vertices.iter().map(|v| (v.x, v.y)).collect()
     }
 
+    /// The outline and holes in tuple format, for `fill_contours`
+    pub fn contour_tuples(&self) -> Vec<Vec<(f32, f32)>> {
+        self.contours()
+            .map(|contour| contour.iter().map(|v| (v.x, v.y)).collect())
+            .collect()
+    }
+
+    /// The hole `(x, y)` is in, if it's in one
+    pub fn hole_at(&self, x: f32, y: f32) -> Option<Vec<(f32, f32)>> {
+        if self.contains(x, y) {
+            return None;
+        }
+        self.holes
+            .iter()
+            .map(|hole| hole.iter().map(|v| (v.x, v.y)).collect::<Vec<_>>())
+            .find(|hole| point_in_polygon(x, y, hole))
+    }
+
+    /// Fill the polygon, leaving its holes
+    pub fn fill(&self, buffer: &mut PixelBuffer, r: u8, g: u8, b: u8) {
+        let nonzero = self.fill_rule == FillRule::NonZero;
+        buffer.fill_contours(&self.contour_tuples(), nonzero, r, g, b);
+    }
+
+    /// Fill the polygon with alpha blending, leaving its holes
+    pub fn fill_blend(&self, buffer: &mut PixelBuffer, r: u8, g: u8, b: u8, a: u8) {
+        let nonzero = self.fill_rule == FillRule::NonZero;
+        buffer.fill_contours_blend(&self.contour_tuples(), nonzero, r, g, b, a);
+    }
+
+    /// Area enclosed by the outline less its holes
+    pub fn area(&self) -> f32 {
+        let mut contours = self.contour_tuples().into_iter();
+        let outline = contours.next().map_or(0.0, |c| signed_area(&c).abs());
+        let holes: f32 = contours.map(|hole| signed_area(&hole).abs()).sum();
+        (outline - holes).max(0.0)
+    }
+
+    /// Group contours that have their area on the left, as
+    /// `geometry::boundary_contours` traces them, into separate polygons
+    /// with holes, largest first
+    pub fn from_contours(contours: Vec<Vec<(f32, f32)>>) -> Vec<Polygon> {
+        let (mut outlines, holes): (Vec<_>, Vec<_>) = contours
+            .into_iter()
+            .partition(|contour| signed_area(contour) > 0.0);
+        outlines.sort_by(|x, y| signed_area(y).total_cmp(&signed_area(x)));
+
+        let to_points = |contour: Vec<(f32, f32)>| -> Vec<Point> {
+            contour.into_iter().map(|(x, y)| Point::new(x, y)).collect()
+        };
+        let mut pieces: Vec<(Vec<(f32, f32)>, Polygon)> = outlines
+            .into_iter()
+            .map(|outline| (outline.clone(), Polygon::from_vertices(to_points(outline))))
+            .collect();
+        // Each hole belongs to the smallest outline around it
+        for hole in holes {
+            // Just left of its first edge: outside the hole, in the piece around it
+            let ((x1, y1), (x2, y2)) = (hole[0], hole[1]);
+            let length = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
+            let x = (x1 + x2) / 2.0 - (y2 - y1) / length * 0.05;
+            let y = (y1 + y2) / 2.0 + (x2 - x1) / length * 0.05;
+            if let Some((_, piece)) = pieces
+                .iter_mut()
+                .rev()
+                .find(|(outline, _)| point_in_polygon(x, y, outline))
+            {
+                piece.holes.push(to_points(hole));
+            }
+        }
+        pieces.into_iter().map(|(_, piece)| piece).collect()
+    }
+
     /// Calculate the centroid (geometric center) of the polygon
     /// Returns None if the polygon is empty
     pub fn centroid(&self) -> Option<Point> {
@@ -102,3 +260,79 @@ impl Default for Polygon {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regions::Shape;
+
+    fn square(x: f32, y: f32, size: f32) -> Vec<Point> {
+        vec![
+            Point::new(x, y),
+            Point::new(x + size, y),
+            Point::new(x + size, y + size),
+            Point::new(x, y + size),
+        ]
+    }
+
+    #[test]
+    fn test_holes_and_fill_rules() {
+        let frame = Polygon::from_vertices(square(0.0, 0.0, 100.0))
+            .with_holes(vec![square(25.0, 25.0, 50.0)]);
+        assert!(frame.contains(10.0, 10.0));
+        assert!(!frame.contains(50.0, 50.0));
+        assert_eq!(frame.spans_at(50.0), vec![(0.0, 25.0), (75.0, 100.0)]);
+        assert!((frame.area() - 7500.0).abs() < 0.01);
+
+        // Nonzero: a hole running the same way as the outline fills in...
+        let same_way = frame.clone().with_fill_rule(FillRule::NonZero);
+        assert!(same_way.contains(50.0, 50.0));
+        assert_eq!(same_way.spans_at(50.0), vec![(0.0, 100.0)]);
+
+        // ...and one running against it is cut out
+        let mut reversed = square(25.0, 25.0, 50.0);
+        reversed.reverse();
+        let against = same_way.with_holes(vec![reversed]);
+        assert!(!against.contains(50.0, 50.0));
+        assert_eq!(against.spans_at(50.0), vec![(0.0, 25.0), (75.0, 100.0)]);
+
+        // The masks rasterize the same way
+        let mut buffer = PixelBuffer::with_size(100, 100);
+        frame.fill(&mut buffer, 255, 0, 0);
+        assert_eq!(buffer.get_pixel(10, 50), Some((255, 0, 0)));
+        assert_eq!(buffer.get_pixel(50, 50), Some((0, 0, 0)));
+    }
+
+    #[test]
+    fn test_collisions_respect_holes() {
+        let frame = Shape::Polygon(
+            Polygon::from_vertices(square(0.0, 0.0, 100.0))
+                .with_holes(vec![square(25.0, 25.0, 50.0)]),
+        );
+        // Clear of the hole's edges there's nothing to hit
+        assert!(frame.circle_collision(50.0, 50.0, 5.0).is_none());
+        assert!(frame.rect_collision(45.0, 45.0, 10.0, 10.0).is_none());
+
+        // Near an edge it's pushed back into the hole
+        let (nx, ny, depth) = frame.circle_collision(30.0, 50.0, 10.0).unwrap();
+        assert!((nx - 1.0).abs() < 1e-3 && ny.abs() < 1e-3 && (depth - 5.0).abs() < 1e-3);
+        let (nx, _, _) = frame.rect_collision(22.0, 45.0, 10.0, 10.0).unwrap();
+        assert!(nx > 0.99);
+
+        // The frame itself is still solid
+        assert!(frame.circle_collision(10.0, 50.0, 2.0).is_some());
+        assert!(frame.rect_collision(5.0, 5.0, 10.0, 10.0).is_some());
+    }
+
+    #[test]
+    fn test_plain_polygons_serialize_as_before() {
+        let json = serde_json::to_string(&Polygon::from_vertices(square(0.0, 0.0, 1.0))).unwrap();
+        assert!(!json.contains("holes") && !json.contains("fill_rule"));
+        let parsed: Polygon = serde_json::from_str(
+            r#"{"vertices": [], "holes": [[{"x": 1, "y": 2}]], "fill_rule": "non_zero"}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.holes.len(), 1);
+        assert_eq!(parsed.fill_rule, FillRule::NonZero);
+    }
+}
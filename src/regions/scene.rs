@@ -1,5 +1,8 @@
-use super::Region;
+use super::{combine_all, BoolOp, Region, Shape};
+use crate::chyron::StripLayout;
+use crate::output::OutputSettings;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
@@ -8,6 +11,32 @@ use std::path::Path;
 pub struct Scene {
     pub name: String,
     pub regions: Vec<Region>,
+    /// Chyron strips for this setup, replacing the config file's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chyron_strips: Option<Vec<StripLayout>>,
+    /// Warp and colour for a single output (or any output named "")
+    #[serde(flatten)]
+    pub output: OutputSettings,
+    /// Warp and colour for named outputs
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub outputs: BTreeMap<String, OutputSettings>,
+    /// Boolean operations on regions, carried out when the scene loads
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub combine: Vec<Combination>,
+}
+
+/// Regions merged into new ones by a boolean operation, e.g.
+/// `{"op": "difference", "regions": ["frame", "window"]}` masks the frame
+/// but not the window inside it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Combination {
+    pub op: BoolOp,
+    /// Combined left to right; they are replaced by the result
+    pub regions: Vec<String>,
+    /// Name for the result, the first region's if not given. A result in
+    /// several pieces gets one region per piece: `name`, `name-2`, ...
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
 }
 
 impl Scene {
@@ -15,7 +44,37 @@ impl Scene {
         Self {
             name: name.into(),
             regions: Vec::new(),
+            chyron_strips: None,
+            output: OutputSettings::default(),
+            outputs: BTreeMap::new(),
+            combine: Vec::new(),
+        }
+    }
+
+    /// The settings for output `name`, if it has any
+    pub fn output(&self, name: &str) -> Option<&OutputSettings> {
+        if name.is_empty() {
+            Some(&self.output)
+        } else {
+            self.outputs.get(name)
+        }
+    }
+
+    pub fn output_mut(&mut self, name: &str) -> &mut OutputSettings {
+        if name.is_empty() {
+            &mut self.output
+        } else {
+            self.outputs.entry(name.to_string()).or_default()
+        }
+    }
+
+    /// Drop corrections left as they started, so the saved scene only carries real ones
+    pub fn drop_identity_outputs(&mut self) {
+        self.output.drop_identity();
+        for output in self.outputs.values_mut() {
+            output.drop_identity();
         }
+        self.outputs.retain(|_, output| !output.is_empty());
     }
 
     pub fn add_region(&mut self, region: Region) {
@@ -30,6 +89,78 @@ impl Scene {
         }
     }
 
+    /// Replace the regions at `indices` with `op` applied to them in that
+    /// order. The pieces of the result take the first region's place, tags
+    /// and content. Returns the names of the new regions.
+    pub fn combine_regions(
+        &mut self,
+        indices: &[usize],
+        op: BoolOp,
+        name: Option<&str>,
+    ) -> Result<Vec<String>, String> {
+        let Some(&first) = indices.first() else {
+            return Err("nothing to combine".to_string());
+        };
+        if indices.iter().any(|&i| i >= self.regions.len()) {
+            return Err("no such region".to_string());
+        }
+        let shapes: Vec<Shape> = indices
+            .iter()
+            .map(|&i| self.regions[i].get_shape().clone())
+            .collect();
+        let pieces = combine_all(&shapes, op);
+        if pieces.is_empty() {
+            return Err(format!("the {} leaves nothing", op.name()));
+        }
+
+        let template = self.regions[first].clone();
+        let base = name.unwrap_or(&template.name).to_string();
+        let mut at = first;
+        let mut removing: Vec<usize> = indices.to_vec();
+        removing.sort_unstable();
+        removing.dedup();
+        for &i in removing.iter().rev() {
+            self.regions.remove(i);
+            if i < first {
+                at -= 1;
+            }
+        }
+
+        let mut names = Vec::new();
+        for (n, piece) in pieces.into_iter().enumerate() {
+            let mut region = template.clone();
+            region.name = if n == 0 {
+                base.clone()
+            } else {
+                format!("{}-{}", base, n + 1)
+            };
+            region.shape = Some(Shape::Polygon(piece));
+            names.push(region.name.clone());
+            self.regions.insert(at + n, region);
+        }
+        Ok(names)
+    }
+
+    /// Carry out the scene's `combine` list, leaving it empty so the saved
+    /// scene holds the results
+    pub fn apply_combinations(&mut self) -> Result<(), String> {
+        for combination in std::mem::take(&mut self.combine) {
+            let indices = combination
+                .regions
+                .iter()
+                .map(|name| {
+                    self.regions
+                        .iter()
+                        .position(|r| &r.name == name)
+                        .ok_or_else(|| format!("combine: no region named '{}'", name))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            self.combine_regions(&indices, combination.op, combination.name.as_deref())
+                .map_err(|e| format!("combine {}: {}", combination.regions.join(", "), e))?;
+        }
+        Ok(())
+    }
+
     /// Find which region contains a point (if any)
     pub fn region_at(&self, x: f32, y: f32) -> Option<&Region> {
         self.regions.iter().find(|r| r.contains(x, y))
@@ -52,7 +183,9 @@ impl Scene {
     /// Load scene from a JSON file
     pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
         let json = fs::read_to_string(path).map_err(|e| e.to_string())?;
-        serde_json::from_str(&json).map_err(|e| e.to_string())
+        let mut scene: Self = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+        scene.apply_combinations()?;
+        Ok(scene)
     }
 }
 
@@ -61,3 +194,46 @@ impl Default for Scene {
         Self::new("untitled")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combine_on_load() {
+        let json = r#"{
+            "name": "test",
+            "regions": [
+                {"name": "frame", "tags": ["wall"], "shape":
+                    {"type": "Rect", "center": {"x": 50, "y": 50}, "width": 100, "height": 100}},
+                {"name": "lamp", "shape":
+                    {"type": "Circle", "center": {"x": 200, "y": 50}, "radius": 10}},
+                {"name": "window", "shape":
+                    {"type": "Rect", "center": {"x": 50, "y": 50}, "width": 50, "height": 50}}
+            ],
+            "combine": [{"op": "difference", "regions": ["frame", "window"]}]
+        }"#;
+        let mut scene: Scene = serde_json::from_str(json).unwrap();
+        scene.apply_combinations().unwrap();
+        assert!(scene.combine.is_empty());
+        let names: Vec<&str> = scene.regions.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["frame", "lamp"]);
+        assert_eq!(scene.regions[0].tags, ["wall"]);
+        assert!(scene.regions[0].contains(10.0, 10.0));
+        assert!(!scene.regions[0].contains(50.0, 50.0));
+
+        // One region per piece
+        let names = scene
+            .combine_regions(&[1, 0], BoolOp::Union, Some("both"))
+            .unwrap();
+        assert_eq!(names, ["both", "both-2"]);
+        assert_eq!(scene.regions.len(), 2);
+
+        scene.combine.push(Combination {
+            op: BoolOp::Union,
+            regions: vec!["both".to_string(), "missing".to_string()],
+            name: None,
+        });
+        assert!(scene.apply_combinations().is_err());
+    }
+}
@@ -0,0 +1,451 @@
+//! Region shapes beyond polygons and circles
+//!
+//! Oval mirrors, round-cornered screens and curved signs are awkward to
+//! click out vertex by vertex. These shapes describe them directly; each is
+//! measured in screen pixels and the rotated ones turn clockwise (y down)
+//! about their center by `angle` radians.
+
+use super::{Point, Polygon};
+use crate::display::spline_points;
+use crate::geometry::flatten_bezier;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+use std::sync::OnceLock;
+
+/// Line segments per curve span when flattening paths
+const CURVE_STEPS: usize = 12;
+
+/// Segments for a full turn of an arc of `radius`, matching `Shape::outline`'s circles
+fn arc_segments(radius: f32) -> usize {
+    ((radius * 0.5) as usize).clamp(16, 256)
+}
+
+/// (x, y) relative to `center`, turned back by `angle` into the shape's own axes
+fn to_local(center: Point, angle: f32, x: f32, y: f32) -> (f32, f32) {
+    let (sin, cos) = angle.sin_cos();
+    let (dx, dy) = (x - center.x, y - center.y);
+    (dx * cos + dy * sin, -dx * sin + dy * cos)
+}
+
+/// A point in the shape's own axes, back in screen space
+fn to_screen(center: Point, angle: f32, lx: f32, ly: f32) -> Point {
+    let (sin, cos) = angle.sin_cos();
+    Point::new(
+        center.x + lx * cos - ly * sin,
+        center.y + lx * sin + ly * cos,
+    )
+}
+
+/// Half extents of a `half_w` x `half_h` box turned by `angle`
+fn turned_extents(half_w: f32, half_h: f32, angle: f32) -> (f32, f32) {
+    let (sin, cos) = angle.sin_cos();
+    let (sin, cos) = (sin.abs(), cos.abs());
+    (half_w * cos + half_h * sin, half_w * sin + half_h * cos)
+}
+
+/// A rectangle, optionally rotated
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rect {
+    pub center: Point,
+    pub width: f32,
+    pub height: f32,
+    #[serde(default)]
+    pub angle: f32,
+}
+
+impl Rect {
+    pub fn new(center: Point, width: f32, height: f32) -> Self {
+        Self {
+            center,
+            width,
+            height,
+            angle: 0.0,
+        }
+    }
+
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        let (lx, ly) = to_local(self.center, self.angle, x, y);
+        lx.abs() <= self.width / 2.0 && ly.abs() <= self.height / 2.0
+    }
+
+    pub fn bounds(&self) -> (f32, f32, f32, f32) {
+        let (ex, ey) = turned_extents(self.width / 2.0, self.height / 2.0, self.angle);
+        let c = self.center;
+        (c.x - ex, c.y - ey, c.x + ex, c.y + ey)
+    }
+
+    pub fn centroid(&self) -> Point {
+        self.center
+    }
+
+    /// Corners clockwise on screen from the top left
+    pub fn corners(&self) -> Vec<Point> {
+        let (hw, hh) = (self.width / 2.0, self.height / 2.0);
+        [(-hw, -hh), (hw, -hh), (hw, hh), (-hw, hh)]
+            .iter()
+            .map(|&(lx, ly)| to_screen(self.center, self.angle, lx, ly))
+            .collect()
+    }
+}
+
+/// An ellipse, optionally rotated
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ellipse {
+    pub center: Point,
+    pub radius_x: f32,
+    pub radius_y: f32,
+    #[serde(default)]
+    pub angle: f32,
+}
+
+impl Ellipse {
+    pub fn new(center: Point, radius_x: f32, radius_y: f32) -> Self {
+        Self {
+            center,
+            radius_x,
+            radius_y,
+            angle: 0.0,
+        }
+    }
+
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        if self.radius_x <= 0.0 || self.radius_y <= 0.0 {
+            return false;
+        }
+        let (lx, ly) = to_local(self.center, self.angle, x, y);
+        (lx / self.radius_x).powi(2) + (ly / self.radius_y).powi(2) <= 1.0
+    }
+
+    pub fn bounds(&self) -> (f32, f32, f32, f32) {
+        let (sin, cos) = self.angle.sin_cos();
+        let (rx, ry) = (self.radius_x, self.radius_y);
+        let ex = (rx * rx * cos * cos + ry * ry * sin * sin).sqrt();
+        let ey = (rx * rx * sin * sin + ry * ry * cos * cos).sqrt();
+        let c = self.center;
+        (c.x - ex, c.y - ey, c.x + ex, c.y + ey)
+    }
+
+    pub fn centroid(&self) -> Point {
+        self.center
+    }
+
+    /// The run inside the ellipse along the line `y`, if it crosses
+    pub fn spans_at(&self, y: f32) -> Vec<(f32, f32)> {
+        if self.radius_x <= 0.0 || self.radius_y <= 0.0 {
+            return Vec::new();
+        }
+        // Inside is a·x² + b·x·y + c·y² <= 1 relative to the center: solve for x
+        let (sin, cos) = self.angle.sin_cos();
+        let (irx, iry) = (self.radius_x.powi(-2), self.radius_y.powi(-2));
+        let a = cos * cos * irx + sin * sin * iry;
+        let b = 2.0 * cos * sin * (irx - iry);
+        let c = sin * sin * irx + cos * cos * iry;
+        let dy = y - self.center.y;
+        let discriminant = (b * dy).powi(2) - 4.0 * a * (c * dy * dy - 1.0);
+        if discriminant <= 0.0 {
+            return Vec::new();
+        }
+        let root = discriminant.sqrt();
+        let x = self.center.x;
+        vec![(
+            x + (-b * dy - root) / (2.0 * a),
+            x + (-b * dy + root) / (2.0 * a),
+        )]
+    }
+
+    pub fn outline(&self) -> Vec<Point> {
+        let segments = arc_segments(self.radius_x.max(self.radius_y));
+        (0..segments)
+            .map(|i| {
+                let t = i as f32 / segments as f32 * TAU;
+                to_screen(
+                    self.center,
+                    self.angle,
+                    t.cos() * self.radius_x,
+                    t.sin() * self.radius_y,
+                )
+            })
+            .collect()
+    }
+}
+
+/// A rectangle with rounded corners, optionally rotated
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoundedRect {
+    pub center: Point,
+    pub width: f32,
+    pub height: f32,
+    /// Corner radius, limited to half the shorter side
+    pub radius: f32,
+    #[serde(default)]
+    pub angle: f32,
+}
+
+impl RoundedRect {
+    pub fn new(center: Point, width: f32, height: f32, radius: f32) -> Self {
+        Self {
+            center,
+            width,
+            height,
+            radius,
+            angle: 0.0,
+        }
+    }
+
+    /// The corner radius actually drawn
+    pub fn corner_radius(&self) -> f32 {
+        self.radius
+            .min(self.width.abs().min(self.height.abs()) / 2.0)
+            .max(0.0)
+    }
+
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        let (lx, ly) = to_local(self.center, self.angle, x, y);
+        let (hw, hh, r) = (self.width / 2.0, self.height / 2.0, self.corner_radius());
+        // Distance past the inner rectangle the corner arcs are centered on
+        let dx = (lx.abs() - (hw - r)).max(0.0);
+        let dy = (ly.abs() - (hh - r)).max(0.0);
+        lx.abs() <= hw && ly.abs() <= hh && dx * dx + dy * dy <= r * r
+    }
+
+    pub fn bounds(&self) -> (f32, f32, f32, f32) {
+        let r = self.corner_radius();
+        let (hw, hh) = (self.width / 2.0 - r, self.height / 2.0 - r);
+        let (ex, ey) = turned_extents(hw, hh, self.angle);
+        let c = self.center;
+        (c.x - ex - r, c.y - ey - r, c.x + ex + r, c.y + ey + r)
+    }
+
+    pub fn centroid(&self) -> Point {
+        self.center
+    }
+
+    /// Clockwise on screen, a quarter arc per corner
+    pub fn outline(&self) -> Vec<Point> {
+        let r = self.corner_radius();
+        let (hw, hh) = (self.width / 2.0 - r, self.height / 2.0 - r);
+        let steps = arc_segments(r) / 4;
+        // Arc centers from the top left, each with the angle its arc starts at
+        let corners = [
+            (-hw, -hh, -PI),
+            (hw, -hh, -FRAC_PI_2),
+            (hw, hh, 0.0),
+            (-hw, hh, FRAC_PI_2),
+        ];
+        corners
+            .iter()
+            .flat_map(|&(cx, cy, start)| {
+                (0..=steps).map(move |i| {
+                    let t = start + FRAC_PI_2 * i as f32 / steps as f32;
+                    (cx + t.cos() * r, cy + t.sin() * r)
+                })
+            })
+            .map(|(lx, ly)| to_screen(self.center, self.angle, lx, ly))
+            .collect()
+    }
+}
+
+/// The band between two circles around the same center
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ring {
+    pub center: Point,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+}
+
+impl Ring {
+    pub fn new(center: Point, inner_radius: f32, outer_radius: f32) -> Self {
+        Self {
+            center,
+            inner_radius,
+            outer_radius,
+        }
+    }
+
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        let d2 = (x - self.center.x).powi(2) + (y - self.center.y).powi(2);
+        d2 >= self.inner_radius * self.inner_radius && d2 <= self.outer_radius * self.outer_radius
+    }
+
+    pub fn bounds(&self) -> (f32, f32, f32, f32) {
+        let (c, r) = (self.center, self.outer_radius);
+        (c.x - r, c.y - r, c.x + r, c.y + r)
+    }
+
+    pub fn centroid(&self) -> Point {
+        self.center
+    }
+
+    /// The runs inside the band along the line `y`: one across the top and
+    /// bottom, two either side of the hole through the middle
+    pub fn spans_at(&self, y: f32) -> Vec<(f32, f32)> {
+        let dy = y - self.center.y;
+        let half = |r: f32| (r * r - dy * dy).max(0.0).sqrt();
+        let (outer, inner) = (half(self.outer_radius), half(self.inner_radius));
+        let x = self.center.x;
+        if outer <= 0.0 {
+            Vec::new()
+        } else if inner <= 0.0 {
+            vec![(x - outer, x + outer)]
+        } else {
+            vec![(x - outer, x - inner), (x + inner, x + outer)]
+        }
+    }
+}
+
+/// How a path's points make a curve
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Curve {
+    /// Smooth through every point
+    #[default]
+    CatmullRom,
+    /// Cubic segments: anchor, control, control, anchor, ...
+    Bezier,
+}
+
+/// A closed curved outline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurvePath {
+    points: Vec<Point>,
+    #[serde(default)]
+    curve: Curve,
+    /// The curve flattened, built on first use and dropped when points move
+    #[serde(skip)]
+    flat: OnceLock<Polygon>,
+}
+
+impl PartialEq for CurvePath {
+    fn eq(&self, other: &Self) -> bool {
+        self.points == other.points && self.curve == other.curve
+    }
+}
+
+impl CurvePath {
+    pub fn new(points: Vec<Point>, curve: Curve) -> Self {
+        Self {
+            points,
+            curve,
+            flat: OnceLock::new(),
+        }
+    }
+
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    /// The points, to edit; the flattened curve is rebuilt after
+    pub fn points_mut(&mut self) -> &mut [Point] {
+        self.flat.take();
+        &mut self.points
+    }
+
+    pub fn curve(&self) -> Curve {
+        self.curve
+    }
+
+    /// A Bezier path through `anchors`, with controls placed so it's as smooth
+    /// as the Catmull-Rom curve through them (ready to be pulled about)
+    pub fn smooth_bezier(anchors: &[Point]) -> Self {
+        let n = anchors.len();
+        let at = |i: usize| anchors[i % n];
+        let points = (0..n)
+            .flat_map(|i| {
+                let (prev, p1, p2, next) = (at(i + n - 1), at(i), at(i + 1), at(i + 2));
+                [
+                    p1,
+                    Point::new(p1.x + (p2.x - prev.x) / 6.0, p1.y + (p2.y - prev.y) / 6.0),
+                    Point::new(p2.x - (next.x - p1.x) / 6.0, p2.y - (next.y - p1.y) / 6.0),
+                ]
+            })
+            .collect();
+        Self::new(points, Curve::Bezier)
+    }
+
+    /// The curve flattened into a polygon
+    pub fn polygon(&self) -> &Polygon {
+        self.flat.get_or_init(|| {
+            let points: Vec<(f32, f32)> = self.points.iter().map(|p| (p.x, p.y)).collect();
+            let flat = match self.curve {
+                Curve::CatmullRom => spline_points(&points, true, CURVE_STEPS),
+                Curve::Bezier => flatten_bezier(&points, true, CURVE_STEPS),
+            };
+            Polygon::from_vertices(flat.into_iter().map(|(x, y)| Point::new(x, y)).collect())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_4;
+
+    #[test]
+    fn test_rotated_rect_and_ellipse() {
+        let mut rect = Rect::new(Point::new(50.0, 50.0), 40.0, 10.0);
+        assert!(rect.contains(68.0, 50.0) && !rect.contains(50.0, 58.0));
+        rect.angle = FRAC_PI_2;
+        assert!(!rect.contains(68.0, 50.0) && rect.contains(50.0, 68.0));
+        let (x0, y0, x1, y1) = rect.bounds();
+        assert!((x0 - 45.0).abs() < 1e-3 && (y1 - 70.0).abs() < 1e-3 && x1 < 55.01 && y0 > 29.99);
+
+        let mut ellipse = Ellipse::new(Point::new(0.0, 0.0), 20.0, 10.0);
+        assert_eq!(ellipse.spans_at(0.0), vec![(-20.0, 20.0)]);
+        assert!(ellipse.spans_at(10.5).is_empty());
+        // Turned 45°, the spans agree with `contains`
+        ellipse.angle = FRAC_PI_4;
+        for y in [-12.0, -3.0, 0.0, 7.5] {
+            let spans = ellipse.spans_at(y);
+            let (a, b) = spans[0];
+            assert!(ellipse.contains(a + 0.01, y) && ellipse.contains(b - 0.01, y));
+            assert!(!ellipse.contains(a - 0.1, y) && !ellipse.contains(b + 0.1, y));
+        }
+        let (x0, y0, x1, y1) = ellipse.bounds();
+        assert!((x1 - x0 - 2.0 * 250.0_f32.sqrt()).abs() < 1e-3 && (y1 + y0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_rounded_rect_and_ring() {
+        let rounded = RoundedRect::new(Point::new(0.0, 0.0), 40.0, 20.0, 50.0);
+        assert_eq!(rounded.corner_radius(), 10.0);
+        let flipped = RoundedRect::new(Point::new(0.0, 0.0), -40.0, 20.0, 50.0);
+        assert_eq!(flipped.corner_radius(), 10.0);
+        // The corner is cut off, the middle of each side isn't
+        assert!(!rounded.contains(19.0, 9.0) && rounded.contains(19.0, 0.0));
+        assert!(rounded.contains(0.0, 9.9));
+        for p in rounded.outline() {
+            assert!(rounded.contains(p.x * 0.999, p.y * 0.999));
+        }
+
+        let ring = Ring::new(Point::new(0.0, 0.0), 5.0, 10.0);
+        assert!(!ring.contains(0.0, 0.0) && ring.contains(7.0, 0.0) && !ring.contains(11.0, 0.0));
+        assert_eq!(ring.spans_at(0.0), vec![(-10.0, -5.0), (5.0, 10.0)]);
+        assert_eq!(ring.spans_at(-8.0).len(), 1);
+    }
+
+    #[test]
+    fn test_curve_paths() {
+        let square =
+            [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)].map(|(x, y)| Point::new(x, y));
+        let spline = CurvePath::new(square.to_vec(), Curve::CatmullRom);
+        let spline = spline.polygon();
+        // Passes through every point, bulging out between them
+        assert_eq!(spline.vertices.len(), 4 * CURVE_STEPS);
+        assert_eq!(spline.vertices[CURVE_STEPS], square[1]);
+        assert!(spline.contains(5.0, -0.5) && !spline.contains(5.0, -5.0));
+
+        let mut bezier = CurvePath::smooth_bezier(&square);
+        assert_eq!((bezier.points().len(), bezier.curve()), (12, Curve::Bezier));
+        let flat = bezier.polygon();
+        assert_eq!(flat.vertices[CURVE_STEPS], square[1]);
+        // Same shape as the spline it was built from
+        for (a, b) in flat.vertices.iter().zip(&spline.vertices) {
+            assert!(a.distance_to(b) < 1e-3);
+        }
+
+        // Moving a point rebuilds the flattened curve
+        let moved = Point::new(20.0, 0.0);
+        bezier.points_mut()[3] = moved;
+        assert_eq!(bezier.polygon().vertices[CURVE_STEPS], moved);
+    }
+}